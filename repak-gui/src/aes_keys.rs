@@ -0,0 +1,333 @@
+use crate::install_mod::{AES_KEY, AES_KEY_HEX};
+use log::{info, warn};
+use repak::utils::AesKey;
+use repak::PakReader;
+use retoc::FGuid;
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Files looked up next to the executable
+pub const KEY_FILE_NAMES: [&str; 2] = ["keys.txt", "aes.json"];
+
+#[derive(Clone)]
+struct LoadedKey {
+    guid: FGuid,
+    /// GUID as written in the keys file, for logging
+    guid_text: String,
+    key: String,
+}
+
+static LOADED_KEYS: LazyLock<RwLock<Vec<LoadedKey>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+// the same key written as "0x..." or in another case
+fn same_key(a: &str, b: &str) -> bool {
+    let digits = |key: &str| {
+        let key = key.trim();
+        key.strip_prefix("0x").or_else(|| key.strip_prefix("0X")).unwrap_or(key).to_ascii_lowercase()
+    };
+    digits(a) == digits(b)
+}
+
+/// Whether `guid` already has `key`. Keys for the zero GUID are extra keys tried after the
+/// built-in one, so any number of them can be loaded; other GUIDs have a single key.
+fn is_known(known: &[(FGuid, String)], guid: FGuid, key: &str) -> bool {
+    if guid == FGuid::default() {
+        same_key(key, AES_KEY_HEX) || known.iter().any(|(g, k)| *g == guid && same_key(k, key))
+    } else {
+        known.iter().any(|(g, _)| *g == guid)
+    }
+}
+
+pub fn keys_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+pub fn is_keys_file(path: &Path) -> bool {
+    let Some(dir) = keys_dir() else {
+        return false;
+    };
+    KEY_FILE_NAMES.iter().any(|name| path == dir.join(name))
+}
+
+// keys.txt: one `GUID:KEY` (or `GUID=KEY`, `GUID KEY`) per line, a bare key uses the zero GUID
+fn parse_keys_txt(data: &str) -> Vec<(String, String)> {
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+        .map(|line| {
+            match line.split_once(|c: char| c == ':' || c == '=' || c.is_whitespace()) {
+                Some((guid, key)) => (guid.trim().to_string(), key.trim().to_string()),
+                None => (String::new(), line.to_string()),
+            }
+        })
+        .collect()
+}
+
+// aes.json: either the FModel layout ({"mainKey", "dynamicKeys": [{"guid", "key"}]})
+// or a flat {"GUID": "KEY"} object
+fn parse_aes_json(data: &str) -> Vec<(String, String)> {
+    let Ok(root) = serde_json::from_str::<Value>(data) else {
+        warn!("aes.json is not valid JSON");
+        return vec![];
+    };
+    let mut keys = vec![];
+    if let Some(main_key) = root.get("mainKey").and_then(Value::as_str) {
+        keys.push((String::new(), main_key.to_string()));
+        for dynamic in root
+            .get("dynamicKeys")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let (Some(guid), Some(key)) = (
+                dynamic.get("guid").and_then(Value::as_str),
+                dynamic.get("key").and_then(Value::as_str),
+            ) {
+                keys.push((guid.to_string(), key.to_string()));
+            }
+        }
+    } else if let Some(obj) = root.as_object() {
+        for (guid, key) in obj {
+            if let Some(key) = key.as_str() {
+                keys.push((guid.clone(), key.to_string()));
+            }
+        }
+    }
+    keys
+}
+
+/// Re-reads `keys.txt` and `aes.json` from next to the executable. Invalid entries are
+/// skipped with a warning.
+pub fn reload_aes_keys() {
+    let mut loaded = Vec::new();
+    if let Some(dir) = keys_dir() {
+        for name in KEY_FILE_NAMES {
+            let path = dir.join(name);
+            let Ok(data) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let entries = if name.ends_with(".json") {
+                parse_aes_json(&data)
+            } else {
+                parse_keys_txt(&data)
+            };
+
+            for (guid_text, key) in entries {
+                let guid = if guid_text.is_empty() {
+                    FGuid::default()
+                } else {
+                    match FGuid::from_str(&guid_text) {
+                        Ok(guid) => guid,
+                        Err(_) => {
+                            warn!("{}: skipping key with invalid GUID {}", name, guid_text);
+                            continue;
+                        }
+                    }
+                };
+                if AesKey::from_str(&key).is_err() || retoc::AesKey::from_str(&key).is_err() {
+                    warn!("{}: skipping invalid AES key for GUID {}", name, guid_text);
+                    continue;
+                }
+                let known = loaded.iter().map(|k: &LoadedKey| (k.guid, k.key.clone())).collect::<Vec<_>>();
+                if is_known(&known, guid, &key) {
+                    warn!("{}: GUID {} is listed twice, keeping the first key", name, guid_text);
+                    continue;
+                }
+                loaded.push(LoadedKey {
+                    guid,
+                    guid_text,
+                    key,
+                });
+            }
+        }
+    }
+
+    if loaded.is_empty() {
+        info!("No extra AES keys loaded");
+    } else {
+        let guids = loaded
+            .iter()
+            .map(|k| {
+                if k.guid_text.is_empty() {
+                    "<default>".to_string()
+                } else {
+                    k.guid_text.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        info!("Loaded {} AES key(s) for GUIDs: {}", loaded.len(), guids.join(", "));
+    }
+    *LOADED_KEYS.write().unwrap() = loaded;
+}
+
+/// Keys loaded from the key files as (GUID, key), an empty GUID for the default key
+pub fn loaded_keys() -> Vec<(String, String)> {
+    LOADED_KEYS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|k| (k.guid_text.clone(), k.key.clone()))
+        .collect()
+}
+
+/// Appends `keys` to `keys.txt` next to the executable and reloads the keys. Invalid keys,
+/// keys already known and GUIDs other than the zero GUID that already have one are skipped.
+/// Returns how many keys were added.
+pub fn add_keys(keys: &[(String, String)]) -> io::Result<usize> {
+    let mut known = LOADED_KEYS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|k| (k.guid, k.key.clone()))
+        .collect::<Vec<_>>();
+    let mut lines = vec![];
+    for (guid_text, key) in keys {
+        let guid = if guid_text.is_empty() {
+            FGuid::default()
+        } else {
+            match FGuid::from_str(guid_text) {
+                Ok(guid) => guid,
+                Err(_) => {
+                    warn!("Skipping imported key with invalid GUID {}", guid_text);
+                    continue;
+                }
+            }
+        };
+        if is_known(&known, guid, key) {
+            continue;
+        }
+        if AesKey::from_str(key).is_err() || retoc::AesKey::from_str(key).is_err() {
+            warn!("Skipping invalid imported AES key for GUID {}", guid_text);
+            continue;
+        }
+        known.push((guid, key.clone()));
+        lines.push(match guid_text.is_empty() {
+            true => key.clone(),
+            false => format!("{}:{}", guid_text, key),
+        });
+    }
+    if lines.is_empty() {
+        return Ok(0);
+    }
+
+    let dir = keys_dir().ok_or_else(|| io::Error::other("Unable to find the folder of the executable"))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(KEY_FILE_NAMES[0]))?;
+    // the file may not end with a line break, blank lines are skipped when reading
+    writeln!(file)?;
+    for line in &lines {
+        writeln!(file, "{}", line)?;
+    }
+    info!("Added {} AES key(s) to {}", lines.len(), KEY_FILE_NAMES[0]);
+    reload_aes_keys();
+    Ok(lines.len())
+}
+
+/// Adds the built-in key for the zero GUID and the loaded keys of every other GUID to a
+/// retoc config. Loaded keys for the zero GUID are only tried by `with_retoc_keys`.
+pub fn insert_retoc_keys(config: &mut retoc::Config) {
+    config.aes_keys.insert(
+        FGuid::default(),
+        retoc::AesKey::from_str(AES_KEY_HEX).unwrap(),
+    );
+    for loaded in LOADED_KEYS.read().unwrap().iter().filter(|k| k.guid != FGuid::default()) {
+        config
+            .aes_keys
+            .insert(loaded.guid, retoc::AesKey::from_str(&loaded.key).unwrap());
+    }
+}
+
+/// Runs `attempt` with the config made by `config` and the keys of `insert_retoc_keys`, then
+/// again with each loaded key for the zero GUID in place of the built-in one until an
+/// attempt succeeds. Returns the error of the built-in key when none does.
+pub fn with_retoc_keys<T, E>(
+    config: impl Fn() -> retoc::Config,
+    mut attempt: impl FnMut(Arc<retoc::Config>) -> Result<T, E>,
+) -> Result<T, E> {
+    let mut first = config();
+    insert_retoc_keys(&mut first);
+    let first_err = match attempt(Arc::new(first)) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    let extra_keys = LOADED_KEYS
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|k| k.guid == FGuid::default())
+        .map(|k| k.key.clone())
+        .collect::<Vec<_>>();
+    for key in extra_keys {
+        let mut retry = config();
+        insert_retoc_keys(&mut retry);
+        retry.aes_keys.insert(FGuid::default(), retoc::AesKey::from_str(&key).unwrap());
+        if let Ok(value) = attempt(Arc::new(retry)) {
+            return Ok(value);
+        }
+    }
+    Err(first_err)
+}
+
+/// Opens a pak with the built-in key, falling back to the loaded keys in order
+pub fn open_pak(path: &Path) -> Result<PakReader, repak::Error> {
+    let first = repak::PakBuilder::new()
+        .key(AES_KEY.clone().0)
+        .reader(&mut BufReader::new(File::open(path)?));
+    let Err(first_err) = first else {
+        return first;
+    };
+    for loaded in LOADED_KEYS.read().unwrap().iter() {
+        let key = AesKey::from_str(&loaded.key).unwrap();
+        let reader = repak::PakBuilder::new()
+            .key(key.0)
+            .reader(&mut BufReader::new(File::open(path)?));
+        if reader.is_ok() {
+            return reader;
+        }
+    }
+    Err(first_err)
+}
+
+/// Whether `path` only fails to open because its index is encrypted with a key we don't
+/// have, as opposed to not being a pak at all
+pub fn needs_key(path: &Path) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    match repak::PakBuilder::new().reader(&mut BufReader::new(file)) {
+        // every version is tried, one of them getting to the index means the key is missing
+        Err(repak::Error::UnsupportedOrEncrypted(log)) => log.contains(&repak::Error::Encrypted.to_string()),
+        Err(repak::Error::Encrypted) => true,
+        _ => false,
+    }
+}
+
+/// Opens a pak with a key typed by the user, as hex or base64
+pub fn open_pak_with_key(path: &Path, key: &str) -> Result<PakReader, String> {
+    let key = AesKey::from_str(key.trim())
+        .map_err(|_| "Not a valid AES key, expected 64 hex digits (0x is optional)".to_string())?;
+    let file = File::open(path).map_err(|e| format!("Unable to open {}: {}", path.display(), e))?;
+    repak::PakBuilder::new()
+        .key(key.0)
+        .reader(&mut BufReader::new(file))
+        .map_err(|_| "This key doesn't open the mod".to_string())
+}
+
+/// GUID of a pak's encryption key as written in keys.txt, empty for the default key
+pub fn guid_text(guid: Option<u128>) -> String {
+    match guid.filter(|&guid| guid != 0) {
+        Some(guid) => format!(
+            "{:08X}-{:08X}-{:08X}-{:08X}",
+            guid as u32,
+            (guid >> 32) as u32,
+            (guid >> 64) as u32,
+            (guid >> 96) as u32
+        ),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,146 @@
+use serde_json::Value;
+use std::io::{self, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Stands in for an AES key in a bug report
+const REDACTED: &str = "<redacted>";
+
+// keys are compared without the 0x prefix, and a stub that short is no key
+fn key_digits(key: &str) -> Option<&str> {
+    let key = key.trim();
+    let digits = key.strip_prefix("0x").or_else(|| key.strip_prefix("0X")).unwrap_or(key);
+    (digits.len() >= 16).then_some(digits)
+}
+
+/// `text` with every occurrence of one of `keys` replaced, ignoring the case of hex keys
+pub fn redact_text(text: &str, keys: &[String]) -> String {
+    let mut out = text.to_string();
+    for digits in keys.iter().filter_map(|key| key_digits(key)) {
+        let needle = digits.to_ascii_lowercase();
+        // ASCII lowercasing keeps the byte offsets of `out`
+        while let Some(start) = out.to_ascii_lowercase().find(&needle) {
+            out.replace_range(start..start + needle.len(), REDACTED);
+        }
+    }
+    out
+}
+
+/// Blanks the known `keys` wherever they are in `config`, and every string or list of
+/// strings under a field whose name mentions a key
+pub fn redact_config(config: &mut Value, keys: &[String]) {
+    match config {
+        Value::String(text) => *text = redact_text(text, keys),
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_config(item, keys)),
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                if name.to_lowercase().contains("key") {
+                    blank_strings(value);
+                } else {
+                    redact_config(value, keys);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn blank_strings(value: &mut Value) {
+    match value {
+        Value::String(text) if !text.is_empty() => *text = REDACTED.to_string(),
+        Value::Array(items) => items.iter_mut().for_each(blank_strings),
+        Value::Object(fields) => fields.values_mut().for_each(blank_strings),
+        _ => {}
+    }
+}
+
+/// Zips the log, the already redacted config and the system info into `path`
+pub fn write_bundle(path: &Path, log: &str, config: &Value, system_info: &str) -> io::Result<()> {
+    let mut zip = ZipWriter::new(std::fs::File::create(path)?);
+    let files = [
+        ("latest.log", log.to_string()),
+        ("repak_mod_manager.json", serde_json::to_string_pretty(config)?),
+        ("system_info.txt", system_info.to_string()),
+    ];
+    for (name, contents) in files {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_config, redact_text, REDACTED};
+    use serde_json::json;
+
+    const KEY: &str = "0x0C263D8C22DCB085894899C3A3796383E9BF9DE0CBFB08C9BF2DEF2E84F29D74";
+
+    fn keys() -> Vec<String> {
+        vec![KEY.to_string()]
+    }
+
+    #[test]
+    fn redact_text_finds_keys_with_or_without_prefix() {
+        let digits = KEY.trim_start_matches("0x");
+        let text = format!("using {} then {}.", KEY, digits);
+        assert_eq!(redact_text(&text, &keys()), format!("using 0x{} then {}.", REDACTED, REDACTED));
+        // a key configured without its prefix still matches a prefixed one in the log
+        assert_eq!(redact_text(KEY, &[digits.to_string()]), format!("0x{}", REDACTED));
+    }
+
+    #[test]
+    fn redact_text_ignores_case() {
+        let lower = KEY.to_lowercase();
+        let text = format!("[{}] [{}]", lower, KEY.replace("0x", "0X"));
+        assert_eq!(redact_text(&text, &keys()), format!("[0x{}] [0X{}]", REDACTED, REDACTED));
+        let upper_key = vec![KEY.to_uppercase()];
+        assert_eq!(redact_text(&lower, &upper_key), format!("0x{}", REDACTED));
+    }
+
+    #[test]
+    fn redact_text_skips_short_keys() {
+        let text = "abc 0x1234 def";
+        assert_eq!(redact_text(text, &["0x1234".to_string(), " ".to_string()]), text);
+    }
+
+    #[test]
+    fn redact_config_blanks_key_named_fields() {
+        let mut config = json!({
+            "aes_key": "not a known key",
+            "extra_aes_keys": ["one", "", "two"],
+            "KeyOverrides": {"game": "three"},
+            "key_count": 2,
+            "game_path": "C:/Games",
+        });
+        redact_config(&mut config, &[]);
+        assert_eq!(
+            config,
+            json!({
+                "aes_key": REDACTED,
+                "extra_aes_keys": [REDACTED, "", REDACTED],
+                "KeyOverrides": {"game": REDACTED},
+                "key_count": 2,
+                "game_path": "C:/Games",
+            })
+        );
+    }
+
+    #[test]
+    fn redact_config_finds_known_keys_anywhere() {
+        let mut config = json!({
+            "notes": format!("key was {}", KEY.to_lowercase()),
+            "mods": [{"comment": KEY}],
+        });
+        redact_config(&mut config, &keys());
+        assert_eq!(
+            config,
+            json!({
+                "notes": format!("key was 0x{}", REDACTED),
+                "mods": [{"comment": format!("0x{}", REDACTED)}],
+            })
+        );
+    }
+}
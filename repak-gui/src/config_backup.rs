@@ -0,0 +1,37 @@
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many launch backups of the config are kept
+pub const BACKUP_COUNT: usize = 3;
+
+/// `repak_mod_manager.json.<n>.bak` next to the config, 1 is the newest
+fn backup_path(config: &Path, n: usize) -> PathBuf {
+    let file_name = config.file_name().unwrap_or_default().to_string_lossy();
+    config.with_file_name(format!("{}.{}.bak", file_name, n))
+}
+
+/// Copies a config that just loaded fine to backup 1, shifting the older ones down and
+/// dropping the oldest
+pub fn rotate(config: &Path) {
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(config, n);
+        if from.exists() {
+            if let Err(e) = fs::rename(&from, backup_path(config, n + 1)) {
+                warn!("Unable to rotate config backup {}: {}", from.display(), e);
+            }
+        }
+    }
+    match fs::copy(config, backup_path(config, 1)) {
+        Ok(_) => info!("Backed up config to {}", backup_path(config, 1).display()),
+        Err(e) => warn!("Unable to back up config: {}", e),
+    }
+}
+
+/// Existing backups, newest first
+pub fn backups(config: &Path) -> Vec<PathBuf> {
+    (1..=BACKUP_COUNT)
+        .map(|n| backup_path(config, n))
+        .filter(|p| p.is_file())
+        .collect()
+}
@@ -0,0 +1,38 @@
+use crate::utoc_utils::read_utoc;
+use repak::PakReader;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Files a mod would override, as lowercase paths relative to the game root.
+/// Iostore mods are listed from their utoc since the pak only holds the chunk names.
+pub fn mod_files(reader: &PakReader, pak_path: &Path) -> Result<BTreeSet<String>, String> {
+    let utoc_path = pak_path.with_extension("utoc");
+    if utoc_path.exists() {
+        Ok(read_utoc(&utoc_path, reader, pak_path)?
+            .into_iter()
+            .flat_map(|entry| entry.chunk_paths)
+            .map(|path| normalize(&path))
+            .collect())
+    } else {
+        Ok(reader
+            .files()
+            .into_iter()
+            .map(|file| normalize(&format!("{}{}", reader.mount_point(), file)))
+            .collect())
+    }
+}
+
+// mount points are relative to the Paks folder ("../../../"), drop that so both kinds of mod
+// end up with "marvel/content/..."
+fn normalize(path: &str) -> String {
+    let mut path = path.replace('\\', "/");
+    while let Some(rest) = path.strip_prefix("../").or_else(|| path.strip_prefix('/')) {
+        path = rest.to_string();
+    }
+    path.to_lowercase()
+}
+
+/// Files present in both sets
+pub fn shared_files<'a>(a: &'a BTreeSet<String>, b: &'a BTreeSet<String>) -> Vec<&'a String> {
+    a.intersection(b).collect()
+}
@@ -0,0 +1,94 @@
+use eframe::egui;
+use log::{debug, info};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+
+/// Files the installer can take straight from a download
+const INSTALLABLE_EXTENSIONS: [&str; 3] = ["pak", "zip", "rar"];
+
+/// How long a file has to stay untouched before it counts as fully downloaded
+const SETTLE_TIME: Duration = Duration::from_secs(3);
+
+/// Watches a downloads folder for new mods. Files are only reported once they stopped
+/// changing for `SETTLE_TIME`, so a download that is still being written isn't picked up.
+pub struct DownloadWatcher {
+    // dropping the watcher stops the notifications
+    _watcher: RecommendedWatcher,
+    events: Receiver<Event>,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+fn is_installable(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| INSTALLABLE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+impl DownloadWatcher {
+    pub fn new(dir: &Path, ctx: egui::Context) -> notify::Result<Self> {
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                if tx.send(event).is_ok() {
+                    // wake the UI so the debounce timer gets checked
+                    ctx.request_repaint();
+                }
+            }
+        })?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        info!("Watching {} for downloaded mods", dir.display());
+        Ok(Self {
+            _watcher: watcher,
+            events: rx,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Returns the mods that finished downloading since the last call
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        while let Ok(event) = self.events.try_recv() {
+            for path in event.paths.into_iter().filter(|p| is_installable(p)) {
+                match event.kind {
+                    EventKind::Remove(_) => {
+                        self.pending.remove(&path);
+                    }
+                    EventKind::Create(_) | EventKind::Modify(_) if path.is_file() => {
+                        self.pending.insert(path, now);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let settled = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| now.duration_since(**changed) >= SETTLE_TIME)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        settled
+            .into_iter()
+            .filter(|path| {
+                self.pending.remove(path);
+                // browsers sometimes still hold the file open right after the last write
+                let ready = File::open(path).is_ok();
+                if ready {
+                    debug!("Download finished: {}", path.display());
+                } else {
+                    self.pending.insert(path.clone(), now);
+                }
+                ready
+            })
+            .collect()
+    }
+
+    /// True while a file is waiting for `SETTLE_TIME` to pass
+    pub fn is_waiting(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
@@ -0,0 +1,279 @@
+use crate::conflicts::mod_files;
+use crate::mod_hash::last_modified;
+use log::{debug, warn};
+use repak::PakReader;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+/// Folder names repeat across every mod of a library, so each path component is stored
+/// once and file paths are kept as lists of component ids
+#[derive(Default)]
+struct Interner {
+    names: Vec<Box<str>>,
+    ids: HashMap<Box<str>, u32>,
+}
+
+impl Interner {
+    fn intern(&mut self, path: &str) -> Box<[u32]> {
+        path.split('/')
+            .map(|name| match self.ids.get(name) {
+                Some(&id) => id,
+                None => {
+                    let id = self.names.len() as u32;
+                    self.names.push(name.into());
+                    self.ids.insert(name.into(), id);
+                    id
+                }
+            })
+            .collect()
+    }
+
+    /// Ids of `path`, `None` when one of its components was never seen so no mod has it
+    fn lookup(&self, path: &str) -> Option<Box<[u32]>> {
+        path.split('/').map(|name| self.ids.get(name).copied()).collect()
+    }
+
+    fn join(&self, ids: &[u32], out: &mut String) {
+        out.clear();
+        for (i, &id) in ids.iter().enumerate() {
+            if i > 0 {
+                out.push('/');
+            }
+            out.push_str(&self.names[id as usize]);
+        }
+    }
+}
+
+struct IndexedMod {
+    /// Newest modification time of the mod's files when it was read
+    modified: SystemTime,
+    /// Sorted, so lookups can binary search
+    files: Vec<Box<[u32]>>,
+}
+
+type IndexJob = (PathBuf, PathBuf, SystemTime, PakReader);
+type IndexResult = (PathBuf, SystemTime, Result<BTreeSet<String>, String>);
+
+/// Mods are indexed by their pak path without extension, so toggling one on or off keeps
+/// its entry
+pub fn mod_key(pak_path: &Path) -> PathBuf {
+    pak_path.with_extension("")
+}
+
+fn normalize(file: &str) -> String {
+    file.trim().replace('\\', "/").to_lowercase()
+}
+
+/// Every file path shipped by every installed mod, to find out which mods replace an asset.
+/// Mods are queued as the mod list loads and read one at a time on a worker thread, so the
+/// index fills up in the background and can be searched before it is complete.
+pub struct FileIndex {
+    interner: Interner,
+    mods: BTreeMap<PathBuf, IndexedMod>,
+    /// Mods handed to the worker, with the modification time they were queued with
+    pending: HashMap<PathBuf, SystemTime>,
+    worker: Option<(Sender<IndexJob>, JoinHandle<()>)>,
+    results_tx: Sender<IndexResult>,
+    results: Receiver<IndexResult>,
+}
+
+impl Default for FileIndex {
+    fn default() -> Self {
+        let (results_tx, results) = channel();
+        Self {
+            interner: Interner::default(),
+            mods: BTreeMap::new(),
+            pending: HashMap::new(),
+            worker: None,
+            results_tx,
+            results,
+        }
+    }
+}
+
+impl FileIndex {
+    /// Queues the mod at `pak_path` for indexing, unless it is indexed or queued already and
+    /// its files haven't changed since
+    pub fn queue(&mut self, pak_path: &Path, reader: &PakReader) {
+        let key = mod_key(pak_path);
+        let modified = last_modified(pak_path);
+        let indexed = self.mods.get(&key).is_some_and(|m| m.modified == modified);
+        if indexed || self.pending.get(&key) == Some(&modified) {
+            return;
+        }
+        self.pending.insert(key.clone(), modified);
+
+        let job = (key, pak_path.to_path_buf(), modified, reader.clone());
+        let job = match &self.worker {
+            Some((jobs, _)) => match jobs.send(job) {
+                Ok(()) => return,
+                // the worker is gone, start a new one for this and later mods
+                Err(failed) => failed.0,
+            },
+            None => job,
+        };
+        let (jobs, rx) = channel::<IndexJob>();
+        let results = self.results_tx.clone();
+        // reading utocs is slow for big libraries, keep it off the UI thread
+        let handle = std::thread::spawn(move || {
+            for (key, pak_path, modified, reader) in rx {
+                // retoc panics on some broken utocs, that mustn't take the worker down
+                let files = catch_unwind(AssertUnwindSafe(|| mod_files(&reader, &pak_path)))
+                    .unwrap_or_else(|_| Err(format!("Reading {} panicked", pak_path.display())));
+                if results.send((key, modified, files)).is_err() {
+                    break;
+                }
+            }
+        });
+        let _ = jobs.send(job);
+        self.worker = Some((jobs, handle));
+    }
+
+    /// Forgets mods that are gone from `installed`, the pak paths of the current mod list
+    pub fn retain_installed(&mut self, installed: &[PathBuf]) {
+        let keys = installed.iter().map(|path| mod_key(path)).collect::<HashSet<_>>();
+        self.mods.retain(|key, _| keys.contains(key));
+        self.pending.retain(|key, _| keys.contains(key));
+    }
+
+    /// Takes in the mods the worker has read, returns true when any arrived
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        // checked first, a stopped worker has sent everything it will by then
+        let stopped = self.worker.as_ref().is_some_and(|(_, handle)| handle.is_finished());
+        while let Ok((key, modified, files)) = self.results.try_recv() {
+            // removed or queued again with newer files in the meantime
+            if self.pending.get(&key) != Some(&modified) {
+                continue;
+            }
+            self.pending.remove(&key);
+            // an unreadable mod is indexed without files rather than queued again every refresh
+            let files = files.unwrap_or_else(|e| {
+                warn!("{}", e);
+                BTreeSet::new()
+            });
+            let mut files = files.iter().map(|file| self.interner.intern(file)).collect::<Vec<_>>();
+            files.sort();
+            debug!("Indexed {} files of {}", files.len(), key.display());
+            self.mods.insert(key, IndexedMod { modified, files });
+            changed = true;
+        }
+        // what is still pending will never arrive, the next refresh queues it again
+        if stopped {
+            warn!("The file index worker stopped, {} mods were left out", self.pending.len());
+            self.worker = None;
+            self.pending.clear();
+            changed = true;
+        }
+        changed
+    }
+
+    /// Mods still waiting for the worker
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn mod_count(&self) -> usize {
+        self.mods.len()
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.mods.values().map(|m| m.files.len()).sum()
+    }
+
+    /// Number of files of the mod at `pak_path`, `None` while it isn't indexed
+    pub fn file_count_of(&self, pak_path: &Path) -> Option<usize> {
+        self.mods.get(&mod_key(pak_path)).map(|m| m.files.len())
+    }
+
+    /// File list of the mod at `pak_path`, `None` while it isn't indexed
+    pub fn files_of(&self, pak_path: &Path) -> Option<BTreeSet<String>> {
+        let indexed = self.mods.get(&mod_key(pak_path))?;
+        let mut path = String::new();
+        Some(
+            indexed
+                .files
+                .iter()
+                .map(|ids| {
+                    self.interner.join(ids, &mut path);
+                    path.clone()
+                })
+                .collect(),
+        )
+    }
+
+    /// `mod_key`s of the indexed mods shipping exactly `file`
+    pub fn mods_containing(&self, file: &str) -> Vec<&Path> {
+        let Some(ids) = self.interner.lookup(&normalize(file)) else {
+            return vec![];
+        };
+        self.mods
+            .iter()
+            .filter(|(_, indexed)| indexed.files.binary_search(&ids).is_ok())
+            .map(|(key, _)| key.as_path())
+            .collect()
+    }
+
+    /// `mod_key`s of the mods among `pak_paths` sharing a file with another of them. Mods
+    /// that aren't indexed yet are left out.
+    pub fn overlapping(&self, pak_paths: &[PathBuf]) -> HashSet<PathBuf> {
+        let keys = pak_paths.iter().map(|path| mod_key(path)).collect::<Vec<_>>();
+        let mut owners: HashMap<&[u32], usize> = HashMap::new();
+        let mut overlapping = HashSet::new();
+        for (i, key) in keys.iter().enumerate() {
+            let Some(indexed) = self.mods.get(key) else {
+                continue;
+            };
+            for ids in &indexed.files {
+                match owners.get(&**ids) {
+                    Some(&owner) if owner != i => {
+                        overlapping.insert(keys[owner].clone());
+                        overlapping.insert(key.clone());
+                    }
+                    Some(_) => {}
+                    None => {
+                        owners.insert(&**ids, i);
+                    }
+                }
+            }
+        }
+        overlapping
+    }
+
+    /// `mod_key`s of mods with a file path containing `query` (case insensitive) and the
+    /// matching paths. At most `limit` paths are returned in total.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(PathBuf, Vec<String>)> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return vec![];
+        }
+        let mut remaining = limit;
+        let mut results = vec![];
+        let mut path = String::new();
+        for (key, indexed) in &self.mods {
+            if remaining == 0 {
+                break;
+            }
+            let mut matches = vec![];
+            for ids in &indexed.files {
+                if matches.len() == remaining {
+                    break;
+                }
+                self.interner.join(ids, &mut path);
+                if path.contains(&query) {
+                    matches.push(path.clone());
+                }
+            }
+            if !matches.is_empty() {
+                matches.sort();
+                remaining -= matches.len();
+                results.push((key.clone(), matches));
+            }
+        }
+        results
+    }
+}
@@ -1,7 +1,9 @@
+use crate::hex_view::HexView;
+use crate::utils::format_bytes;
 use crate::utoc_utils::read_utoc;
 use eframe::egui;
 use eframe::egui::OutputCommand::CopyText;
-use eframe::egui::RichText;
+use eframe::egui::{Label, RichText, Sense};
 use egui_extras::{Column, TableBuilder};
 use repak::PakReader;
 use rfd::FileDialog;
@@ -19,6 +21,25 @@ pub struct FileTable {
     file_contents: Vec<FileEntry>,
     selection: usize,
     showing_utoc: bool,
+    sort_column: SortColumn,
+    sort_ascending: bool,
+    inspector: Option<HexView>,
+    /// Why the file list is empty when the utoc couldn't be read
+    load_error: Option<String>,
+}
+
+/// Number of columns whose width is remembered, the last column fills the remaining space
+pub const PERSISTED_COLUMNS: usize = 4;
+const DEFAULT_COLUMN_WIDTHS: [f32; PERSISTED_COLUMNS] = [700., 110., 110., 100.];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SortColumn {
+    Path,
+    Uncompressed,
+    Compressed,
+    Compression,
+    /// Offset for paks, chunk count for utoc
+    Last,
 }
 
 #[derive(Clone, Debug)]
@@ -28,11 +49,14 @@ pub(crate) struct FileEntry {
     pub(crate) pak_path: PathBuf,
     // entry: Entry,
     pub(crate) pak_reader: PakReader,
-    pub(crate) compressed: String,
-    pub(crate) uncompressed: String,
-    pub(crate) offset: String,
+    pub(crate) compressed: u64,
+    pub(crate) uncompressed: u64,
+    pub(crate) compression: String,
+    pub(crate) offset: Option<u64>,
     pub(crate) bulkdata: Option<usize>,
     pub(crate) package_data: Option<usize>,
+    /// Chunk paths inside the utoc, package data first
+    pub(crate) chunk_paths: Vec<String>,
 }
 impl Default for FileTable {
     fn default() -> Self {
@@ -43,6 +67,10 @@ impl Default for FileTable {
             file_contents: vec![],
             selection: usize::MAX,
             showing_utoc: false,
+            sort_column: SortColumn::Path,
+            sort_ascending: true,
+            inspector: None,
+            load_error: None,
         }
     }
 }
@@ -55,9 +83,14 @@ impl FileTable {
         let mut utoc_path = pak_path.to_path_buf();
         utoc_path.set_extension("utoc");
 
+        let mut load_error = None;
         let file_entries = {
             if utoc_path.exists() {
-                read_utoc(&utoc_path, pak_reader,pak_path)
+                read_utoc(&utoc_path, pak_reader, pak_path).unwrap_or_else(|e| {
+                    log::error!("{}", e);
+                    load_error = Some(e);
+                    vec![]
+                })
             }
             else {
                 let entries = pak_reader
@@ -67,45 +100,107 @@ impl FileTable {
                     .iter()
                     .map(|entry| {
                         let entry_pak = pak_reader.get_file_entry(entry).unwrap();
+                        let compression = entry_pak
+                            .compression_slot
+                            .and_then(|slot| pak_reader.compression().get(slot as usize).copied().flatten())
+                            .map(|c| c.to_string())
+                            .unwrap_or_else(|| "None".to_string());
                         FileEntry {
                             file_path: entry.clone(),
                             pak_path: PathBuf::from(pak_path),
                             pak_reader: pak_reader.clone(),
                             // entry: pak_reader.get_file_entry(entry).unwrap(),
-                            compressed: entry_pak.compressed.to_string(),
-                            uncompressed: entry_pak.uncompressed.to_string(),
-                            offset: format!("{:#x}", entry_pak.offset),
+                            compressed: entry_pak.compressed,
+                            uncompressed: entry_pak.uncompressed,
+                            compression,
+                            offset: Some(entry_pak.offset),
                             bulkdata: None,
                             package_data: None,
+                            chunk_paths: vec![],
                         }
                     })
                     .collect::<Vec<_>>()
             }
         };
 
-        Self {
+        let mut table = Self {
             file_contents: file_entries,
             showing_utoc: utoc_path.exists(),
+            load_error,
             ..Default::default()
+        };
+        table.sort();
+        table
+    }
+
+    fn sort(&mut self) {
+        let column = self.sort_column;
+        self.file_contents.sort_by(|a, b| {
+            let ordering = match column {
+                SortColumn::Path => a.file_path.cmp(&b.file_path),
+                SortColumn::Uncompressed => a.uncompressed.cmp(&b.uncompressed),
+                SortColumn::Compressed => a.compressed.cmp(&b.compressed),
+                SortColumn::Compression => a.compression.cmp(&b.compression),
+                SortColumn::Last => a
+                    .offset
+                    .cmp(&b.offset)
+                    .then((a.bulkdata, a.package_data).cmp(&(b.bulkdata, b.package_data))),
+            };
+            // keep equal rows in path order so the table doesn't jump around
+            ordering.then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        if !self.sort_ascending {
+            self.file_contents.reverse();
+        }
+        self.selection = usize::MAX;
+    }
+
+    fn sort_header(&mut self, ui: &mut egui::Ui, name: &str, column: SortColumn) {
+        let text = if self.sort_column == column {
+            format!("{} {}", name, if self.sort_ascending { "⏶" } else { "⏷" })
+        } else {
+            name.to_string()
+        };
+        let response = ui
+            .add(Label::new(RichText::new(text).strong()).sense(Sense::click()))
+            .on_hover_text("Click to sort");
+        if response.clicked() {
+            if self.sort_column == column {
+                self.sort_ascending = !self.sort_ascending;
+            } else {
+                self.sort_column = column;
+                self.sort_ascending = true;
+            }
+            self.sort();
         }
     }
 
-    pub fn table_ui(&mut self, ui: &mut egui::Ui) {
+    /// Draws the table. `column_widths` holds the saved widths of the resizable columns,
+    /// returns true once the user finished resizing one so the caller can persist them.
+    pub fn table_ui(&mut self, ui: &mut egui::Ui, column_widths: &mut Vec<f32>) -> bool {
+        if let Some(error) = &self.load_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+            return false;
+        }
+        let initial_widths: Vec<f32> = (0..PERSISTED_COLUMNS)
+            .map(|i| column_widths.get(i).copied().unwrap_or(DEFAULT_COLUMN_WIDTHS[i]))
+            .collect();
         let available_height = ui.available_height();
         let mut table = TableBuilder::new(ui)
+            .id_salt("file_table")
             .striped(self.striped)
             .resizable(self.resizable)
             .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
             .column(
-                Column::initial(800.)
-                    .at_least(500.0)
+                Column::initial(initial_widths[0])
+                    .at_least(200.0)
                     .clip(true)
-                    .resizable(true)
-                    .at_most(1000.),
+                    .resizable(true),
             ) // PATH
-            .column(Column::remainder()) // Offset
-            .column(Column::remainder()) // Compressed Size
-            .column(Column::remainder()) // Uncompressed Size
+            .column(Column::initial(initial_widths[1]).at_least(60.0).clip(true)) // Uncompressed Size
+            .column(Column::initial(initial_widths[2]).at_least(60.0).clip(true)) // Compressed Size
+            .column(Column::initial(initial_widths[3]).at_least(60.0).clip(true)) // Compression
+            .column(Column::remainder()) // Offset / Chunks
             .min_scrolled_height(0.0)
             .max_scroll_height(available_height);
 
@@ -113,41 +208,26 @@ impl FileTable {
             table = table.sense(egui::Sense::click());
         }
 
-
+        let mut header_widths = Vec::with_capacity(PERSISTED_COLUMNS);
         table
             .header(20.0, |mut header| {
-                header.col(|ui| {
-                    ui.label("Path");
-                });
-
-
-                if self.showing_utoc {
-                    header.col(|ui| {
-                        ui.label("Bulkdata Chunks");
-                    });
-
-                    header.col(|ui| {
-                        ui.label("PackageData Chunks");
-                    });
-                }
-                else{
-                    header.col(|ui| {
-                        ui.label("Offset");
-                    });
+                let (_, response) =
+                    header.col(|ui| self.sort_header(ui, "Path", SortColumn::Path));
+                header_widths.push(response.rect.width());
+                let (_, response) =
+                    header.col(|ui| self.sort_header(ui, "Uncompressed", SortColumn::Uncompressed));
+                header_widths.push(response.rect.width());
+                let (_, response) =
+                    header.col(|ui| self.sort_header(ui, "Compressed", SortColumn::Compressed));
+                header_widths.push(response.rect.width());
+                let (_, response) =
+                    header.col(|ui| self.sort_header(ui, "Compression", SortColumn::Compression));
+                header_widths.push(response.rect.width());
 
-                    header.col(|ui| {
-                        ui.label("Compressed");
-                    });
-                    header.col(|ui| {
-                        ui.label("Uncompressed");
-                    });
-                }
-                // header.col(|ui| {
-                //     ui.label("Compression Slot");
-                // });
+                let last = if self.showing_utoc { "Chunks" } else { "Offset" };
+                header.col(|ui| self.sort_header(ui, last, SortColumn::Last));
             })
             .body(|body| {
-                // let mut file = self.file_contents.clone();
                 body.rows(20.0, self.file_contents.len(), |mut row| {
                     let row_idx = row.index();
 
@@ -160,31 +240,63 @@ impl FileTable {
                         };
                     })
                     .1
-                    .context_menu(|ui| show_ctx_menu(ui, entry,self.showing_utoc));
+                    .context_menu(|ui| {
+                        if ui.button("Inspect bytes").clicked() {
+                            self.inspector = Some(HexView::open(entry, self.showing_utoc));
+                            ui.close_menu();
+                        }
+                        show_ctx_menu(ui, entry, self.showing_utoc)
+                    });
+
+                    row.col(|ui| {
+                        ui.label(format_bytes(entry.uncompressed))
+                            .on_hover_text(format!("{} bytes", entry.uncompressed));
+                    });
+                    row.col(|ui| {
+                        ui.label(format_bytes(entry.compressed))
+                            .on_hover_text(format!("{} bytes", entry.compressed));
+                    });
+                    row.col(|ui| {
+                        ui.label(&entry.compression);
+                    });
 
                     if self.showing_utoc {
                         row.col(|ui| {
-                            ui.label(&entry.bulkdata.unwrap_or(0).to_string());
-                        });
-                        row.col(|ui| {
-                            ui.label(&entry.package_data.unwrap_or(0).to_string());
+                            ui.label(format!(
+                                "{} package / {} bulk",
+                                entry.package_data.unwrap_or(0),
+                                entry.bulkdata.unwrap_or(0)
+                            ));
                         });
                     }
                     else {
                         row.col(|ui| {
-                            ui.label(&entry.offset);
-                        });
-                        row.col(|ui| {
-                            ui.label(&entry.compressed);
-                        });
-                        row.col(|ui| {
-                            ui.label(&entry.uncompressed);
+                            ui.label(format!("{:#x}", entry.offset.unwrap_or(0)));
                         });
                     }
 
                     self.toggle_row_selection(row_idx, &row.response());
                 });
             });
+
+        if let Some(inspector) = &mut self.inspector {
+            if !inspector.show(ui.ctx()) {
+                self.inspector = None;
+            }
+        }
+
+        // only report once the drag is over, otherwise every frame of a resize would be saved
+        let resizing = ui.input(|i| i.pointer.any_down());
+        let changed = header_widths.len() == PERSISTED_COLUMNS
+            && header_widths
+                .iter()
+                .zip(initial_widths.iter())
+                .any(|(new, old)| (new - old).abs() > 0.5);
+        if changed && !resizing {
+            *column_widths = header_widths;
+            return true;
+        }
+        false
     }
     fn toggle_row_selection(&mut self, row_index: usize, row_response: &egui::Response) {
         if row_response.clicked() {
@@ -223,7 +335,7 @@ fn show_ctx_menu(ui: &mut egui::Ui, entry: &FileEntry, is_utoc: bool) {
         ui.close_menu();
     }
     if ui.button("Copy Offset").clicked() {
-        ui.output_mut(|o| o.commands = vec![CopyText(entry.offset.clone().to_string())]);
+        ui.output_mut(|o| o.commands = vec![CopyText(format!("{:#x}", entry.offset.unwrap_or(0)))]);
         ui.close_menu();
     }
 
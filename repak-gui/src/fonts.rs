@@ -0,0 +1,55 @@
+use eframe::egui::{self, FontData, FontDefinitions, FontFamily};
+use log::{info, warn};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Fallback fonts for mod names in Chinese, Japanese and Korean, the first file found in each
+/// group is used. They ship with the OS, bundling one would add well over 10 MB to the app.
+#[cfg(target_os = "windows")]
+const CJK_FONTS: &[&[&str]] = &[
+    // Microsoft YaHei covers kana as well, the others are for installs without it
+    &["msyh.ttc", "YuGothM.ttc", "msgothic.ttc"],
+    &["malgun.ttf"],
+];
+#[cfg(not(target_os = "windows"))]
+const CJK_FONTS: &[&[&str]] = &[&[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+]];
+
+#[cfg(target_os = "windows")]
+fn font_path(name: &str) -> PathBuf {
+    let windir = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+    PathBuf::from(windir).join("Fonts").join(name)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn font_path(name: &str) -> PathBuf {
+    PathBuf::from(name)
+}
+
+/// Sets egui's default fonts, with the system CJK fonts as fallbacks when `cjk` is on.
+/// Sizes come from the text styles, so `set_custom_font_size` scales the fallbacks too.
+pub fn set_fonts(ctx: &egui::Context, cjk: bool) {
+    let mut fonts = FontDefinitions::default();
+    if cjk {
+        for candidates in CJK_FONTS {
+            let found = candidates
+                .iter()
+                .map(|name| font_path(name))
+                .find_map(|path| std::fs::read(&path).ok().map(|data| (path, data)));
+            let Some((path, data)) = found else {
+                warn!("No CJK font found, tried {:?}", candidates);
+                continue;
+            };
+            info!("Using {} for CJK text", path.display());
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            fonts.font_data.insert(name.clone(), Arc::new(FontData::from_owned(data)));
+            for family in [FontFamily::Proportional, FontFamily::Monospace] {
+                fonts.families.entry(family).or_default().push(name.clone());
+            }
+        }
+    }
+    ctx.set_fonts(fonts);
+}
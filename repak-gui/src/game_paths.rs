@@ -0,0 +1,55 @@
+use log::info;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Name of the known game path list, kept next to the config
+pub const GAME_PATHS_FILE: &str = "game_paths.json";
+
+/// Asset packages known to exist in the current game version. Mods replacing packages that
+/// aren't in here were most likely made for an older version.
+pub struct GamePaths {
+    packages: HashSet<String>,
+}
+
+impl GamePaths {
+    /// Reads a JSON array of paths relative to the game root (`Marvel/Content/...`), with or
+    /// without file extension
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let paths: Vec<String> = serde_json::from_str(&data)
+            .map_err(|e| format!("Expected a JSON list of game file paths: {}", e))?;
+        let packages = paths.iter().map(|p| package_path(p)).collect::<HashSet<_>>();
+        info!("Loaded {} known game paths from {}", packages.len(), path.display());
+        Ok(Self { packages })
+    }
+
+    pub fn count(&self) -> usize {
+        self.packages.len()
+    }
+
+    /// Assets of a mod (as listed by `conflicts::mod_files`) the game doesn't have. Only
+    /// packages are checked, other files may be new on purpose.
+    pub fn missing(&self, mod_files: &BTreeSet<String>) -> Vec<String> {
+        mod_files
+            .iter()
+            .filter(|file| file.ends_with(".uasset") || file.ends_with(".umap"))
+            .filter(|file| !self.packages.contains(&package_path(file)))
+            .cloned()
+            .collect()
+    }
+}
+
+// lowercase, relative to the game root and without extension, so "Marvel/Content/A.uasset"
+// and "marvel/content/a" are the same package
+fn package_path(path: &str) -> String {
+    let mut path = path.trim().replace('\\', "/").to_lowercase();
+    while let Some(rest) = path.strip_prefix("../").or_else(|| path.strip_prefix('/')) {
+        path = rest.to_string();
+    }
+    let file_start = path.rfind('/').map_or(0, |i| i + 1);
+    if let Some(dot) = path[file_start..].find('.') {
+        path.truncate(file_start + dot);
+    }
+    path
+}
@@ -0,0 +1,193 @@
+use crate::aes_keys::with_retoc_keys;
+use crate::file_table::FileEntry;
+use crate::utils::{format_bytes, MEMORY_MAP_UCAS};
+use eframe::egui::{self, RichText, ScrollArea, TextStyle};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::time::Duration;
+
+/// Bytes shown when the viewer opens, and added by each "Load more"
+const PAGE_SIZE: usize = 16 * 1024;
+
+/// Entries bigger than this are not read at all
+const MAX_READ_SIZE: u64 = 64 * 1024 * 1024;
+
+const BYTES_PER_ROW: usize = 16;
+
+/// Leading bytes of formats commonly found in mods
+const MAGICS: [(&[u8], &str); 10] = [
+    (&[0xC1, 0x83, 0x2A, 0x9E], "Unreal package (.uasset)"),
+    (b"\x89PNG", "PNG image"),
+    (&[0xFF, 0xD8, 0xFF], "JPEG image"),
+    (b"DDS ", "DDS texture"),
+    (b"BKHD", "Wwise sound bank"),
+    (b"AKPK", "Wwise file package"),
+    (b"RIFF", "RIFF container (WAV / WEM)"),
+    (b"OggS", "Ogg stream"),
+    (&[0xEF, 0xBB, 0xBF], "UTF-8 text"),
+    (b"{", "JSON"),
+];
+
+/// Raw byte view of a single pak entry or iostore package
+pub struct HexView {
+    pub title: String,
+    data: Result<Vec<u8>, String>,
+    /// Set while the entry is read on a worker thread, `data` is filled in once it is done
+    loading: Option<Receiver<Result<Vec<u8>, String>>>,
+    shown: usize,
+    /// Iostore packages have no magic, so they're labelled from where they came from
+    zen_package: bool,
+}
+
+impl HexView {
+    /// Opens the viewer and starts reading the entry on a worker thread
+    pub fn open(entry: &FileEntry, is_utoc: bool) -> Self {
+        let mut view = Self {
+            title: entry.file_path.clone(),
+            data: Ok(vec![]),
+            loading: None,
+            shown: PAGE_SIZE,
+            zen_package: is_utoc,
+        };
+        if entry.uncompressed > MAX_READ_SIZE {
+            view.data = Err(format!(
+                "Entry is {}, only entries up to {} can be inspected",
+                format_bytes(entry.uncompressed),
+                format_bytes(MAX_READ_SIZE)
+            ));
+            return view;
+        }
+        let (tx, rx) = channel();
+        let entry = entry.clone();
+        std::thread::spawn(move || {
+            let data = match is_utoc {
+                true => read_package(&entry),
+                false => read_pak_entry(&entry),
+            };
+            // the viewer may have been closed in the meantime
+            let _ = tx.send(data);
+        });
+        view.loading = Some(rx);
+        view
+    }
+
+    fn poll_loading(&mut self) {
+        let Some(loading) = &self.loading else {
+            return;
+        };
+        match loading.try_recv() {
+            Ok(data) => self.data = data,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => self.data = Err("Reading the entry failed unexpectedly".to_string()),
+        }
+        self.loading = None;
+    }
+
+    /// Draws the viewer window, returns false once it was closed
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        self.poll_loading();
+        let mut open = true;
+        egui::Window::new("Inspect bytes")
+            .id(egui::Id::new("hex_view"))
+            .open(&mut open)
+            .default_size([720., 480.])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.title).strong());
+                if self.loading.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Reading the entry...");
+                    });
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                    return;
+                }
+                let data = match &self.data {
+                    Ok(data) => data,
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                        return;
+                    }
+                };
+                let shown = self.shown.min(data.len());
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} of {} shown",
+                        format_bytes(shown as u64),
+                        format_bytes(data.len() as u64)
+                    ));
+                    ui.separator();
+                    let kind = detect_type(data)
+                        .or(self.zen_package.then_some("Zen package"))
+                        .unwrap_or("Unknown");
+                    ui.label(format!("Type: {}", kind));
+                });
+                ui.separator();
+
+                let row_height = ui.text_style_height(&TextStyle::Monospace);
+                let rows = shown.div_ceil(BYTES_PER_ROW);
+                ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .max_height(ui.available_height() - 30.)
+                    .show_rows(ui, row_height, rows, |ui, range| {
+                        for row in range {
+                            let start = row * BYTES_PER_ROW;
+                            let end = (start + BYTES_PER_ROW).min(shown);
+                            ui.label(RichText::new(hex_row(start, &data[start..end])).monospace());
+                        }
+                    });
+
+                if shown < data.len() && ui.button("Load more").clicked() {
+                    self.shown += PAGE_SIZE;
+                }
+            });
+        open
+    }
+}
+
+pub(crate) fn read_pak_entry(entry: &FileEntry) -> Result<Vec<u8>, String> {
+    let file = File::open(&entry.pak_path).map_err(|e| e.to_string())?;
+    entry
+        .pak_reader
+        .get(&entry.file_path, &mut BufReader::new(file))
+        .map_err(|e| e.to_string())
+}
+
+// packages are read through retoc so the ucas blocks get decompressed
+pub(crate) fn read_package(entry: &FileEntry) -> Result<Vec<u8>, String> {
+    let chunk_path = entry
+        .chunk_paths
+        .first()
+        .ok_or_else(|| "Package has no chunks".to_string())?;
+    let config = || retoc::Config {
+        container_header_version_override: None,
+        memory_map: MEMORY_MAP_UCAS.load(Ordering::Relaxed),
+        ..Default::default()
+    };
+    with_retoc_keys(config, |config| {
+        retoc::read_chunk_by_path(PathBuf::from(&entry.pak_path).with_extension("utoc"), chunk_path, config)
+    })
+    .map_err(|e| e.to_string())
+}
+
+fn detect_type(data: &[u8]) -> Option<&'static str> {
+    MAGICS
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, name)| *name)
+}
+
+fn hex_row(offset: usize, bytes: &[u8]) -> String {
+    let hex = bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let ascii = bytes
+        .iter()
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+        .collect::<String>();
+    format!("{:08x}  {:<47}  {}", offset, hex, ascii)
+}
@@ -0,0 +1,73 @@
+use log::{LevelFilter, Log, Metadata, Record};
+use simplelog::{Config, SharedLogger};
+use std::cell::RefCell;
+
+thread_local! {
+    /// Lines of the captures running on this thread, innermost last. A rayon thread waiting
+    /// on a mod can pick up another mod's install, which nests its capture in the first one.
+    static CAPTURES: RefCell<Vec<Vec<String>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` and returns the log lines it produced on the current thread, so the log of a
+/// single mod's install can be shown on its own. Lines logged by helper threads `f` starts
+/// only end up in the global log.
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<String>) {
+    CAPTURES.with(|captures| captures.borrow_mut().push(vec![]));
+    // popped by a guard, a panicking install must not leave its capture behind
+    struct Pop;
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            CAPTURES.with(|captures| captures.borrow_mut().pop());
+        }
+    }
+    let pop = Pop;
+    let result = f();
+    let lines = CAPTURES.with(|captures| captures.borrow_mut().last_mut().map(std::mem::take).unwrap_or_default());
+    drop(pop);
+    (result, lines)
+}
+
+/// Logger that hands every line to the innermost `capture` of the logging thread, added
+/// next to the terminal and file loggers
+pub struct InstallLogger {
+    level: LevelFilter,
+}
+
+impl InstallLogger {
+    pub fn new(level: LevelFilter) -> Box<InstallLogger> {
+        Box::new(InstallLogger { level })
+    }
+}
+
+impl Log for InstallLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        CAPTURES.with(|captures| {
+            if let Some(lines) = captures.borrow_mut().last_mut() {
+                lines.push(format!("[{}] {}", record.level(), record.args()));
+            }
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+impl SharedLogger for InstallLogger {
+    fn level(&self) -> LevelFilter {
+        self.level
+    }
+
+    fn config(&self) -> Option<&Config> {
+        None
+    }
+
+    fn as_log(self: Box<Self>) -> Box<dyn Log> {
+        Box::new(*self)
+    }
+}
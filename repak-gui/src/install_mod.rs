@@ -2,20 +2,28 @@ pub mod install_mod_logic;
 
 use crate::install_mod::install_mod_logic::archives::*;
 use crate::install_mod::install_mod_logic::pak_files::create_repak_from_pak;
-use crate::uasset_detection::{detect_mesh_files, detect_texture_files};
-use crate::utils::{collect_files, get_current_pak_characteristics};
+use crate::uasset_detection::{detect_skeletal_meshes, detect_texture_files};
+use crate::utils::{collect_files, get_current_pak_characteristics, mod_type_category};
 use crate::utoc_utils::read_utoc;
 use crate::{setup_custom_style, ICON};
 use eframe::egui;
 use eframe::egui::{Align, Checkbox, ComboBox, Context, Label, TextEdit};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use dirs;
 use serde_json::Value as JsonValue;
 use egui_extras::{Column, TableBuilder};
 use egui_flex::{item, Flex, FlexAlign};
-use install_mod_logic::install_mods_in_viewport;
-use log::{debug, error};
+use install_mod_logic::{
+    display_base_name, install_mods_in_viewport, with_thread_limit, InstallReport, ModNaming, ModUpdate,
+    NameCollision, DEFAULT_PRIORITY,
+};
+use install_mod_logic::content_root::detect_wrapper_depth;
+use install_mod_logic::patch_filter::PatchFilter;
+use install_mod_logic::project::RepakProject;
+use install_mod_logic::readme::find_readmes;
+use install_mod_logic::split_archives::{group_split_archives, is_split_part};
+use log::{debug, error, info, warn};
 use repak::utils::AesKey;
 use repak::Compression::Oodle;
 use repak::{Compression, PakReader};
@@ -26,11 +34,27 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::atomic::{AtomicBool, AtomicI32};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
 use std::sync::{Arc, LazyLock};
 use std::thread;
+use std::time::Duration;
 use tempfile::tempdir;
 use walkdir::WalkDir;
 
+/// Categories a mod can be tagged with by hand
+pub const MOD_CATEGORIES: [&str; 10] = [
+    "Character",
+    "UI",
+    "Audio",
+    "Movies",
+    "VFX",
+    "Map",
+    "Texture",
+    "Mesh",
+    "Animation",
+    "Other",
+];
+
 #[derive(Debug, Clone)]
 pub struct InstallableMod {
     pub mod_name: String,
@@ -40,11 +64,24 @@ pub struct InstallableMod {
     pub repak: bool,
     pub fix_mesh: bool,
     pub fix_textures: bool,
+    // which files fix_mesh / fix_textures may touch
+    pub patch_filter: PatchFilter,
     pub is_dir: bool,
+    // leading folders of a directory mod skipped when packing, see `content_root`
+    pub strip_components: usize,
+    // files left out when packing, see `collect_pack_files`
+    pub pack_ignore: String,
     pub editing: bool,
     pub path_hash_seed: String,
     pub mount_point: String,
     pub compression: Compression,
+    // Oodle level for the ucas blocks, None keeps the writer default
+    pub compression_level: Option<oodle_loader::CompressionLevel>,
+    // Oodle compress the companion chunknames pak of iostore conversions
+    pub compress_chunknames: bool,
+    // Oodle failed to load and the user chose to pack without compression, applies to the
+    // pak entries and to the blocks of iostore conversions
+    pub skip_compression: bool,
     pub reader: Option<PakReader>,
     pub mod_path: PathBuf,
     pub total_files: usize,
@@ -52,9 +89,27 @@ pub struct InstallableMod {
     // the only reason we keep this is to filter out the archives during collection
     pub is_archived: bool,
     pub enabled: bool,
+    // readmes that came in the same archive, kept with the mod once it's installed
+    pub readmes: Vec<PathBuf>,
+    // installed mod with the same name, see `ModInstallRequest::mark_updates`
+    pub update: Option<ModUpdate>,
+    // `mod_hash::hash_source` of mod_path, worked out early for updates
+    pub source_hash: Option<String>,
     // pub audio_mod: bool,
 }
 
+impl InstallableMod {
+    /// Seed the mod is packed with, a blank field packs with 0
+    pub fn parse_path_hash_seed(&self) -> Result<u64, String> {
+        let seed = self.path_hash_seed.trim();
+        if seed.is_empty() {
+            return Ok(0);
+        }
+        seed.parse()
+            .map_err(|_| format!("Path hash seed \"{}\" of {} is not a number", seed, self.mod_name))
+    }
+}
+
 impl Default for InstallableMod {
     fn default() -> Self {
         InstallableMod{
@@ -65,17 +120,26 @@ impl Default for InstallableMod {
             repak: false,
             fix_mesh: false,
             fix_textures: false,
+            patch_filter: PatchFilter::default(),
             is_dir: false,
+            strip_components: 0,
+            pack_ignore: String::new(),
             editing: false,
             path_hash_seed: "".to_string(),
             mount_point: "".to_string(),
             compression: Default::default(),
+            compression_level: None,
+            compress_chunknames: false,
+            skip_compression: false,
             reader: None,
             mod_path: Default::default(),
             total_files: 0,
             iostore: false,
             is_archived: false,
             enabled: true,
+            readmes: Vec::new(),
+            update: None,
+            source_hash: None,
         }
     }
 }
@@ -95,9 +159,89 @@ pub struct ModInstallRequest {
     pub show_unknown_tagging_dialog: bool,
     pub unknown_mod_being_tagged: Option<usize>,
     pub new_tag_input: String,
+    /// File naming used for this install, starts out as the global default
+    pub naming: ModNaming,
+    /// Priority `ModNaming::Suffix` appends
+    pub default_priority: u32,
+    /// Receives the report of the install thread once it is done
+    pub install_report: Option<Receiver<InstallReport>>,
+    /// Threads packing may use, 0 for all cores
+    pub threads: usize,
+    /// Leave the installed mods disabled
+    pub install_disabled: bool,
+    /// Set while the archives of the install are still being unpacked
+    pub extraction: Option<ArchiveExtraction>,
+    /// Game folder the installed files are linked into when `mod_directory` is a staging
+    /// folder, see `staging::link_installed`
+    pub link_dir: Option<PathBuf>,
+    /// Sub folder of `mod_directory` per type category, see `type_install_dir`
+    pub type_dirs: BTreeMap<String, String>,
+    /// Read every installed pak back once it is written, see `verify_installed`
+    pub verify: bool,
+    /// What happens to a mod whose name an installed mod already has
+    pub on_collision: NameCollision,
+    /// Start installing as soon as the mods are listed, for quick install mode
+    pub auto_install: bool,
+    /// Names of the drops waiting for this install to be closed, filled in by the main window
+    pub queued: Vec<String>,
+    /// Files dropped onto the install window, the main window queues them
+    pub dropped_files: Vec<egui::DroppedFile>,
+    /// Names of the mods the finished install put in, read by the main window for its usage
+    /// stats and to move them into `folder_id`
+    pub installed: Vec<String>,
+    /// Folder of the mod list the installed mods go into, when they were dropped on its header
+    pub folder_id: Option<String>,
+    /// Log lines of each installed mod, the main window keeps them for "View install log"
+    pub logs: Vec<(String, Vec<String>)>,
+    /// Source hash of each mod `mark_updates` found installed and whether it changed, hashed
+    /// on a worker thread
+    update_hashes: Option<Receiver<(PathBuf, Option<String>, Option<bool>)>>,
+}
+
+/// Archives being unpacked on a worker thread before the mods in them can be listed.
+/// Dropping it, e.g. by closing the install dialog, cancels the extraction.
+pub struct ArchiveExtraction {
+    pub progress: Arc<ExtractProgress>,
+    mods: Receiver<Vec<InstallableMod>>,
+}
+
+impl Drop for ArchiveExtraction {
+    fn drop(&mut self) {
+        self.progress.cancel.store(true, SeqCst);
+    }
 }
+
+impl ArchiveExtraction {
+    fn ui(&self, ui: &mut egui::Ui, show_callback: &mut bool) {
+        let done = self.progress.done.load(SeqCst);
+        let total = self.progress.total.load(SeqCst);
+        let current = self.progress.current.lock().unwrap().clone();
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 3.0);
+            ui.spinner();
+            ui.label(format!("Extracting {} ({}/{} files)...", current, done, total));
+            ui.add(
+                egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                    .desired_width(400.0)
+                    .show_percentage(),
+            );
+            if ui.button("Cancel").clicked() {
+                self.progress.cancel.store(true, SeqCst);
+                *show_callback = false;
+            }
+        });
+        ui.ctx().request_repaint_after(Duration::from_millis(200));
+    }
+}
+
 impl ModInstallRequest {
-    pub fn new(mods: Vec<InstallableMod>, mod_directory: PathBuf) -> Self {
+    pub fn new(
+        mods: Vec<InstallableMod>,
+        mod_directory: PathBuf,
+        naming: ModNaming,
+        default_priority: u32,
+        threads: usize,
+    ) -> Self {
         let len = mods.iter().map(|m| m.total_files).sum::<usize>();
         Self {
             animate: false,
@@ -112,10 +256,180 @@ impl ModInstallRequest {
             show_unknown_tagging_dialog: false,
             unknown_mod_being_tagged: None,
             new_tag_input: String::new(),
+            naming,
+            default_priority,
+            install_report: None,
+            threads,
+            install_disabled: false,
+            extraction: None,
+            link_dir: None,
+            type_dirs: BTreeMap::new(),
+            verify: true,
+            on_collision: NameCollision::default(),
+            auto_install: false,
+            queued: vec![],
+            dropped_files: vec![],
+            installed: vec![],
+            folder_id: None,
+            logs: vec![],
+            update_hashes: None,
+        }
+    }
+
+    /// Unpacks the archives among `paths` on a worker thread, the dialog shows the progress
+    /// until `extracted_mods` hands out the mods found
+    pub fn extract_archives(&mut self, paths: Vec<PathBuf>) {
+        let progress = Arc::new(ExtractProgress::default());
+        let worker_progress = progress.clone();
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let _ = tx.send(map_paths_to_mods_with_progress(&paths, &worker_progress));
+        });
+        self.extraction = Some(ArchiveExtraction { progress, mods: rx });
+    }
+
+    /// Mods found by `extract_archives` once it is done, empty when nothing was found in them,
+    /// and the archives that couldn't be extracted
+    pub fn extracted_mods(&mut self) -> Option<(Vec<InstallableMod>, Vec<String>)> {
+        let extraction = self.extraction.as_ref()?;
+        let mods = match extraction.mods.try_recv() {
+            Ok(mods) => mods,
+            Err(TryRecvError::Empty) => return None,
+            // the worker panicked
+            Err(TryRecvError::Disconnected) => vec![],
+        };
+        let problems = std::mem::take(&mut *extraction.progress.problems.lock().unwrap());
+        self.extraction = None;
+        Some((mods, problems))
+    }
+
+    /// Marks mods named like an installed one, ignoring the `_P` suffix, as an update of it.
+    /// `installed` holds each installed pak and the source hash of its last version, which
+    /// tells whether the files actually changed. The mods are hashed on a worker thread, see
+    /// `poll_update_hashes`.
+    pub fn mark_updates(&mut self, installed: &[(PathBuf, Option<String>)]) {
+        let base = |name: &str| display_base_name(name).to_lowercase();
+        let mut to_hash = vec![];
+        for installable_mod in &mut self.mods {
+            let name = base(&installable_mod.mod_name);
+            let Some((path, last_hash)) = installed
+                .iter()
+                .find(|(path, _)| base(&path.file_stem().unwrap_or_default().to_string_lossy()) == name)
+            else {
+                continue;
+            };
+            debug!("{} updates {}", installable_mod.mod_name, path.display());
+            installable_mod.update = Some(ModUpdate {
+                installed: path.clone(),
+                changed: None,
+                replace: true,
+            });
+            to_hash.push((installable_mod.mod_path.clone(), last_hash.clone()));
+        }
+        if to_hash.is_empty() {
+            return;
+        }
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            for (mod_path, last_hash) in to_hash {
+                let source_hash = crate::mod_hash::hash_source(&mod_path)
+                    .map_err(|e| warn!("Unable to hash {}: {}", mod_path.display(), e))
+                    .ok();
+                let changed = match (&last_hash, &source_hash) {
+                    (Some(old), Some(new)) => Some(old != new),
+                    _ => None,
+                };
+                if tx.send((mod_path, source_hash, changed)).is_err() {
+                    // the install dialog was closed
+                    return;
+                }
+            }
+        });
+        self.update_hashes = Some(rx);
+    }
+
+    /// Takes the hashes `mark_updates` has worked out so far
+    fn poll_update_hashes(&mut self, ctx: &egui::Context) {
+        let Some(hashes) = &self.update_hashes else {
+            return;
+        };
+        loop {
+            match hashes.try_recv() {
+                Ok((mod_path, source_hash, changed)) => {
+                    let Some(installable_mod) = self.mods.iter_mut().find(|m| m.mod_path == mod_path) else {
+                        continue;
+                    };
+                    debug!("{} changed: {:?}", installable_mod.mod_name, changed);
+                    // the install hashes mods itself when it starts before their hash is in
+                    installable_mod.source_hash = source_hash;
+                    if let Some(update) = &mut installable_mod.update {
+                        update.changed = changed;
+                    }
+                }
+                Err(TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(200));
+                    return;
+                }
+                Err(TryRecvError::Disconnected) => {
+                    self.update_hashes = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Patterns of files left out when packing folder mods, see `collect_pack_files`
+    pub fn set_pack_ignore(&mut self, pack_ignore: &str) {
+        for installable_mod in &mut self.mods {
+            installable_mod.pack_ignore = pack_ignore.to_string();
+        }
+    }
+
+    /// Unchecks the mesh and texture fixes the scan picked for each mod
+    pub fn clear_suggested_fixes(&mut self) {
+        for installable_mod in &mut self.mods {
+            installable_mod.fix_mesh = false;
+            installable_mod.fix_textures = false;
+        }
+    }
+
+    /// Tags every mod with what `type_tags` maps its type category to. The tags show up in
+    /// the dialog, so they can still be removed before installing.
+    pub fn apply_type_tags(&mut self, type_tags: &BTreeMap<String, String>) {
+        for installable_mod in &mut self.mods {
+            let Some(tag) = type_tags.get(mod_type_category(&installable_mod.mod_type)) else {
+                continue;
+            };
+            let tag = tag.trim();
+            if !tag.is_empty() && !installable_mod.custom_tags.iter().any(|t| t == tag) {
+                installable_mod.custom_tags.push(tag.to_string());
+                installable_mod.custom_tags.sort();
+            }
         }
     }
 }
 
+/// Picker for `ModNaming`, returns true when the choice changed
+pub(crate) fn naming_ui(ui: &mut egui::Ui, naming: &mut ModNaming, default_priority: u32) -> bool {
+    let before = *naming;
+    ui.horizontal(|ui| {
+        for option in [ModNaming::KeepOriginal, ModNaming::Suffix] {
+            ui.radio_value(naming, option, option.label(default_priority));
+        }
+        let custom = match *naming {
+            ModNaming::Priority(priority) => ModNaming::Priority(priority),
+            _ => ModNaming::Priority(default_priority),
+        };
+        ui.radio_value(naming, custom, custom.label(default_priority));
+        if let ModNaming::Priority(priority) = naming {
+            ui.add(egui::DragValue::new(priority).range(0..=DEFAULT_PRIORITY).prefix("_").suffix("_P"));
+        }
+    })
+    .response
+    .on_hover_text("Unreal loads paks with a higher number in the _<number>_P suffix later, so they override others");
+    *naming != before
+}
+
 impl ModInstallRequest {
     pub fn new_mod_dialog(&mut self, ctx: &egui::Context, show_callback: &mut bool) {
         let viewport_options = egui::ViewportBuilder::default()
@@ -135,11 +449,34 @@ impl ModInstallRequest {
                 );
 
                 setup_custom_style(ctx);
+                self.poll_update_hashes(ctx);
+                let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+                if !dropped.is_empty() {
+                    self.dropped_files.extend(dropped);
+                    ctx.request_repaint_of(egui::ViewportId::ROOT);
+                }
+                if let Some(extraction) = &self.extraction {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        extraction.ui(ui, show_callback);
+                        self.queue_ui(ui);
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        *show_callback = false;
+                    }
+                    return;
+                }
                 egui::CentralPanel::default().show(ctx, |ui| {
                     ui.label("Mods to install");
+                    self.queue_ui(ui);
                     
                     // Add filtering UI
                     self.show_filter_ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.label("File names:");
+                        naming_ui(ui, &mut self.naming, self.default_priority);
+                    });
+                    ui.checkbox(&mut self.install_disabled, "Install disabled")
+                        .on_hover_text("The mods are installed but stay off until you enable them in the list");
                     ui.separator();
                     
                     self.table_ui(ui);
@@ -153,11 +490,19 @@ impl ModInstallRequest {
                             .h_auto()
                             .show(ui, |ui| {
                                 let selection_bg_color = ctx.style().visuals.selection.bg_fill;
+                                // a bad seed would only fail once the mod is being packed
+                                let seed_error = self.mods.iter().find_map(|m| m.parse_path_hash_seed().err());
 
-                                let install_mod = ui.add(
-                                    item(),
-                                    egui::Button::new("Install mod").fill(selection_bg_color),
-                                );
+                                let install_mod = ui.add(item(), |ui: &mut egui::Ui| {
+                                    let button = ui.add_enabled(
+                                        seed_error.is_none(),
+                                        egui::Button::new("Install mod").fill(selection_bg_color),
+                                    );
+                                    match &seed_error {
+                                        Some(err) => button.on_disabled_hover_text(err),
+                                        None => button,
+                                    }
+                                });
 
                                 let cancel = ui.add(item(), egui::Button::new("Cancel"));
                                 cancel.clicked().then(|| {
@@ -165,19 +510,40 @@ impl ModInstallRequest {
                                     *show_callback = false;
                                 });
 
-                                if install_mod.clicked() {
+                                let auto_install = std::mem::take(&mut self.auto_install) && seed_error.is_none();
+                                if (install_mod.clicked() || auto_install) && confirm_oodle_available(&mut self.mods) {
                                     let mut mods = self.mods.to_vec(); // clone
 
                                     let dir = self.mod_directory.clone();
+                                    let link_dir = self.link_dir.clone();
+                                    let type_dirs = self.type_dirs.clone();
                                     let new_atomic = self.installed_mods_cbk.clone();
                                     let new_stop_thread = self.stop_thread.clone();
+                                    let naming = self.naming;
+                                    let default_priority = self.default_priority;
+                                    let threads = self.threads;
+                                    let install_disabled = self.install_disabled;
+                                    let verify = self.verify;
+                                    let on_collision = self.on_collision;
+                                    let (tx, rx) = channel();
+                                    self.install_report = Some(rx);
                                     self.joined_thread = Some(std::thread::spawn(move || {
-                                        install_mods_in_viewport(
-                                            &mut mods,
-                                            &dir,
-                                            &new_atomic,
-                                            &new_stop_thread,
-                                        );
+                                        let report = with_thread_limit(threads, || {
+                                            install_mods_in_viewport(
+                                                &mut mods,
+                                                &dir,
+                                                link_dir.as_deref(),
+                                                &type_dirs,
+                                                naming,
+                                                default_priority,
+                                                install_disabled,
+                                                verify,
+                                                on_collision,
+                                                &new_atomic,
+                                                &new_stop_thread,
+                                            )
+                                        });
+                                        let _ = tx.send(report);
                                     }));
                                     self.animate = true;
                                 }
@@ -187,10 +553,25 @@ impl ModInstallRequest {
                         let installed = self
                             .installed_mods_cbk
                             .load(std::sync::atomic::Ordering::SeqCst);
-                        let mut percentage = installed as f32 / total_mods;
-                        if installed == -255 {
-                            percentage = 1.0;
-                        }
+                        let report = match self.install_report.as_ref().map(|rx| rx.try_recv()) {
+                            Some(Ok(report)) => Some(report),
+                            // the install thread panicked, nothing tells which mods made it
+                            Some(Err(TryRecvError::Disconnected)) => Some(InstallReport {
+                                failed: self
+                                    .mods
+                                    .iter()
+                                    .filter(|m| m.enabled)
+                                    .map(|m| (m.mod_name.clone(), "the install stopped unexpectedly, see the log".to_string()))
+                                    .collect(),
+                                ..Default::default()
+                            }),
+                            Some(Err(TryRecvError::Empty)) | None => None,
+                        };
+                        let percentage = if report.is_some() {
+                            1.0
+                        } else {
+                            installed as f32 / total_mods
+                        };
                         ui.add(
                             egui::ProgressBar::new(percentage)
                                 .text("Installing mods...")
@@ -198,9 +579,13 @@ impl ModInstallRequest {
                                 .show_percentage(),
                         );
 
-                        if installed == -255 {
+                        if let Some(report) = report {
                             self.animate = false;
+                            self.install_report = None;
                             *show_callback = false;
+                            self.installed = report.succeeded.clone();
+                            self.logs = report.logs.clone();
+                            report_install_results(&report);
                         }
                     });
                 if ctx.input(|i| i.viewport().close_requested()) {
@@ -212,6 +597,14 @@ impl ModInstallRequest {
         self.show_unknown_tagging_dialog(ctx);
     }
 
+    fn queue_ui(&self, ui: &mut egui::Ui) {
+        if self.queued.is_empty() {
+            return;
+        }
+        ui.weak(format!("{} more file(s) queued, they open once this install is closed", self.queued.len()))
+            .on_hover_text(self.queued.join("\n"));
+    }
+
     fn show_filter_ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.checkbox(&mut self.filter_enabled, "Enable filtering");
@@ -272,24 +665,10 @@ impl ModInstallRequest {
                                  
                                 ui.separator();
                                  
-                                // Predefined categories based on existing system
-                                let categories = vec![
-                                    "Character".to_string(),
-                                    "UI".to_string(),
-                                    "Audio".to_string(),
-                                    "Movies".to_string(),
-                                    "VFX".to_string(),
-                                    "Map".to_string(),
-                                    "Texture".to_string(),
-                                    "Mesh".to_string(),
-                                    "Animation".to_string(),
-                                    "Other".to_string(),
-                                ];
-                                 
-                                for category in &categories {
+                                for category in MOD_CATEGORIES {
                                     if ui.button(category).clicked() {
                                         if let Some(mod_item) = self.mods.get_mut(mod_index) {
-                                            mod_item.mod_type = category.clone();
+                                            mod_item.mod_type = category.to_string();
                                         }
                                         self.show_unknown_tagging_dialog = false;
                                         self.unknown_mod_being_tagged = None;
@@ -312,6 +691,7 @@ impl ModInstallRequest {
 
     fn table_ui(&mut self, ui: &mut egui::Ui) {
         let available_height = ui.available_height();
+        let hashing = self.update_hashes.is_some();
         ui.separator();
 
         let table = TableBuilder::new(ui)
@@ -424,6 +804,18 @@ impl ModInstallRequest {
                                             if ui.button("✏").clicked() {
                                                 mods.editing = true;
                                             }
+                                            if let Some(update) = &mods.update {
+                                                let installed = update.installed.file_name().unwrap_or_default().to_string_lossy();
+                                                let (label, hover) = match update.changed {
+                                                    Some(true) => ("update", "the files changed since it was installed"),
+                                                    Some(false) => ("same version", "the files are the same as the installed version"),
+                                                    None if hashing && mods.source_hash.is_none() => {
+                                                        ("checking...", "comparing the files with the installed version")
+                                                    }
+                                                    None => ("installed", "installed before versions were recorded"),
+                                                };
+                                                ui.weak(label).on_hover_text(format!("Already installed as {}, {}", installed, hover));
+                                            }
                                         },
                                     );
                                 }
@@ -431,9 +823,24 @@ impl ModInstallRequest {
                         });
                         row.col(|ui| {
                             ui.horizontal(|ui| {
-                                ui.label(&mods.mod_type);
+                                // Folders get packed here, so their category decides how
+                                if mods.is_dir {
+                                    ComboBox::new(format!("mod_category_{}", rowidx), "")
+                                        .selected_text(&mods.mod_type)
+                                        .show_ui(ui, |ui| {
+                                            for category in MOD_CATEGORIES {
+                                                ui.selectable_value(
+                                                    &mut mods.mod_type,
+                                                    category.to_string(),
+                                                    category,
+                                                );
+                                            }
+                                        });
+                                } else {
+                                    ui.label(&mods.mod_type);
+                                }
                                 // Add "Tag" button for Unknown mods
-                                if mods.mod_type == "Unknown" || mods.mod_type.contains("(Unknown)") {
+                                if !mods.is_dir && (mods.mod_type == "Unknown" || mods.mod_type.contains("(Unknown)")) {
                                     if ui.small_button("🏷 Tag").clicked() {
                                         self.show_unknown_tagging_dialog = true;
                                         self.unknown_mod_being_tagged = Some(rowidx);
@@ -455,6 +862,25 @@ impl ModInstallRequest {
                         });
                         row.col(|ui| {
                             ui.collapsing("Options", |ui| {
+                                if let Some(update) = &mut mods.update {
+                                    let installed = update.installed.file_name().unwrap_or_default().to_string_lossy().to_string();
+                                    ui.checkbox(&mut update.replace, format!("Replace {}", installed))
+                                        .on_hover_text("The installed version is removed and its name, tags and folder carry over. Unchecked, both stay installed.");
+                                }
+                                if mods.is_dir {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Strip leading folders:");
+                                        ui.add(egui::DragValue::new(&mut mods.strip_components).range(0..=8));
+                                        if ui.small_button("Detect").clicked() {
+                                            mods.strip_components = detect_wrapper_depth(&mods.mod_path);
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text(
+                                        "For mods with extra folders around Marvel/..., packs from that \
+                                         many levels down. Detect counts the folders above Marvel.",
+                                    );
+                                }
                                 ui.add_enabled(
                                     !mods.is_dir,
                                     Checkbox::new(&mut mods.repak, "To repak"),
@@ -468,14 +894,55 @@ impl ModInstallRequest {
                                     Checkbox::new(&mut mods.fix_textures, "Fix textures (NoMipmaps)"),
                                 );
 
+                                if (mods.is_dir || mods.repak) && (mods.fix_mesh || mods.fix_textures) {
+                                    let hover = "Comma separated. \".uasset\" matches an extension, \
+                                                 anything else a path inside the mod, with * and ? \
+                                                 as wildcards (e.g. */Weapons/*).";
+                                    ui.horizontal(|ui| {
+                                        ui.label("Only patch:");
+                                        ui.add(
+                                            TextEdit::singleline(&mut mods.patch_filter.include)
+                                                .hint_text("everything"),
+                                        )
+                                        .on_hover_text(hover);
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Never patch:");
+                                        ui.add(
+                                            TextEdit::singleline(&mut mods.patch_filter.exclude)
+                                                .hint_text("nothing"),
+                                        )
+                                        .on_hover_text(hover);
+                                    });
+                                }
+
+                                ui.add_enabled(
+                                    mods.is_dir || mods.repak,
+                                    Checkbox::new(&mut mods.compress_chunknames, "Compress chunknames pak"),
+                                )
+                                .on_hover_text(
+                                    "Oodle compress the small companion .pak next to the utoc/ucas.\n\
+                                     Saves a little space per mod, but some loaders only read an \
+                                     uncompressed one. The pak is read back after writing and falls \
+                                     back to uncompressed if that fails.",
+                                );
+
                                 let text_edit = TextEdit::singleline(&mut mods.mount_point);
                                 ui.add(text_edit.hint_text("Enter mount point..."));
 
                                 // Text edit for path_hash_seed with hint
-                                let text_edit = TextEdit::singleline(&mut mods.path_hash_seed);
-                                ui.add(text_edit.hint_text("Enter path hash seed..."));
+                                let seed_error = mods.parse_path_hash_seed().err();
+                                let mut text_edit = TextEdit::singleline(&mut mods.path_hash_seed)
+                                    .hint_text("Enter path hash seed...");
+                                if seed_error.is_some() {
+                                    text_edit = text_edit.text_color(ui.visuals().error_fg_color);
+                                }
+                                let seed_edit = ui.add(text_edit);
+                                if let Some(err) = seed_error {
+                                    seed_edit.on_hover_text(err);
+                                }
 
-                                ComboBox::new("comp_level", "Compression Algorithm")
+                                ComboBox::new(format!("comp_level_{}", rowidx), "Compression Algorithm")
                                     .selected_text(format!("{:?}", mods.compression))
                                     .show_ui(ui, |ui| {
                                         ui.selectable_value(
@@ -557,6 +1024,96 @@ impl ModInstallRequest {
     }
 }
 
+/// Packing compresses with Oodle, which is loaded from a DLL that antivirus software likes to
+/// block or delete. When it can't be loaded the user is told how to fix it and can choose to
+/// pack without compression. Returns whether to go ahead with the install.
+fn confirm_oodle_available(mods: &mut [InstallableMod]) -> bool {
+    let packs = mods.iter().any(|m| m.is_dir || m.repak);
+    if !packs {
+        return true;
+    }
+    let Err(e) = oodle_loader::oodle() else {
+        return true;
+    };
+    error!("Unable to load Oodle: {}", e);
+    let result = rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Error)
+        .set_title("Oodle couldn't be loaded")
+        .set_description(format!(
+            "The Oodle compression library couldn't be loaded ({}).\n\n\
+             It is written next to repak-gui.exe on first use and is often blocked or removed by \
+             antivirus software. Add an exclusion for the Repak folder, or extract the release \
+             zip again, then restart Repak.\n\n\
+             Install without compression instead? The mods work but take more space, and mods \
+             that are already Oodle compressed can't be unpacked.",
+            e
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+    if !matches!(result, rfd::MessageDialogResult::Yes) {
+        return false;
+    }
+    for m in mods.iter_mut() {
+        m.skip_compression = true;
+        m.compress_chunknames = false;
+    }
+    true
+}
+
+/// Tells the user which mods failed and why, or how many went in when a batch succeeded
+fn report_install_results(report: &InstallReport) {
+    let failed = report
+        .failed
+        .iter()
+        .map(|(name, reason)| format!("{}: {}", name, reason))
+        .collect::<Vec<_>>();
+    let succeeded = report.succeeded.len();
+    let packed = report
+        .packed
+        .iter()
+        .map(|(name, sizes)| format!("{}: {}", name, sizes.describe()))
+        .collect::<Vec<_>>();
+    let mut packed_summary = if packed.is_empty() {
+        String::new()
+    } else {
+        format!("\n\nCompression:\n{}", packed.join("\n"))
+    };
+    if report.verified > 0 {
+        packed_summary.push_str(&format!("\n\n{} mod(s) were read back and verified.", report.verified));
+    }
+    if !report.collisions.is_empty() {
+        let collisions = report
+            .collisions
+            .iter()
+            .map(|(name, outcome)| format!("{}: {}", name, outcome))
+            .collect::<Vec<_>>();
+        packed_summary.push_str(&format!("\n\nAlready installed:\n{}", collisions.join("\n")));
+    }
+
+    if failed.is_empty() {
+        if succeeded > 1 || !packed.is_empty() || !report.collisions.is_empty() {
+            rfd::MessageDialog::new()
+                .set_title("Mods installed")
+                .set_description(format!("Installed {} mod(s).{}", succeeded, packed_summary))
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+        }
+        return;
+    }
+    rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Error)
+        .set_title("Some mods failed to install")
+        .set_description(format!(
+            "{} installed, {} failed:\n\n{}{}",
+            succeeded,
+            failed.len(),
+            failed.join("\n"),
+            packed_summary
+        ))
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show();
+}
+
 // Read global custom tags from the main config and pending file
 fn read_global_custom_tags() -> Vec<String> {
     let mut out: BTreeSet<String> = BTreeSet::new();
@@ -597,9 +1154,11 @@ fn read_global_custom_tags() -> Vec<String> {
     out.into_iter().collect()
 }
 
+/// Built-in Marvel Rivals key, used for the zero GUID unless a keys file overrides it
+pub const AES_KEY_HEX: &str = "0C263D8C22DCB085894899C3A3796383E9BF9DE0CBFB08C9BF2DEF2E84F29D74";
+
 pub static AES_KEY: LazyLock<AesKey> = LazyLock::new(|| {
-    AesKey::from_str("0C263D8C22DCB085894899C3A3796383E9BF9DE0CBFB08C9BF2DEF2E84F29D74")
-        .expect("Unable to initialise AES_KEY")
+    AesKey::from_str(AES_KEY_HEX).expect("Unable to initialise AES_KEY")
 });
 
 fn find_mods_from_archive(path: &str) -> Vec<InstallableMod> {
@@ -627,13 +1186,18 @@ fn find_mods_from_archive(path: &str) -> Vec<InstallableMod> {
                     // this is a mod of type s2, create a new Installable mod from its characteristics
                     let utoc_path = path.with_extension("utoc");
 
-                    let files = read_utoc(&utoc_path, &builder, &path);
-                    let files = files
-                        .iter()
-                        .map(|x| x.file_path.clone())
-                        .collect::<Vec<_>>();
-                    len = files.len();
-                    modtype = get_current_pak_characteristics(files);
+                    match read_utoc(&utoc_path, &builder, &path) {
+                        Ok(files) => {
+                            let files = files
+                                .iter()
+                                .map(|x| x.file_path.clone())
+                                .collect::<Vec<_>>();
+                            len = files.len();
+                            modtype = get_current_pak_characteristics(files);
+                        }
+                        // still installable, the type just stays unknown
+                        Err(e) => warn!("{}", e),
+                    }
                     iostore = true;
                 }
                 // IF ONLY PAK IS FOUND WE NEED TO EXTRACT AND INSTALL THE PAK
@@ -644,7 +1208,7 @@ fn find_mods_from_archive(path: &str) -> Vec<InstallableMod> {
                 }
 
                 let installable_mod = InstallableMod {
-                    mod_name: path.file_stem().unwrap().to_str().unwrap().to_string(),
+                    mod_name: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
                     mod_type: modtype.to_string(),
                     repak: true,
                     fix_mesh: false,
@@ -669,11 +1233,93 @@ fn find_mods_from_archive(path: &str) -> Vec<InstallableMod> {
     new_mods
 }
 
-fn map_to_mods_internal(paths: &[PathBuf]) -> Vec<InstallableMod> {
+// unpacks the archive at `path` into a temp folder and lists the mods in it
+fn mods_in_archive(path: &Path, progress: &ExtractProgress) -> std::io::Result<Vec<InstallableMod>> {
+    let tempdir = tempdir()
+        .unwrap()
+        .path()
+        .as_os_str()
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // the archive readers take &str paths
+    let Some(archive_path) = path.to_str() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Unsupported characters in archive path",
+        ));
+    };
+    extract_archive(archive_path, &tempdir, progress)?;
+
+    // Now find pak files / s2 archives and turn them into installable mods
+    let mut new_mods = find_mods_from_archive(&tempdir);
+    let readmes = find_readmes(Path::new(&tempdir));
+    for new_mod in &mut new_mods {
+        new_mod.readmes = readmes.clone();
+    }
+    Ok(new_mods)
+}
+
+// a cancelled extraction is not a problem worth reporting
+fn report_archive_error(archive: &str, e: &std::io::Error, progress: &ExtractProgress) {
+    match e.kind() {
+        std::io::ErrorKind::Interrupted => info!("Extraction of {} cancelled", archive),
+        _ => {
+            error!("Unable to extract {}: {}", archive, e);
+            progress.problems.lock().unwrap().push(format!("{}: {}", archive, e));
+        }
+    }
+}
+
+fn map_to_mods_internal(paths: &[PathBuf], progress: &ExtractProgress) -> Vec<InstallableMod> {
     let mut extensible_vec: Vec<InstallableMod> = Vec::new();
+
+    // split downloads are grouped from whichever part was dropped and extracted as one
+    let (paths, split_archives) = group_split_archives(paths);
+    let archive_total = paths
+        .iter()
+        .filter(|path| is_archive_path(path))
+        .filter_map(|path| path.to_str())
+        .map(archive_length)
+        .sum::<usize>();
+    progress.total.fetch_add(archive_total, SeqCst);
+    for archive in split_archives {
+        let archive = match archive {
+            Ok(archive) => archive,
+            Err(e) => {
+                error!("{}", e);
+                progress.problems.lock().unwrap().push(e);
+                continue;
+            }
+        };
+        let label = format!("{} ({} parts)", archive.name(), archive.part_count());
+        let join_dir = match tempdir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                report_archive_error(&label, &e, progress);
+                continue;
+            }
+        };
+        let result = archive.join(join_dir.path(), progress).and_then(|joined| {
+            progress.total.fetch_add(joined.to_str().map_or(0, archive_length), SeqCst);
+            mods_in_archive(&joined, progress)
+        });
+        match result {
+            Ok(mut new_mods) => extensible_vec.append(&mut new_mods),
+            Err(e) => report_archive_error(&label, &e, progress),
+        }
+    }
+
     let mut installable_mods = paths
         .iter()
         .map(|path| {
+            // a project folder packs its loose files under the name and settings it was unpacked with
+            let project = RepakProject::load(path);
+            let path = &match project {
+                Some(_) => RepakProject::files_dir(path),
+                None => path.clone(),
+            };
             let is_dir = path.clone().is_dir();
             let extension = path.extension().unwrap_or_default();
             let is_archive = extension == "zip" || extension == "rar";
@@ -683,6 +1329,7 @@ fn map_to_mods_internal(paths: &[PathBuf]) -> Vec<InstallableMod> {
             let mut len = 1;
             let mut auto_fix_mesh = false;
             let mut auto_fix_textures = false;
+            let mut strip_components = 0;
 
             if !is_dir && !is_archive {
                 let builder = repak::PakBuilder::new()
@@ -695,9 +1342,8 @@ fn map_to_mods_internal(paths: &[PathBuf]) -> Vec<InstallableMod> {
                         modtype = get_current_pak_characteristics(files.clone());
                         len = files.len();
                         
-                        // Auto-detect mesh and texture files in pak files
-                        auto_fix_mesh = detect_mesh_files(&files);
-                        auto_fix_textures = detect_texture_files(&files);
+                        auto_fix_mesh = detect_skeletal_meshes(&files);
+                        auto_fix_textures = suggest_texture_fix(&modtype, &files);
                     }
                     Err(e) => {
                         error!("Error reading pak file: {}", e);
@@ -711,48 +1357,48 @@ fn map_to_mods_internal(paths: &[PathBuf]) -> Vec<InstallableMod> {
                 collect_files(&mut files, path)?;
                 let files = files
                     .iter()
-                    .map(|s| s.to_str().unwrap().to_string())
+                    .map(|s| s.to_string_lossy().to_string())
                     .collect::<Vec<_>>();
                 len = files.len();
                 modtype = get_current_pak_characteristics(files.clone());
                 
-                // Auto-detect mesh and texture files
-                auto_fix_mesh = detect_mesh_files(&files);
-                auto_fix_textures = detect_texture_files(&files);
+                auto_fix_mesh = detect_skeletal_meshes(&files);
+                auto_fix_textures = suggest_texture_fix(&modtype, &files);
+                strip_components = detect_wrapper_depth(path);
             }
 
             if is_archive {
                 modtype = "Season 2 Archives".to_string();
-                let tempdir = tempdir()
-                    .unwrap()
-                    .path()
-                    .as_os_str()
-                    .to_str()
-                    .unwrap()
-                    .to_string();
-
-                if extension == "zip" {
-                    extract_zip(path.to_str().unwrap(), &tempdir).expect("Unable to install mod")
-                } else if extension == "rar" {
-                    extract_rar(path.to_str().unwrap(), &tempdir).expect("Unable to install mod")
+                match mods_in_archive(path, progress) {
+                    Ok(mut new_mods) => extensible_vec.append(&mut new_mods),
+                    Err(e) => {
+                        report_archive_error(&path.to_string_lossy(), &e, progress);
+                        return Err(repak::Error::Other(format!("Unable to extract {}: {}", path.display(), e)));
+                    }
                 }
-
-                // Now find pak files / s2 archives and turn them into installable mods
-                let mut new_mods = find_mods_from_archive(&tempdir);
-                extensible_vec.append(&mut new_mods);
             }
 
+            let (mod_name, mount_point, path_hash_seed) = match project {
+                Some(project) => (project.mod_name, project.mount_point, project.path_hash_seed),
+                None => (
+                    path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+                    "../../../".to_string(),
+                    "00000000".to_string(),
+                ),
+            };
+
             Ok(InstallableMod {
-                mod_name: path.file_stem().unwrap().to_str().unwrap().to_string(),
+                mod_name,
                 mod_type: modtype,
                 repak: !is_dir,
                 fix_mesh: auto_fix_mesh,
                 fix_textures: auto_fix_textures,
                 is_dir,
+                strip_components,
                 reader: pak,
                 mod_path: path.clone(),
-                mount_point: "../../../".to_string(),
-                path_hash_seed: "00000000".to_string(),
+                mount_point,
+                path_hash_seed,
                 total_files: len,
                 is_archived: is_archive,
                 ..Default::default()
@@ -768,17 +1414,77 @@ fn map_to_mods_internal(paths: &[PathBuf]) -> Vec<InstallableMod> {
     installable_mods
 }
 
+// the texture fix only matters for skins, other mods are left alone even if they ship textures
+fn suggest_texture_fix(mod_type: &str, files: &[String]) -> bool {
+    mod_type_category(mod_type) == "Character" && detect_texture_files(files)
+}
+
 pub fn map_paths_to_mods(paths: &[PathBuf]) -> Vec<InstallableMod> {
-    let installable_mods = map_to_mods_internal(paths);
+    let installable_mods = map_to_mods_internal(paths, &ExtractProgress::default());
     installable_mods
 }
 
-pub fn map_dropped_file_to_mods(dropped_files: &[egui::DroppedFile]) -> Vec<InstallableMod> {
-    let paths = dropped_files
-        .iter()
-        .map(|f| f.path.clone().unwrap())
-        .collect::<Vec<_>>();
+/// Zip and rar files and the parts of split archives
+pub fn is_archive_path(path: &Path) -> bool {
+    let extension = path.extension().unwrap_or_default();
+    extension == "zip" || extension == "rar" || is_split_part(path)
+}
+
+/// Zip and rar files are unpacked before their mods can be listed, which can take a while
+pub fn has_archives(paths: &[PathBuf]) -> bool {
+    paths.iter().any(|path| is_archive_path(path))
+}
 
-    let installable_mods = map_to_mods_internal(&paths);
+/// `map_paths_to_mods` counting the archive entries unpacked in `progress`. Nothing is
+/// returned once `progress` is cancelled.
+pub fn map_paths_to_mods_with_progress(paths: &[PathBuf], progress: &ExtractProgress) -> Vec<InstallableMod> {
+    let installable_mods = map_to_mods_internal(paths, progress);
+    if progress.cancel.load(SeqCst) {
+        return vec![];
+    }
     installable_mods
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{map_paths_to_mods, InstallableMod};
+    use std::fs;
+
+    fn with_seed(seed: &str) -> InstallableMod {
+        InstallableMod {
+            mod_name: "SomeMod_9999999_P".to_string(),
+            path_hash_seed: seed.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_path_hash_seed_accepts_numbers_and_blank() {
+        assert_eq!(with_seed(" 12345 ").parse_path_hash_seed(), Ok(12345));
+        assert_eq!(with_seed("").parse_path_hash_seed(), Ok(0));
+    }
+
+    #[test]
+    fn parse_path_hash_seed_rejects_non_numbers() {
+        for seed in ["0x1F", "-1", "abc", "18446744073709551616"] {
+            let err = with_seed(seed).parse_path_hash_seed().unwrap_err();
+            assert!(err.contains(seed) && err.contains("SomeMod_9999999_P"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn map_paths_to_mods_handles_unusual_names() {
+        let temp = tempfile::tempdir().unwrap();
+        let mod_dir = temp.path().join("Ünïcødé Mod ✨ (v2) 日本語");
+        let meshes = mod_dir.join("Marvel/Content/Marvel/Characters/1011/Meshes");
+        fs::create_dir_all(&meshes).unwrap();
+        fs::write(meshes.join("SK Hulk é.uasset"), b"").unwrap();
+
+        let mods = map_paths_to_mods(&[mod_dir.clone()]);
+        assert_eq!(mods.len(), 1);
+        assert_eq!(mods[0].mod_name, "Ünïcødé Mod ✨ (v2) 日本語");
+        assert!(mods[0].is_dir);
+        assert_eq!(mods[0].mod_path, mod_dir);
+        assert_eq!(mods[0].total_files, 1);
+    }
+}
@@ -1,142 +1,664 @@
 pub mod archives;
+pub mod compact;
+pub mod content_root;
+pub mod convert;
+pub mod extract_all;
 pub mod iotoc;
+pub mod normalize;
+pub mod patch_filter;
 pub mod pak_files;
 pub mod patch_meshes;
+pub mod project;
+pub mod readme;
+pub mod rename_pattern;
+pub mod split_archives;
+pub mod staging;
 
 use crate::install_mod::install_mod_logic::archives::*;
 use crate::install_mod::InstallableMod;
+use crate::aes_keys::open_pak;
+use crate::install_log;
+use crate::load_order::split_priority;
+use crate::utils::{format_bytes, mod_type_category};
 use iotoc::convert_to_iostore_directory;
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
 use pak_files::create_repak_from_pak;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::fs;
 use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
-use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::{BTreeMap, HashSet};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use dirs;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
+/// Priority number the default suffix uses unless changed in the settings, high enough to
+/// load after every game pak
+pub const DEFAULT_PRIORITY: u32 = 9999999;
+
+/// How installed mod files are named
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ModNaming {
+    /// Keep the name the mod came with
+    KeepOriginal,
+    /// Append `_<default priority>_P`, `_9999999_P` unless changed in the settings
+    #[default]
+    Suffix,
+    /// Append `_<priority>_P`
+    Priority(u32),
+}
+
+impl ModNaming {
+    pub fn label(&self, default_priority: u32) -> String {
+        match self {
+            ModNaming::KeepOriginal => "Keep original name".to_string(),
+            ModNaming::Suffix => format!("Append _{}_P", default_priority),
+            ModNaming::Priority(_) => "Custom priority".to_string(),
+        }
+    }
+}
+
+/// What an install does when a mod's name is taken by a mod already in the folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NameCollision {
+    /// Write over the installed mod's files
+    #[default]
+    Overwrite,
+    /// Leave the installed mod alone and don't install the new one
+    Skip,
+    /// Install the new mod as `<name>_1`, `<name>_2`, ... keeping the priority suffix
+    KeepBoth,
+}
+
+impl NameCollision {
+    pub const ALL: [NameCollision; 3] = [NameCollision::Overwrite, NameCollision::Skip, NameCollision::KeepBoth];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NameCollision::Overwrite => "Overwrite",
+            NameCollision::Skip => "Skip",
+            NameCollision::KeepBoth => "Keep both",
+        }
+    }
+}
+
+/// Extensions the files of an installed mod named `<base>` can have
+const INSTALLED_EXTENSIONS: [&str; 5] = ["pak", "bak_repak", "pak_disabled", "utoc", "ucas"];
+
+/// First file of a mod named `base` already in one of `mod_directories`
+fn installed_file(mod_directories: &[PathBuf], base: &str) -> Option<PathBuf> {
+    mod_directories
+        .iter()
+        .flat_map(|dir| INSTALLED_EXTENSIONS.iter().map(move |ext| dir.join(format!("{}.{}", base, ext))))
+        .find(|path| path.symlink_metadata().is_ok())
+}
+
+/// Removes every file of the mod named `base` from `mod_directories`, so a mod installed
+/// over it leaves none of them behind, e.g. the utoc and ucas of an iostore mod replaced by
+/// a classic pak
+fn remove_installed(mod_directories: &[PathBuf], base: &str) -> Result<(), String> {
+    let files = mod_directories
+        .iter()
+        .flat_map(|dir| INSTALLED_EXTENSIONS.iter().map(move |ext| dir.join(format!("{}.{}", base, ext))))
+        .filter(|path| path.symlink_metadata().is_ok());
+    for file in files {
+        fs::remove_file(&file).map_err(|e| format!("Unable to remove {} to overwrite it: {}", file.display(), e))?;
+        info!("Removed {}, it is overwritten", file.display());
+    }
+    Ok(())
+}
+
+/// `name` with `_<n>` put before its priority suffix, the first one neither installed in
+/// `mod_directories` nor in `taken` (lowercase names of the rest of the batch)
+fn free_name(name: &str, mod_directories: &[PathBuf], taken: &HashSet<String>) -> String {
+    let (base, _) = split_priority(name);
+    let suffix = &name[base.len()..];
+    (1..)
+        .map(|n| format!("{}_{}{}", base, n, suffix))
+        .find(|candidate| {
+            !taken.contains(&candidate.to_lowercase()) && installed_file(mod_directories, candidate).is_none()
+        })
+        .unwrap()
+}
+
+/// `name` as `naming` names it, `ModNaming::Suffix` appends `default_priority`. A `_P` or
+/// `_<priority>_P` the name already ends with is replaced.
+pub fn normalize_mod_base_name(name: &str, naming: ModNaming, default_priority: u32) -> String {
+    let priority = match naming {
+        ModNaming::KeepOriginal => return name.to_string(),
+        ModNaming::Suffix => default_priority,
+        ModNaming::Priority(priority) => priority,
+    };
+    let (base, _) = split_priority(name);
+    format!("{}_{}_P", base, priority)
+}
+
+/// Name shown in the mod list, without the `_<priority>_P` / `_P` suffix, whichever
+/// priority the mod was installed with.
+pub fn display_base_name(name: &str) -> &str {
+    split_priority(name).0
+}
+
+/// Chunk number of paks named `pakchunk<N>-...`, the convention the engine uses for paks
+/// split out by the cooker. These don't necessarily carry a `_P` suffix.
+pub fn pakchunk_index(name: &str) -> Option<u32> {
+    let rest = name
+        .get(..8)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("pakchunk"))
+        .map(|_| &name[8..])?;
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    rest[..digits].parse().ok()
+}
+
+/// One install of a mod, kept in its metadata to show when it was updated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModVersion {
+    /// Seconds since the Unix epoch
+    pub installed_at: u64,
+    /// `mod_hash::hash_source` of the files it was installed from
+    pub source_hash: String,
+}
+
+/// Installed mod that a mod in the install dialog has the same name as
+#[derive(Debug, Clone)]
+pub struct ModUpdate {
+    /// Pak of the installed mod, with the disabled extension when it is off
+    pub installed: PathBuf,
+    /// Whether the files differ from the installed version, unknown for mods installed
+    /// before versions were recorded
+    pub changed: Option<bool>,
+    /// Remove the installed mod once the new one is in, instead of keeping both
+    pub replace: bool,
+}
+
+/// A version waiting in `pending_versions.json` for the main app to store
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingVersion {
+    pub version: ModVersion,
+    /// Mod the version replaced, its name, tags and folder carry over to the new file
+    pub replaces: Option<PathBuf>,
+}
+
+/// Size of a mod's files before packing and of the pak/utoc/ucas that were written
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PackSizes {
+    pub input: u64,
+    pub output: u64,
+}
+
+impl PackSizes {
+    pub fn measure(input_files: &[PathBuf], mod_dir: &Path, base_name: &str) -> Self {
+        let size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            input: input_files.iter().map(|path| size(path)).sum(),
+            output: ["pak", "utoc", "ucas"]
+                .iter()
+                .map(|ext| size(&mod_dir.join(format!("{}.{}", base_name, ext))))
+                .sum(),
+        }
+    }
+
+    /// Output size as a fraction of the input, 0.25 means the mod shrank to a quarter
+    pub fn ratio(&self) -> f64 {
+        if self.input == 0 {
+            return 1.0;
+        }
+        self.output as f64 / self.input as f64
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} -> {} ({:.0}% of original)",
+            format_bytes(self.input),
+            format_bytes(self.output),
+            self.ratio() * 100.
+        )
+    }
+}
+
+/// What an install batch did, sent back to the install dialog once it is done
+#[derive(Debug, Clone, Default)]
+pub struct InstallReport {
+    pub succeeded: Vec<String>,
+    /// Mod name and the reason it failed
+    pub failed: Vec<(String, String)>,
+    /// Only mods packed here, copied mods keep whatever compression they had
+    pub packed: Vec<(String, PackSizes)>,
+    /// Mods read back successfully after writing them, see `verify_installed`
+    pub verified: usize,
+    /// Mods whose name was taken by an installed mod and what was done about it
+    pub collisions: Vec<(String, String)>,
+    /// Log lines of each mod's install, see `install_log::capture`
+    pub logs: Vec<(String, Vec<String>)>,
+}
+
+/// Runs `f` on a rayon pool with `threads` threads so every parallel step inside it stays
+/// within that limit. 0 uses every core.
+pub fn with_thread_limit<R: Send>(threads: usize, f: impl FnOnce() -> R + Send) -> R {
+    match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(f),
+        Err(e) => {
+            warn!("Unable to create a limited thread pool, using the default one: {}", e);
+            f()
+        }
+    }
+}
+
+/// Folder mods of `mod_type` are installed to: the sub folder `type_dirs` maps its type
+/// category to, inside `mod_directory`. Unmapped types, and sub folders that would end up
+/// outside `mod_directory`, stay in `mod_directory` itself.
+pub fn type_install_dir(mod_directory: &Path, type_dirs: &BTreeMap<String, String>, mod_type: &str) -> PathBuf {
+    let Some(sub) = type_dirs
+        .get(mod_type_category(mod_type))
+        .map(|sub| sub.trim())
+        .filter(|sub| !sub.is_empty())
+    else {
+        return mod_directory.to_path_buf();
+    };
+    let sub = Path::new(sub);
+    if !sub.components().all(|c| matches!(c, Component::Normal(_))) {
+        warn!("Ignoring install folder {} for {} mods, it has to stay inside the mods folder", sub.display(), mod_type);
+        return mod_directory.to_path_buf();
+    }
+    mod_directory.join(sub)
+}
+
+/// Installs `mods` to `mod_directory`. With `link_dir` set, `mod_directory` is a staging
+/// folder and the game folder `link_dir` gets links to the installed files.
 pub fn install_mods_in_viewport(
     mods: &mut [InstallableMod],
     mod_directory: &Path,
+    link_dir: Option<&Path>,
+    type_dirs: &BTreeMap<String, String>,
+    naming: ModNaming,
+    default_priority: u32,
+    install_disabled: bool,
+    verify: bool,
+    on_collision: NameCollision,
     installed_mods_ptr: &AtomicI32,
     stop_thread: &AtomicBool,
-) {
+) -> InstallReport {
+    // Ensure naming suffix consistency up-front for all flows
     for installable_mod in mods.iter_mut() {
-        // Ensure naming suffix consistency up-front for all flows
-        installable_mod.mod_name = normalize_mod_base_name(&installable_mod.mod_name);
-        
-        if !installable_mod.enabled{
+        installable_mod.mod_name = normalize_mod_base_name(&installable_mod.mod_name, naming, default_priority);
+    }
+
+    let mut report = InstallReport::default();
+    // Resolved before anything is written, so every install branch sees the final name
+    // and a suffixed name can't clash with another mod of the batch
+    let mut taken = mods.iter().filter(|m| m.enabled).map(|m| m.mod_name.to_lowercase()).collect::<HashSet<_>>();
+    let mut skipped = HashSet::new();
+    let mut overwritten = HashSet::new();
+    for installable_mod in mods.iter_mut().filter(|m| m.enabled) {
+        // an update replacing the installed mod writes over it on purpose
+        if installable_mod.update.as_ref().is_some_and(|u| u.replace) {
             continue;
         }
+        let mod_directories = [Some(mod_directory), link_dir]
+            .into_iter()
+            .flatten()
+            .map(|dir| type_install_dir(dir, type_dirs, &installable_mod.mod_type))
+            .collect::<Vec<_>>();
+        let Some(existing) = installed_file(&mod_directories, &installable_mod.mod_name) else {
+            continue;
+        };
+        let name = installable_mod.mod_name.clone();
+        let existing = existing.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let outcome = match on_collision {
+            NameCollision::Overwrite => {
+                overwritten.insert(name.to_lowercase());
+                format!("overwrote {}", existing)
+            }
+            NameCollision::Skip => {
+                skipped.insert(name.to_lowercase());
+                format!("skipped, {} is already installed", existing)
+            }
+            NameCollision::KeepBoth => {
+                let renamed = free_name(&name, &mod_directories, &taken);
+                taken.insert(renamed.to_lowercase());
+                installable_mod.mod_name = renamed.clone();
+                format!("installed as {}, {} is kept", renamed, existing)
+            }
+        };
+        info!("{} collides with {}: {}", name, existing, outcome);
+        report.collisions.push((name, outcome));
+    }
 
-pub fn normalize_mod_base_name(name: &str) -> String {
-    if name.ends_with("_9999999_P") {
-        name.to_string()
-    } else if name.ends_with("_P") {
-        // replace trailing _P with _9999999_P
-        let trimmed = name.strip_suffix("_P").unwrap_or(name);
-        format!("{}_9999999_P", trimmed)
-    } else {
-        format!("{}_9999999_P", name)
+    let report = Mutex::new(report);
+    let mut seen = HashSet::new();
+    let mut sequential = vec![];
+    let mut parallel = vec![];
+    for installable_mod in mods.iter().filter(|m| m.enabled) {
+        if skipped.contains(&installable_mod.mod_name.to_lowercase()) {
+            continue;
+        }
+        if !seen.insert(installable_mod.mod_name.to_lowercase()) {
+            report.lock().unwrap().failed.push((
+                installable_mod.mod_name.clone(),
+                "Another mod in this batch has the same name".to_string(),
+            ));
+            continue;
+        }
+        // The texture fixer runs its own capped pool of UAssetBridge processes, installing
+        // those mods one at a time keeps the total under that cap
+        if installable_mod.is_dir && !installable_mod.fix_textures {
+            parallel.push(installable_mod);
+        } else {
+            sequential.push(installable_mod);
+        }
     }
-}
 
-pub fn record_installed_tags(base_name: &str, tags: &Vec<String>) {
-    if tags.is_empty() { return; }
-    let mut cfg_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-    cfg_dir.push("repak_manager");
-    let _ = fs::create_dir_all(&cfg_dir);
-    let mut path = cfg_dir.clone();
-    path.push("pending_custom_tags.json");
-
-    let mut map: BTreeMap<String, Vec<String>> = if path.exists() {
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|s| serde_json::from_str::<BTreeMap<String, Vec<String>>>(&s).ok())
-            .unwrap_or_default()
-    } else { BTreeMap::new() };
-
-    let entry = map.entry(base_name.to_string()).or_default();
-    for t in tags {
-        if !entry.contains(t) { entry.push(t.clone()); }
-    }
-    entry.sort();
-    entry.dedup();
-    let _ = fs::write(&path, serde_json::to_string_pretty(&map).unwrap());
-}
-        
-        
+    let run_install = |installable_mod: &InstallableMod| {
         if stop_thread.load(Ordering::SeqCst) {
             warn!("Stopping thread");
-            break;
-        }
-
-        if installable_mod.iostore {
-            // copy the iostore files
-            let pak_path = installable_mod.mod_path.with_extension("pak");
-            let utoc_path = installable_mod.mod_path.with_extension("utoc");
-            let ucas_path = installable_mod.mod_path.with_extension("ucas");
-
-            // Ensure output names follow suffix rule
-            let base = normalize_mod_base_name(&installable_mod.mod_name);
-            let dests = vec![
-                (pak_path, format!("{}.pak", base)),
-                (utoc_path, format!("{}.utoc", base)),
-                (ucas_path, format!("{}.ucas", base)),
-            ];
-
-            for (src, dest_name) in dests {
-                if let Err(e) = std::fs::copy(&src, mod_directory.join(dest_name)) {
-                    error!("Unable to copy file {:?}: {:?}", src, e);
+            return;
+        }
+        let mod_directory = &type_install_dir(mod_directory, type_dirs, &installable_mod.mod_type);
+        if let Err(e) = fs::create_dir_all(mod_directory) {
+            error!("Unable to create {}: {}", mod_directory.display(), e);
+            report.lock().unwrap().failed.push((
+                installable_mod.mod_name.clone(),
+                format!("Unable to create {}: {}", mod_directory.display(), e),
+            ));
+            return;
+        }
+        // hashed before installing, the mesh fixer patches folder mods in place
+        let source_hash = installable_mod.source_hash.clone().or_else(|| {
+            crate::mod_hash::hash_source(&installable_mod.mod_path)
+                .map_err(|e| warn!("Unable to hash {}: {}", installable_mod.mod_path.display(), e))
+                .ok()
+        });
+        // a staging folder keeps the files, the game folder gets links to them
+        let game_directory = &match link_dir {
+            Some(link_dir) => type_install_dir(link_dir, type_dirs, &installable_mod.mod_type),
+            None => mod_directory.clone(),
+        };
+        // the files of the mod written over go first, the new mod may not have all of them
+        let replaced = match overwritten.contains(&installable_mod.mod_name.to_lowercase()) {
+            true => remove_installed(&[mod_directory.clone(), game_directory.clone()], &installable_mod.mod_name),
+            false => Ok(()),
+        };
+        // checked before an update removes the mod it replaces
+        let started = SystemTime::now();
+        let installed = replaced.and_then(|()| install_one(installable_mod, mod_directory, installed_mods_ptr)).and_then(|sizes| {
+            if verify {
+                verify_installed(mod_directory, &installable_mod.mod_name, started)?;
+                report.lock().unwrap().verified += 1;
+            }
+            if link_dir.is_some() {
+                staging::link_installed(mod_directory, game_directory, &installable_mod.mod_name)?;
+            }
+            Ok(sizes)
+        });
+        let (result, sizes) = match installed {
+            Ok(sizes) => {
+                info!("Installed mod: {}", installable_mod.mod_name);
+                let replaces = installable_mod.update.as_ref().filter(|u| u.replace).map(|u| u.installed.clone());
+                if let Some(source_hash) = source_hash {
+                    record_version(&installable_mod.mod_name, source_hash, replaces.clone());
+                }
+                if let Some(old) = &replaces {
+                    let staging_dir = link_dir.is_some().then_some(mod_directory.as_path());
+                    remove_replaced(game_directory, staging_dir, &installable_mod.mod_name, old, install_disabled);
                 }
+                if let Some(sizes) = sizes {
+                    info!("Packed {}: {}", installable_mod.mod_name, sizes.describe());
+                    record_pack_sizes(&installable_mod.mod_name, sizes);
+                }
+                readme::store_readmes(&installable_mod.mod_name, &installable_mod.readmes);
+                let result = if install_disabled {
+                    disable_installed(game_directory, &installable_mod.mod_name)
+                } else {
+                    Ok(())
+                };
+                (result, sizes)
             }
-            // Record tags for pickup by main app
-            record_installed_tags(&base, &installable_mod.custom_tags);
-            continue;
+            Err(e) => {
+                error!("Failed to install {}: {}", installable_mod.mod_name, e);
+                (Err(e), None)
+            }
+        };
+        let mut report = report.lock().unwrap();
+        let name = installable_mod.mod_name.clone();
+        if let Some(sizes) = sizes {
+            report.packed.push((name.clone(), sizes));
+        }
+        match result {
+            Ok(()) => report.succeeded.push(name),
+            Err(e) => report.failed.push((name, e)),
         }
+    };
+    let install = |installable_mod: &InstallableMod| {
+        let ((), lines) = install_log::capture(|| run_install(installable_mod));
+        if !lines.is_empty() {
+            report.lock().unwrap().logs.push((installable_mod.mod_name.clone(), lines));
+        }
+    };
+
+    sequential.into_iter().for_each(&install);
+    parallel.into_par_iter().for_each(install);
 
-        if installable_mod.repak {
-            if let Err(e) = create_repak_from_pak(
-                installable_mod,
-                PathBuf::from(mod_directory),
-                installed_mods_ptr,
-            ) {
-                error!("Failed to create repak from pak: {}", e);
-            } else {
-                let base = normalize_mod_base_name(&installable_mod.mod_name);
-                record_installed_tags(&base, &installable_mod.custom_tags);
+    report.into_inner().unwrap()
+}
+
+/// Opens the pak just written as `<base>.pak` again, so a write cut short by a full disk or
+/// a pak the known AES keys can't read fails the install instead of the game. The utoc and
+/// ucas of iostore output have to be there and non-empty too. The files of a mod that fails
+/// are removed, the game would choke on them, but only those written since `started`: an
+/// install over another mod leaves the files of that one it didn't replace.
+fn verify_installed(mod_directory: &Path, base: &str, started: SystemTime) -> Result<(), String> {
+    let pak = mod_directory.join(format!("{}.pak", base));
+    let files = [pak.clone(), pak.with_extension("utoc"), pak.with_extension("ucas")];
+    // file systems keep coarser times than the clock, FAT down to 2 seconds
+    let since = started.checked_sub(Duration::from_secs(2)).unwrap_or(started);
+    let written = |file: &Path| {
+        fs::metadata(file).and_then(|meta| meta.modified()).is_ok_and(|modified| modified >= since)
+    };
+    let iostore = written(&files[1]) || written(&files[2]);
+    let result = open_pak(&pak)
+        .map_err(|e| format!("Written pak can't be read back: {}", e))
+        .and_then(|_| match iostore {
+            true => files[1..].iter().try_for_each(|file| match fs::metadata(file) {
+                Ok(meta) if meta.len() > 0 => Ok(()),
+                _ => Err(format!("{} is missing or empty after writing", file.display())),
+            }),
+            false => Ok(()),
+        });
+    match &result {
+        Ok(()) => debug!("Verified {}", pak.display()),
+        Err(e) => {
+            error!("Verifying {} failed: {}", base, e);
+            for file in files.iter().filter(|file| written(file)) {
+                if let Err(e) = fs::remove_file(file) {
+                    warn!("Unable to remove broken {}: {}", file.display(), e);
+                }
             }
         }
+    }
+    result
+}
 
-        // This shit shouldnt even be possible why do I still have this in the codebase???
-        if !installable_mod.repak && !installable_mod.is_dir {
-            // just move files to the correct location
-            info!(
-                "Copying mod instead of repacking: {}",
-                installable_mod.mod_name
-            );
-            let base = normalize_mod_base_name(&installable_mod.mod_name);
-            std::fs::copy(&installable_mod.mod_path, mod_directory.join(format!("{}.pak", &base)))
-            .unwrap();
-            record_installed_tags(&base, &installable_mod.custom_tags);
-            installed_mods_ptr.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+/// Removes the files of the installed mod an update replaces. Files the update was just
+/// written to are left alone, they share names when only the extension of the pak differs.
+fn remove_replaced(
+    mod_directory: &Path,
+    staging_dir: Option<&Path>,
+    base: &str,
+    old_pak: &Path,
+    install_disabled: bool,
+) {
+    let new_pak_ext = if install_disabled { "bak_repak" } else { "pak" };
+    let new_files = [new_pak_ext, "utoc", "ucas"].map(|ext| mod_directory.join(format!("{}.{}", base, ext)));
+    let new_staged = staging_dir.map(|dir| ["pak", "utoc", "ucas"].map(|ext| dir.join(format!("{}.{}", base, ext))));
+    for old in [old_pak.to_path_buf(), old_pak.with_extension("utoc"), old_pak.with_extension("ucas")] {
+        if old.symlink_metadata().is_err() || new_files.contains(&old) {
             continue;
         }
+        // the link goes first, the staged file behind it is found through it
+        let staged = staging_dir.map(|dir| staging::staged_file(dir, &old));
+        match fs::remove_file(&old) {
+            Ok(()) => info!("Removed {}, replaced by {}", old.display(), base),
+            Err(e) => warn!("Unable to remove replaced {}: {}", old.display(), e),
+        }
+        let Some(staged) = staged.filter(|staged| staged.is_file()) else {
+            continue;
+        };
+        if new_staged.as_ref().is_some_and(|new| new.contains(&staged)) {
+            continue;
+        }
+        match fs::remove_file(&staged) {
+            Ok(()) => info!("Removed staged {}, replaced by {}", staged.display(), base),
+            Err(e) => warn!("Unable to remove replaced {}: {}", staged.display(), e),
+        }
+    }
+}
 
-        if installable_mod.is_dir {
-            let res = convert_to_iostore_directory(
-                installable_mod,
-                PathBuf::from(&mod_directory),
-                PathBuf::from(&installable_mod.mod_path),
-                installed_mods_ptr,
-            );
-            if let Err(e) = res {
-                error!("Failed to create repak from pak: {}", e);
-            } else {
-                info!("Installed mod: {}", installable_mod.mod_name);
-            }
+/// Disables a freshly installed mod the way the mod list does, by renaming its pak to
+/// `.bak_repak`. The utoc and ucas keep their names, the game skips them without the pak.
+fn disable_installed(mod_directory: &Path, base: &str) -> Result<(), String> {
+    let pak = mod_directory.join(format!("{}.pak", base));
+    fs::rename(&pak, pak.with_extension("bak_repak")).map_err(|e| {
+        error!("Failed to disable {}: {}", base, e);
+        format!("Installed, but could not be disabled: {}", e)
+    })?;
+    info!("Installed {} disabled", base);
+    Ok(())
+}
+
+fn install_one(
+    installable_mod: &InstallableMod,
+    mod_directory: &Path,
+    installed_mods_ptr: &AtomicI32,
+) -> Result<Option<PackSizes>, String> {
+    let base = &installable_mod.mod_name;
+
+    if installable_mod.iostore {
+        copy_iostore_files(&installable_mod.mod_path, mod_directory, base)?;
+        // Record tags for pickup by main app
+        record_installed_tags(base, &installable_mod.custom_tags);
+        return Ok(None);
+    }
+
+    if installable_mod.repak {
+        let sizes = create_repak_from_pak(
+            installable_mod,
+            PathBuf::from(mod_directory),
+            installed_mods_ptr,
+        )
+        .map_err(|e| format!("Failed to create repak from pak: {}", e))?;
+        record_installed_tags(base, &installable_mod.custom_tags);
+        return Ok(Some(sizes));
+    }
+
+    // This shit shouldnt even be possible why do I still have this in the codebase???
+    if !installable_mod.is_dir {
+        // just move files to the correct location
+        info!(
+            "Copying mod instead of repacking: {}",
+            installable_mod.mod_name
+        );
+        std::fs::copy(&installable_mod.mod_path, mod_directory.join(format!("{}.pak", base)))
+            .map_err(|e| format!("Unable to copy file {:?}: {}", installable_mod.mod_path, e))?;
+        record_installed_tags(base, &installable_mod.custom_tags);
+        installed_mods_ptr.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        return Ok(None);
+    }
+
+    // paths inside the mod are taken relative to this, so wrapper folders don't end up in them
+    let root = content_root::content_root(&installable_mod.mod_path, installable_mod.strip_components)?;
+    let sizes = convert_to_iostore_directory(
+        installable_mod,
+        PathBuf::from(&mod_directory),
+        root,
+        installed_mods_ptr,
+    )
+    .map_err(|e| format!("Failed to convert directory: {}", e))?;
+    record_installed_tags(base, &installable_mod.custom_tags);
+    Ok(Some(sizes))
+}
+
+/// Copies the pak/utoc/ucas of an iostore mod as `<base>.*`. All three have to be there
+/// and non-empty, the game fails in confusing ways on a partial set.
+fn copy_iostore_files(mod_path: &Path, mod_directory: &Path, base: &str) -> Result<(), String> {
+    let sources = ["pak", "utoc", "ucas"].map(|ext| mod_path.with_extension(ext));
+    for src in &sources {
+        match fs::metadata(src) {
+            Ok(meta) if meta.len() > 0 => {}
+            Ok(_) => return Err(format!("{} is empty", src.display())),
+            Err(_) => return Err(format!("{} is missing", src.display())),
+        }
+    }
+
+    for src in &sources {
+        let ext = src.extension().unwrap_or_default().to_string_lossy();
+        let dest = mod_directory.join(format!("{}.{}", base, ext));
+        let copied = fs::copy(src, &dest).map_err(|e| format!("Unable to copy file {:?}: {}", src, e))?;
+        let expected = fs::metadata(src).map(|m| m.len()).unwrap_or_default();
+        let written = fs::metadata(&dest).map(|m| m.len()).unwrap_or_default();
+        if copied != expected || written != expected {
+            return Err(format!(
+                "{} was not copied completely ({} of {} bytes)",
+                dest.display(),
+                written,
+                expected
+            ));
         }
     }
-    // set i32 to -255 magic value to indicate mod installation is done
-    AtomicI32::store(installed_mods_ptr, -255, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Updates the entry of `base_name` in the pending file `file` of the config dir, which the
+/// main app stores in the mod's metadata once the new pak shows up. `update` gets the entry
+/// already there, if any, and returns the new one.
+pub fn update_pending<T>(file: &str, base_name: &str, update: impl FnOnce(Option<T>) -> T)
+where
+    T: Serialize + serde::de::DeserializeOwned,
+{
+    let mut cfg_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    cfg_dir.push("repak_manager");
+    let _ = fs::create_dir_all(&cfg_dir);
+    let path = cfg_dir.join(file);
+
+    // installs run in parallel, keep the read-modify-write from interleaving
+    static LOCK: Mutex<()> = Mutex::new(());
+    let _guard = LOCK.lock().unwrap();
+    let mut map: BTreeMap<String, T> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let value = update(map.remove(base_name));
+    map.insert(base_name.to_string(), value);
+    let _ = fs::write(&path, serde_json::to_string_pretty(&map).unwrap());
+}
+
+pub fn record_installed_tags(base_name: &str, tags: &Vec<String>) {
+    if tags.is_empty() { return; }
+    update_pending("pending_custom_tags.json", base_name, |entry: Option<Vec<String>>| {
+        let mut entry = entry.unwrap_or_default();
+        for t in tags {
+            if !entry.contains(t) { entry.push(t.clone()); }
+        }
+        entry.sort();
+        entry.dedup();
+        entry
+    });
+}
+
+/// Like `record_pack_sizes`, leaves the installed version and the mod it replaced for the
+/// main app to store in the mod's metadata
+pub fn record_version(base_name: &str, source_hash: String, replaces: Option<PathBuf>) {
+    let installed_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let pending = PendingVersion {
+        version: ModVersion { installed_at, source_hash },
+        replaces,
+    };
+    update_pending("pending_versions.json", base_name, |_| pending);
+}
+
+/// Like `record_installed_tags`, leaves the sizes in a pending file the main app stores in
+/// the mod's metadata once the new pak shows up
+pub fn record_pack_sizes(base_name: &str, sizes: PackSizes) {
+    update_pending("pending_pack_sizes.json", base_name, |_| sizes);
 }
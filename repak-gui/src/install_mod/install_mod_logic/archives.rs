@@ -1,8 +1,81 @@
-pub fn extract_zip(zip_path: &str, output_dir: &str) -> io::Result<()> {
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::Mutex;
+use unrar::Archive;
+use zip::ZipArchive;
+
+/// Progress of the archives unpacked for an install, shared with the install dialog
+#[derive(Debug, Default)]
+pub struct ExtractProgress {
+    /// Entries unpacked so far, over all archives of the install
+    pub done: AtomicUsize,
+    pub total: AtomicUsize,
+    /// File name of the archive being unpacked
+    pub current: Mutex<String>,
+    /// Set by the dialog, extraction stops at the next entry or 1 MiB of a zip entry
+    pub cancel: AtomicBool,
+    /// Archives that couldn't be extracted and why, shown once extraction is done
+    pub problems: Mutex<Vec<String>>,
+}
+
+impl ExtractProgress {
+    fn check_cancel(&self) -> io::Result<()> {
+        match self.cancel.load(SeqCst) {
+            true => Err(io::Error::new(io::ErrorKind::Interrupted, "Extraction cancelled")),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Unpacks the zip or rar at `archive_path` into `output_dir`. The half written `output_dir`
+/// is removed again when extraction fails or is cancelled.
+pub fn extract_archive(archive_path: &str, output_dir: &str, progress: &ExtractProgress) -> io::Result<()> {
+    let path = Path::new(archive_path);
+    *progress.current.lock().unwrap() = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let result = match path.extension().unwrap_or_default().to_str() {
+        Some("zip") => extract_zip(archive_path, output_dir, progress),
+        Some("rar") => extract_rar(archive_path, output_dir, progress),
+        _ => Err(io::Error::new(io::ErrorKind::Unsupported, "Not a zip or rar archive")),
+    };
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(output_dir);
+    }
+    result
+}
+
+/// Entries in the zip or rar at `archive_path`, 0 when it can't be read
+pub fn archive_length(archive_path: &str) -> usize {
+    match Path::new(archive_path).extension().unwrap_or_default().to_str() {
+        Some("zip") => zip_length(archive_path).unwrap_or(0),
+        Some("rar") => rar_length(archive_path).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+// io::copy in 1 MiB steps, a single entry of a big archive can take a while on its own
+pub(crate) fn copy_cancellable(reader: &mut impl Read, writer: &mut impl Write, progress: &ExtractProgress) -> io::Result<()> {
+    let mut buf = vec![0; 1 << 20];
+    loop {
+        progress.check_cancel()?;
+        let read = match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..read])?;
+    }
+}
+
+pub fn extract_zip(zip_path: &str, output_dir: &str, progress: &ExtractProgress) -> io::Result<()> {
     let file = File::open(zip_path)?;
     let mut archive = ZipArchive::new(file)?;
 
     for i in 0..archive.len() {
+        progress.check_cancel()?;
         let mut file = archive.by_index(i)?;
         let outpath = Path::new(output_dir).join(file.mangled_name());
 
@@ -15,31 +88,33 @@ pub fn extract_zip(zip_path: &str, output_dir: &str) -> io::Result<()> {
                 }
             }
             let mut outfile = File::create(&outpath)?;
-            io::copy(&mut file, &mut outfile)?;
+            copy_cancellable(&mut file, &mut outfile, progress)?;
         }
+        progress.done.fetch_add(1, SeqCst);
     }
 
     Ok(())
 }
 
-use std::fs::File;
-use std::io;
-use unrar::Archive;
-use std::path::Path;
-use zip::ZipArchive;
+fn unrar_error(e: unrar::error::UnrarError) -> io::Error {
+    io::Error::other(e.to_string())
+}
 
-pub fn extract_rar(rar_path: &str, output_dir: &str) -> Result<(), unrar::error::UnrarError> {
+pub fn extract_rar(rar_path: &str, output_dir: &str, progress: &ExtractProgress) -> io::Result<()> {
     let output_dir = Path::new(output_dir);
     let mut archive =
         Archive::new(rar_path)
-            .open_for_processing()?;
-    while let Some(header) = archive.read_header()? {
+            .open_for_processing()
+            .map_err(unrar_error)?;
+    while let Some(header) = archive.read_header().map_err(unrar_error)? {
+        progress.check_cancel()?;
         let filename = header.entry().filename.clone();
         archive = if header.entry().is_file() {
-            header.extract_to(output_dir.join(filename))?
+            header.extract_to(output_dir.join(filename)).map_err(unrar_error)?
         } else {
-            header.skip()?
+            header.skip().map_err(unrar_error)?
         };
+        progress.done.fetch_add(1, SeqCst);
     }
     Ok(())
 }
@@ -57,4 +132,4 @@ pub fn zip_length(zip_path: &str) -> Result<usize, io::Error> {
     let file = File::open(zip_path)?;
     let archive = ZipArchive::new(file)?;
     Ok(archive.len())
-}
\ No newline at end of file
+}
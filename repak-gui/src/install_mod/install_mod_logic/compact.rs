@@ -0,0 +1,209 @@
+use crate::aes_keys::with_retoc_keys;
+use crate::install_mod::install_mod_logic::pak_files::{extract_pak_to_dir, repak_dir};
+use crate::install_mod::{InstallableMod, AES_KEY};
+use crate::utils::{get_current_pak_characteristics, mod_pak_files, MEMORY_MAP_UCAS};
+use log::{info, warn};
+use oodle_loader::CompressionLevel;
+use repak::Compression;
+use retoc::{action_recompress, Config};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI32, Ordering};
+use tempfile::tempdir;
+
+/// Oodle levels offered by the optimize action, slowest/smallest last
+pub const OPTIMIZE_LEVELS: [(&str, CompressionLevel); 5] = [
+    ("Optimal1", CompressionLevel::Optimal1),
+    ("Optimal2", CompressionLevel::Optimal2),
+    ("Optimal3", CompressionLevel::Optimal3),
+    ("Optimal4", CompressionLevel::Optimal4),
+    ("Optimal5", CompressionLevel::Optimal5),
+];
+
+/// Number of steps reported through the progress counter of `compact_mod`
+pub const COMPACT_STEPS: i32 = 3;
+
+/// Outcome of a finished recompression. The original files stay in `backup_dir`
+/// until the user either keeps the new files or restores the old ones.
+#[derive(Debug, Clone)]
+pub struct CompactResult {
+    pub mod_path: PathBuf,
+    pub original_size: u64,
+    pub compacted_size: u64,
+    pub backup_dir: PathBuf,
+    original_files: Vec<PathBuf>,
+    written_files: Vec<PathBuf>,
+}
+
+impl CompactResult {
+    pub fn saved_bytes(&self) -> i64 {
+        self.original_size as i64 - self.compacted_size as i64
+    }
+
+    /// Keep the recompressed files and drop the backup
+    pub fn confirm(&self) -> std::io::Result<()> {
+        fs::remove_dir_all(&self.backup_dir)
+    }
+
+    /// Put the original files back and remove anything the recompression added
+    pub fn restore(&self) -> std::io::Result<()> {
+        for written in &self.written_files {
+            if !self.original_files.contains(written) && written.exists() {
+                fs::remove_file(written)?;
+            }
+        }
+        for original in &self.original_files {
+            fs::copy(backup_path(&self.backup_dir, original)?, original)?;
+        }
+        fs::remove_dir_all(&self.backup_dir)
+    }
+}
+
+/// Audio and movie mods are already compressed media, recompressing them gains nothing
+pub fn is_compactable(mod_type: &str) -> bool {
+    mod_type != "Audio" && mod_type != "Movies"
+}
+
+fn total_size(paths: &[PathBuf]) -> u64 {
+    paths
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Where `file` is kept inside `backup_dir`
+fn backup_path(backup_dir: &Path, file: &Path) -> std::io::Result<PathBuf> {
+    let name = file.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid file path {:?}", file))
+    })?;
+    Ok(backup_dir.join(name))
+}
+
+fn backup_root() -> PathBuf {
+    let mut dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    dir.push("repak_manager");
+    dir.push("compact_backup");
+    dir
+}
+
+/// Recompresses an installed mod at a higher Oodle level.
+///
+/// Iostore mods get their ucas blocks rewritten in place, classic paks are unpacked and
+/// repacked as classic paks through `repak_dir`, so a mod keeps its format. The originals are copied to a backup folder
+/// first, see `CompactResult::confirm` and `CompactResult::restore`. `type_override` replaces
+/// the detected mod type when rebuilding a classic pak.
+pub fn compact_mod(
+    pak_path: &Path,
+    type_override: Option<&str>,
+    level: CompressionLevel,
+    progress: &AtomicI32,
+) -> Result<CompactResult, repak::Error> {
+    let stem = pak_path
+        .file_stem()
+        .ok_or_else(|| repak::Error::Other(format!("Invalid mod path {:?}", pak_path)))?
+        .to_string_lossy()
+        .to_string();
+    let original_files = mod_pak_files(pak_path);
+    let original_size = total_size(&original_files);
+
+    let backup_dir = backup_root().join(&stem);
+    if backup_dir.exists() {
+        fs::remove_dir_all(&backup_dir)?;
+    }
+    fs::create_dir_all(&backup_dir)?;
+    for file in &original_files {
+        fs::copy(file, backup_path(&backup_dir, file)?)?;
+    }
+    progress.fetch_add(1, Ordering::SeqCst);
+
+    let staging = tempdir()?;
+    let utoc_path = pak_path.with_extension("utoc");
+    if utoc_path.exists() {
+        let config = || Config {
+            container_header_version_override: None,
+            oodle_compression_level: Some(level),
+            memory_map: MEMORY_MAP_UCAS.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+
+        // keep the file name so the container id does not change
+        let staged_utoc = staging.path().join(format!("{}.utoc", stem));
+        with_retoc_keys(config, |config| action_recompress(utoc_path.clone(), staged_utoc.clone(), config))
+            .map_err(|e| repak::Error::Other(e.to_string()))?;
+        // the companion pak only lists chunknames, carry it over untouched
+        fs::copy(pak_path, staging.path().join(format!("{}.pak", stem)))?;
+    } else {
+        let reader = repak::PakBuilder::new()
+            .key(AES_KEY.clone().0)
+            .reader(&mut BufReader::new(File::open(pak_path)?))?;
+        let installable_mod = InstallableMod {
+            mod_name: stem.clone(),
+            mod_type: type_override
+                .map(str::to_string)
+                .unwrap_or_else(|| get_current_pak_characteristics(reader.files())),
+            mount_point: "../../../".to_string(),
+            path_hash_seed: reader.path_hash_seed().unwrap_or_default().to_string(),
+            compression: Compression::Oodle,
+            compression_level: Some(level),
+            reader: Some(reader),
+            mod_path: pak_path.to_path_buf(),
+            repak: true,
+            ..Default::default()
+        };
+        let extracted = tempdir()?;
+        extract_pak_to_dir(&installable_mod, extracted.path().to_path_buf())?;
+        // repak_dir writes a fresh chunknames list, drop the old one so it isn't packed twice
+        let old_chunknames = extracted.path().join("chunknames");
+        if old_chunknames.exists() {
+            fs::remove_file(old_chunknames)?;
+        }
+        // file counter of the repack is not interesting here
+        let files_packed = AtomicI32::new(0);
+        repak_dir(
+            &installable_mod,
+            extracted.path().to_path_buf(),
+            staging.path().to_path_buf(),
+            &files_packed,
+        )?;
+    }
+    progress.fetch_add(1, Ordering::SeqCst);
+
+    let staged_files = mod_pak_files(&staging.path().join(format!("{}.pak", stem)));
+    let compacted_size = total_size(&staged_files);
+    let mod_dir = pak_path.parent().unwrap_or(Path::new("."));
+    let mut written_files = Vec::new();
+    for staged in &staged_files {
+        // keep a disabled mod disabled by reusing its current pak extension
+        let target = if staged.extension().is_some_and(|ext| ext == "pak") {
+            pak_path.to_path_buf()
+        } else {
+            backup_path(mod_dir, staged)?
+        };
+        fs::copy(staged, &target)?;
+        written_files.push(target);
+    }
+    progress.fetch_add(1, Ordering::SeqCst);
+
+    let result = CompactResult {
+        mod_path: pak_path.to_path_buf(),
+        original_size,
+        compacted_size,
+        backup_dir,
+        original_files,
+        written_files,
+    };
+    if result.saved_bytes() <= 0 {
+        warn!(
+            "Recompressing {} did not reduce its size ({} -> {} bytes)",
+            stem, original_size, compacted_size
+        );
+    } else {
+        info!(
+            "Recompressed {}: {} -> {} bytes",
+            stem, original_size, compacted_size
+        );
+    }
+    Ok(result)
+}
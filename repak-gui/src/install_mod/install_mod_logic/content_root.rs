@@ -0,0 +1,59 @@
+use log::warn;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Wrapper folders searched through before giving up on finding the game's folder layout
+const MAX_WRAPPER_DEPTH: usize = 8;
+
+fn subdirs(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Unable to read {}: {}", dir.display(), e))?;
+    let mut dirs = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Folder `strip` levels below `dir` that a loose mod is packed from, for mods zipped with
+/// extra folders around `Marvel/...`. Every stripped level has to hold exactly one folder,
+/// loose files next to it (readmes and such) are left out of the mod.
+pub fn content_root(dir: &Path, strip: usize) -> Result<PathBuf, String> {
+    let mut root = dir.to_path_buf();
+    for _ in 0..strip {
+        let mut dirs = subdirs(&root)?;
+        if dirs.len() != 1 {
+            return Err(format!(
+                "Can't strip {} as it holds {} folders, lower \"Strip leading folders\"",
+                root.display(),
+                dirs.len()
+            ));
+        }
+        let skipped = fs::read_dir(&root).map_or(0, |entries| entries.count()).saturating_sub(1);
+        if skipped > 0 {
+            warn!("Leaving {} file(s) in {} out of the mod", skipped, root.display());
+        }
+        root = dirs.remove(0);
+    }
+    Ok(root)
+}
+
+/// Number of wrapper folders above the first `Marvel` folder of `dir`, 0 when the mod is
+/// already laid out from the game root or no such folder is found
+pub fn detect_wrapper_depth(dir: &Path) -> usize {
+    let mut current = dir.to_path_buf();
+    for depth in 0..MAX_WRAPPER_DEPTH {
+        let Ok(dirs) = subdirs(&current) else {
+            return 0;
+        };
+        if dirs.iter().any(|d| d.file_name().is_some_and(|n| n.eq_ignore_ascii_case("Marvel"))) {
+            return depth;
+        }
+        match dirs.as_slice() {
+            [only] => current = only.clone(),
+            _ => return 0,
+        }
+    }
+    0
+}
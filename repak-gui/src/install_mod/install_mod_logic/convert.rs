@@ -0,0 +1,239 @@
+use crate::aes_keys::with_retoc_keys;
+use crate::install_log;
+use crate::install_mod::install_mod_logic::compact::is_compactable;
+use crate::install_mod::install_mod_logic::pak_files::create_repak_from_pak;
+use crate::install_mod::install_mod_logic::with_thread_limit;
+use crate::install_mod::InstallableMod;
+use crate::utils::{get_current_pak_characteristics, mod_pak_files, MEMORY_MAP_UCAS};
+use log::{error, info, warn};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use repak::{Compression, PakReader};
+use retoc::{action_to_legacy, ActionToLegacy, Config};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI32, Ordering};
+use tempfile::tempdir;
+
+/// Format `convert_mods` turns mods into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConvertTarget {
+    /// pak + utoc + ucas, what the installer writes
+    #[default]
+    IoStore,
+    /// A single classic pak
+    Pak,
+}
+
+impl ConvertTarget {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConvertTarget::IoStore => "IoStore (pak + utoc + ucas)",
+            ConvertTarget::Pak => "Classic pak",
+        }
+    }
+
+    /// Whether the mod at `pak_path` isn't in this format yet
+    pub fn applies_to(&self, pak_path: &Path) -> bool {
+        let iostore = pak_path.with_extension("utoc").exists();
+        match self {
+            ConvertTarget::IoStore => !iostore,
+            ConvertTarget::Pak => iostore,
+        }
+    }
+}
+
+/// A mod handed to `convert_mods`
+pub struct ConvertJob {
+    pub pak_path: PathBuf,
+    pub reader: PakReader,
+    /// Type picked by the user, replaces the detected one
+    pub type_override: Option<String>,
+}
+
+/// Result of converting one mod, listed to the user once the batch is done
+#[derive(Debug, Clone)]
+pub struct ConvertOutcome {
+    pub mod_name: String,
+    pub result: Result<(), String>,
+    /// Log lines of the conversion, see `install_log::capture`
+    pub log: Vec<String>,
+}
+
+/// Folder the originals of converted mods are moved to
+pub fn backup_dir() -> PathBuf {
+    let mut dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    dir.push("repak_manager");
+    dir.push("convert_backup");
+    dir
+}
+
+/// Whether a backup of `mod_name` from an earlier conversion is still kept
+pub fn has_backup(mod_name: &str) -> bool {
+    backup_dir().join(mod_name).exists()
+}
+
+/// Converts every job to `target` using at most `threads` threads (0 uses every core).
+/// The original files of each converted mod are kept in a backup folder, see
+/// `backup_dir`. An older backup of a mod is only replaced with `replace_backups`, otherwise
+/// the new one goes next to it. `progress` counts the mods that are done.
+pub fn convert_mods(
+    jobs: &[ConvertJob],
+    target: ConvertTarget,
+    threads: usize,
+    replace_backups: bool,
+    progress: &AtomicI32,
+) -> Vec<ConvertOutcome> {
+    with_thread_limit(threads, || {
+        jobs.par_iter()
+            .map(|job| {
+                let mod_name = job.pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let (result, log) = install_log::capture(|| {
+                    let result = match target {
+                        ConvertTarget::IoStore => to_iostore(job, &mod_name, replace_backups),
+                        ConvertTarget::Pak => to_pak(job, &mod_name, replace_backups),
+                    };
+                    match &result {
+                        Ok(()) => info!("Converted {} to {:?}", mod_name, target),
+                        Err(e) => error!("Failed to convert {}: {}", mod_name, e),
+                    }
+                    result
+                });
+                progress.fetch_add(1, Ordering::SeqCst);
+                ConvertOutcome { mod_name, result, log }
+            })
+            .collect()
+    })
+}
+
+fn to_iostore(job: &ConvertJob, mod_name: &str, replace_backups: bool) -> Result<(), String> {
+    let mod_type = job
+        .type_override
+        .clone()
+        .unwrap_or_else(|| get_current_pak_characteristics(job.reader.files()));
+    if !is_compactable(&mod_type) {
+        return Err(format!("{} mods are kept as classic paks", mod_type));
+    }
+    let staging = tempdir().map_err(|e| e.to_string())?;
+    let installable_mod = InstallableMod {
+        mod_name: mod_name.to_string(),
+        mod_type,
+        mount_point: "../../../".to_string(),
+        path_hash_seed: job.reader.path_hash_seed().unwrap_or_default().to_string(),
+        compression: Compression::Oodle,
+        reader: Some(job.reader.clone()),
+        mod_path: job.pak_path.clone(),
+        repak: true,
+        ..Default::default()
+    };
+    // file counter of the pak conversion is not interesting here
+    let files_packed = AtomicI32::new(0);
+    create_repak_from_pak(&installable_mod, staging.path().to_path_buf(), &files_packed)
+        .map_err(|e| e.to_string())?;
+    replace_mod(&job.pak_path, mod_name, staging.path(), replace_backups)
+}
+
+fn to_pak(job: &ConvertJob, mod_name: &str, replace_backups: bool) -> Result<(), String> {
+    let staging = tempdir().map_err(|e| e.to_string())?;
+    let config = || Config {
+        container_header_version_override: None,
+        memory_map: MEMORY_MAP_UCAS.load(Ordering::Relaxed),
+        ..Default::default()
+    };
+    with_retoc_keys(config, |config| {
+        let action = ActionToLegacy::new(
+            job.pak_path.with_extension("utoc"),
+            staging.path().join(format!("{}.pak", mod_name)),
+        );
+        action_to_legacy(action, config)
+    })
+    .map_err(|e| e.to_string())?;
+    replace_mod(&job.pak_path, mod_name, staging.path(), replace_backups)
+}
+
+/// Renames `from` to `to`, copying when they are on different drives
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+/// Backup folder for `mod_name`. An older backup is deleted with `replace`, otherwise the
+/// new one gets a numbered folder next to it.
+fn backup_folder(mod_name: &str, replace: bool) -> Result<PathBuf, String> {
+    let mut backup = backup_dir().join(mod_name);
+    if backup.exists() && replace {
+        fs::remove_dir_all(&backup).map_err(|e| format!("Unable to replace the old backup: {}", e))?;
+    }
+    let mut n = 1;
+    while backup.exists() {
+        backup = backup_dir().join(format!("{}.{}", mod_name, n));
+        n += 1;
+    }
+    fs::create_dir_all(&backup).map_err(|e| e.to_string())?;
+    Ok(backup)
+}
+
+/// Swaps the files of the mod at `pak_path` for the ones staged as `<mod_name>.*`. The
+/// originals are moved to the backup folder first and moved back if writing fails.
+fn replace_mod(pak_path: &Path, mod_name: &str, staging: &Path, replace_backups: bool) -> Result<(), String> {
+    let staged_files = mod_pak_files(&staging.join(format!("{}.pak", mod_name)));
+    if staged_files.is_empty() {
+        return Err("The conversion wrote no files".to_string());
+    }
+
+    let backup = backup_folder(mod_name, replace_backups)?;
+    let mut moved = vec![];
+    for file in mod_pak_files(pak_path) {
+        let backed_up = backup.join(file.file_name().unwrap_or_default());
+        if let Err(e) = move_file(&file, &backed_up) {
+            restore_originals(&moved, &[]);
+            return Err(format!("Unable to back up {}: {}", file.display(), e));
+        }
+        moved.push((file, backed_up));
+    }
+
+    let mod_dir = pak_path.parent().unwrap_or(Path::new("."));
+    let mut written = vec![];
+    for staged in &staged_files {
+        // keep a disabled mod disabled by reusing its current pak extension
+        let target = if staged.extension().is_some_and(|e| e == "pak") {
+            pak_path.to_path_buf()
+        } else {
+            mod_dir.join(staged.file_name().unwrap_or_default())
+        };
+        if let Err(e) = fs::copy(staged, &target) {
+            written.push(target.clone());
+            restore_originals(&moved, &written);
+            return Err(format!(
+                "Unable to write {}, the original files were put back: {}",
+                target.display(),
+                e
+            ));
+        }
+        written.push(target);
+    }
+    Ok(())
+}
+
+/// Undoes a failed `replace_mod`: removes the `written` files and moves the originals back
+/// from the backup
+fn restore_originals(moved: &[(PathBuf, PathBuf)], written: &[PathBuf]) {
+    for file in written.iter().filter(|file| file.exists()) {
+        if let Err(e) = fs::remove_file(file) {
+            warn!("Unable to remove {}: {}", file.display(), e);
+        }
+    }
+    for (original, backed_up) in moved {
+        match move_file(backed_up, original) {
+            Ok(()) => info!("Restored {}", original.display()),
+            Err(e) => error!(
+                "Unable to restore {}, it is kept at {}: {}",
+                original.display(),
+                backed_up.display(),
+                e
+            ),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::aes_keys::with_retoc_keys;
+use crate::install_mod::install_mod_logic::pak_files::extract_pak_to_dir;
+use crate::install_mod::InstallableMod;
+use crate::utils::MEMORY_MAP_UCAS;
+use log::{error, info};
+use repak::PakReader;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Outcome of `extract_mods`
+#[derive(Debug, Default)]
+pub struct ExtractResult {
+    pub extracted: usize,
+    /// Mods that couldn't be extracted, as "name: error"
+    pub failed: Vec<String>,
+}
+
+/// Extracts every mod to `<dest>/<mod name>/`. A failing mod is recorded and the rest of
+/// the batch carries on. `progress` counts the mods that are done.
+pub fn extract_mods(mods: &[(PathBuf, PakReader)], dest: &Path, progress: &AtomicI32) -> ExtractResult {
+    let mut result = ExtractResult::default();
+    for (pak_path, reader) in mods {
+        let mod_name = pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        match extract_mod(pak_path, reader, &dest.join(&mod_name)) {
+            Ok(()) => {
+                info!("Extracted {} to {}", mod_name, dest.display());
+                result.extracted += 1;
+            }
+            Err(e) => {
+                error!("Failed to extract {}: {}", mod_name, e);
+                result.failed.push(format!("{}: {}", mod_name, e));
+            }
+        }
+        progress.fetch_add(1, Ordering::SeqCst);
+    }
+    result
+}
+
+/// Extracts a single mod into `out_dir`, iostore mods through their utoc
+pub fn extract_mod(pak_path: &Path, reader: &PakReader, out_dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(out_dir).map_err(|e| e.to_string())?;
+
+    // the pak of an iostore mod only holds the chunknames list, the files are in the ucas
+    let utoc_path = pak_path.with_extension("utoc");
+    if utoc_path.exists() {
+        let config = || retoc::Config {
+            container_header_version_override: None,
+            memory_map: MEMORY_MAP_UCAS.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+        return with_retoc_keys(config, |config| retoc::unpack_to_dir(&utoc_path, out_dir, false, config))
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+    }
+
+    let installable_mod = InstallableMod {
+        mod_name: pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        reader: Some(reader.clone()),
+        mod_path: pak_path.to_path_buf(),
+        ..Default::default()
+    };
+    extract_pak_to_dir(&installable_mod, out_dir.to_path_buf()).map_err(|e| e.to_string())
+}
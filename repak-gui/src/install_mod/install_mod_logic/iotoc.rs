@@ -1,19 +1,20 @@
-use crate::install_mod::install_mod_logic::pak_files::repak_dir;
+use crate::install_mod::install_mod_logic::pak_files::{build_entry_or_uncompressed, collect_pack_files, repak_dir};
+use crate::install_mod::install_mod_logic::patch_filter::PatchFilter;
 use crate::install_mod::install_mod_logic::patch_meshes;
+use crate::install_mod::install_mod_logic::PackSizes;
 use crate::install_mod::{InstallableMod, AES_KEY};
 use crate::uasset_detection::{modify_texture_mipmaps, patch_mesh_files};
 use crate::uasset_api_integration::process_texture_with_uasset_api;
-use crate::utils::collect_files;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
-use repak::Version;
-use std::io::BufWriter;
-use std::path::PathBuf;
-use std::str::FromStr;
+use repak::{Compression, Version};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use crate::aes_keys::insert_retoc_keys;
 use std::sync::atomic::AtomicI32;
 use retoc::*;
 use std::sync::Arc;
-use log::{debug, error, warn};
+use log::{debug, error, info, warn};
 use std::fs::File;
 use path_slash::PathExt;
 
@@ -23,12 +24,13 @@ pub fn convert_to_iostore_directory(
     mod_dir: PathBuf,
     to_pak_dir: PathBuf,
     packed_files_count: &AtomicI32,
-) -> Result<(), repak::Error> {
+) -> Result<PackSizes, repak::Error> {
     let mod_type = pak.mod_type.clone();
     if mod_type == "Audio" || mod_type == "Movies" {
         debug!("{} mod detected. Not creating iostore packages",mod_type);
-        repak_dir(pak, to_pak_dir, mod_dir, packed_files_count)?;
-        return Ok(());
+        let paths = collect_pack_files(pak, &to_pak_dir)?;
+        repak_dir(pak, to_pak_dir, mod_dir.clone(), packed_files_count)?;
+        return Ok(PackSizes::measure(&paths, &mod_dir, &pak.mod_name));
     }
 
 
@@ -38,15 +40,14 @@ pub fn convert_to_iostore_directory(
     let mut utoc_name = pak.mod_name.clone();
     utoc_name.push_str(".utoc");
 
-    let mut paths = vec![];
-    collect_files(&mut paths, &to_pak_dir)?;
+    let mut paths = collect_pack_files(pak, &to_pak_dir)?;
 
     if pak.fix_mesh {
-        patch_meshes::mesh_patch(&mut paths, &to_pak_dir.to_path_buf())?;
+        patch_meshes::mesh_patch(&mut paths, &to_pak_dir.to_path_buf(), &pak.patch_filter)?;
     }
 
     if pak.fix_textures {
-        if let Err(e) = process_texture_files(&paths) {
+        if let Err(e) = process_texture_files(&paths, &to_pak_dir, &pak.patch_filter) {
             error!("Failed to process texture files: {}", e);
         }
     }
@@ -58,22 +59,22 @@ pub fn convert_to_iostore_directory(
     );
     let mut config = Config {
         container_header_version_override: None,
+        oodle_compression_level: pak.compression_level,
+        // blocks Oodle chokes on are stored as is, the mod still installs
+        uncompressed_fallback: true,
+        // the user chose to install without Oodle
+        disable_compression: pak.skip_compression,
         ..Default::default()
     };
 
-    let aes_toc =
-        retoc::AesKey::from_str("0C263D8C22DCB085894899C3A3796383E9BF9DE0CBFB08C9BF2DEF2E84F29D74")
-            .unwrap();
-
-    config.aes_keys.insert(FGuid::default(), aes_toc.clone());
+    insert_retoc_keys(&mut config);
     let config = Arc::new(config);
 
-    action_to_zen(action, config).expect("Failed to convert to zen");
+    action_to_zen(action, config)
+        .map_err(|e| repak::Error::Other(format!("Failed to convert to zen: {}", e)))?;
 
     // NOW WE CREATE THE FAKE PAK FILE WITH THE CONTENTS BEING A TEXT FILE LISTING ALL CHUNKNAMES
 
-    let output_file = File::create(mod_dir.join(pak_name))?;
-
     let rel_paths = paths
         .par_iter()
         .map(|p| {
@@ -86,86 +87,152 @@ pub fn convert_to_iostore_directory(
         })
         .collect::<Vec<_>>();
 
-    // Build the tiny companion PAK uncompressed on purpose.
-    // Rationale: Only UCAS should be compressed; the small PAK is only a mount aid (chunknames)
-    // and keeping it uncompressed improves compatibility.
-    // To revert: add `.compression(vec![pak.compression])` back below and set build_entry to true.
-    let builder = repak::PakBuilder::new()
-        .key(AES_KEY.clone().0);
+    let pak_path = mod_dir.join(pak_name);
+    if pak.compress_chunknames {
+        if let Err(e) = write_chunknames_pak(pak, &pak_path, &rel_paths, true) {
+            warn!("Compressed chunknames pak failed verification ({}), writing it uncompressed", e);
+            write_chunknames_pak(pak, &pak_path, &rel_paths, false)?;
+        }
+    } else {
+        write_chunknames_pak(pak, &pak_path, &rel_paths, false)?;
+    }
+
+    log::info!("Wrote pak file successfully");
+    packed_files_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    Ok(PackSizes::measure(&paths, &mod_dir, &pak.mod_name))
+
+    // now generate the fake pak file
+}
+
+/// Writes the companion pak, a single "chunknames" entry listing every file of the mod.
+///
+/// It is uncompressed by default: only the ucas needs compressing, the pak is just a mount
+/// aid and some loaders choke on a compressed one. When `compress` is set the entry is
+/// Oodle compressed and read back afterwards to make sure it survived.
+fn write_chunknames_pak(
+    pak: &InstallableMod,
+    pak_path: &Path,
+    rel_paths: &[String],
+    compress: bool,
+) -> Result<(), repak::Error> {
+    let mut builder = repak::PakBuilder::new().key(AES_KEY.clone().0);
+    if compress {
+        builder = builder.compression(vec![Compression::Oodle]);
+    }
 
     let mut pak_writer = builder.writer(
-        BufWriter::new(output_file),
+        BufWriter::new(File::create(pak_path)?),
         Version::V11,
         pak.mount_point.clone(),
-        Some(pak.path_hash_seed.parse().unwrap()),
+        Some(pak.parse_path_hash_seed().map_err(repak::Error::Other)?),
     );
     let entry_builder = pak_writer.entry_builder();
 
     let rel_paths_bytes: Vec<u8> = rel_paths.join("\n").into_bytes();
-    // Write the chunknames entry without compression
-    let entry = entry_builder
-        .build_entry(false, rel_paths_bytes, "chunknames")
-        .expect("Failed to build entry");
+    let entry = build_entry_or_uncompressed(&entry_builder, compress, "chunknames", || Ok(rel_paths_bytes.clone()))?;
 
     pak_writer.write_entry("chunknames".to_string(), entry)?;
     pak_writer.write_index()?;
 
-    log::info!("Wrote pak file successfully");
-    packed_files_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    if compress {
+        let mut reader = BufReader::new(File::open(pak_path)?);
+        let written = repak::PakBuilder::new()
+            .key(AES_KEY.clone().0)
+            .reader(&mut reader)?
+            .get("chunknames", &mut reader)?;
+        if written != rel_paths_bytes {
+            return Err(repak::Error::Other(
+                "chunknames entry changed after compression".to_string(),
+            ));
+        }
+    }
     Ok(())
+}
 
-    // now generate the fake pak file
+/// UAssetBridge processes allowed to run at once while fixing textures
+const MAX_TEXTURE_JOBS: usize = 4;
+
+enum TextureFix {
+    UAssetApi,
+    Toolkit,
+    Unchanged,
+    Failed(String),
 }
 
 /// Process texture files to set MipGenSettings to NoMipmaps
-pub fn process_texture_files(paths: &Vec<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+pub fn process_texture_files(
+    paths: &Vec<PathBuf>,
+    mod_dir: &Path,
+    filter: &PatchFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
     let texture_files: Vec<_> = paths
         .iter()
         .filter(|p| {
             p.extension().and_then(|ext| ext.to_str()) == Some("uasset") &&
-            crate::uasset_detection::is_texture_uasset_heuristic(p)
+            crate::uasset_detection::is_texture_uasset_heuristic(p) &&
+            patch_meshes::is_allowed(p, mod_dir, filter)
         })
         .collect();
 
     debug!("Found {} texture files to process", texture_files.len());
 
-    for uasset_file in &texture_files {
-        let uexp_file = uasset_file.with_extension("uexp");
-        
-        // Create backups
-        if let Err(e) = std::fs::copy(uasset_file, format!("{}.bak", uasset_file.display())) {
-            warn!("Failed to create backup for {}: {}", uasset_file.display(), e);
-        }
-        
-        // Try UAssetAPI processing first
-        match process_texture_with_uasset_api(uasset_file) {
-            Ok(true) => {
-                debug!("Successfully processed texture with UAssetAPI: {:?}", uasset_file);
-                continue;
-            }
-            Ok(false) => {
-                debug!("UAssetAPI processing not available, falling back to toolkit");
-            }
-            Err(e) => {
-                warn!("UAssetAPI processing failed for {:?}: {}", uasset_file, e);
-            }
-        }
-        
-        // Fallback to existing toolkit method
-        match modify_texture_mipmaps(uasset_file, &uexp_file) {
-            Ok(modified) => {
-                if modified {
-                    debug!("Successfully modified texture mipmaps: {:?}", uasset_file);
-                } else {
-                    debug!("No mipmap modification needed for: {:?}", uasset_file);
-                }
-            }
-            Err(e) => {
-                error!("Failed to modify texture mipmaps for {:?}: {}", uasset_file, e);
+    // every file spawns its own bridge process, a dedicated pool keeps their number bounded,
+    // and within the install's thread limit
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(MAX_TEXTURE_JOBS.min(rayon::current_num_threads()))
+        .build()?;
+    let results = pool.install(|| {
+        texture_files
+            .par_iter()
+            .map(|uasset_file| (uasset_file, fix_texture(uasset_file)))
+            .collect::<Vec<_>>()
+    });
+
+    let (mut uasset_api, mut toolkit, mut unchanged) = (0, 0, 0);
+    for (uasset_file, result) in &results {
+        match result {
+            TextureFix::UAssetApi => uasset_api += 1,
+            TextureFix::Toolkit => toolkit += 1,
+            TextureFix::Unchanged => unchanged += 1,
+            TextureFix::Failed(e) => {
+                error!("Failed to modify texture mipmaps for {:?}: {}", uasset_file, e)
             }
         }
     }
-    
+    info!(
+        "Processed {} textures: {} with UAssetAPI, {} with the toolkit, {} unchanged, {} failed",
+        results.len(),
+        uasset_api,
+        toolkit,
+        unchanged,
+        results.len() - uasset_api - toolkit - unchanged
+    );
+
     Ok(())
 }
 
+fn fix_texture(uasset_file: &Path) -> TextureFix {
+    let uexp_file = uasset_file.with_extension("uexp");
+
+    // Create backups
+    if let Err(e) = std::fs::copy(uasset_file, format!("{}.bak", uasset_file.display())) {
+        warn!("Failed to create backup for {}: {}", uasset_file.display(), e);
+    }
+
+    // Try UAssetAPI processing first
+    match process_texture_with_uasset_api(uasset_file) {
+        Ok(true) => return TextureFix::UAssetApi,
+        Ok(false) => {}
+        Err(e) => {
+            debug!("UAssetAPI processing failed for {:?}, falling back to toolkit: {}", uasset_file, e);
+        }
+    }
+
+    // Fallback to existing toolkit method
+    match modify_texture_mipmaps(uasset_file, &uexp_file) {
+        Ok(true) => TextureFix::Toolkit,
+        Ok(false) => TextureFix::Unchanged,
+        Err(e) => TextureFix::Failed(e.to_string()),
+    }
+}
+
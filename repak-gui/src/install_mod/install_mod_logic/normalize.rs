@@ -0,0 +1,104 @@
+use crate::install_mod::install_mod_logic::{normalize_mod_base_name, ModNaming};
+use crate::preview::SIDECAR_EXTENSIONS;
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of `normalize_mod_names`
+#[derive(Debug, Default)]
+pub struct NormalizeResult {
+    /// Old and new pak path of every renamed mod
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    /// Mods that were left alone, with the reason
+    pub skipped: Vec<String>,
+}
+
+// every file belonging to a mod: the pak itself, the iostore pair and a preview image
+fn companion_files(pak_path: &Path) -> Vec<PathBuf> {
+    ["utoc", "ucas"]
+        .iter()
+        .chain(SIDECAR_EXTENSIONS.iter())
+        .map(|ext| pak_path.with_extension(ext))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn rename_mod(pak_path: &Path, base_name: &str) -> Result<PathBuf, String> {
+    let mut moves = vec![pak_path.to_path_buf()];
+    moves.extend(companion_files(pak_path));
+    let moves = moves
+        .into_iter()
+        .map(|from| {
+            // keeps .bak_repak on disabled mods
+            let ext = from.extension().unwrap_or_default().to_string_lossy();
+            let to = from.with_file_name(format!("{}.{}", base_name, ext));
+            (from, to)
+        })
+        .collect::<Vec<_>>();
+
+    if let Some((_, to)) = moves.iter().find(|(_, to)| to.exists()) {
+        return Err(format!("{} already exists", to.display()));
+    }
+
+    for (done, (from, to)) in moves.iter().enumerate() {
+        if let Err(e) = fs::rename(from, to) {
+            // put back what was already moved so the mod isn't left half renamed
+            for (from, to) in &moves[..done] {
+                let _ = fs::rename(to, from);
+            }
+            return Err(format!("Unable to rename {}: {}", from.display(), e));
+        }
+    }
+    Ok(moves[0].1.clone())
+}
+
+/// Renames each mod in `renames` (pak path, new file stem) with its utoc/ucas/preview, the
+/// batch rename tool checks for collisions with `rename_collisions` first
+pub fn rename_mods(renames: &[(PathBuf, String)]) -> NormalizeResult {
+    let mut result = NormalizeResult::default();
+    for (pak_path, base_name) in renames {
+        let stem = pak_path.file_stem().unwrap_or_default().to_string_lossy();
+        if *base_name == stem {
+            continue;
+        }
+        match rename_mod(pak_path, base_name) {
+            Ok(new_path) => {
+                info!("Renamed {} to {}", stem, base_name);
+                result.renamed.push((pak_path.clone(), new_path));
+            }
+            Err(e) => {
+                warn!("Skipping {}: {}", stem, e);
+                result.skipped.push(format!("{}: {}", stem, e));
+            }
+        }
+    }
+    result
+}
+
+/// Renames every mod in `pak_paths` (and its utoc/ucas/preview) to the name `naming` gives it.
+/// Mods that already have that name are not touched.
+pub fn normalize_mod_names(
+    pak_paths: &[PathBuf],
+    naming: ModNaming,
+    default_priority: u32,
+) -> NormalizeResult {
+    let mut result = NormalizeResult::default();
+    for pak_path in pak_paths {
+        let stem = pak_path.file_stem().unwrap_or_default().to_string_lossy();
+        let base_name = normalize_mod_base_name(&stem, naming, default_priority);
+        if base_name == stem {
+            continue;
+        }
+        match rename_mod(pak_path, &base_name) {
+            Ok(new_path) => {
+                info!("Renamed {} to {}", stem, base_name);
+                result.renamed.push((pak_path.clone(), new_path));
+            }
+            Err(e) => {
+                warn!("Skipping {}: {}", stem, e);
+                result.skipped.push(format!("{}: {}", stem, e));
+            }
+        }
+    }
+    result
+}
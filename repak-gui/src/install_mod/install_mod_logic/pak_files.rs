@@ -1,20 +1,41 @@
+use crate::install_mod::install_mod_logic::patch_filter;
 use crate::install_mod::install_mod_logic::patch_meshes::mesh_patch;
 use crate::install_mod::{InstallableMod, AES_KEY};
 use crate::utils::collect_files;
-use log::debug;
+use log::{debug, info, warn};
 use path_clean::PathClean;
 use path_slash::PathExt;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
-use repak::Version;
+use repak::{EntryBuilder, PartialEntry, Version};
 use std::fs;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{BufReader, BufWriter};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicI32;
 use tempfile::tempdir;
 
 use super::iotoc::convert_to_iostore_directory;
+use super::PackSizes;
+
+/// Files left out when packing a folder, backups from earlier fixer runs and OS clutter.
+/// Uses the pattern syntax of `PatchFilter`.
+pub const DEFAULT_PACK_IGNORE: &str = ".bak, .tmp, .temp, Thumbs.db, desktop.ini, .DS_Store";
+
+/// Files under `dir` that go into the mod, everything but what `pak.pack_ignore` matches
+pub fn collect_pack_files(pak: &InstallableMod, dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    collect_files(&mut paths, dir)?;
+    paths.retain(|p| {
+        let rel = p.strip_prefix(dir).unwrap_or(p).to_string_lossy();
+        let ignored = patch_filter::matches_any(&pak.pack_ignore, &rel);
+        if ignored {
+            info!("Not packing {} (ignored file)", rel);
+        }
+        !ignored
+    });
+    Ok(paths)
+}
 
 pub fn extract_pak_to_dir(pak: &InstallableMod, install_dir: PathBuf) -> Result<(), repak::Error> {
     let pak_reader = pak.clone().reader.clone().unwrap();
@@ -60,20 +81,16 @@ pub fn extract_pak_to_dir(pak: &InstallableMod, install_dir: PathBuf) -> Result<
         .filter_map(|x| x.transpose())
         .collect::<Result<Vec<_>, _>>()?;
 
-    entries.par_iter().for_each(|entry| {
+    // entries are streamed straight to their files, so a large asset never sits in memory whole
+    entries.par_iter().try_for_each(|entry| {
         log::debug!("Unpacking: {}", entry.entry_path);
-        fs::create_dir_all(&entry.out_dir).unwrap();
-        let mut reader = BufReader::new(File::open(&pak.mod_path).unwrap());
-        let buffer = pak_reader
-            .get(&entry.entry_path, &mut reader)
-            .expect("Failed to read entry");
-        File::create(&entry.out_path)
-            .unwrap()
-            .write_all(&buffer)
-            .unwrap();
+        fs::create_dir_all(&entry.out_dir)?;
+        let mut reader = BufReader::new(File::open(&pak.mod_path)?);
+        let mut writer = BufWriter::new(File::create(&entry.out_path)?);
+        pak_reader.read_file(&entry.entry_path, &mut reader, &mut writer)?;
         log::info!("Unpacked: {:?}", entry.out_path);
-    });
-    Ok(())
+        Ok(())
+    })
 }
 
 
@@ -81,23 +98,25 @@ pub fn create_repak_from_pak(
     pak: &InstallableMod,
     mod_dir: PathBuf,
     packed_files_count: &AtomicI32,
-) -> Result<(), repak::Error> {
+) -> Result<PackSizes, repak::Error> {
     // extract the pak first into a temporary dir
     let temp_dir = tempdir().map_err(repak::Error::Io)?;
     let temp_path = temp_dir.path(); // Get the path of the temporary directory
 
     extract_pak_to_dir(pak, temp_path.to_path_buf())?;
-    convert_to_iostore_directory(
+    // measured against the extracted files, so the ratio is relative to the uncompressed mod
+    let sizes = convert_to_iostore_directory(
         pak,
         mod_dir.clone(),
         temp_path.to_path_buf(),
         packed_files_count,
     )?;
     // repak_dir(pak, PathBuf::from(temp_path), mod_dir,packed_files_count)?;
-    Ok(())
+    Ok(sizes)
 }
 
-// leaving this here for legacy reasons
+/// Packs `to_pak_dir` into a classic `<mod_name>.pak` in `mod_dir`, at the Oodle level of
+/// `pak.compression_level` if set
 pub fn repak_dir(
     pak: &InstallableMod,
     to_pak_dir: PathBuf,
@@ -108,24 +127,26 @@ pub fn repak_dir(
     pak_name.push_str(".pak");
     let output_file = File::create(mod_dir.join(pak_name))?;
 
-    let mut paths = vec![];
-    collect_files(&mut paths, &to_pak_dir)?;
+    let mut paths = collect_pack_files(pak, &to_pak_dir)?;
 
     if pak.fix_mesh {
-        mesh_patch(&mut paths, &to_pak_dir.to_path_buf())?;
+        mesh_patch(&mut paths, &to_pak_dir.to_path_buf(), &pak.patch_filter)?;
     }
 
     paths.sort();
 
-    let builder = repak::PakBuilder::new()
+    let mut builder = repak::PakBuilder::new()
         .compression(vec![pak.compression])
         .key(AES_KEY.clone().0);
+    if let Some(level) = pak.compression_level {
+        builder = builder.oodle_level(level);
+    }
 
     let mut pak_writer = builder.writer(
         BufWriter::new(output_file),
         Version::V11,
         pak.mount_point.clone(),
-        Some(pak.path_hash_seed.parse().unwrap()),
+        Some(pak.parse_path_hash_seed().map_err(repak::Error::Other)?),
     );
     let entry_builder = pak_writer.entry_builder();
 
@@ -138,12 +159,10 @@ pub fn repak_dir(
                 .to_slash()
                 .expect("failed to convert to slash path");
 
-            let entry = entry_builder
-                .build_entry(true, std::fs::read(p).expect("WTF"), rel)
-                .expect("Failed to build entry");
-            (rel.to_string(), entry)
+            let entry = build_entry_or_uncompressed(&entry_builder, !pak.skip_compression, rel, || fs::read(p))?;
+            Ok((rel.to_string(), entry))
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>, repak::Error>>()?;
 
     let mut rel_paths = vec![];
     for (path, entry) in partial_entry {
@@ -155,13 +174,31 @@ pub fn repak_dir(
 
     let rel_paths_bytes: Vec<u8> = rel_paths.join("\n").into_bytes();
 
-    let entry = entry_builder
-        .build_entry(true, rel_paths_bytes, "chunknames")
-        .expect("Failed to build entry");
+    let entry = build_entry_or_uncompressed(&entry_builder, !pak.skip_compression, "chunknames", || {
+        Ok(rel_paths_bytes.clone())
+    })?;
 
     pak_writer.write_entry("chunknames".to_string(), entry)?;
     pak_writer.write_index()?;
 
     log::info!("Wrote pak file successfully");
     Ok::<(), repak::Error>(())
-}
\ No newline at end of file
+}
+
+/// Builds the pak entry of `path` from the data `read` returns, compressed when `compress`
+/// is set. Oodle gives up on some inputs, those entries are stored uncompressed so the mod
+/// still installs, only larger.
+pub(super) fn build_entry_or_uncompressed(
+    entry_builder: &EntryBuilder,
+    compress: bool,
+    path: &str,
+    read: impl Fn() -> std::io::Result<Vec<u8>>,
+) -> Result<PartialEntry<Vec<u8>>, repak::Error> {
+    match entry_builder.build_entry(compress, read()?, path) {
+        Err(repak::Error::OodleFailed(oodle_loader::Error::CompressionFailed)) if compress => {
+            warn!("Oodle failed to compress {}, storing it uncompressed", path);
+            entry_builder.build_entry(false, read()?, path)
+        }
+        result => result,
+    }
+}
@@ -0,0 +1,75 @@
+/// Limits which files the mesh and texture fixers touch.
+///
+/// Both lists hold patterns separated by commas or new lines. `.ext` matches a file
+/// extension, anything else is matched case insensitively against the path inside the mod
+/// (`Marvel/Content/...`). `*` and `?` work as wildcards, a pattern without them matches
+/// any path containing it.
+#[derive(Debug, Clone, Default)]
+pub struct PatchFilter {
+    /// Only files matching one of these are patched, empty patches everything
+    pub include: String,
+    /// Files matching one of these are never patched
+    pub exclude: String,
+}
+
+impl PatchFilter {
+    pub fn allows(&self, rel_path: &str) -> bool {
+        let path = rel_path.replace('\\', "/").to_lowercase();
+        let include = patterns(&self.include);
+        if !include.is_empty() && !include.iter().any(|p| matches(p, &path)) {
+            return false;
+        }
+        !patterns(&self.exclude).iter().any(|p| matches(p, &path))
+    }
+}
+
+/// Whether `rel_path` matches one of the patterns in `list`, written like the lists above
+pub fn matches_any(list: &str, rel_path: &str) -> bool {
+    let path = rel_path.replace('\\', "/").to_lowercase();
+    patterns(list).iter().any(|p| matches(p, &path))
+}
+
+fn patterns(list: &str) -> Vec<String> {
+    list.split([',', '\n'])
+        .map(|p| p.trim().replace('\\', "/").to_lowercase())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn matches(pattern: &str, path: &str) -> bool {
+    if pattern.starts_with('.') && !pattern.contains(['/', '*', '?']) {
+        return path.ends_with(pattern);
+    }
+    if !pattern.contains(['*', '?']) {
+        return path.contains(pattern);
+    }
+    wildcard(pattern.as_bytes(), path.as_bytes())
+}
+
+// `*` matches any run of characters (slashes included), `?` exactly one
+fn wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // last `*` seen and the text position it was tried at, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
@@ -1,10 +1,11 @@
+use crate::install_mod::install_mod_logic::patch_filter::PatchFilter;
 use colored::Colorize;
 use log::info;
 use path_slash::PathExt;
 use std::fs;
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uasset_mesh_patch_rivals::Logger;
 use uasset_mesh_patch_rivals::PatchFixer;
 
@@ -17,12 +18,30 @@ impl Logger for PrintLogger {
     }
 }
 
-pub fn mesh_patch(paths: &mut Vec<PathBuf>, mod_dir: &PathBuf) -> Result<(), repak::Error> {
+/// Checks a file against the patch filter by its path inside the mod
+pub fn is_allowed(path: &Path, mod_dir: &Path, filter: &PatchFilter) -> bool {
+    let allowed = path
+        .strip_prefix(mod_dir)
+        .ok()
+        .and_then(|rel| rel.to_slash())
+        .map_or(true, |rel| filter.allows(&rel));
+    if !allowed {
+        info!("Not patching {} (excluded by the patch filter)", path.display());
+    }
+    allowed
+}
+
+pub fn mesh_patch(
+    paths: &mut Vec<PathBuf>,
+    mod_dir: &PathBuf,
+    filter: &PatchFilter,
+) -> Result<(), repak::Error> {
     let uasset_files = paths
         .iter()
         .filter(|p| {
             p.extension().and_then(|ext| ext.to_str()) == Some("uasset")
-                && (p.to_str().unwrap().to_lowercase().contains("meshes"))
+                && (p.to_string_lossy().to_lowercase().contains("meshes"))
+                && is_allowed(p, mod_dir, filter)
         })
         .cloned()
         .collect::<Vec<PathBuf>>();
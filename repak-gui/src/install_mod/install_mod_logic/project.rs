@@ -0,0 +1,69 @@
+use crate::install_mod::install_mod_logic::extract_all::extract_mod;
+use log::{info, warn};
+use repak::PakReader;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the metadata file at the root of a project folder
+pub const PROJECT_FILE: &str = ".repakproject";
+
+/// Loose files of a project live here, so the metadata never ends up in the packed mod
+const FILES_DIR: &str = "files";
+
+/// A mod unpacked for editing. Packing the project folder again reuses these settings
+/// instead of the defaults of a freshly dropped folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepakProject {
+    /// Pak the project was unpacked from
+    pub source_pak: PathBuf,
+    /// File name (without extension) the repacked mod is installed as
+    pub mod_name: String,
+    /// Mount point written when repacking, the files are laid out relative to it
+    pub mount_point: String,
+    pub path_hash_seed: String,
+}
+
+impl RepakProject {
+    /// Reads the project file of `dir`, `None` if it isn't a project folder
+    pub fn load(dir: &Path) -> Option<Self> {
+        let path = dir.join(PROJECT_FILE);
+        let data = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&data) {
+            Ok(project) => Some(project),
+            Err(e) => {
+                warn!("Ignoring unreadable {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Folder holding the loose files of the project in `dir`
+    pub fn files_dir(dir: &Path) -> PathBuf {
+        dir.join(FILES_DIR)
+    }
+}
+
+/// Unpacks a mod into `<dest>/<mod name>/` and writes its project file. Returns the
+/// project folder. An existing project folder is refused rather than merged into.
+pub fn unpack_project(pak_path: &Path, reader: &PakReader, dest: &Path) -> Result<PathBuf, String> {
+    let mod_name = pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let project_dir = dest.join(&mod_name);
+    if project_dir.join(PROJECT_FILE).exists() {
+        return Err(format!("{} is already a project folder", project_dir.display()));
+    }
+
+    extract_mod(pak_path, reader, &RepakProject::files_dir(&project_dir))?;
+
+    let project = RepakProject {
+        source_pak: pak_path.to_path_buf(),
+        mod_name,
+        // extraction strips the game root, so that is where the files mount again
+        mount_point: "../../../".to_string(),
+        path_hash_seed: reader.path_hash_seed().unwrap_or_default().to_string(),
+    };
+    let json = serde_json::to_string_pretty(&project).map_err(|e| e.to_string())?;
+    fs::write(project_dir.join(PROJECT_FILE), json).map_err(|e| e.to_string())?;
+    info!("Unpacked {} as a project in {}", project.mod_name, project_dir.display());
+    Ok(project_dir)
+}
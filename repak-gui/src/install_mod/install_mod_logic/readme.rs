@@ -0,0 +1,57 @@
+use crate::install_mod::install_mod_logic::update_pending;
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Extensions of the files kept as a mod's readme
+const README_EXTENSIONS: [&str; 3] = ["txt", "md", "pdf"];
+
+/// Readmes bundled in an extracted archive, every mod found in it shares them
+pub fn find_readmes(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| README_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+        })
+        .collect()
+}
+
+/// Readmes of installed mods are copied here, the archive they came in is usually gone
+pub fn readme_dir() -> PathBuf {
+    let mut dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    dir.push("repak_manager");
+    dir.push("readmes");
+    dir
+}
+
+/// Copies `readmes` to `<readme_dir>/<base_name>/` and leaves their new paths in a pending
+/// file, like `record_pack_sizes`, for the main app to store in the mod's metadata
+pub fn store_readmes(base_name: &str, readmes: &[PathBuf]) {
+    if readmes.is_empty() {
+        return;
+    }
+    let dest_dir = readme_dir().join(base_name);
+    if let Err(e) = fs::create_dir_all(&dest_dir) {
+        warn!("Unable to create {}: {}", dest_dir.display(), e);
+        return;
+    }
+    let mut stored = Vec::new();
+    for src in readmes {
+        let dest = dest_dir.join(src.file_name().unwrap_or_default());
+        match fs::copy(src, &dest) {
+            Ok(_) => stored.push(dest),
+            Err(e) => warn!("Unable to keep readme {}: {}", src.display(), e),
+        }
+    }
+    if stored.is_empty() {
+        return;
+    }
+    info!("Kept {} readme(s) for {}", stored.len(), base_name);
+
+    update_pending("pending_readmes.json", base_name, |_| stored);
+}
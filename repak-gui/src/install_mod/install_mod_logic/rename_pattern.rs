@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Placeholders a rename pattern can use, shown as help next to the pattern field
+pub const PLACEHOLDERS: [(&str, &str); 7] = [
+    ("{name}", "current name in the list"),
+    ("{file}", "file name without the _P suffix"),
+    ("{type}", "detected or assigned mod type"),
+    ("{author}", "author from the mod's manifest"),
+    ("{folder}", "folder the mod is in"),
+    ("{n}", "counter"),
+    ("{n:3}", "counter padded to 3 digits"),
+];
+
+/// Values of the placeholders for one mod
+pub struct PatternFields<'a> {
+    pub name: &'a str,
+    pub file: &'a str,
+    pub mod_type: &'a str,
+    pub author: &'a str,
+    pub folder: &'a str,
+}
+
+/// Fills in the placeholders of `pattern`, `{{` and `}}` stand for literal braces
+pub fn expand_pattern(pattern: &str, fields: &PatternFields, counter: usize) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let Some(end) = rest.find('}').filter(|_| rest.starts_with('{')) else {
+            return Err("Unmatched brace, use {{ or }} for a literal one".to_string());
+        };
+        let placeholder = &rest[1..end];
+        rest = &rest[end + 1..];
+        let value = match placeholder.split_once(':') {
+            Some(("n", width)) => {
+                let width = width
+                    .parse::<usize>()
+                    .map_err(|_| format!("{{n:{}}} needs a number of digits", width))?;
+                format!("{:0width$}", counter, width = width)
+            }
+            _ => match placeholder {
+                "name" => fields.name.to_string(),
+                "file" => fields.file.to_string(),
+                "type" => fields.mod_type.to_string(),
+                "author" => fields.author.to_string(),
+                "folder" => fields.folder.to_string(),
+                "n" => counter.to_string(),
+                other => return Err(format!("Unknown placeholder {{{}}}", other)),
+            },
+        };
+        out.push_str(&value);
+    }
+    out.push_str(rest);
+    let out = out.trim().to_string();
+    if out.is_empty() {
+        return Err("The pattern gives an empty name".to_string());
+    }
+    Ok(out)
+}
+
+/// `name` with the characters Windows doesn't allow in file names replaced by `_`
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_end_matches(['.', ' '])
+        .to_string()
+}
+
+/// Extensions a file of another mod taking the new name could have
+const MOD_EXTENSIONS: [&str; 5] = ["pak", "pak_disabled", "bak_repak", "utoc", "ucas"];
+
+/// Problems with renaming the mods in `renames` (pak path, new file stem): two mods ending
+/// up with the same name, or files of another mod already having it. Keyed by pak path.
+pub fn rename_collisions(renames: &[(PathBuf, String)]) -> HashMap<PathBuf, String> {
+    let mut problems = HashMap::new();
+    let mut seen: HashMap<String, &Path> = HashMap::new();
+    for (path, stem) in renames {
+        let old_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        // the file systems mods live on are usually case insensitive
+        if let Some(other) = seen.insert(stem.to_lowercase(), path) {
+            let message = format!("Same name as {}", other.file_stem().unwrap_or_default().to_string_lossy());
+            problems.insert(path.clone(), message);
+            continue;
+        }
+        if old_stem.eq_ignore_ascii_case(stem) {
+            continue;
+        }
+        // mods renamed in the same batch still hold their old name, renames run one by one
+        let taken = MOD_EXTENSIONS
+            .iter()
+            .map(|ext| path.with_file_name(format!("{}.{}", stem, ext)))
+            .find(|target| target.exists());
+        if let Some(target) = taken {
+            problems.insert(path.clone(), format!("{} already exists", target.display()));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_pattern, rename_collisions, PatternFields};
+    use std::fs;
+
+    const FIELDS: PatternFields = PatternFields {
+        name: "Cool Hulk",
+        file: "CoolHulk",
+        mod_type: "Hulk - Default",
+        author: "someone",
+        folder: "Skins",
+    };
+
+    #[test]
+    fn expand_pattern_fills_placeholders_and_escapes_braces() {
+        assert_eq!(expand_pattern("{type}_{name}", &FIELDS, 1), Ok("Hulk - Default_Cool Hulk".to_string()));
+        assert_eq!(expand_pattern("{{{folder}}} {author}", &FIELDS, 1), Ok("{Skins} someone".to_string()));
+        assert_eq!(expand_pattern("}}{file}{{", &FIELDS, 1), Ok("}CoolHulk{".to_string()));
+    }
+
+    #[test]
+    fn expand_pattern_pads_the_counter() {
+        assert_eq!(expand_pattern("{n} {file}", &FIELDS, 7), Ok("7 CoolHulk".to_string()));
+        assert_eq!(expand_pattern("{n:3}_{file}", &FIELDS, 7), Ok("007_CoolHulk".to_string()));
+        assert_eq!(expand_pattern("{n:2}", &FIELDS, 1234), Ok("1234".to_string()));
+        assert!(expand_pattern("{n:x}", &FIELDS, 1).is_err());
+    }
+
+    #[test]
+    fn expand_pattern_rejects_bad_patterns() {
+        let err = expand_pattern("{nickname}", &FIELDS, 1).unwrap_err();
+        assert!(err.contains("{nickname}"), "{}", err);
+        for pattern in ["{name", "name}", "{", "  "] {
+            assert!(expand_pattern(pattern, &FIELDS, 1).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn rename_collisions_finds_duplicates_and_taken_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("A_100_P.pak");
+        let b = dir.path().join("B_100_P.pak");
+        let c = dir.path().join("C_100_P.pak");
+        for pak in [&a, &b, &c] {
+            fs::write(pak, b"").unwrap();
+        }
+        fs::write(dir.path().join("Taken_100_P.utoc"), b"").unwrap();
+
+        let problems = rename_collisions(&[
+            (a.clone(), "Same_100_P".to_string()),
+            (b.clone(), "same_100_p".to_string()),
+            (c.clone(), "Taken_100_P".to_string()),
+        ]);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[&b].contains("A_100_P"), "{}", problems[&b]);
+        assert!(problems[&c].contains("Taken_100_P.utoc"), "{}", problems[&c]);
+    }
+
+    #[test]
+    fn rename_collisions_allows_case_only_renames() {
+        let dir = tempfile::tempdir().unwrap();
+        let pak = dir.path().join("Mod_100_P.pak");
+        fs::write(&pak, b"").unwrap();
+        assert!(rename_collisions(&[(pak, "MOD_100_P".to_string())]).is_empty());
+    }
+}
@@ -0,0 +1,447 @@
+use crate::install_mod::install_mod_logic::archives::{copy_cancellable, ExtractProgress};
+use log::info;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// How a download was cut into parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SplitKind {
+    /// `mod.part1.rar`, `mod.part2.rar`...
+    RarVolumes,
+    /// `mod.rar`, `mod.r00`, `mod.r01`...
+    OldRarVolumes,
+    /// `mod.z01`, `mod.z02`... and `mod.zip` as the last part
+    SpannedZip,
+    /// `mod.zip.001`, `mod.zip.002`... cut at fixed sizes, the parts only need joining
+    Numbered,
+}
+
+/// One file of a split archive
+struct Part {
+    /// Folder and name the parts share, `mod` for `mod.part1.rar` or `mod.zip` for `mod.zip.001`
+    set: PathBuf,
+    kind: SplitKind,
+    /// Order of the part, the `.rar` of old volumes is 0 and the `.zip` of spanned zips last
+    number: u32,
+}
+
+/// An archive downloaded in several parts, grouped from the part that was dropped
+#[derive(Debug)]
+pub struct SplitArchive {
+    kind: SplitKind,
+    set: PathBuf,
+    /// All parts in order
+    parts: Vec<PathBuf>,
+}
+
+fn digits(s: &str) -> Option<u32> {
+    match !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        true => s.parse().ok(),
+        false => None,
+    }
+}
+
+fn parse_part(path: &Path) -> Option<Part> {
+    let name = path.file_name()?.to_str()?;
+    let (stem, ext) = name.rsplit_once('.')?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let ext = ext.to_lowercase();
+    let part = |set: &str, kind, number| Some(Part { set: dir.join(set), kind, number });
+    match ext.as_str() {
+        "rar" => {
+            let volume = stem.rsplit_once('.').and_then(|(base, volume)| {
+                Some((base, digits(volume.to_lowercase().strip_prefix("part")?)?))
+            });
+            match volume {
+                Some((base, number)) => part(base, SplitKind::RarVolumes, number),
+                None => part(stem, SplitKind::OldRarVolumes, 0),
+            }
+        }
+        "zip" => part(stem, SplitKind::SpannedZip, u32::MAX),
+        _ if ext.len() == 3 && ext.starts_with('r') => part(stem, SplitKind::OldRarVolumes, digits(&ext[1..])? + 1),
+        _ if ext.len() == 3 && ext.starts_with('z') => part(stem, SplitKind::SpannedZip, digits(&ext[1..])?),
+        _ => {
+            let number = digits(&ext)?;
+            let inner = Path::new(stem).extension()?.to_str()?.to_lowercase();
+            match inner == "zip" || inner == "rar" {
+                true => part(stem, SplitKind::Numbered, number),
+                false => None,
+            }
+        }
+    }
+}
+
+/// True for parts of split archives other than the `.rar`/`.zip` that can also stand alone
+pub fn is_split_part(path: &Path) -> bool {
+    parse_part(path).is_some_and(|part| match part.kind {
+        SplitKind::OldRarVolumes => part.number > 0,
+        SplitKind::SpannedZip => part.number != u32::MAX,
+        SplitKind::RarVolumes | SplitKind::Numbered => true,
+    })
+}
+
+// part `number` as users see it in the file names
+fn part_label(kind: SplitKind, number: u32) -> String {
+    match kind {
+        SplitKind::RarVolumes => format!(".part{}.rar", number),
+        SplitKind::OldRarVolumes if number == 0 => ".rar".to_string(),
+        SplitKind::OldRarVolumes => format!(".r{:02}", number - 1),
+        SplitKind::SpannedZip => format!(".z{:02}", number),
+        SplitKind::Numbered => format!(".{:03}", number),
+    }
+}
+
+fn part_list(kind: SplitKind, numbers: &[u32]) -> String {
+    numbers.iter().map(|&n| part_label(kind, n)).collect::<Vec<_>>().join(", ")
+}
+
+// every part of the set is looked up next to the dropped one, users usually drop only one
+fn find_set(set: &Path, kind: SplitKind) -> Result<Option<SplitArchive>, String> {
+    let dir = set.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let entries = fs::read_dir(dir).map_err(|e| format!("Unable to read {}: {}", dir.display(), e))?;
+    let mut parts = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter_map(|path| parse_part(&path).map(|part| (part, path)))
+        .filter(|(part, _)| part.kind == kind && part.set.file_name() == set.file_name())
+        .map(|(part, path)| (part.number, path))
+        .collect::<BTreeMap<_, _>>();
+    let name = set.file_name().unwrap_or_default().to_string_lossy();
+
+    // the .zip of a spanned zip is its last part, it holds the end of the archive
+    let last_zip = match kind {
+        SplitKind::SpannedZip => parts.remove(&u32::MAX),
+        _ => None,
+    };
+    // a lone .rar or .zip is a normal archive
+    let standalone = match kind {
+        SplitKind::OldRarVolumes => parts.len() == 1 && parts.contains_key(&0),
+        SplitKind::SpannedZip => parts.is_empty() && last_zip.is_some(),
+        _ => false,
+    };
+    if standalone {
+        return Ok(None);
+    }
+    if kind == SplitKind::SpannedZip && last_zip.is_none() {
+        return Err(format!("{} is split into parts but {}.zip is missing", name, name));
+    }
+    let first = match kind {
+        SplitKind::OldRarVolumes => 0,
+        _ => 1,
+    };
+    let numbers = parts.keys().copied().collect::<Vec<_>>();
+    let last = numbers.last().copied().unwrap_or(first);
+    let missing = (first..=last).filter(|n| !parts.contains_key(n)).collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(format!(
+            "{} is missing {} (found {})",
+            name,
+            part_list(kind, &missing),
+            part_list(kind, &numbers)
+        ));
+    }
+    let parts = parts.into_values().chain(last_zip).collect();
+    Ok(Some(SplitArchive { kind, set: set.to_path_buf(), parts }))
+}
+
+/// Sorts split archives out of dropped `paths`. Returns the other paths, including
+/// `.rar` and `.zip` files without parts, and every split archive with all of its parts or
+/// why it can't be extracted. Several parts of the same archive end up as one entry.
+pub fn group_split_archives(paths: &[PathBuf]) -> (Vec<PathBuf>, Vec<Result<SplitArchive, String>>) {
+    let mut others = vec![];
+    let mut sets = BTreeMap::new();
+    for path in paths {
+        match parse_part(path).filter(|_| path.is_file()) {
+            Some(part) => {
+                sets.entry((part.set, part.kind)).or_insert_with(|| path.clone());
+            }
+            None => others.push(path.clone()),
+        }
+    }
+    let mut split = vec![];
+    for ((set, kind), path) in sets {
+        match find_set(&set, kind) {
+            Ok(Some(archive)) => split.push(Ok(archive)),
+            Ok(None) => others.push(path),
+            Err(e) => split.push(Err(e)),
+        }
+    }
+    (others, split)
+}
+
+impl SplitArchive {
+    pub fn name(&self) -> String {
+        self.set.file_name().unwrap_or_default().to_string_lossy().to_string()
+    }
+
+    pub fn part_count(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Archive the parts can be extracted from. Rar volumes are read straight from the first
+    /// part, other parts are joined into a single file in `dir`.
+    pub fn join(&self, dir: &Path, progress: &ExtractProgress) -> io::Result<PathBuf> {
+        if matches!(self.kind, SplitKind::RarVolumes | SplitKind::OldRarVolumes) {
+            return Ok(self.parts[0].clone());
+        }
+        *progress.current.lock().unwrap() = format!("{} ({} parts)", self.name(), self.parts.len());
+        let joined = match self.kind {
+            SplitKind::SpannedZip => dir.join(format!("{}.zip", self.name())),
+            _ => dir.join(self.name()),
+        };
+        let mut out = File::create(&joined)?;
+        let mut part_offsets = vec![];
+        let mut offset = 0;
+        for part in &self.parts {
+            part_offsets.push(offset);
+            let mut input = File::open(part)?;
+            copy_cancellable(&mut input, &mut out, progress)?;
+            offset += fs::metadata(part)?.len();
+        }
+        drop(out);
+        if self.kind == SplitKind::SpannedZip {
+            unspan_zip(&joined, &part_offsets)?;
+        }
+        info!("Joined {} parts of {}", self.parts.len(), self.name());
+        Ok(joined)
+    }
+}
+
+fn u16_at(buf: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([buf[at], buf[at + 1]])
+}
+
+fn u32_at(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+fn unsupported(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn too_big() -> io::Error {
+    unsupported("Split zips over 4 GiB aren't supported, join the parts with 7-Zip first")
+}
+
+/// Turns the joined parts of a spanned zip into a plain zip. Offsets in a spanned zip count
+/// from the start of the part they point into, they are moved to count from the start of
+/// the joined file and every entry is put on the first (and only) disk.
+fn unspan_zip(path: &Path, part_offsets: &[u64]) -> io::Result<()> {
+    const EOCD: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+    const CENTRAL: u32 = 0x02014b50;
+    let mut file = fs::OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+
+    // the end of central directory record sits before a comment of up to 64 KiB
+    let tail_len = len.min(22 + u16::MAX as u64);
+    let mut tail = vec![0; tail_len as usize];
+    file.seek(SeekFrom::Start(len - tail_len))?;
+    file.read_exact(&mut tail)?;
+    let pos = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| tail[i..i + 4] == EOCD)
+        .ok_or_else(|| unsupported("The last part isn't the end of a zip"))?;
+    let eocd_at = len - tail_len + pos as u64;
+    let mut eocd = tail[pos..pos + 22].to_vec();
+    let (cd_disk, entries, cd_size, cd_offset) =
+        (u16_at(&eocd, 6), u16_at(&eocd, 10), u32_at(&eocd, 12), u32_at(&eocd, 16));
+    if entries == u16::MAX || cd_size == u32::MAX || cd_offset == u32::MAX {
+        return Err(too_big());
+    }
+    let part_offset = |disk: u16| {
+        part_offsets
+            .get(disk as usize)
+            .copied()
+            .ok_or_else(|| unsupported("The zip refers to a part that wasn't found"))
+    };
+
+    let cd_at = part_offset(cd_disk)? + cd_offset as u64;
+    let mut cd = vec![0; cd_size as usize];
+    file.seek(SeekFrom::Start(cd_at))?;
+    file.read_exact(&mut cd)?;
+    let mut at = 0;
+    for _ in 0..entries {
+        if at + 46 > cd.len() || u32_at(&cd, at) != CENTRAL {
+            return Err(unsupported("Broken central directory in the split zip"));
+        }
+        let (disk, offset) = (u16_at(&cd, at + 34), u32_at(&cd, at + 42));
+        if disk == u16::MAX || offset == u32::MAX {
+            return Err(too_big());
+        }
+        let offset = u32::try_from(part_offset(disk)? + offset as u64).map_err(|_| too_big())?;
+        cd[at + 34..at + 36].copy_from_slice(&0u16.to_le_bytes());
+        cd[at + 42..at + 46].copy_from_slice(&offset.to_le_bytes());
+        at += 46 + u16_at(&cd, at + 28) as usize + u16_at(&cd, at + 30) as usize + u16_at(&cd, at + 32) as usize;
+    }
+    file.seek(SeekFrom::Start(cd_at))?;
+    file.write_all(&cd)?;
+
+    let cd_at = u32::try_from(cd_at).map_err(|_| too_big())?;
+    eocd[4..6].copy_from_slice(&0u16.to_le_bytes());
+    eocd[6..8].copy_from_slice(&0u16.to_le_bytes());
+    eocd[8..10].copy_from_slice(&entries.to_le_bytes());
+    eocd[16..20].copy_from_slice(&cd_at.to_le_bytes());
+    file.seek(SeekFrom::Start(eocd_at))?;
+    file.write_all(&eocd)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipArchive;
+
+    fn parsed(name: &str) -> Option<(PathBuf, SplitKind, u32)> {
+        parse_part(Path::new(name)).map(|part| (part.set, part.kind, part.number))
+    }
+
+    #[test]
+    fn parse_part_reads_every_naming_scheme() {
+        assert_eq!(parsed("mod.part2.rar"), Some(("mod".into(), SplitKind::RarVolumes, 2)));
+        assert_eq!(parsed("mod.PART10.RAR"), Some(("mod".into(), SplitKind::RarVolumes, 10)));
+        assert_eq!(parsed("mod.rar"), Some(("mod".into(), SplitKind::OldRarVolumes, 0)));
+        assert_eq!(parsed("mod.r00"), Some(("mod".into(), SplitKind::OldRarVolumes, 1)));
+        assert_eq!(parsed("mod.z01"), Some(("mod".into(), SplitKind::SpannedZip, 1)));
+        assert_eq!(parsed("mod.zip"), Some(("mod".into(), SplitKind::SpannedZip, u32::MAX)));
+        assert_eq!(parsed("mod.zip.003"), Some(("mod.zip".into(), SplitKind::Numbered, 3)));
+        assert_eq!(parsed("dir/mod.rar.001"), Some(("dir/mod.rar".into(), SplitKind::Numbered, 1)));
+    }
+
+    #[test]
+    fn parse_part_ignores_other_files() {
+        for name in ["mod.pak", "mod.7z.001", "mod.001", "mod.rxx", "mod.zip2", "mod"] {
+            assert!(parsed(name).is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn standalone_archives_are_not_split_parts() {
+        assert!(!is_split_part(Path::new("mod.rar")));
+        assert!(!is_split_part(Path::new("mod.zip")));
+        assert!(is_split_part(Path::new("mod.r00")));
+        assert!(is_split_part(Path::new("mod.z01")));
+        assert!(is_split_part(Path::new("mod.part1.rar")));
+        assert!(is_split_part(Path::new("mod.zip.001")));
+    }
+
+    fn touch(dir: &Path, names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                fs::write(&path, name).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn group_split_archives_finds_the_other_parts() {
+        let temp = tempfile::tempdir().unwrap();
+        let paths = touch(temp.path(), &["a.part1.rar", "a.part2.rar", "a.part3.rar", "b.pak"]);
+        // only one part and the pak are dropped
+        let (others, split) = group_split_archives(&[paths[1].clone(), paths[3].clone()]);
+        assert_eq!(others, vec![paths[3].clone()]);
+        assert_eq!(split.len(), 1);
+        let archive = split[0].as_ref().unwrap();
+        assert_eq!(archive.name(), "a");
+        assert_eq!(archive.parts, paths[..3].to_vec());
+    }
+
+    #[test]
+    fn group_split_archives_merges_parts_of_one_archive() {
+        let temp = tempfile::tempdir().unwrap();
+        let paths = touch(temp.path(), &["a.zip.001", "a.zip.002"]);
+        let (others, split) = group_split_archives(&paths);
+        assert!(others.is_empty());
+        assert_eq!(split.len(), 1);
+        assert_eq!(split[0].as_ref().unwrap().part_count(), 2);
+    }
+
+    #[test]
+    fn group_split_archives_passes_standalone_archives_through() {
+        let temp = tempfile::tempdir().unwrap();
+        let paths = touch(temp.path(), &["a.rar", "b.zip"]);
+        let (others, split) = group_split_archives(&paths);
+        assert_eq!(others, paths);
+        assert!(split.is_empty());
+    }
+
+    #[test]
+    fn group_split_archives_reports_missing_parts() {
+        let temp = tempfile::tempdir().unwrap();
+        let paths = touch(temp.path(), &["a.part1.rar", "a.part3.rar", "b.z01"]);
+        let (others, split) = group_split_archives(&paths);
+        assert!(others.is_empty());
+        let errors = split.into_iter().map(|s| s.unwrap_err()).collect::<Vec<_>>();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains(".part2.rar"), "{}", errors[0]);
+        assert!(errors[1].contains("b.zip is missing"), "{}", errors[1]);
+    }
+
+    /// Cuts a plain zip into a `.z01` holding the first entry and a `.zip` holding the rest,
+    /// with the offsets of the second part counting from its own start like a spanned zip
+    fn span_zip(zip: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let eocd_at = zip.len() - 22;
+        let (entries, cd_offset) = (u16_at(zip, eocd_at + 10), u32_at(zip, eocd_at + 16));
+        let mut offsets = vec![];
+        let mut at = cd_offset as usize;
+        for _ in 0..entries {
+            offsets.push((at, u32_at(zip, at + 42)));
+            at += 46 + u16_at(zip, at + 28) as usize + u16_at(zip, at + 30) as usize + u16_at(zip, at + 32) as usize;
+        }
+        let cut = offsets[1].1;
+        let mut spanned = zip.to_vec();
+        for (record, offset) in offsets.into_iter().filter(|(_, offset)| *offset >= cut) {
+            spanned[record + 34..record + 36].copy_from_slice(&1u16.to_le_bytes());
+            spanned[record + 42..record + 46].copy_from_slice(&(offset - cut).to_le_bytes());
+        }
+        spanned[eocd_at + 4..eocd_at + 6].copy_from_slice(&1u16.to_le_bytes());
+        spanned[eocd_at + 6..eocd_at + 8].copy_from_slice(&1u16.to_le_bytes());
+        spanned[eocd_at + 8..eocd_at + 10].copy_from_slice(&(entries - 1).to_le_bytes());
+        spanned[eocd_at + 16..eocd_at + 20].copy_from_slice(&(cd_offset - cut).to_le_bytes());
+        let last = spanned.split_off(cut as usize);
+        (spanned, last)
+    }
+
+    #[test]
+    fn spanned_zip_round_trip() {
+        let files = [("Mod_P.pak", vec![7u8; 3000]), ("readme.txt", b"hello".to_vec()), ("Mod_P.utoc", vec![1u8; 500])];
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in &files {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        let zip = writer.finish().unwrap().into_inner();
+        let (first, last) = span_zip(&zip);
+
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("mod.z01"), first).unwrap();
+        fs::write(temp.path().join("mod.zip"), last).unwrap();
+        let (others, split) = group_split_archives(&[temp.path().join("mod.zip")]);
+        assert!(others.is_empty());
+        let archive = split.into_iter().next().unwrap().unwrap();
+        assert_eq!(archive.part_count(), 2);
+
+        let out = tempfile::tempdir().unwrap();
+        let joined = archive.join(out.path(), &ExtractProgress::default()).unwrap();
+        let mut archive = ZipArchive::new(File::open(joined).unwrap()).unwrap();
+        for (name, data) in &files {
+            let mut read = vec![];
+            archive.by_name(name).unwrap().read_to_end(&mut read).unwrap();
+            assert_eq!(&read, data, "{}", name);
+        }
+    }
+
+    #[test]
+    fn unspan_zip_rejects_files_that_are_not_zips() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("mod.zip");
+        fs::write(&path, vec![0u8; 100]).unwrap();
+        assert!(unspan_zip(&path, &[0]).is_err());
+    }
+}
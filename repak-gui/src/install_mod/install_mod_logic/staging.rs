@@ -0,0 +1,131 @@
+use log::{info, warn};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Files of an installed mod put in the game folder, the pak last so the game never sees
+/// the pak of an iostore mod without its utoc and ucas
+const STAGED_EXTENSIONS: [&str; 3] = ["utoc", "ucas", "pak"];
+
+#[cfg(windows)]
+fn symlink_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+#[cfg(unix)]
+fn symlink_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+/// Puts `staged` at `target` as a symbolic link, or as a copy where links aren't permitted
+/// (Windows without developer mode or admin rights). Returns whether it was linked. A file
+/// or link already at `target` is replaced.
+fn place(staged: &Path, target: &Path) -> io::Result<bool> {
+    if target.symlink_metadata().is_ok() {
+        fs::remove_file(target)?;
+    }
+    match symlink_file(staged, target) {
+        Ok(()) => Ok(true),
+        Err(e) => {
+            warn!("Unable to link {}, copying it instead: {}", target.display(), e);
+            fs::copy(staged, target).map(|_| false)
+        }
+    }
+}
+
+/// Links the pak, utoc and ucas of the mod `base`, installed to `staging_dir`, into
+/// `game_dir`. Enabling and disabling the mod then renames the links and leaves the staged
+/// files alone.
+pub fn link_installed(staging_dir: &Path, game_dir: &Path, base: &str) -> Result<(), String> {
+    fs::create_dir_all(game_dir).map_err(|e| format!("Unable to create {}: {}", game_dir.display(), e))?;
+    let mut copied = 0;
+    for ext in STAGED_EXTENSIONS {
+        let staged = staging_dir.join(format!("{}.{}", base, ext));
+        if !staged.is_file() {
+            continue;
+        }
+        let target = game_dir.join(format!("{}.{}", base, ext));
+        match place(&staged, &target) {
+            Ok(true) => {}
+            Ok(false) => copied += 1,
+            Err(e) => {
+                return Err(format!(
+                    "Installed to the staging folder, but {} could not be written: {}",
+                    target.display(),
+                    e
+                ))
+            }
+        }
+    }
+    match copied {
+        0 => info!("Linked {} into {}", base, game_dir.display()),
+        n => info!("Copied {} file(s) of {} into {}, links aren't permitted there", n, base, game_dir.display()),
+    }
+    Ok(())
+}
+
+/// Staged file in `staging_dir` behind `game_file`, the link or copy of it in the game
+/// folder. A disabled pak is staged under its `.pak` name.
+pub fn staged_file(staging_dir: &Path, game_file: &Path) -> PathBuf {
+    if let Ok(target) = fs::read_link(game_file) {
+        return target;
+    }
+    let staged = staging_dir.join(game_file.file_name().unwrap_or_default());
+    match game_file.extension().is_some_and(|ext| ext == "bak_repak") {
+        true => staged.with_extension("pak"),
+        false => staged,
+    }
+}
+
+/// Staged files in `staging_dir` without a link or copy left in `game_dir`, e.g. of mods
+/// deleted from the mod list. Links are followed, so renamed mods keep their staged files;
+/// copies only match by name.
+pub fn orphaned_files(staging_dir: &Path, game_dir: &Path) -> Vec<PathBuf> {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut linked = HashSet::new();
+    let mut copied = HashSet::new();
+    for entry in WalkDir::new(game_dir).into_iter().filter_map(Result::ok) {
+        let path = entry.path();
+        match fs::read_link(path) {
+            Ok(target) => {
+                let target = match target.is_relative() {
+                    true => path.parent().unwrap_or(game_dir).join(target),
+                    false => target,
+                };
+                linked.insert(canonical(&target));
+            }
+            Err(_) if entry.file_type().is_file() => {
+                if let Ok(relative) = path.strip_prefix(game_dir) {
+                    copied.insert(relative.to_path_buf());
+                }
+            }
+            Err(_) => {}
+        }
+    }
+
+    WalkDir::new(staging_dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|staged| {
+            let ext = staged.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+            if !STAGED_EXTENSIONS.contains(&ext.as_str()) {
+                return false;
+            }
+            if linked.contains(&canonical(staged)) {
+                return false;
+            }
+            let Ok(relative) = staged.strip_prefix(staging_dir) else {
+                return false;
+            };
+            let mut names = vec![relative.to_path_buf()];
+            if ext == "pak" {
+                names.push(relative.with_extension("bak_repak"));
+            }
+            !names.iter().any(|name| copied.contains(name))
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use log::info;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings recovered from a config written by an older repak-gui release
+pub struct LegacyConfig {
+    pub game_path: PathBuf,
+    pub default_font_size: Option<f32>,
+    pub folders: Vec<LegacyFolder>,
+    pub mods: Vec<LegacyMod>,
+}
+
+pub struct LegacyFolder {
+    /// Id of the folder in the old config, if it had one
+    pub id: Option<String>,
+    pub name: String,
+}
+
+pub struct LegacyMod {
+    pub path: PathBuf,
+    pub custom_name: Option<String>,
+    /// Folder id or folder name, whichever the old config stored
+    pub folder: Option<String>,
+}
+
+fn string_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+fn parse_folders(value: Option<&Value>) -> Vec<LegacyFolder> {
+    match value {
+        Some(Value::Array(folders)) => folders
+            .iter()
+            .filter_map(|folder| match folder {
+                Value::String(name) => Some(LegacyFolder {
+                    id: None,
+                    name: name.clone(),
+                }),
+                Value::Object(_) => Some(LegacyFolder {
+                    id: string_field(folder, &["id"]),
+                    name: string_field(folder, &["name"])?,
+                }),
+                _ => None,
+            })
+            .collect(),
+        // some releases stored folders as a map keyed by name
+        Some(Value::Object(folders)) => folders
+            .keys()
+            .map(|name| LegacyFolder {
+                id: None,
+                name: name.clone(),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn parse_mods(root: &Value) -> Vec<LegacyMod> {
+    let Some(mods) = ["mod_metadata", "mods", "pak_files"]
+        .iter()
+        .find_map(|key| root.get(*key).and_then(Value::as_array))
+    else {
+        return vec![];
+    };
+
+    mods.iter()
+        .filter_map(|entry| match entry {
+            Value::String(path) => Some(LegacyMod {
+                path: PathBuf::from(path),
+                custom_name: None,
+                folder: None,
+            }),
+            Value::Object(_) => Some(LegacyMod {
+                path: PathBuf::from(string_field(entry, &["path"])?),
+                custom_name: string_field(entry, &["custom_name", "name"]),
+                folder: string_field(entry, &["folder_id", "folder"]),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether `root` has the shape the current release writes: `game_path`,
+/// `default_font_size` and a `folders` list of objects with every field. Such a config
+/// that fails to load is damaged, not old, and must not be imported over.
+fn is_current_schema(root: &Value) -> bool {
+    let folders_current = root.get("folders").and_then(Value::as_array).is_some_and(|folders| {
+        folders.iter().all(|folder| {
+            ["id", "name", "enabled", "expanded"]
+                .iter()
+                .all(|key| folder.get(*key).is_some())
+        })
+    });
+    root.get("game_path").is_some_and(Value::is_string)
+        && root.get("default_font_size").is_some_and(Value::is_number)
+        && folders_current
+}
+
+/// Tries to read `data` as an old style config. Only the game path is required,
+/// everything else is picked up when present. Configs in the current layout are never
+/// taken for old ones.
+pub fn parse_legacy_config(data: &str) -> Option<LegacyConfig> {
+    let root: Value = serde_json::from_str(data).ok()?;
+    if is_current_schema(&root) {
+        return None;
+    }
+    let game_path = string_field(&root, &["game_path", "mod_path", "path"])?;
+
+    Some(LegacyConfig {
+        game_path: PathBuf::from(game_path),
+        default_font_size: root
+            .get("default_font_size")
+            .and_then(Value::as_f64)
+            .map(|size| size as f32),
+        folders: parse_folders(root.get("folders")),
+        mods: parse_mods(&root),
+    })
+}
+
+/// Copies the config next to itself as `<name>.old`, or `<name>.old.N` if that is taken
+pub fn backup_config(path: &Path) -> std::io::Result<PathBuf> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut backup = path.with_file_name(format!("{}.old", file_name));
+    let mut n = 1;
+    while backup.exists() {
+        backup = path.with_file_name(format!("{}.old.{}", file_name, n));
+        n += 1;
+    }
+    fs::copy(path, &backup)?;
+    info!("Backed up old config to {}", backup.to_string_lossy());
+    Ok(backup)
+}
@@ -0,0 +1,44 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Splits a file stem into the name and the priority of its `_<priority>_P` suffix. Stems
+/// with a bare `_P` have no priority, stems without one are returned whole.
+pub fn split_priority(stem: &str) -> (&str, Option<u32>) {
+    let Some(rest) = stem.strip_suffix("_P") else {
+        return (stem, None);
+    };
+    match rest.rsplit_once('_') {
+        Some((name, digits)) if !name.is_empty() && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            match digits.parse() {
+                Ok(priority) => (name, Some(priority)),
+                Err(_) => (rest, None),
+            }
+        }
+        _ => (rest, None),
+    }
+}
+
+/// Priorities for `count` mods in load order, the last one is the highest and loads last so
+/// its files win. They count down from `top`, or up from 0 when `top` is too low for that.
+pub fn ascending_priorities(count: usize, top: u32) -> Vec<u32> {
+    let count = count as u32;
+    let start = top.checked_sub(count.saturating_sub(1)).unwrap_or(0);
+    (start..start + count).collect()
+}
+
+/// Every file shipped by more than one of `files`, the files of mods in load order, with the
+/// position of the mod it ends up coming from: the last one to load.
+pub fn winners(files: &[BTreeSet<String>]) -> BTreeMap<&str, usize> {
+    let mut owners: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for (position, mod_files) in files.iter().enumerate() {
+        for file in mod_files {
+            let owner = owners.entry(file.as_str()).or_insert((0, position));
+            owner.0 += 1;
+            owner.1 = position;
+        }
+    }
+    owners
+        .into_iter()
+        .filter(|(_, (count, _))| *count > 1)
+        .map(|(file, (_, position))| (file, position))
+        .collect()
+}
@@ -1,28 +1,87 @@
 extern crate core;
 
+mod aes_keys;
+mod bug_report;
+mod config_backup;
+mod conflicts;
+mod download_watch;
+mod file_index;
 mod file_table;
+mod fonts;
+mod game_paths;
+mod hex_view;
+mod install_log;
 mod install_mod;
+mod legacy_config;
+mod load_order;
+mod mod_hash;
+mod mod_list_export;
+mod mod_manifest;
+mod preview;
+mod settings_file;
+mod share_list;
 mod uasset_detection;
 mod uasset_api_integration;
+mod usage_stats;
 mod utils;
 
 pub mod ios_widget;
 mod utoc_utils;
 mod welcome;
 
+use crate::aes_keys::{
+    add_keys, guid_text, is_keys_file, keys_dir, loaded_keys, needs_key, open_pak, open_pak_with_key,
+    reload_aes_keys,
+};
+use crate::conflicts::{mod_files, shared_files};
+use crate::download_watch::DownloadWatcher;
+use crate::file_index::{mod_key, FileIndex};
 use crate::file_table::FileTable;
+use crate::game_paths::{GamePaths, GAME_PATHS_FILE};
+use crate::load_order::{ascending_priorities, split_priority, winners};
+use crate::mod_hash::HashCache;
+use crate::mod_list_export::{render, ExportFormat, ModListRow};
+use crate::mod_manifest::ModManifest;
+use crate::preview::PreviewCache;
+use crate::share_list::{decode, encode, short_hash, SharedMod};
+use crate::uasset_detection::sniff_mod_type;
+use crate::usage_stats::{UsageEvent, UsageStats};
 use crate::install_mod::{
-    map_dropped_file_to_mods, map_paths_to_mods, InstallableMod, ModInstallRequest, AES_KEY,
+    has_archives, is_archive_path, map_paths_to_mods, naming_ui, InstallableMod, ModInstallRequest,
+    MOD_CATEGORIES,
 };
 use crate::utils::find_marvel_rivals;
-use crate::utils::get_current_pak_characteristics;
+use crate::utils::format_bytes;
+use crate::utils::{check_writable, copy_mod_files, format_age, game_running, mod_file_copies, mod_pak_files, open_with_default_app, open_with_tool, pasted_paths, show_in_folder, suggest_mods_dir, watch_game};
+use crate::utils::{
+    get_current_pak_characteristics, mod_type_category, path_categories, DETECTED_CATEGORIES, MEMORY_MAP_UCAS,
+};
 use crate::utoc_utils::read_utoc;
 use eframe::egui::{
     self, style::Selection, Align, Align2, Button, Color32, IconData, Id, Label, LayerId, Order,
     RichText, ScrollArea, Stroke, Style, TextEdit, TextStyle, Theme,
 };
 use egui_flex::{item, Flex, FlexAlign};
-use install_mod::install_mod_logic::pak_files::extract_pak_to_dir;
+use install_mod::install_mod_logic::compact::{
+    compact_mod, is_compactable, CompactResult, COMPACT_STEPS, OPTIMIZE_LEVELS,
+};
+use install_mod::install_mod_logic::convert::{
+    backup_dir as convert_backup_dir, convert_mods, has_backup as has_convert_backup, ConvertJob, ConvertOutcome,
+    ConvertTarget,
+};
+use install_mod::install_mod_logic::extract_all::{extract_mods, ExtractResult};
+use install_mod::install_mod_logic::normalize::{normalize_mod_names, rename_mods, NormalizeResult};
+use install_mod::install_mod_logic::pak_files::{extract_pak_to_dir, DEFAULT_PACK_IGNORE};
+use install_mod::install_mod_logic::project::{unpack_project, RepakProject, PROJECT_FILE};
+use install_mod::install_mod_logic::rename_pattern::{
+    expand_pattern, rename_collisions, sanitize_file_name, PatternFields, PLACEHOLDERS,
+};
+use install_mod::install_mod_logic::staging;
+use install_mod::install_mod_logic::{
+    display_base_name, pakchunk_index, with_thread_limit, ModNaming, ModVersion, NameCollision, PackSizes,
+    PendingVersion, DEFAULT_PRIORITY,
+};
+use legacy_config::{backup_config, parse_legacy_config};
 use log::{debug, error, info, trace, warn, LevelFilter};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use path_clean::PathClean;
@@ -30,20 +89,31 @@ use repak::PakReader;
 use rfd::{FileDialog, MessageButtons};
 use serde::{Deserialize, Serialize};
 use simplelog::{ColorChoice, CombinedLogger, Config, TermLogger, TerminalMode, WriteLogger};
+use install_log::InstallLogger;
 use std::cell::LazyCell;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
 use std::io::BufReader;
-use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{fs, thread};
 use uuid::Uuid;
 use walkdir::WalkDir;
 
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
+/// Most matching paths the file search lists at once
+const FILE_SEARCH_LIMIT: usize = 500;
+
+/// Lowest contrast ratio text may have against the panel background, the WCAG minimum for
+/// large text
+const MIN_TEXT_CONTRAST: f32 = 3.0;
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
 struct CustomPalette {
     // Stored as RGBA components for serde compatibility
     accent: [u8; 4],
@@ -54,6 +124,10 @@ struct CustomPalette {
     widget_active: [u8; 4],
     widget_open: [u8; 4],
     text: Option<[u8; 4]>,
+    /// Name colors of mods by state, see `ModState`
+    state_disabled: [u8; 4],
+    state_conflicting: [u8; 4],
+    state_broken: [u8; 4],
 }
 
 impl Default for CustomPalette {
@@ -68,6 +142,263 @@ impl Default for CustomPalette {
             widget_active: [0x4a, 0x55, 0x68, 0xff],
             widget_open: [0x55, 0x3c, 0x4e, 0xff],
             text: Some([0xf9, 0xfa, 0xfb, 0xff]),
+            state_disabled: [0x8a, 0x8f, 0x98, 0xff],
+            state_conflicting: [0xf5, 0x9e, 0x0b, 0xff],
+            state_broken: [0xef, 0x44, 0x44, 0xff],
+        }
+    }
+}
+
+/// Pages of the settings window
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+enum SettingsSection {
+    #[default]
+    Appearance,
+    Paths,
+    InstallDefaults,
+    Advanced,
+}
+
+impl SettingsSection {
+    const ALL: [SettingsSection; 4] = [
+        SettingsSection::Appearance,
+        SettingsSection::Paths,
+        SettingsSection::InstallDefaults,
+        SettingsSection::Advanced,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SettingsSection::Appearance => "Appearance",
+            SettingsSection::Paths => "Paths",
+            SettingsSection::InstallDefaults => "Install defaults",
+            SettingsSection::Advanced => "Advanced",
+        }
+    }
+
+    /// Settings shown on this page, used by the search box
+    fn entries(&self) -> &'static [&'static str] {
+        match self {
+            SettingsSection::Appearance => &[
+                "Font size",
+                "Fonts for Chinese / Japanese / Korean",
+                "Dark / light mode",
+                "Custom color palette",
+                "Preview thumbnails in mod list",
+                "Color mods by state",
+                "Group mod list",
+                "Mod list density",
+                "Quick install mode",
+            ],
+            SettingsSection::Paths => &[
+                "Mod folder",
+                "Watch downloads folder",
+                "Watch mod folder",
+                "Rescan delay",
+                "External tool",
+            ],
+            SettingsSection::InstallDefaults => &[
+                "Install target",
+                "Staging folder",
+                "Installed mod file names",
+                "Default priority",
+                "Install threads",
+                "Suggest mesh / texture fixes",
+                "Ignored files when packing",
+                "Tag new mods by type",
+                "Install folders by type",
+                "Verify installed mods",
+                "Name already installed",
+            ],
+            SettingsSection::Advanced => &[
+                "Check for file conflicts when enabling a mod",
+                "Show welcome screen after updates",
+                "Back up config on launch",
+                "Memory-map .ucas files",
+                "Flag mods that may be outdated",
+                "Folders remember mod states",
+                "List game files separately",
+                "Count feature usage",
+            ],
+        }
+    }
+}
+
+/// AES key being typed for a pak that none of the known keys open
+struct KeyPrompt {
+    path: PathBuf,
+    key: String,
+    /// Why the last key that was tried didn't work
+    error: Option<String>,
+}
+
+/// Settings of the batch rename window
+struct BatchRename {
+    pattern: String,
+    /// First value of the `{n}` counter
+    start: usize,
+    /// Rename the files too instead of only the names shown in the list
+    rename_files: bool,
+    /// Pattern, counter start, file toggle and selected mods `preview` was made for, it is
+    /// made again once one of them changes
+    previewed_for: Option<(String, usize, bool, Vec<PathBuf>)>,
+    preview: Vec<RenamePreview>,
+}
+
+/// Enabled mods sharing files, put in the order the game should load them
+struct LoadOrder {
+    /// Pak path and files of each mod, the first loads first and the last wins
+    mods: Vec<(PathBuf, BTreeSet<String>)>,
+    /// Files shipped by more than one of the mods and the position of the one that wins
+    shared: Vec<(String, usize)>,
+    /// Mods that keep their name and place, locked mods and game files, with the reason
+    fixed: BTreeMap<PathBuf, &'static str>,
+}
+
+impl LoadOrder {
+    fn new(mut mods: Vec<(PathBuf, BTreeSet<String>)>, fixed: BTreeMap<PathBuf, &'static str>) -> Self {
+        // start from the order the current suffixes give
+        mods.sort_by_cached_key(|(path, _)| {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            (split_priority(&stem).1.unwrap_or(0), stem.to_lowercase())
+        });
+        let mut order = LoadOrder { mods, shared: vec![], fixed };
+        order.update_shared();
+        order
+    }
+
+    fn update_shared(&mut self) {
+        let files = self.mods.iter().map(|(_, files)| files.clone()).collect::<Vec<_>>();
+        self.shared = winners(&files).into_iter().map(|(file, winner)| (file.to_string(), winner)).collect();
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.mods.swap(a, b);
+        self.update_shared();
+    }
+
+    /// New file stem of every mod, numbered so the list order is the load order. Fixed
+    /// mods keep theirs.
+    fn new_stems(&self, top: u32) -> Vec<String> {
+        let priorities = ascending_priorities(self.mods.len(), top);
+        self.mods
+            .iter()
+            .zip(priorities)
+            .map(|((path, _), priority)| {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                match self.fixed.contains_key(path) {
+                    true => stem.to_string(),
+                    false => format!("{}_{}_P", split_priority(&stem).0, priority),
+                }
+            })
+            .collect()
+    }
+
+    fn is_fixed(&self, position: usize) -> bool {
+        self.mods.get(position).is_some_and(|(path, _)| self.fixed.contains_key(path))
+    }
+}
+
+/// What the batch rename would do to one selected mod
+struct RenamePreview {
+    index: usize,
+    old_name: String,
+    /// New name, or why the mod can't be renamed
+    new_name: Result<String, String>,
+    /// New file stem when files are renamed, with the priority suffix kept
+    new_stem: Option<String>,
+}
+
+/// Health of a mod, shown by the color of its name unless `skip_state_colors` is set
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ModState {
+    Normal,
+    Disabled,
+    /// Enabled and shipping files another enabled mod ships too
+    Conflicting,
+    /// Only one of the utoc and ucas of an iostore mod is there
+    Broken,
+}
+
+impl ModState {
+    fn description(self) -> &'static str {
+        match self {
+            ModState::Normal => "Enabled",
+            ModState::Disabled => "Disabled",
+            ModState::Conflicting => "Shares files with another enabled mod, one of them wins",
+            ModState::Broken => "The utoc or ucas of this iostore mod is missing, the game can't load it",
+        }
+    }
+}
+
+/// How long the mod folder has to be quiet before changes in it trigger a rescan
+const DEFAULT_WATCH_DEBOUNCE_MS: u64 = 500;
+
+fn default_watch_debounce() -> u64 {
+    DEFAULT_WATCH_DEBOUNCE_MS
+}
+
+fn default_mod_priority() -> u32 {
+    DEFAULT_PRIORITY
+}
+
+fn default_pack_ignore() -> String {
+    DEFAULT_PACK_IGNORE.to_string()
+}
+
+/// Where new installs are written
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+enum InstallTarget {
+    /// The `~mods` folder picked in Settings > Paths
+    #[default]
+    Mods,
+    /// The game's `Paks` folder the `~mods` folder sits in
+    Paks,
+}
+
+impl InstallTarget {
+    fn label(&self) -> &'static str {
+        match self {
+            InstallTarget::Mods => "~mods folder (recommended)",
+            InstallTarget::Paks => "Game's Paks folder",
+        }
+    }
+}
+
+/// Spacing of the mod list
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+enum ListDensity {
+    /// A framed box per mod
+    #[default]
+    Comfortable,
+    /// Unframed rows with less padding and smaller names
+    Compact,
+}
+
+impl ListDensity {
+    fn label(&self) -> &'static str {
+        match self {
+            ListDensity::Comfortable => "Comfortable",
+            ListDensity::Compact => "Compact",
+        }
+    }
+}
+
+/// Sections the mod list is split into
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+enum ListGrouping {
+    /// The user's folders, then ungrouped mods
+    #[default]
+    Folder,
+    /// One section per detected type, folders are ignored
+    Type,
+}
+
+impl ListGrouping {
+    fn label(&self) -> &'static str {
+        match self {
+            ListGrouping::Folder => "Group by folder",
+            ListGrouping::Type => "Group by type",
         }
     }
 }
@@ -75,6 +406,62 @@ impl Default for CustomPalette {
 impl CustomPalette {
     fn rgba(c: [u8; 4]) -> Color32 { Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3]) }
     fn accent_color(&self) -> Color32 { Self::rgba(self.accent) }
+
+    // relative luminance as defined by WCAG
+    fn luminance(c: [u8; 4]) -> f32 {
+        let channel = |v: u8| {
+            let v = v as f32 / 255.;
+            if v <= 0.03928 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * channel(c[0]) + 0.7152 * channel(c[1]) + 0.0722 * channel(c[2])
+    }
+
+    fn contrast(a: [u8; 4], b: [u8; 4]) -> f32 {
+        let (la, lb) = (Self::luminance(a), Self::luminance(b));
+        (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+    }
+
+    /// Fixes colors that would leave the UI unreadable, returns a line per change made
+    fn sanitize(&mut self) -> Vec<String> {
+        let mut adjusted = vec![];
+        for (name, fill) in [("Panel", &mut self.panel_fill), ("Window", &mut self.window_fill)] {
+            if fill[3] < 128 {
+                fill[3] = 255;
+                adjusted.push(format!("{} background was see-through, made it opaque", name));
+            }
+        }
+
+        let background = self.panel_fill;
+        // black or white, whichever stands out more on the panel
+        let readable = if Self::contrast([0xff; 4], background) >= Self::contrast([0, 0, 0, 0xff], background) {
+            ([0xff; 4], "white")
+        } else {
+            ([0, 0, 0, 0xff], "black")
+        };
+        let mut fix_color = |name: &str, color: &mut [u8; 4]| {
+            if color[3] < 128 {
+                color[3] = 255;
+                adjusted.push(format!("{} color was see-through, made it opaque", name));
+            }
+            let contrast = Self::contrast(*color, background);
+            if contrast < MIN_TEXT_CONTRAST {
+                *color = readable.0;
+                adjusted.push(format!(
+                    "{} color was hard to read on the panel background ({:.1}:1), replaced it with {}",
+                    name, contrast, readable.1
+                ));
+            }
+        };
+        if let Some(text) = &mut self.text {
+            fix_color("Text", text);
+        }
+        // selected mod names are drawn in the accent color
+        fix_color("Accent", &mut self.accent);
+        fix_color("Disabled mod", &mut self.state_disabled);
+        fix_color("Conflicting mod", &mut self.state_conflicting);
+        fix_color("Broken mod", &mut self.state_broken);
+        adjusted
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -86,16 +473,51 @@ struct RepakModManager {
     mod_metadata: Vec<ModMetadata>,
     #[serde(skip)]
     current_pak_file_idx: Option<usize>,
+    /// Mod selected when the config was last saved, selected again on the next launch
+    #[serde(default)]
+    last_selected_mod: Option<PathBuf>,
     #[serde(skip)]
     pak_files: Vec<ModEntry>,
     #[serde(skip)]
     table: Option<FileTable>,
+    /// Widths of the resizable file table columns
+    #[serde(default)]
+    file_table_widths: Vec<f32>,
     #[serde(skip)]
     file_drop_viewport_open: bool,
     #[serde(skip)]
     install_mod_dialog: Option<ModInstallRequest>,
+    /// Paths the open install window was started with
+    #[serde(skip)]
+    install_batch: Vec<PathBuf>,
+    /// Drops and pastes that came in while the install window was open, one batch each with
+    /// the folder it was dropped on
+    #[serde(skip)]
+    install_queue: Vec<(Vec<PathBuf>, Option<String>)>,
+    /// Folder headers drawn this frame and where, to find the folder files are dropped on
+    #[serde(skip)]
+    folder_header_rects: Vec<(String, egui::Rect)>,
+    /// Last pointer position reported while files are dragged over the window, `None` when
+    /// the platform reports none during the drag
+    #[serde(skip)]
+    file_drag_pos: Option<egui::Pos2>,
+    /// Folder each mod installed by a drop onto a folder header goes into, by lowercase file
+    /// stem, until the mod is listed
+    #[serde(skip)]
+    pending_folders: BTreeMap<String, String>,
     #[serde(skip)]
     receiver: Option<Receiver<Event>>,
+    /// Milliseconds of quiet in the mod folder before watcher events trigger a rescan, so a
+    /// batch of copied files causes one rescan instead of one per file
+    #[serde(default = "default_watch_debounce")]
+    watch_debounce_ms: u64,
+    /// Don't watch the mod folder, for network drives and antivirus setups where the watcher
+    /// rescans constantly or fails. The list then only reloads on Refresh.
+    #[serde(default)]
+    skip_folder_watch: bool,
+    /// When the pending rescan from watcher events is due
+    #[serde(skip)]
+    watch_rescan_at: Option<Instant>,
     #[serde(skip)]
     welcome_screen: Option<ShowWelcome>,
     #[serde(skip)]
@@ -104,6 +526,9 @@ struct RepakModManager {
     creating_folder: bool,
     #[serde(skip)]
     new_folder_name: String,
+    /// Name typed under "Move to folder" for a folder to create and move the selection into
+    #[serde(skip)]
+    bulk_folder_name: String,
     #[serde(skip)]
     game_path_input: String,
     #[serde(skip)]
@@ -120,10 +545,16 @@ struct RepakModManager {
     show_tag_filter_dropdown: bool,
     #[serde(skip)]
     custom_tag_filter_enabled: bool,
+    /// Only list starred mods
+    #[serde(skip)]
+    favorites_only: bool,
     #[serde(skip)]
     selected_custom_tag_filters: std::collections::HashSet<String>,
     #[serde(default)]
     custom_tag_catalog: Vec<String>,
+    /// Optional chip color per custom tag, tags without an entry use the default style
+    #[serde(default)]
+    tag_colors: std::collections::HashMap<String, [u8; 3]>,
     #[serde(skip)]
     show_custom_tag_filter_dropdown: bool,
     #[serde(skip)]
@@ -143,6 +574,9 @@ struct RepakModManager {
     #[serde(skip)]
     bulk_remove_choice: Option<String>,
     version: Option<String>,
+    /// Load a system CJK font as fallback so Chinese, Japanese and Korean mod names render
+    #[serde(default)]
+    cjk_font: bool,
     // Custom palette support
     #[serde(default)]
     use_custom_palette: bool,
@@ -151,19 +585,243 @@ struct RepakModManager {
     #[serde(skip)]
     show_palette_window: bool,
     #[serde(skip)]
+    show_settings_window: bool,
+    /// Last open page of the settings window
+    #[serde(default)]
+    settings_section: SettingsSection,
+    /// Scroll offset of the settings page, restored when the window opens
+    #[serde(default)]
+    settings_scroll: f32,
+    #[serde(skip)]
+    settings_search: String,
+    #[serde(skip)]
+    settings_scroll_restored: bool,
+    #[serde(skip)]
     preset_name_input: String,
     #[serde(skip)]
     refresh_after_delete: bool,
+    /// Set by the Refresh button, handled together with watcher events so both end up as one scan
     #[serde(skip)]
-    delete_sender: Option<Sender<Vec<std::path::PathBuf>>>,
+    refresh_requested: bool,
+    /// Anchor for shift-click range selection
     #[serde(skip)]
-    delete_results: Option<Receiver<Result<Vec<std::path::PathBuf>, String>>>,
+    last_selected_idx: Option<usize>,
+    /// Row a drag over the checkboxes started on and the state it paints onto other rows
+    #[serde(skip)]
+    drag_select: Option<(usize, bool)>,
+    #[serde(skip)]
+    delete_sender: Option<Sender<DeleteJob>>,
+    #[serde(skip)]
+    delete_results: Option<Receiver<DeleteUpdate>>,
+    /// Files deleted and total of the job the delete worker is on
+    #[serde(skip)]
+    delete_progress: Option<(usize, usize)>,
     #[serde(skip)]
     deleting_mods: std::collections::HashSet<std::path::PathBuf>,
     #[serde(skip)]
     pending_remove_paths: Vec<std::path::PathBuf>,
     #[serde(skip)]
     pending_restart: bool,
+    #[serde(skip)]
+    compact_target: Option<PathBuf>,
+    #[serde(skip)]
+    compact_level_idx: usize,
+    #[serde(skip)]
+    compact_running: Option<PathBuf>,
+    #[serde(skip)]
+    compact_progress: Arc<AtomicI32>,
+    #[serde(skip)]
+    compact_results: Option<Receiver<Result<CompactResult, String>>>,
+    #[serde(skip)]
+    compact_pending_confirm: Option<CompactResult>,
+    #[serde(skip)]
+    normalize_results: Option<Receiver<NormalizeResult>>,
+    #[serde(skip)]
+    extract_total: usize,
+    #[serde(skip)]
+    extract_progress: Arc<AtomicI32>,
+    #[serde(skip)]
+    extract_results: Option<Receiver<ExtractResult>>,
+    /// Mod being unpacked as a project and the project folder or error once done
+    #[serde(skip)]
+    unpack_project_name: String,
+    #[serde(skip)]
+    unpack_project_results: Option<Receiver<Result<PathBuf, String>>>,
+    /// Mod being copied by "Copy mod files to...", with its pak, the folder and the copied
+    /// files once done
+    #[serde(skip)]
+    copy_mod_name: String,
+    #[serde(skip)]
+    copy_mod_results: Option<Receiver<(PathBuf, PathBuf, std::io::Result<Vec<PathBuf>>)>>,
+    #[serde(skip)]
+    type_scan_total: usize,
+    #[serde(skip)]
+    type_scan_progress: Arc<AtomicI32>,
+    #[serde(skip)]
+    type_scan_results: Option<Receiver<Vec<TypeScanResult>>>,
+    /// Size of every file in the install folder, summed up by `collect_pak_files`
+    #[serde(skip)]
+    library_size: u64,
+    #[serde(skip)]
+    show_convert_mods: bool,
+    #[serde(skip)]
+    convert_target: ConvertTarget,
+    #[serde(skip)]
+    convert_total: usize,
+    #[serde(skip)]
+    convert_progress: Arc<AtomicI32>,
+    #[serde(skip)]
+    convert_results: Option<Receiver<Vec<ConvertOutcome>>>,
+    /// Outcomes of the last conversion, listed in the convert window
+    #[serde(skip)]
+    convert_outcomes: Vec<ConvertOutcome>,
+    #[serde(skip)]
+    show_file_search: bool,
+    #[serde(skip)]
+    file_search_query: String,
+    #[serde(skip)]
+    file_search_results: Vec<(PathBuf, Vec<String>)>,
+    /// Filled in the background as mods are loaded, see `FileIndex::queue`
+    #[serde(skip)]
+    file_index: FileIndex,
+    #[serde(skip)]
+    show_share_list: bool,
+    /// Share code pasted to compare against the installed mods
+    #[serde(skip)]
+    share_code_input: String,
+    /// Offer to install mods that show up in `downloads_dir`
+    #[serde(default)]
+    watch_downloads: bool,
+    /// Folder watched for downloaded mods, the system downloads folder if unset
+    #[serde(default)]
+    downloads_dir: Option<PathBuf>,
+    /// Program mods can be opened with from the context menu, e.g. FModel
+    #[serde(default)]
+    external_tool: Option<PathBuf>,
+    #[serde(skip)]
+    download_watcher: Option<DownloadWatcher>,
+    /// Finished downloads waiting for the user to install or ignore them
+    #[serde(skip)]
+    downloaded_mods: Vec<PathBuf>,
+    /// Set when the current mod was changed from the keyboard, the list scrolls to it once
+    #[serde(skip)]
+    scroll_to_current: bool,
+    /// Default file naming for new installs
+    #[serde(default)]
+    mod_naming: ModNaming,
+    /// Priority in the `_<priority>_P` suffix of `ModNaming::Suffix`
+    #[serde(default = "default_mod_priority")]
+    default_priority: u32,
+    /// Tag new installs by their detected type, using `type_tags`
+    #[serde(default)]
+    auto_type_tags: bool,
+    /// Don't pre-check the mesh and texture fixes the mod scan suggests
+    #[serde(default)]
+    skip_suggested_fixes: bool,
+    /// Don't open installed paks again to check they were written completely
+    #[serde(default)]
+    skip_install_verify: bool,
+    /// What installing a mod under the name of an installed one does
+    #[serde(default)]
+    name_collision: NameCollision,
+    /// Patterns of files left out when packing loose folder mods, see `collect_pack_files`
+    #[serde(default = "default_pack_ignore")]
+    pack_ignore: String,
+    /// Custom tag for each type category, an empty tag leaves that category untagged
+    #[serde(default)]
+    type_tags: BTreeMap<String, String>,
+    /// Sub folder of `~mods` each type category is installed to, unset ones go in `~mods`
+    #[serde(default)]
+    type_dirs: BTreeMap<String, String>,
+    /// Folder new installs go to, the mod list shows the same folder
+    #[serde(default)]
+    install_target: InstallTarget,
+    /// Library folder installs are written to instead, the install target gets links to
+    /// the files, or copies where links aren't permitted
+    #[serde(default)]
+    staging_dir: Option<PathBuf>,
+    #[serde(skip)]
+    previews: PreviewCache,
+    #[serde(skip)]
+    hashes: HashCache,
+    /// Show preview thumbnails next to the mod names
+    #[serde(default)]
+    show_list_thumbnails: bool,
+    /// Keep every mod name in the text color instead of coloring them by `ModState`
+    #[serde(default)]
+    skip_state_colors: bool,
+    /// Enabled mods sharing files, as `mod_key`s, and the enabled mods they were found among
+    #[serde(skip)]
+    mod_conflicts: Option<(Vec<PathBuf>, std::collections::HashSet<PathBuf>)>,
+    #[serde(default)]
+    list_density: ListDensity,
+    /// Only a mod list and a drop zone, drops install right away with the default options
+    #[serde(default)]
+    quick_install: bool,
+    #[serde(default)]
+    list_grouping: ListGrouping,
+    /// Type sections folded away while grouping by type
+    #[serde(default)]
+    collapsed_type_groups: std::collections::BTreeSet<String>,
+    /// Detected type of each pak, detection reads the whole file list so it's done once per scan
+    #[serde(skip)]
+    detected_types: std::collections::HashMap<PathBuf, DetectedType>,
+    /// Don't look for overlapping files when a mod is enabled
+    #[serde(default)]
+    skip_conflict_check: bool,
+    /// Don't open the welcome screen after an update, the Donate button still shows it
+    #[serde(default)]
+    skip_welcome_on_update: bool,
+    #[serde(default)]
+    skip_config_backups: bool,
+    /// Read .ucas files through a memory map, see `utils::MEMORY_MAP_UCAS`
+    #[serde(default)]
+    memory_map_ucas: bool,
+    /// Flag mods replacing assets that aren't in the known game path list
+    #[serde(default)]
+    check_outdated: bool,
+    #[serde(skip)]
+    game_paths: Option<GamePaths>,
+    /// Assets of each mod missing from `game_paths`, only mods with missing assets are listed
+    #[serde(skip)]
+    outdated_assets: Option<std::collections::HashMap<PathBuf, Vec<String>>>,
+    /// List paks that look like game files in their own read-only section
+    #[serde(default)]
+    separate_system_mods: bool,
+    /// File name parts that mark a pak as a game file, separated by commas or new lines
+    #[serde(default)]
+    system_mod_patterns: String,
+    #[serde(skip)]
+    system_mods_unlocked: bool,
+    /// Switching a folder back on restores the mods that were on before it went off,
+    /// instead of turning every mod in it on
+    #[serde(default)]
+    folder_keeps_mod_states: bool,
+    /// Threads installs and recompression may use, 0 for all cores
+    #[serde(default)]
+    install_threads: usize,
+    /// Count how often features are used, off unless the user turns it on
+    #[serde(default)]
+    usage_stats_enabled: bool,
+    #[serde(default)]
+    usage_stats: UsageStats,
+    #[serde(skip)]
+    show_usage_stats: bool,
+    /// Paks with an encrypted index that no known key opens, left out of `pak_files`
+    #[serde(skip)]
+    encrypted_paks: Vec<PathBuf>,
+    #[serde(skip)]
+    key_prompt: Option<KeyPrompt>,
+    #[serde(skip)]
+    batch_rename: Option<BatchRename>,
+    #[serde(skip)]
+    load_order: Option<LoadOrder>,
+    /// Log lines of the mods installed or converted this session, by lowercase file stem
+    #[serde(skip)]
+    install_logs: BTreeMap<String, Vec<String>>,
+    /// File stem of the mod whose install log is shown
+    #[serde(skip)]
+    install_log_view: Option<String>,
 }
 
 impl Default for RepakModManager {
@@ -174,15 +832,26 @@ impl Default for RepakModManager {
             folders: Vec::new(),
             mod_metadata: Vec::new(),
             current_pak_file_idx: None,
+            last_selected_mod: None,
             pak_files: Vec::new(),
             table: None,
+            file_table_widths: Vec::new(),
             file_drop_viewport_open: false,
             install_mod_dialog: None,
+            install_batch: vec![],
+            install_queue: vec![],
+            folder_header_rects: vec![],
+            pending_folders: BTreeMap::new(),
+            file_drag_pos: None,
             receiver: None,
+            watch_debounce_ms: DEFAULT_WATCH_DEBOUNCE_MS,
+            skip_folder_watch: false,
+            watch_rescan_at: None,
             welcome_screen: None,
             hide_welcome: false,
             creating_folder: false,
             new_folder_name: String::new(),
+            bulk_folder_name: String::new(),
             game_path_input: String::new(),
             search_query: String::new(),
             filtered_mods: Vec::new(),
@@ -191,8 +860,10 @@ impl Default for RepakModManager {
             selected_tag_filters: std::collections::HashSet::new(),
             show_tag_filter_dropdown: false,
             custom_tag_filter_enabled: false,
+            favorites_only: false,
             selected_custom_tag_filters: std::collections::HashSet::new(),
             custom_tag_catalog: Vec::new(),
+            tag_colors: std::collections::HashMap::new(),
             show_custom_tag_filter_dropdown: false,
             show_tag_manager: false,
             new_global_tag_input: String::new(),
@@ -203,16 +874,104 @@ impl Default for RepakModManager {
             bulk_tag_input: String::new(),
             bulk_remove_choice: None,
             version: None,
+            cjk_font: false,
             use_custom_palette: false,
             custom_palette: CustomPalette::default(),
             show_palette_window: false,
+            show_settings_window: false,
+            settings_section: SettingsSection::default(),
+            settings_scroll: 0.0,
+            settings_search: String::new(),
+            settings_scroll_restored: false,
             preset_name_input: String::new(),
             refresh_after_delete: false,
+            refresh_requested: false,
+            last_selected_idx: None,
+            drag_select: None,
             delete_sender: None,
             delete_results: None,
+            delete_progress: None,
             deleting_mods: std::collections::HashSet::new(),
             pending_remove_paths: Vec::new(),
             pending_restart: false,
+            compact_target: None,
+            compact_level_idx: OPTIMIZE_LEVELS.len() - 1,
+            compact_running: None,
+            compact_progress: Arc::new(AtomicI32::new(0)),
+            compact_results: None,
+            normalize_results: None,
+            extract_total: 0,
+            extract_progress: Arc::new(AtomicI32::new(0)),
+            library_size: 0,
+            show_convert_mods: false,
+            convert_target: ConvertTarget::default(),
+            convert_total: 0,
+            convert_progress: Arc::new(AtomicI32::new(0)),
+            convert_results: None,
+            convert_outcomes: Vec::new(),
+            extract_results: None,
+            unpack_project_name: String::new(),
+            unpack_project_results: None,
+            copy_mod_name: String::new(),
+            copy_mod_results: None,
+            type_scan_total: 0,
+            type_scan_progress: Arc::new(AtomicI32::new(0)),
+            type_scan_results: None,
+            show_file_search: false,
+            file_search_query: String::new(),
+            file_search_results: Vec::new(),
+            file_index: FileIndex::default(),
+            show_share_list: false,
+            share_code_input: String::new(),
+            watch_downloads: false,
+            downloads_dir: None,
+            external_tool: None,
+            download_watcher: None,
+            downloaded_mods: Vec::new(),
+            scroll_to_current: false,
+            compact_pending_confirm: None,
+            mod_naming: ModNaming::default(),
+            default_priority: DEFAULT_PRIORITY,
+            auto_type_tags: false,
+            skip_suggested_fixes: false,
+            skip_install_verify: false,
+            name_collision: NameCollision::default(),
+            pack_ignore: default_pack_ignore(),
+            type_tags: BTreeMap::new(),
+            type_dirs: BTreeMap::new(),
+            install_target: InstallTarget::default(),
+            staging_dir: None,
+            previews: PreviewCache::default(),
+            hashes: HashCache::default(),
+            show_list_thumbnails: false,
+            skip_state_colors: false,
+            mod_conflicts: None,
+            list_density: ListDensity::default(),
+            quick_install: false,
+            list_grouping: ListGrouping::default(),
+            collapsed_type_groups: std::collections::BTreeSet::new(),
+            detected_types: std::collections::HashMap::new(),
+            skip_conflict_check: false,
+            skip_welcome_on_update: false,
+            skip_config_backups: false,
+            memory_map_ucas: false,
+            check_outdated: false,
+            game_paths: None,
+            outdated_assets: None,
+            separate_system_mods: false,
+            system_mod_patterns: String::new(),
+            system_mods_unlocked: false,
+            folder_keeps_mod_states: false,
+            install_threads: 0,
+            usage_stats_enabled: false,
+            usage_stats: UsageStats::default(),
+            show_usage_stats: false,
+            encrypted_paks: vec![],
+            key_prompt: None,
+            batch_rename: None,
+            load_order: None,
+            install_logs: BTreeMap::new(),
+            install_log_view: None,
         }
     }
 }
@@ -226,6 +985,48 @@ struct ModFolder {
     color: Option<[u8; 3]>,
 }
 
+/// Drag-and-drop payload of a folder header, the folder id
+struct FolderDrag(String);
+
+/// Mod re-read by "Re-scan all types" with its new reader and type, or why it couldn't be read
+type TypeScanResult = (PathBuf, Result<(PakReader, DetectedType), String>);
+
+/// Type detected for a mod
+#[derive(Clone)]
+struct DetectedType {
+    mod_type: String,
+    /// Asset categories the mod mixes, empty unless it has more than one
+    mixed: Vec<&'static str>,
+}
+
+/// Mods queued on the delete worker, `files` holds their pak/utoc/ucas
+struct DeleteJob {
+    mods: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+/// Sent back by the delete worker while it goes through a `DeleteJob`
+enum DeleteUpdate {
+    Progress { done: usize, total: usize },
+    Finished {
+        mods: Vec<PathBuf>,
+        deleted: usize,
+        /// Bytes of the deleted files
+        freed: u64,
+        failed: Vec<String>,
+    },
+}
+
+/// Another mod a mod needs to work, marked by the user
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct ModDependency {
+    /// File name of the required mod without the priority suffix
+    name: String,
+    /// Source hash of its last install through Repak, it finds the mod after a rename
+    #[serde(default)]
+    source_hash: Option<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 struct ModMetadata {
     path: PathBuf,
@@ -233,6 +1034,29 @@ struct ModMetadata {
     folder_id: Option<String>,
     #[serde(default)]
     custom_tags: Vec<String>,
+    /// Sizes from when the mod was packed by the installer
+    #[serde(default)]
+    pack_sizes: Option<PackSizes>,
+    /// Type picked by the user, replaces the detected one
+    #[serde(default)]
+    type_override: Option<String>,
+    #[serde(default)]
+    favorite: bool,
+    /// Readmes kept from the archive the mod was installed from
+    #[serde(default)]
+    readmes: Vec<PathBuf>,
+    /// Whether the mod was on before its folder was switched off, see `folder_keeps_mod_states`
+    #[serde(default)]
+    enabled_before_folder_off: Option<bool>,
+    /// Every install of the mod through Repak, oldest first
+    #[serde(default)]
+    versions: Vec<ModVersion>,
+    /// Locked mods can't be disabled, deleted or renamed until they are unlocked
+    #[serde(default)]
+    locked: bool,
+    /// Mods this one needs, enabling it warns when one of them is off or missing
+    #[serde(default)]
+    dependencies: Vec<ModDependency>,
 }
 
 #[derive(Clone)]
@@ -244,6 +1068,25 @@ struct ModEntry {
     editing_name: bool,
     folder_id: Option<String>,
     custom_tags: Vec<String>,
+    pack_sizes: Option<PackSizes>,
+    type_override: Option<String>,
+    /// Starred by the user, see `favorites_only`
+    favorite: bool,
+    /// Manifest packed in the mod, it fills in name and tags of newly found mods
+    manifest: Option<ModManifest>,
+    /// A utoc and ucas sit next to the pak, checked when the list is collected
+    iostore: bool,
+    readmes: Vec<PathBuf>,
+    enabled_before_folder_off: Option<bool>,
+    versions: Vec<ModVersion>,
+    locked: bool,
+    dependencies: Vec<ModDependency>,
+    /// Pak, utoc and ucas together, read when the list is collected
+    disk_size: u64,
+    /// Files in the pak, the assets of iostore mods are counted by the file index instead
+    pak_file_count: usize,
+    /// Only one of the utoc and ucas sits next to the pak
+    incomplete: bool,
 }
 fn use_bubbly_light_theme(style: &mut egui::Style) {
     // Bubbly pastel colors for light mode
@@ -311,19 +1154,29 @@ pub fn setup_custom_style(ctx: &egui::Context) {
     ctx.style_mut_of(Theme::Light, use_bubbly_light_theme);
 }
 
+/// Undoes a custom palette. The light theme doesn't set a text color, so the one the palette
+/// forced has to be cleared by hand.
+fn restore_builtin_theme(ctx: &egui::Context) {
+    ctx.style_mut_of(Theme::Light, |style| style.visuals.override_text_color = None);
+    setup_custom_style(ctx);
+}
+
 impl RepakModManager {
     fn ensure_delete_worker(&mut self) {
         let need_spawn = self.delete_sender.is_none() || self.delete_results.is_none();
         if !need_spawn { return; }
-        let (job_tx, job_rx): (Sender<Vec<std::path::PathBuf>>, Receiver<Vec<std::path::PathBuf>>) = channel();
-        let (res_tx, res_rx): (Sender<Result<Vec<std::path::PathBuf>, String>>, Receiver<Result<Vec<std::path::PathBuf>, String>>) = channel();
+        let (job_tx, job_rx): (Sender<DeleteJob>, Receiver<DeleteJob>) = channel();
+        let (res_tx, res_rx): (Sender<DeleteUpdate>, Receiver<DeleteUpdate>) = channel();
 
         // Spawn a background thread to process deletions off the UI thread
         std::thread::spawn(move || {
-            while let Ok(paths) = job_rx.recv() {
+            while let Ok(job) = job_rx.recv() {
                 // Try to delete each file; ignore NotFound but report other errors
-                let mut first_err: Option<String> = None;
-                for p in &paths {
+                let total = job.files.len();
+                let mut deleted = 0;
+                let mut freed = 0;
+                let mut failed: Vec<String> = Vec::new();
+                for (i, p) in job.files.iter().enumerate() {
                     // Try to rename to a temporary ".pending_delete" extension first to
                     // sidestep possible locks and make deletion safer on Windows
                     let mut target = p.clone();
@@ -336,91 +1189,727 @@ impl RepakModManager {
                             target = tmp;
                         }
                     }
+                    let size = std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
                     match std::fs::remove_file(&target) {
-                        Ok(_) => {}
+                        Ok(_) => {
+                            deleted += 1;
+                            freed += size;
+                        }
                         Err(e) => {
                             if e.kind() == std::io::ErrorKind::NotFound {
                                 // Ignore
                             } else {
-                                if first_err.is_none() {
-                                    first_err = Some(format!("{}: {}", target.display(), e));
-                                }
+                                failed.push(format!("{}: {}", target.display(), e));
                             }
                         }
                     }
+                    let _ = res_tx.send(DeleteUpdate::Progress { done: i + 1, total });
                 }
                 // Send result back to UI thread
-                let _ = match first_err {
-                    Some(err) => res_tx.send(Err(err)),
-                    None => res_tx.send(Ok(paths)),
-                };
+                let _ = res_tx.send(DeleteUpdate::Finished { mods: job.mods, deleted, freed, failed });
             }
         });
 
         self.delete_sender = Some(job_tx);
         self.delete_results = Some(res_rx);
     }
-    fn apply_custom_palette_to_style(&self, style: &mut egui::Style) {
-        let p = &self.custom_palette;
-        style.visuals.panel_fill = CustomPalette::rgba(p.panel_fill);
-        style.visuals.window_fill = CustomPalette::rgba(p.window_fill);
-        style.visuals.widgets.inactive.bg_fill = CustomPalette::rgba(p.widget_inactive);
-        style.visuals.widgets.hovered.bg_fill = CustomPalette::rgba(p.widget_hovered);
-        style.visuals.widgets.active.bg_fill = CustomPalette::rgba(p.widget_active);
-        style.visuals.widgets.open.bg_fill = CustomPalette::rgba(p.widget_open);
-        if let Some(txt) = p.text { style.visuals.override_text_color = Some(CustomPalette::rgba(txt)); }
-    }
-    fn accent(&self) -> Color32 { self.custom_palette.accent_color() }
-
-    fn app_dir() -> PathBuf {
-        std::env::current_exe()
-            .ok()
-            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-            .unwrap_or_else(|| PathBuf::from("."))
-    }
-
-    fn palettes_dir() -> PathBuf {
-        let mut d = Self::app_dir();
-        d.push("palettes");
-        let _ = fs::create_dir_all(&d);
-        d
-    }
 
-    fn list_palette_presets(&self) -> Vec<String> {
-        let d = Self::palettes_dir();
-        let mut out = Vec::new();
-        if let Ok(read) = fs::read_dir(d) {
-            for e in read.flatten() {
-                if let Some(ext) = e.path().extension() {
-                    if ext == "json" {
-                        if let Some(stem) = e.path().file_stem() {
-                            out.push(stem.to_string_lossy().to_string());
-                        }
-                    }
+    /// Hands the mods at `base_paths` with their utoc and ucas to the delete worker and
+    /// takes them off the list
+    fn queue_delete(&mut self, base_paths: Vec<PathBuf>) {
+        self.ensure_delete_worker();
+        // Prepare files to delete: try fast rename to .pending_delete first
+        let mut files_to_delete: Vec<std::path::PathBuf> = Vec::new();
+        for pak_path in &base_paths {
+            let utoc_path = pak_path.with_extension("utoc");
+            let ucas_path = pak_path.with_extension("ucas");
+            for p in [pak_path, &utoc_path, &ucas_path] {
+                if !p.exists() { continue; }
+                let mut tmp = p.clone();
+                let mut ext = tmp.extension().and_then(|s| s.to_str()).unwrap_or("").to_string();
+                if ext.is_empty() { ext = "pending_delete".to_string(); } else { ext.push_str(".pending_delete"); }
+                tmp.set_extension(ext);
+                match std::fs::rename(p, &tmp) {
+                    Ok(_) => files_to_delete.push(tmp),
+                    Err(_e) => files_to_delete.push(p.clone()),
                 }
             }
         }
-        out.sort();
-        out
+
+        // Queue one batch job; if channel fails, log and skip
+        let mut queued = false;
+        if let Some(tx) = &self.delete_sender {
+            let job = DeleteJob { mods: base_paths.clone(), files: files_to_delete };
+            if tx.send(job).is_ok() { queued = true; }
+        }
+
+        // Update UI state regardless; background worker will finish deletion
+        // Mark each as deleting and remove from UI list next frame
+        for p in base_paths {
+            self.deleting_mods.insert(p.clone());
+            self.pending_remove_paths.push(p);
+        }
+        // Clear selection and current table
+        self.selected_mods.clear();
+        self.current_pak_file_idx = None;
+        self.table = None;
+        // Schedule refresh
+        self.refresh_after_delete = true;
+        if !queued { error!("Failed to queue bulk delete"); }
     }
 
-    fn save_palette_preset(&self, name: &str) -> std::io::Result<()> {
-        let mut path = Self::palettes_dir();
-        path.push(format!("{}.json", name));
-        let json = serde_json::to_string_pretty(&self.custom_palette)
+    /// Deletes every disabled mod after asking, locked ones are kept
+    fn purge_disabled_mods(&mut self) {
+        let disabled = self
+            .pak_files
+            .iter()
+            .filter(|m| !m.enabled && !m.locked && !self.deleting_mods.contains(&m.path))
+            .collect::<Vec<_>>();
+        if disabled.is_empty() {
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Info)
+                .set_title("Purge disabled mods")
+                .set_description("There are no disabled mods to delete.")
+                .set_buttons(MessageButtons::Ok)
+                .show();
+            return;
+        }
+        let size = disabled.iter().map(|m| m.disk_size).sum::<u64>();
+        let mut names = disabled
+            .iter()
+            .take(20)
+            .map(|m| self.get_mod_display_name(m))
+            .collect::<Vec<_>>();
+        if disabled.len() > names.len() {
+            names.push(format!("...and {} more", disabled.len() - names.len()));
+        }
+        let confirmed = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Purge disabled mods")
+            .set_description(format!(
+                "Delete {} disabled mod(s) for good? This frees {}.\n\n{}",
+                disabled.len(),
+                format_bytes(size),
+                names.join("\n")
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+            == rfd::MessageDialogResult::Yes;
+        if !confirmed {
+            return;
+        }
+        let paths = disabled.iter().map(|m| m.path.clone()).collect::<Vec<_>>();
+        info!("Purging {} disabled mod(s), {}", paths.len(), format_bytes(size));
+        self.queue_delete(paths);
+    }
+
+    fn start_compact(&mut self, pak_path: PathBuf) {
+        let (res_tx, res_rx) = channel();
+        let level = OPTIMIZE_LEVELS[self.compact_level_idx].1;
+        let progress = Arc::new(AtomicI32::new(0));
+        self.compact_progress = progress.clone();
+
+        let type_override = self
+            .pak_files
+            .iter()
+            .find(|m| m.path == pak_path)
+            .and_then(|m| m.type_override.clone());
+
+        // Recompression is slow at the optimal levels, keep it off the UI thread
+        let path = pak_path.clone();
+        let threads = self.install_threads;
+        std::thread::spawn(move || {
+            let res = with_thread_limit(threads, || {
+                compact_mod(&path, type_override.as_deref(), level, &progress)
+            })
+            .map_err(|e| e.to_string());
+            let _ = res_tx.send(res);
+        });
+
+        self.compact_results = Some(res_rx);
+        self.compact_running = Some(pak_path);
+    }
+    fn start_normalize_names(&mut self) {
+        let result = rfd::MessageDialog::new()
+            .set_title("Normalize mod names")
+            .set_description(format!(
+                "Rename every installed mod to follow \"{}\"? Their .utoc/.ucas files and preview images are renamed along with them. Locked and system mods are left alone.",
+                self.mod_naming.label(self.default_priority)
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        if !matches!(result, rfd::MessageDialogResult::Yes) {
+            return;
+        }
+
+        let (res_tx, res_rx) = channel();
+        // locked mods keep their file names, and so do system mods unless they are unlocked
+        let paths = (0..self.pak_files.len())
+            .filter(|&i| !self.pak_files[i].locked)
+            .filter(|&i| self.system_mods_unlocked || !self.is_system_mod(i))
+            .map(|i| self.pak_files[i].path.clone())
+            .collect::<Vec<_>>();
+        let naming = self.mod_naming;
+        let default_priority = self.default_priority;
+        std::thread::spawn(move || {
+            let _ = res_tx.send(normalize_mod_names(&paths, naming, default_priority));
+        });
+        self.normalize_results = Some(res_rx);
+    }
+
+    /// Extracts the selected mods, or all of them outside selection mode, into a picked folder
+    fn start_extract_all(&mut self) {
+        let Some(dest) = FileDialog::new().set_title("Extract mods to").pick_folder() else {
+            return;
+        };
+        let mods = self
+            .pak_files
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.selection_mode || self.selected_mods.contains(i))
+            .map(|(_, m)| (m.path.clone(), m.reader.clone()))
+            .collect::<Vec<_>>();
+
+        let (res_tx, res_rx) = channel();
+        let progress = Arc::new(AtomicI32::new(0));
+        self.extract_progress = progress.clone();
+        self.extract_total = mods.len();
+        std::thread::spawn(move || {
+            let _ = res_tx.send(extract_mods(&mods, &dest, &progress));
+        });
+        self.extract_results = Some(res_rx);
+    }
+
+    /// Unpacks the mod at `pak_path` as a project into `dest` on a worker thread
+    fn start_unpack_project(&mut self, pak_path: PathBuf, reader: PakReader, dest: PathBuf) {
+        let (res_tx, res_rx) = channel();
+        self.unpack_project_name = pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        std::thread::spawn(move || {
+            let _ = res_tx.send(unpack_project(&pak_path, &reader, &dest));
+        });
+        self.unpack_project_results = Some(res_rx);
+    }
+
+    /// Copies the files of the mod at `pak_path` into `dest` on a worker thread, after asking
+    /// before replacing files already there
+    fn start_copy_mod(&mut self, pak_path: PathBuf, dest: PathBuf) {
+        let copies = match mod_file_copies(&pak_path, &dest) {
+            Ok(copies) => copies,
+            Err(e) => return show_copy_result(&pak_path, &dest, Err(e)),
+        };
+        let existing = copies
+            .iter()
+            .filter(|(_, copy)| copy.exists())
+            .map(|(_, copy)| copy.file_name().unwrap_or_default().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        if !existing.is_empty() {
+            let overwrite = rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Copy mod files")
+                .set_description(format!(
+                    "{} already has:\n\n{}\n\nReplace these files?",
+                    dest.display(),
+                    existing.join("\n")
+                ))
+                .set_buttons(MessageButtons::YesNo)
+                .show();
+            if !matches!(overwrite, rfd::MessageDialogResult::Yes) {
+                return;
+            }
+        }
+        let (res_tx, res_rx) = channel();
+        self.copy_mod_name = pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        std::thread::spawn(move || {
+            let result = copy_mod_files(&pak_path, &dest);
+            let _ = res_tx.send((pak_path, dest, result));
+        });
+        self.copy_mod_results = Some(res_rx);
+    }
+
+    /// Re-reads the mods at `paths` on a worker thread and detects their types again
+    fn start_type_scan(&mut self, paths: Vec<PathBuf>) {
+        let (res_tx, res_rx) = channel();
+        let progress = Arc::new(AtomicI32::new(0));
+        self.type_scan_progress = progress.clone();
+        self.type_scan_total = paths.len();
+        std::thread::spawn(move || {
+            let results = paths
+                .into_iter()
+                .map(|path| {
+                    // a panic reading one mod fails that mod, not the whole scan
+                    let result = std::panic::catch_unwind(|| rescan_mod(&path))
+                        .unwrap_or_else(|_| Err("Reading the mod panicked".to_string()));
+                    progress.fetch_add(1, Ordering::SeqCst);
+                    (path, result)
+                })
+                .collect::<Vec<_>>();
+            let _ = res_tx.send(results);
+        });
+        self.type_scan_results = Some(res_rx);
+    }
+
+    /// Takes in a re-read mod, returns its cached type from before
+    fn apply_rescan(&mut self, path: PathBuf, reader: PakReader, detected: DetectedType) -> Option<String> {
+        if let Some(entry) = self.pak_files.iter_mut().find(|m| m.path == path) {
+            entry.pak_file_count = reader.files().len();
+            entry.disk_size = mod_disk_size(&path);
+            entry.reader = reader;
+            self.file_index.queue(&entry.path, &entry.reader);
+        }
+        self.detected_types.insert(path, detected).map(|old| old.mod_type)
+    }
+
+    /// Re-reads the mod at `index` and detects its type again, for mods edited outside the app
+    fn rescan_type(&mut self, index: usize) {
+        self.start_type_scan(vec![self.pak_files[index].path.clone()]);
+    }
+
+    /// Mods the convert window works on: the selected ones in selection mode, all otherwise,
+    /// minus those already in the target format
+    fn convert_candidates(&self) -> Vec<usize> {
+        (0..self.pak_files.len())
+            .filter(|i| !self.selection_mode || self.selected_mods.contains(i))
+            .filter(|&i| self.convert_target.applies_to(&self.pak_files[i].path))
+            .collect()
+    }
+
+    fn start_convert_mods(&mut self) {
+        if !self.confirm_game_closed() {
+            return;
+        }
+        let jobs = self
+            .convert_candidates()
+            .into_iter()
+            .map(|i| ConvertJob {
+                pak_path: self.pak_files[i].path.clone(),
+                reader: self.pak_files[i].reader.clone(),
+                type_override: self.pak_files[i].type_override.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let backed_up = jobs
+            .iter()
+            .map(|job| job.pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string())
+            .filter(|name| has_convert_backup(name))
+            .collect::<Vec<_>>();
+        let mut replace_backups = false;
+        if !backed_up.is_empty() {
+            let result = rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Convert mods")
+                .set_description(format!(
+                    "The original files of these mods are still backed up from an earlier conversion:\n\n{}\n\nReplace those backups? Choose No to keep them and back up the current files next to them.",
+                    backed_up.join("\n")
+                ))
+                .set_buttons(MessageButtons::YesNoCancel)
+                .show();
+            match result {
+                rfd::MessageDialogResult::Yes => replace_backups = true,
+                rfd::MessageDialogResult::No => {}
+                _ => return,
+            }
+        }
+
+        let (res_tx, res_rx) = channel();
+        let progress = Arc::new(AtomicI32::new(0));
+        self.convert_progress = progress.clone();
+        self.convert_total = jobs.len();
+        self.convert_outcomes.clear();
+        let (target, threads) = (self.convert_target, self.install_threads);
+        std::thread::spawn(move || {
+            let _ = res_tx.send(convert_mods(&jobs, target, threads, replace_backups, &progress));
+        });
+        self.convert_results = Some(res_rx);
+    }
+
+    fn show_convert_mods(&mut self, ctx: &egui::Context) {
+        // polled with the window closed too, a conversion keeps running when it is closed
+        if let Some(rx) = &self.convert_results {
+            if let Ok(outcomes) = rx.try_recv() {
+                let converted = outcomes.iter().filter(|o| o.result.is_ok()).count();
+                self.count_usage(UsageEvent::Conversion, converted);
+                for outcome in &outcomes {
+                    self.install_logs.insert(outcome.mod_name.to_lowercase(), outcome.log.clone());
+                }
+                self.convert_outcomes = outcomes;
+                self.convert_results = None;
+                self.refresh_requested = true;
+            } else {
+                ctx.request_repaint_after(Duration::from_millis(200));
+            }
+        }
+        if !self.show_convert_mods {
+            return;
+        }
+
+        let running = self.convert_results.is_some();
+        let candidates = self.convert_candidates().len();
+        let mut open = true;
+        let mut start = false;
+        egui::Window::new("Convert mods")
+            .open(&mut open)
+            .default_size([480., 360.])
+            .show(ctx, |ui| {
+                ui.label(if self.selection_mode {
+                    "Converts the selected mods to:"
+                } else {
+                    "Converts every installed mod to:"
+                });
+                ui.add_enabled_ui(!running, |ui| {
+                    for target in [ConvertTarget::IoStore, ConvertTarget::Pak] {
+                        ui.radio_value(&mut self.convert_target, target, target.label());
+                    }
+                });
+                ui.weak(format!(
+                    "Mods already in that format are skipped. The original files are kept in {}",
+                    convert_backup_dir().display()
+                ));
+                ui.add_space(4.0);
+                if running {
+                    let done = self.convert_progress.load(Ordering::SeqCst);
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Converting mods ({}/{})...", done, self.convert_total));
+                        ui.add(
+                            egui::ProgressBar::new(done as f32 / self.convert_total.max(1) as f32)
+                                .desired_width(200.0)
+                                .show_percentage(),
+                        );
+                    });
+                } else if ui
+                    .add_enabled(
+                        candidates > 0,
+                        Button::new(format!("Convert {} mod(s)", candidates)).corner_radius(egui::CornerRadius::same(8)),
+                    )
+                    .on_disabled_hover_text("No mods to convert to this format")
+                    .clicked()
+                {
+                    start = true;
+                }
+
+                if !self.convert_outcomes.is_empty() {
+                    ui.separator();
+                    let failed = self.convert_outcomes.iter().filter(|o| o.result.is_err()).count();
+                    ui.label(format!(
+                        "Converted {} mod(s), {} failed",
+                        self.convert_outcomes.len() - failed,
+                        failed
+                    ));
+                    ScrollArea::vertical().auto_shrink([false, true]).show(ui, |ui| {
+                        for outcome in &self.convert_outcomes {
+                            match &outcome.result {
+                                Ok(()) => {
+                                    ui.label(format!("✔ {}", outcome.mod_name));
+                                }
+                                Err(e) => {
+                                    ui.colored_label(ui.visuals().error_fg_color, format!("🗙 {}", outcome.mod_name))
+                                        .on_hover_text(e);
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+        if start {
+            self.start_convert_mods();
+        }
+        self.show_convert_mods = open;
+    }
+
+    /// Writes name, file, type, tags, folder and state of every installed mod to a file the
+    /// user picks, as CSV or markdown depending on the chosen extension
+    fn export_mod_list(&mut self) {
+        let Some(path) = FileDialog::new()
+            .set_title("Export mod list")
+            .set_file_name("mods.csv")
+            .add_filter("CSV", &["csv"])
+            .add_filter("Markdown", &["md"])
+            .save_file()
+        else {
+            return;
+        };
+        let types = (0..self.pak_files.len()).map(|i| self.mod_type_of(i)).collect::<Vec<_>>();
+        let rows = self
+            .pak_files
+            .iter()
+            .zip(types)
+            .map(|(entry, mod_type)| ModListRow {
+                name: self.get_mod_display_name(entry),
+                file_name: entry.path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                mod_type,
+                tags: entry.custom_tags.clone(),
+                folder: entry
+                    .folder_id
+                    .as_ref()
+                    .and_then(|id| self.folders.iter().find(|f| &f.id == id))
+                    .map(|f| f.name.clone()),
+                enabled: entry.enabled,
+            })
+            .collect::<Vec<_>>();
+
+        let format = ExportFormat::from_path(&path);
+        let (level, description) = match fs::write(&path, render(&rows, format)) {
+            Ok(()) => {
+                info!("Exported {} mods to {}", rows.len(), path.display());
+                (rfd::MessageLevel::Info, format!("Exported {} mods to {}", rows.len(), path.display()))
+            }
+            Err(e) => {
+                error!("Failed to export mod list: {}", e);
+                (rfd::MessageLevel::Error, format!("Unable to write {}: {}", path.display(), e))
+            }
+        };
+        rfd::MessageDialog::new()
+            .set_level(level)
+            .set_title("Export mod list")
+            .set_description(description)
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+
+    /// Zips `latest.log`, the config with the AES keys taken out and some system info next
+    /// to the log, then shows the zip in the file manager
+    fn create_bug_report(&self) {
+        let keys = loaded_keys().into_iter().map(|(_, key)| key).collect::<Vec<_>>();
+        let log = match fs::read_to_string("latest.log") {
+            Ok(log) => bug_report::redact_text(&log, &keys),
+            Err(e) => format!("latest.log could not be read: {}", e),
+        };
+        let mut config = serde_json::to_value(self).unwrap_or_else(|e| serde_json::Value::String(format!("The config could not be serialized: {}", e)));
+        bug_report::redact_config(&mut config, &keys);
+        let system_info = format!(
+            "Repak version: {}\nOS: {} {}\nMods: {} ({} enabled)\nAES keys loaded: {}\n",
+            VERSION,
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            self.pak_files.len(),
+            self.pak_files.iter().filter(|m| m.enabled).count(),
+            keys.len(),
+        );
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let path = std::path::absolute(format!("repak_bug_report_{}.zip", secs))
+            .unwrap_or_else(|_| PathBuf::from(format!("repak_bug_report_{}.zip", secs)));
+        match bug_report::write_bundle(&path, &log, &config, &system_info) {
+            Ok(()) => {
+                info!("Created bug report {}", path.display());
+                if let Err(e) = show_in_folder(&path) {
+                    warn!("Unable to open the folder of {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => {
+                error!("Failed to create bug report: {}", e);
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Create bug report")
+                    .set_description(format!("Unable to write {}: {}", path.display(), e))
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+            }
+        }
+    }
+
+    /// Writes the app settings and theme to a file the user picks, to set up Repak the same
+    /// way on another machine. Mods and folders are left out, AES keys unless the user asks.
+    fn export_settings(&self, ctx: &egui::Context) {
+        let Some(path) = FileDialog::new()
+            .set_title("Export settings")
+            .set_file_name("repak_settings.json")
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        let include_keys = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Info)
+            .set_title("Export settings")
+            .set_description("Include your AES keys in the settings file? Leave them out if you plan to share it.")
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        let keys = match include_keys {
+            rfd::MessageDialogResult::Yes => loaded_keys(),
+            _ => vec![],
+        };
+        let theme = ctx.options(|o| o.theme_preference);
+        let result = serde_json::to_value(self)
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                fs::write(&path, settings_file::export(&config, theme, &keys)).map_err(|e| e.to_string())
+            });
+        let (level, description) = match result {
+            Ok(()) => {
+                info!("Exported settings to {}", path.display());
+                (rfd::MessageLevel::Info, format!("Saved settings to {}", path.display()))
+            }
+            Err(e) => {
+                error!("Failed to export settings: {}", e);
+                (rfd::MessageLevel::Error, format!("Unable to write {}: {}", path.display(), e))
+            }
+        };
+        rfd::MessageDialog::new()
+            .set_level(level)
+            .set_title("Export settings")
+            .set_description(description)
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+
+    /// Merges a settings file made by `export_settings` into the current settings. Nothing
+    /// is applied when one of its settings doesn't fit.
+    fn import_settings(&mut self, ctx: &egui::Context) {
+        let Some(path) = FileDialog::new()
+            .set_title("Import settings")
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let result = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| settings_file::parse(&data))
+            .and_then(|file| {
+                let mut config = serde_json::to_value(&*self).map_err(|e| e.to_string())?;
+                let report = settings_file::merge(&mut config, &file.settings);
+                let merged = serde_json::from_value::<Self>(config).map_err(|e| format!("Invalid setting: {}", e))?;
+                Ok((file, report, merged))
+            });
+        let (file, report, merged) = match result {
+            Ok(imported) => imported,
+            Err(e) => {
+                error!("Failed to import settings from {}: {}", path.display(), e);
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Import settings")
+                    .set_description(format!("Unable to import {}:\n\n{}", path.display(), e))
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+                return;
+            }
+        };
+
+        self.take_settings(merged, ctx);
+        if let Some(theme) = file.theme {
+            ctx.set_theme(theme);
+        }
+        let keys = match add_keys(&file.keys) {
+            Ok(_) if file.keys.is_empty() => String::new(),
+            Ok(added) => format!("{} new AES key(s) added.", added),
+            Err(e) => {
+                warn!("Unable to save imported AES keys: {}", e);
+                format!("The AES keys could not be saved: {}", e)
+            }
+        };
+        self.save_state().ok();
+        info!("Imported {} settings from {}", report.applied.len(), path.display());
+
+        let mut description = format!("Imported {} setting(s). {}", report.applied.len(), keys).trim_end().to_string();
+        if !report.skipped.is_empty() {
+            description.push_str("\n\nKept as they were:");
+            for (setting, reason) in &report.skipped {
+                description.push_str(&format!("\n{}: {}", setting, reason));
+            }
+        }
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Info)
+            .set_title("Import settings")
+            .set_description(description)
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+
+    /// Copies the `settings_file::SETTING_KEYS` fields of `from` and applies them
+    fn take_settings(&mut self, from: Self, ctx: &egui::Context) {
+        let reload = from.game_path != self.game_path || from.install_target != self.install_target;
+        let restart_watcher = from.watch_downloads != self.watch_downloads || from.downloads_dir != self.downloads_dir;
+        let folder_watch_changed = from.skip_folder_watch != self.skip_folder_watch;
+
+        self.copy_settings(from);
+
+        self.game_path_input = self.game_path.to_string_lossy().to_string();
+        fonts::set_fonts(ctx, self.cjk_font);
+        set_custom_font_size(ctx, self.default_font_size);
+        if !self.use_custom_palette {
+            restore_builtin_theme(ctx);
+        }
+        MEMORY_MAP_UCAS.store(self.memory_map_ucas, Ordering::Relaxed);
+        self.reload_game_paths();
+        if restart_watcher {
+            self.restart_download_watcher(ctx);
+        }
+        if folder_watch_changed {
+            self.apply_folder_watch();
+        }
+        if reload {
+            // the mods and the file watcher only move to the new folder after a restart
+            self.pending_restart = true;
+        }
+        self.update_search_filter();
+    }
+
+    fn apply_custom_palette_to_style(&self, style: &mut egui::Style) {
+        let p = &self.custom_palette;
+        style.visuals.panel_fill = CustomPalette::rgba(p.panel_fill);
+        style.visuals.window_fill = CustomPalette::rgba(p.window_fill);
+        style.visuals.widgets.inactive.bg_fill = CustomPalette::rgba(p.widget_inactive);
+        style.visuals.widgets.hovered.bg_fill = CustomPalette::rgba(p.widget_hovered);
+        style.visuals.widgets.active.bg_fill = CustomPalette::rgba(p.widget_active);
+        style.visuals.widgets.open.bg_fill = CustomPalette::rgba(p.widget_open);
+        if let Some(txt) = p.text { style.visuals.override_text_color = Some(CustomPalette::rgba(txt)); }
+    }
+    fn accent(&self) -> Color32 { self.custom_palette.accent_color() }
+
+    fn app_dir() -> PathBuf {
+        std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    fn palettes_dir() -> PathBuf {
+        let mut d = Self::app_dir();
+        d.push("palettes");
+        let _ = fs::create_dir_all(&d);
+        d
+    }
+
+    fn list_palette_presets(&self) -> Vec<String> {
+        let d = Self::palettes_dir();
+        let mut out = Vec::new();
+        if let Ok(read) = fs::read_dir(d) {
+            for e in read.flatten() {
+                if let Some(ext) = e.path().extension() {
+                    if ext == "json" {
+                        if let Some(stem) = e.path().file_stem() {
+                            out.push(stem.to_string_lossy().to_string());
+                        }
+                    }
+                }
+            }
+        }
+        out.sort();
+        out
+    }
+
+    fn save_palette_preset(&self, name: &str) -> std::io::Result<()> {
+        let mut path = Self::palettes_dir();
+        path.push(format!("{}.json", name));
+        let json = serde_json::to_string_pretty(&self.custom_palette)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
         fs::write(path, json)
     }
 
-    fn load_palette_preset(&mut self, name: &str) -> std::io::Result<()> {
+    /// Loads a saved palette, returns the adjustments `CustomPalette::sanitize` had to make
+    fn load_palette_preset(&mut self, name: &str) -> std::io::Result<Vec<String>> {
         let mut path = Self::palettes_dir();
         path.push(format!("{}.json", name));
         let data = fs::read_to_string(path)?;
-        let pal: CustomPalette = serde_json::from_str(&data)
+        let mut pal: CustomPalette = serde_json::from_str(&data)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        let adjusted = pal.sanitize();
+        for change in &adjusted {
+            warn!("Palette preset {}: {}", name, change);
+        }
         self.custom_palette = pal;
         self.use_custom_palette = true;
-        Ok(())
+        Ok(adjusted)
     }
 
     fn delete_palette_preset(&self, name: &str) -> std::io::Result<()> {
@@ -464,8 +1953,7 @@ impl RepakModManager {
 
         let mut game_path = PathBuf::new();
         if let Some(path) = game_install_path {
-            game_path = path.join("~mods").clean();
-            fs::create_dir_all(&game_path).unwrap();
+            game_path = Self::create_mods_dir(path.join("~mods").clean());
         }
         setup_custom_style(&cc.egui_ctx);
         let mut x = Self {
@@ -474,6 +1962,7 @@ impl RepakModManager {
             folders: vec![],
             pak_files: vec![],
             current_pak_file_idx: None,
+            last_selected_mod: None,
             table: None,
             version: Some(VERSION.to_string()),
             creating_folder: false,
@@ -486,19 +1975,234 @@ impl RepakModManager {
         x
     }
 
+    /// Folder installs are written to and mods are listed from
+    fn install_dir(&self) -> PathBuf {
+        match self.install_target {
+            InstallTarget::Mods => self.game_path.clone(),
+            InstallTarget::Paks => self.paks_dir(),
+        }
+    }
+
+    /// The game's Paks folder, the parent of the mod folder when that is `~mods`
+    fn paks_dir(&self) -> PathBuf {
+        let is_mods_dir = self
+            .game_path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case("~mods"));
+        match self.game_path.parent() {
+            Some(paks) if is_mods_dir => paks.to_path_buf(),
+            _ => self.game_path.clone(),
+        }
+    }
+
+    /// Why `dir` can't be the staging folder: the game loads every pak under its Paks
+    /// folder, so staged files there would be loaded twice
+    fn staging_conflict(&self, dir: &Path) -> Option<String> {
+        let resolve = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let (dir, paks) = (resolve(dir), resolve(&self.paks_dir()));
+        if dir.starts_with(&paks) {
+            Some(format!(
+                "{} is inside the game's Paks folder, the game would load the staged mods as well as their links.",
+                dir.display()
+            ))
+        } else if paks.starts_with(&dir) {
+            Some(format!("{} contains the game's Paks folder, pick a folder outside the game.", dir.display()))
+        } else {
+            None
+        }
+    }
+
+    /// Turns staging off when the mod folder or install target moved so the staging folder
+    /// now clashes with the game's Paks folder
+    fn revalidate_staging_dir(&mut self) {
+        let Some(conflict) = self.staging_dir.as_deref().and_then(|dir| self.staging_conflict(dir)) else {
+            return;
+        };
+        warn!("Staging turned off: {}", conflict);
+        self.staging_dir = None;
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Staging turned off")
+            .set_description(format!("{}\n\nMods are installed straight to the game folder until you pick another staging folder.", conflict))
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+
+    /// Offers to delete the staged files no mod in the game folder links to anymore
+    fn clean_up_staging(&self) {
+        let Some(staging_dir) = &self.staging_dir else {
+            return;
+        };
+        let orphans = staging::orphaned_files(staging_dir, &self.install_dir());
+        if orphans.is_empty() {
+            rfd::MessageDialog::new()
+                .set_title("Clean up staging folder")
+                .set_description("Every staged file is still linked into the game folder.")
+                .set_buttons(MessageButtons::Ok)
+                .show();
+            return;
+        }
+        let mut listed = orphans
+            .iter()
+            .take(10)
+            .map(|path| path.strip_prefix(staging_dir).unwrap_or(path).to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        if orphans.len() > listed.len() {
+            listed.push(format!("and {} more", orphans.len() - listed.len()));
+        }
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Clean up staging folder")
+            .set_description(format!(
+                "{} staged file(s) aren't linked into the game folder anymore, e.g. of deleted mods:\n\n{}\n\nDelete them?",
+                orphans.len(),
+                listed.join("\n")
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        if !matches!(result, rfd::MessageDialogResult::Yes) {
+            return;
+        }
+        let mut removed = 0;
+        for path in &orphans {
+            match fs::remove_file(path) {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("Unable to remove {}: {}", path.display(), e),
+            }
+        }
+        info!("Removed {} orphaned staged file(s) from {}", removed, staging_dir.display());
+    }
+
+    /// Naming used for new installs. The game only loads loose paks from Paks when they
+    /// carry a `_P` patch suffix, so the original name is never kept there.
+    fn install_naming(&self) -> ModNaming {
+        match (self.install_target, self.mod_naming) {
+            (InstallTarget::Paks, ModNaming::KeepOriginal) => ModNaming::Suffix,
+            (_, naming) => naming,
+        }
+    }
+
+    /// Opens the install dialog for `paths`. Archives are unpacked in the background first,
+    /// the dialog shows their progress until the mods in them are listed.
+    fn start_install(&mut self, paths: Vec<PathBuf>) {
+        self.install_into_folder(paths, None);
+    }
+
+    /// Installs `paths` like `start_install`, the installed mods go into the folder
+    /// `folder_id` of the mod list once they are listed
+    fn install_into_folder(&mut self, paths: Vec<PathBuf>, folder_id: Option<String>) {
+        if self.install_mod_dialog.is_some() {
+            self.queue_install(paths, folder_id);
+            return;
+        }
+        self.install_batch = paths.clone();
+        if has_archives(&paths) {
+            let mut request = self.install_request(vec![]);
+            request.folder_id = folder_id;
+            request.extract_archives(paths);
+            self.file_drop_viewport_open = true;
+            self.install_mod_dialog = Some(request);
+            return;
+        }
+        let mods = map_paths_to_mods(&paths);
+        if mods.is_empty() {
+            error!("No mods found in {} file(s).", paths.len());
+            return;
+        }
+        self.file_drop_viewport_open = true;
+        debug!("Mods: {:?}", mods);
+        let mut request = self.install_request(mods);
+        request.folder_id = folder_id;
+        self.install_mod_dialog = Some(request);
+    }
+
+    /// Keeps `paths` for once the open install window is closed. Paths already in it or
+    /// queued are skipped.
+    fn queue_install(&mut self, paths: Vec<PathBuf>, folder_id: Option<String>) {
+        let mut batch: Vec<PathBuf> = vec![];
+        for path in paths {
+            let queued = self.install_batch.contains(&path)
+                || batch.contains(&path)
+                || self.install_queue.iter().flat_map(|(batch, _)| batch).any(|p| *p == path);
+            match queued {
+                true => info!("{} is already queued for install", path.display()),
+                false => batch.push(path),
+            }
+        }
+        if batch.is_empty() {
+            return;
+        }
+        info!("Queued {} file(s) to install after the current batch", batch.len());
+        self.install_queue.push((batch, folder_id));
+    }
+
+    /// Install request for `mods`, writing to the active install target
+    fn install_request(&self, mods: Vec<InstallableMod>) -> ModInstallRequest {
+        let (target, link_dir) = match &self.staging_dir {
+            Some(staging_dir) => (staging_dir.clone(), Some(self.install_dir())),
+            None => (self.install_dir(), None),
+        };
+        let mut request =
+            ModInstallRequest::new(
+                mods,
+                target,
+                self.install_naming(),
+                self.default_priority,
+                self.install_threads,
+            );
+        request.link_dir = link_dir;
+        if self.auto_type_tags {
+            request.apply_type_tags(&self.type_tags);
+        }
+        if self.skip_suggested_fixes {
+            request.clear_suggested_fixes();
+        }
+        request.set_pack_ignore(&self.pack_ignore);
+        request.verify = !self.skip_install_verify;
+        request.on_collision = self.name_collision;
+        request.auto_install = self.quick_install;
+        // the game's own paks are only listed from the top of Paks, keep mods there too
+        if self.install_target == InstallTarget::Mods {
+            request.type_dirs = self.type_dirs.clone();
+        }
+        // an update never replaces a locked mod, the new one is installed like any other mod
+        let installed = self
+            .pak_files
+            .iter()
+            .filter(|m| !m.locked)
+            .map(|m| (m.path.clone(), m.versions.last().map(|v| v.source_hash.clone())))
+            .collect::<Vec<_>>();
+        request.mark_updates(&installed);
+        request
+    }
+
     fn collect_pak_files(&mut self) {
-        if self.game_path.exists() {
+        let install_dir = self.install_dir();
+        if install_dir.exists() {
             let mut vecs = vec![];
-
-            for entry in WalkDir::new(&self.game_path)
+            let mut encrypted = vec![];
+            let mut library_size = 0;
+            // previews may have been added, replaced or removed along with the mods
+            self.previews.clear();
+
+            // the game's own paks live next to installed ones in Paks, only `_P` paks are mods there
+            let in_paks = self.install_target == InstallTarget::Paks;
+            let max_depth = if in_paks { 1 } else { usize::MAX };
+            for entry in WalkDir::new(&install_dir)
+                .max_depth(max_depth)
                 .into_iter()
                 .filter_map(Result::ok)
-                .filter(|e| e.file_type().is_file())
+                // mods linked from a staging folder are links to files
+                .filter(|e| e.file_type().is_file() || (e.path_is_symlink() && e.path().is_file()))
             {
                 let path = entry.path();
                 if path.is_dir() {
                     continue;
                 }
+                if in_paks && !path.file_stem().unwrap_or_default().to_string_lossy().ends_with("_P") {
+                    continue;
+                }
+                library_size += entry.metadata().map(|m| m.len()).unwrap_or(0);
                 let mut disabled = false;
 
                 if path.extension().unwrap_or_default() != "pak" {
@@ -512,42 +2216,86 @@ impl RepakModManager {
                     }
                 }
 
-                let mut builder = repak::PakBuilder::new();
-                builder = builder.key(AES_KEY.clone().0);
-                let pak = builder.reader(&mut BufReader::new(File::open(path.clone()).unwrap()));
-
-                if let Err(_e) = pak {
-                    warn!("Error opening pak file");
-                    continue;
-                }
-                let pak = pak.unwrap();
-                
-                // Find existing metadata for this path
-                let metadata = self.mod_metadata.iter().find(|m| m.path == path.to_path_buf());
-                
+                let pak = match open_pak(path) {
+                    Ok(pak) => pak,
+                    Err(e) if needs_key(path) => {
+                        warn!("{} is encrypted with an unknown key: {}", path.display(), e);
+                        encrypted.push(path.to_path_buf());
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("Error opening pak file {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+                
+                // Find existing metadata for this path
+                let metadata = self.mod_metadata.iter().find(|m| m.path == path.to_path_buf());
+                let manifest = ModManifest::read(path, &pak);
+                let disk_size = mod_disk_size(path);
+                let pak_file_count = pak.files().len();
+                
+                // the manifest only pre-fills mods seen for the first time, after that the
+                // user's name and tags are kept even when cleared
                 let entry = ModEntry {
                     reader: pak,
                     path: path.to_path_buf(),
                     enabled: !disabled,
-                    custom_name: metadata.and_then(|m| m.custom_name.clone()),
+                    custom_name: match metadata {
+                        Some(m) => m.custom_name.clone(),
+                        None => manifest.as_ref().and_then(|m| m.name.clone()),
+                    },
                     editing_name: false,
                     folder_id: metadata.and_then(|m| m.folder_id.clone()),
-                    custom_tags: metadata
-                        .map(|m| m.custom_tags.clone())
-                        .unwrap_or_default(),
+                    custom_tags: match metadata {
+                        Some(m) => m.custom_tags.clone(),
+                        None => manifest.as_ref().map(|m| m.tags.clone()).unwrap_or_default(),
+                    },
+                    pack_sizes: metadata.and_then(|m| m.pack_sizes),
+                    type_override: metadata.and_then(|m| m.type_override.clone()),
+                    favorite: metadata.is_some_and(|m| m.favorite),
+                    manifest,
+                    iostore: path.with_extension("utoc").is_file() && path.with_extension("ucas").is_file(),
+                    readmes: metadata.map(|m| m.readmes.clone()).unwrap_or_default(),
+                    enabled_before_folder_off: metadata.and_then(|m| m.enabled_before_folder_off),
+                    versions: metadata.map(|m| m.versions.clone()).unwrap_or_default(),
+                    locked: metadata.is_some_and(|m| m.locked),
+                    dependencies: metadata.map(|m| m.dependencies.clone()).unwrap_or_default(),
+                    disk_size,
+                    pak_file_count,
+                    incomplete: path.with_extension("utoc").is_file() != path.with_extension("ucas").is_file(),
                 };
+                self.file_index.queue(&entry.path, &entry.reader);
                 vecs.push(entry);
             }
+            // pakchunk paks go after the other mods in chunk order, the rest keep the folder order
+            vecs.sort_by_key(|entry| pakchunk_index(&entry.path.file_stem().unwrap_or_default().to_string_lossy()));
             self.pak_files = vecs;
-            // Merge any pending custom tags recorded during install
+            self.encrypted_paks = encrypted;
+            self.library_size = library_size;
+            // Merge any pending custom tags and pack sizes recorded during install. Versions
+            // go first, they read the metadata of replaced mods before it is synced away.
+            self.apply_pending_versions();
             self.apply_pending_custom_tags();
+            self.apply_pending_pack_sizes();
+            self.apply_pending_readmes();
+            self.apply_pending_folders();
+            // mods that are gone leave the file index, new or changed ones were queued above
+            let paths = self.pak_files.iter().map(|m| m.path.clone()).collect::<Vec<_>>();
+            self.file_index.retain_installed(&paths);
+            self.detected_types.clear();
+            self.outdated_assets = None;
+            self.mod_conflicts = None;
+            self.last_selected_idx = None;
             self.update_search_filter();
         }
     }
 
-    fn create_folder(&mut self, name: String) {
+    /// Adds a folder and returns its id
+    fn create_folder(&mut self, name: String) -> String {
+        let id = Uuid::new_v4().to_string();
         let folder = ModFolder {
-            id: Uuid::new_v4().to_string(),
+            id: id.clone(),
             name,
             enabled: true,
             expanded: true,
@@ -555,24 +2303,81 @@ impl RepakModManager {
         };
         self.folders.push(folder);
         self.save_state().ok();
+        id
+    }
+
+    /// Moves a folder to `position` in the list, the folder there shifts towards where the
+    /// moved one came from
+    fn move_folder(&mut self, folder_id: &str, position: usize) {
+        let Some(from) = self.folders.iter().position(|f| f.id == folder_id) else {
+            return;
+        };
+        if from == position {
+            return;
+        }
+        let folder = self.folders.remove(from);
+        self.folders.insert(position.min(self.folders.len()), folder);
+        self.save_state().ok();
     }
 
+    /// Switches a folder and the mods in it on or off. Each mod is toggled like its own
+    /// switch does, renaming its pak between `.pak` and `.bak_repak`, so the game sees the
+    /// change right away. Locked mods stay on.
     fn toggle_folder(&mut self, folder_id: &str) {
+        if !self.confirm_game_closed() {
+            return;
+        }
+        let keep_states = self.folder_keeps_mod_states;
+        let Some(folder_enabled) = self.folders.iter().find(|f| f.id == folder_id).map(|f| f.enabled) else {
+            return;
+        };
+        if !folder_enabled {
+            // same rule as below, mods that stay off don't need their dependencies
+            let enabling = (0..self.pak_files.len())
+                .filter(|&i| {
+                    let m = &self.pak_files[i];
+                    m.folder_id.as_deref() == Some(folder_id)
+                        && !m.enabled
+                        && (!keep_states || m.enabled_before_folder_off.unwrap_or(true))
+                })
+                .collect::<Vec<_>>();
+            if !self.confirm_batch_dependencies(&enabling) {
+                return;
+            }
+        }
         if let Some(folder) = self.folders.iter_mut().find(|f| f.id == folder_id) {
             folder.enabled = !folder.enabled;
-            let target_enabled = folder.enabled;
+            let folder_enabled = folder.enabled;
             
             // Toggle all mods in this folder
             for mod_entry in &mut self.pak_files {
-                if mod_entry.folder_id.as_ref() == Some(&folder_id.to_string()) {
-                    mod_entry.enabled = target_enabled;
+                if mod_entry.folder_id.as_deref() != Some(folder_id) {
+                    continue;
+                }
+                let target_enabled = if folder_enabled {
+                    // mods that were off before the folder was switched off stay off,
+                    // unless they were turned on by hand in the meantime
+                    let was_enabled = mod_entry.enabled_before_folder_off.take();
+                    !keep_states || mod_entry.enabled || was_enabled.unwrap_or(true)
+                } else {
+                    if keep_states {
+                        mod_entry.enabled_before_folder_off = Some(mod_entry.enabled);
+                    }
+                    false
+                };
+                if mod_entry.enabled != target_enabled {
+                    Self::toggle_mod_enabled(mod_entry);
                 }
             }
             self.save_state().ok();
         }
     }
 
-    fn toggle_mod_enabled(&mut self, mod_entry: &mut ModEntry) {
+    fn toggle_mod_enabled(mod_entry: &mut ModEntry) {
+        if mod_entry.locked && mod_entry.enabled {
+            info!("Leaving locked mod enabled: {:?}", mod_entry.path);
+            return;
+        }
         mod_entry.enabled = !mod_entry.enabled;
         if mod_entry.enabled {
             let new_pak = &mod_entry.path.with_extension("pak");
@@ -601,22 +2406,352 @@ impl RepakModManager {
         }
     }
 
+    /// Warns when the game is running, renames and deletes fail on the files it has locked.
+    /// Returns whether to go ahead anyway.
+    fn confirm_game_closed(&self) -> bool {
+        if !game_running() {
+            return true;
+        }
+        warn!("Marvel Rivals is running while modifying mods");
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Game is running")
+            .set_description("The game appears to be running; close it before modifying mods.\n\nContinue anyway?")
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        matches!(result, rfd::MessageDialogResult::Yes)
+    }
+
+    /// Enables or disables the mod at `index` by renaming its pak
+    fn toggle_mod_at(&mut self, index: usize) {
+        if self.pak_files[index].locked && self.pak_files[index].enabled {
+            warn!("{:?} is locked, unlock it before disabling it", self.pak_files[index].path);
+            return;
+        }
+        if !self.confirm_game_closed() {
+            return;
+        }
+        if !self.pak_files[index].enabled && !(self.confirm_dependencies(index) && self.confirm_enable(index)) {
+            return;
+        }
+        let pak_file = &mut self.pak_files[index];
+        pak_file.enabled = !pak_file.enabled;
+        
+        if pak_file.enabled {
+            let new_pak = pak_file.path.with_extension("pak");
+            match std::fs::rename(&pak_file.path, &new_pak) {
+                Ok(_) => {
+                    pak_file.path = new_pak;
+                    info!("Enabled mod: {:?}", pak_file.path);
+                }
+                Err(e) => {
+                    warn!("Failed to enable mod: {:?}", e);
+                    pak_file.enabled = false;
+                }
+            }
+        } else {
+            let new_pak = pak_file.path.with_extension("bak_repak");
+            match std::fs::rename(&pak_file.path, &new_pak) {
+                Ok(_) => {
+                    pak_file.path = new_pak;
+                    info!("Disabled mod: {:?}", pak_file.path);
+                }
+                Err(e) => {
+                    warn!("Failed to disable mod: {:?}", e);
+                    pak_file.enabled = true;
+                }
+            }
+        }
+    }
+
+    /// Mods in the order the list shows them: expanded folders first, then ungrouped mods.
+    /// When grouping by type, the mods of each expanded type section.
+    fn visible_mod_order(&mut self) -> Vec<usize> {
+        if self.list_grouping == ListGrouping::Type {
+            return self
+                .type_groups()
+                .into_iter()
+                .filter(|(group, _)| self.type_group_expanded(group))
+                .flat_map(|(_, mods)| mods)
+                .collect();
+        }
+        let mut order = vec![];
+        for folder in &self.folders {
+            if folder.expanded || self.should_expand_folder_for_search(&folder.id) {
+                order.extend((0..self.pak_files.len()).filter(|&i| {
+                    self.pak_files[i].folder_id.as_ref() == Some(&folder.id) && self.in_mod_list(i)
+                }));
+            }
+        }
+        order.extend(
+            (0..self.pak_files.len())
+                .filter(|&i| self.pak_files[i].folder_id.is_none() && self.in_mod_list(i)),
+        );
+        order
+    }
+
+    /// Selects `last_selected_mod` again after a restart
+    fn restore_last_selection(&mut self) {
+        let Some(last) = &self.last_selected_mod else {
+            return;
+        };
+        // the mod may have been toggled since, which only changes its extension
+        let last = last.with_extension("");
+        if let Some(index) = self.pak_files.iter().position(|m| m.path.with_extension("") == last) {
+            self.select_mod(index);
+            self.scroll_to_current = true;
+        }
+    }
+
+    fn select_mod(&mut self, index: usize) {
+        let entry = &self.pak_files[index];
+        self.table = Some(FileTable::new(&entry.reader, &entry.path));
+        self.current_pak_file_idx = Some(index);
+    }
+
+    /// Up/Down move through the visible mods, Enter toggles the current one and Space
+    /// selects it in selection mode. Ignored while a text field has focus, except F5 which
+    /// rescans the mod folder.
+    fn handle_list_keys(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F5)) {
+            // picked up at the start of the next frame
+            self.refresh_requested = true;
+            ctx.request_repaint();
+        }
+        if ctx.memory(|m| m.focused().is_some()) || self.install_mod_dialog.is_some() {
+            return;
+        }
+        let (up, down, enter, space) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Space),
+            )
+        });
+
+        if up || down {
+            let order = self.visible_mod_order();
+            let position = self
+                .current_pak_file_idx
+                .and_then(|current| order.iter().position(|&i| i == current));
+            let next = match (position, down) {
+                (None, true) => order.first(),
+                (None, false) => order.last(),
+                (Some(p), true) => order.get(p + 1),
+                (Some(p), false) => p.checked_sub(1).and_then(|p| order.get(p)),
+            };
+            if let Some(&next) = next {
+                self.select_mod(next);
+                self.scroll_to_current = true;
+            }
+        }
+
+        let Some(current) = self.current_pak_file_idx.filter(|&i| i < self.pak_files.len()) else {
+            return;
+        };
+        if enter {
+            self.toggle_mod_at(current);
+            self.save_state().ok();
+        }
+        if space && self.selection_mode && !self.selected_mods.remove(&current) {
+            self.selected_mods.insert(current);
+        }
+    }
+
+    /// Installed mod a dependency points to: the one installed from the same source, else
+    /// the one with the same file name
+    fn dependency_index(&self, dependency: &ModDependency) -> Option<usize> {
+        let by_hash = dependency.source_hash.as_ref().and_then(|hash| {
+            self.pak_files
+                .iter()
+                .position(|m| m.versions.iter().any(|v| v.source_hash == *hash))
+        });
+        by_hash.or_else(|| {
+            self.pak_files
+                .iter()
+                .position(|m| self.mod_base_name(m).eq_ignore_ascii_case(&dependency.name))
+        })
+    }
+
+    /// File stem of a mod without its priority suffix, what dependencies are matched by, so
+    /// they still match after the load order changed
+    fn mod_base_name(&self, entry: &ModEntry) -> String {
+        let stem = entry.path.file_stem().unwrap_or_default().to_string_lossy();
+        split_priority(&stem).0.to_string()
+    }
+
+    /// Dependency on the mod at `index`
+    fn dependency_on(&self, index: usize) -> ModDependency {
+        let entry = &self.pak_files[index];
+        ModDependency {
+            name: self.mod_base_name(entry),
+            source_hash: entry.versions.last().map(|v| v.source_hash.clone()),
+        }
+    }
+
+    /// Dependencies of the mod at `index` that aren't enabled, with why. Mods in `enabling`
+    /// count as enabled.
+    fn unmet_dependencies(&self, index: usize, enabling: &[usize]) -> Vec<String> {
+        self.pak_files[index]
+            .dependencies
+            .iter()
+            .filter_map(|dependency| match self.dependency_index(dependency) {
+                Some(i) if self.pak_files[i].enabled || enabling.contains(&i) => None,
+                Some(i) => Some(format!("{} (disabled)", self.get_mod_display_name(&self.pak_files[i]))),
+                None => Some(format!("{} (not installed)", dependency.name)),
+            })
+            .collect()
+    }
+
+    /// Asks before enabling a mod whose dependencies are disabled or missing
+    fn confirm_dependencies(&self, index: usize) -> bool {
+        let unmet = self.unmet_dependencies(index, &[]);
+        if unmet.is_empty() {
+            return true;
+        }
+        let name = self.get_mod_display_name(&self.pak_files[index]);
+        warn!("{} needs mods that aren't enabled: {}", name, unmet.join(", "));
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Required mods aren't enabled")
+            .set_description(format!(
+                "{} needs these mods, which aren't enabled:\n\n{}\n\nEnable it anyway?",
+                name,
+                unmet.join("\n")
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        matches!(result, rfd::MessageDialogResult::Yes)
+    }
+
+    /// `confirm_dependencies` for enabling all of `indices` at once, dependencies among them
+    /// are met
+    fn confirm_batch_dependencies(&self, indices: &[usize]) -> bool {
+        let unmet = indices
+            .iter()
+            .filter_map(|&index| {
+                let unmet = self.unmet_dependencies(index, indices);
+                (!unmet.is_empty())
+                    .then(|| format!("{} needs {}", self.get_mod_display_name(&self.pak_files[index]), unmet.join(", ")))
+            })
+            .collect::<Vec<_>>();
+        if unmet.is_empty() {
+            return true;
+        }
+        warn!("Enabling mods that need mods that aren't enabled: {}", unmet.join("; "));
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Required mods aren't enabled")
+            .set_description(format!(
+                "Some of these mods need mods which aren't enabled:\n\n{}\n\nEnable them anyway?",
+                unmet.join("\n")
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        matches!(result, rfd::MessageDialogResult::Yes)
+    }
+
+    /// Warns when the mod at `index` overrides files of an enabled mod. Returns false if
+    /// the user chose not to enable it.
+    fn confirm_enable(&self, index: usize) -> bool {
+        if self.skip_conflict_check {
+            return true;
+        }
+        let target = &self.pak_files[index];
+        // mods are looked up in the index so a big library doesn't freeze the window, only the
+        // ones it doesn't have yet are read here
+        let files = match self.file_index.files_of(&target.path) {
+            Some(files) => files,
+            None => match mod_files(&target.reader, &target.path) {
+                Ok(files) => files,
+                Err(e) => {
+                    warn!("Skipping the conflict check: {}", e);
+                    return true;
+                }
+            },
+        };
+        let mut shared_by: BTreeMap<usize, Vec<&String>> = BTreeMap::new();
+        for file in &files {
+            for other in self.find_mods_containing(file) {
+                shared_by.entry(other).or_default().push(file);
+            }
+        }
+        for (i, other) in self.pak_files.iter().enumerate() {
+            if i == index || !other.enabled || self.file_index.file_count_of(&other.path).is_some() {
+                continue;
+            }
+            match mod_files(&other.reader, &other.path) {
+                Ok(other_files) => {
+                    let shared = files.iter().filter(|f| other_files.contains(*f)).collect::<Vec<_>>();
+                    if !shared.is_empty() {
+                        shared_by.insert(i, shared);
+                    }
+                }
+                Err(e) => warn!("Leaving {} out of the conflict check: {}", other.path.display(), e),
+            }
+        }
+        let conflicts = shared_by
+            .into_iter()
+            .filter(|(i, _)| *i != index && self.pak_files[*i].enabled)
+            .filter_map(|(i, shared)| {
+                let other = &self.pak_files[i];
+                let first = shared.first()?.to_string();
+                Some(format!(
+                    "{} ({} files, e.g. {})",
+                    self.get_mod_display_name(other),
+                    shared.len(),
+                    first
+                ))
+            })
+            .collect::<Vec<_>>();
+        if conflicts.is_empty() {
+            return true;
+        }
+
+        warn!(
+            "{} overlaps with enabled mods: {}",
+            self.get_mod_display_name(target),
+            conflicts.join(", ")
+        );
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Mod conflict")
+            .set_description(format!(
+                "{} replaces files that are also in these enabled mods:\n\n{}\n\nOnly one of them will work in game. Enable it anyway?",
+                self.get_mod_display_name(target),
+                conflicts.join("\n")
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        matches!(result, rfd::MessageDialogResult::Yes)
+    }
+
     fn get_mod_display_name(&self, pak_file: &ModEntry) -> String {
-        pak_file.custom_name.as_ref().unwrap_or(&pak_file
-            .path
-            .file_stem()
-            .unwrap()
-            .to_string_lossy()
-            .to_string()).clone()
+        pak_file.custom_name.clone().unwrap_or_else(|| {
+            let stem = pak_file.path.file_stem().unwrap_or_default().to_string_lossy();
+            display_base_name(&stem).to_string()
+        })
     }
 
+    /// Callers save the state, so bulk moves write it once
     fn assign_mod_to_folder(&mut self, mod_index: usize, folder_id: Option<String>) {
         if let Some(mod_entry) = self.pak_files.get_mut(mod_index) {
             mod_entry.folder_id = folder_id;
-            self.save_state().ok();
         }
     }
 
+    /// Moves every selected mod into a folder, `None` takes them out of their folder
+    fn move_selected_to_folder(&mut self, folder_id: Option<String>) {
+        let selected = self.selected_mods.iter().copied().collect::<Vec<_>>();
+        for &i in &selected {
+            self.assign_mod_to_folder(i, folder_id.clone());
+        }
+        info!("Moved {} selected mod(s) to folder {:?}", selected.len(), folder_id);
+        self.update_search_filter();
+        let _ = self.save_state();
+    }
+
     fn update_search_filter(&mut self) {
         self.filtered_mods.clear();
         self.expanded_folders_for_search.clear();
@@ -626,7 +2761,7 @@ impl RepakModManager {
         let has_custom_tag_filter = self.custom_tag_filter_enabled
             && !self.selected_custom_tag_filters.is_empty();
         
-        if !has_search && !has_tag_filter && !has_custom_tag_filter {
+        if !has_search && !has_tag_filter && !has_custom_tag_filter && !self.favorites_only {
             // If no filters are active, show all mods
             for i in 0..self.pak_files.len() {
                 // Skip entries currently being deleted
@@ -637,14 +2772,19 @@ impl RepakModManager {
         }
         
         let query = self.search_query.to_lowercase();
+        // types come from the cache, detecting them reads the mods
+        let types = match has_tag_filter {
+            true => (0..self.pak_files.len()).map(|i| self.mod_type_of(i)).collect::<Vec<_>>(),
+            false => vec![],
+        };
         
         for (index, pak_file) in self.pak_files.iter().enumerate() {
             // Skip entries currently being deleted
             if self.deleting_mods.contains(&pak_file.path) { continue; }
-            let mut matches = true;
-            
+            let mut matches = !self.favorites_only || pak_file.favorite;
+
             // Check search query match
-            if has_search {
+            if has_search && matches {
                 let display_name = self.get_mod_display_name(pak_file).to_lowercase();
                 let file_name = pak_file.path.file_stem()
                     .unwrap_or_default()
@@ -658,8 +2798,7 @@ impl RepakModManager {
             
             // Check tag filter match
             if has_tag_filter && matches {
-                let mod_type = self.get_mod_type(&pak_file.reader, &pak_file.path);
-                if !self.selected_tag_filters.contains(&mod_type) {
+                if !self.selected_tag_filters.contains(&types[index]) {
                     matches = false;
                 }
             }
@@ -686,32 +2825,95 @@ impl RepakModManager {
         }
     }
 
-    fn get_mod_type(&self, pak_reader: &PakReader, pak_path: &PathBuf) -> String {
-        let mut utoc_path = pak_path.to_path_buf();
-        utoc_path.set_extension("utoc");
+    /// Type of the mod at `index`, the detected type is cached in `detected_types`
+    fn mod_type_of(&mut self, index: usize) -> String {
+        match &self.pak_files[index].type_override {
+            Some(mod_type) => mod_type.clone(),
+            None => self.detected_type_of(index),
+        }
+    }
 
-        let paths = {
-            if utoc_path.exists() {
-                let file = read_utoc(&utoc_path, pak_reader, pak_path)
-                    .iter()
-                    .map(|entry| entry.file_path.clone())
-                    .collect::<Vec<_>>();
-                file
-            } else {
-                pak_reader.files().into_iter().collect::<Vec<_>>()
+    /// Detected type of the mod at `index`, ignoring a manual override
+    fn detected_type_of(&mut self, index: usize) -> String {
+        self.detection_of(index).mod_type
+    }
+
+    fn detection_of(&mut self, index: usize) -> DetectedType {
+        let entry = &self.pak_files[index];
+        match self.detected_types.get(&entry.path) {
+            Some(detected) => detected.clone(),
+            None => {
+                let detected = detect_mod_type(&entry.reader, &entry.path);
+                self.detected_types.insert(entry.path.clone(), detected.clone());
+                detected
             }
-        };
+        }
+    }
 
-        get_current_pak_characteristics(paths)
+    /// Type section of the mod at `index`
+    fn mod_type_group(&mut self, index: usize) -> String {
+        mod_type_category(&self.mod_type_of(index)).to_string()
     }
 
-    fn get_all_mod_types(&self) -> std::collections::BTreeSet<String> {
-        let mut types = std::collections::BTreeSet::new();
-        for pak_file in &self.pak_files {
-            let mod_type = self.get_mod_type(&pak_file.reader, &pak_file.path);
-            types.insert(mod_type);
+    /// Hover text of a mod's name, everything in it is cached so hovering stays cheap
+    fn mod_details(&mut self, index: usize) -> String {
+        let mod_type = self.mod_type_of(index);
+        let entry = &self.pak_files[index];
+        let files = match entry.iostore {
+            true => self
+                .file_index
+                .file_count_of(&entry.path)
+                .map_or("still being indexed".to_string(), |count| count.to_string()),
+            false => entry.pak_file_count.to_string(),
+        };
+        format!(
+            "{}\nSize: {}\nFiles: {}\nType: {}\nState: {}",
+            entry.path.display(),
+            format_bytes(entry.disk_size),
+            files,
+            mod_type,
+            self.mod_state(index).description()
+        )
+    }
+
+    /// Mods of the list by type section, sections sorted by name
+    fn type_groups(&mut self) -> BTreeMap<String, Vec<usize>> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for i in 0..self.pak_files.len() {
+            if self.in_mod_list(i) {
+                let group = self.mod_type_group(i);
+                groups.entry(group).or_default().push(i);
+            }
         }
-        types
+        groups
+    }
+
+    // searching opens every section, like it does for folders
+    fn type_group_expanded(&self, group: &str) -> bool {
+        !self.collapsed_type_groups.contains(group) || !self.search_query.trim().is_empty()
+    }
+
+    fn get_all_mod_types(&mut self) -> std::collections::BTreeSet<String> {
+        (0..self.pak_files.len()).map(|i| self.mod_type_of(i)).collect()
+    }
+
+    /// Chip for a custom tag, filled with its color if one was assigned
+    fn tag_chip(&self, tag: &str, default_fill: Color32) -> egui::Button<'static> {
+        let mut text = egui::RichText::new(tag).size(10.0);
+        let fill = match self.tag_colors.get(tag) {
+            Some(&[r, g, b]) => {
+                // keep the label readable on light and dark chips
+                let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+                text = text.color(if luma > 150.0 { Color32::BLACK } else { Color32::WHITE });
+                Color32::from_rgb(r, g, b)
+            }
+            None => default_fill,
+        };
+        egui::Button::new(text)
+            .fill(fill)
+            .frame(true)
+            .corner_radius(egui::CornerRadius::same(10))
+            .small()
     }
 
     fn get_all_custom_tags(&self) -> std::collections::BTreeSet<String> {
@@ -727,6 +2929,29 @@ impl RepakModManager {
         tags
     }
 
+    /// Moves the listed mods of `pending_folders` into their folder, folders deleted in the
+    /// meantime are dropped
+    fn apply_pending_folders(&mut self) {
+        if self.pending_folders.is_empty() {
+            return;
+        }
+        let mut moved = 0;
+        for i in 0..self.pak_files.len() {
+            let Some(folder_id) = take_pending(&mut self.pending_folders, &self.pak_files[i].path) else {
+                continue;
+            };
+            if self.folders.iter().any(|f| f.id == folder_id) {
+                self.assign_mod_to_folder(i, Some(folder_id));
+                moved += 1;
+            }
+        }
+        if moved > 0 {
+            info!("Moved {} installed mod(s) into the folder they were dropped on", moved);
+            self.update_search_filter();
+            let _ = self.save_state();
+        }
+    }
+
     fn apply_pending_custom_tags(&mut self) {
         // pending file lives in the same config dir as main config
         let mut cfg = Self::config_path();
@@ -779,8 +3004,124 @@ impl RepakModManager {
         }
     }
 
+    /// Adds the versions the installer recorded to their mods. An update installed under a
+    /// new file name takes over the name, tags and folder of the mod it replaced.
+    fn apply_pending_versions(&mut self) {
+        let mut pending = Self::config_path();
+        pending.set_file_name("pending_versions.json");
+        let Ok(s) = fs::read_to_string(&pending) else { return; };
+        let mut map: std::collections::BTreeMap<String, PendingVersion> = match serde_json::from_str(&s) {
+            Ok(m) => m,
+            Err(_) => return,
+        };
+
+        let mut changed = false;
+        for pak_file in &mut self.pak_files {
+            let stem = pak_file.path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            // the replaced mod shares the stem until the installer has removed it
+            if map.get(&stem).is_none_or(|installed| installed.replaces.as_ref() == Some(&pak_file.path)) {
+                continue;
+            }
+            let Some(installed) = map.remove(&stem) else { continue; };
+            let is_new = !self.mod_metadata.iter().any(|m| m.path == pak_file.path);
+            let replaced = installed
+                .replaces
+                .as_ref()
+                .and_then(|old| self.mod_metadata.iter().find(|m| m.path == *old))
+                .filter(|_| is_new);
+            if let Some(old) = replaced {
+                info!("{} takes over the settings of {}", stem, old.path.display());
+                pak_file.custom_name = old.custom_name.clone();
+                pak_file.folder_id = old.folder_id.clone();
+                for tag in &old.custom_tags {
+                    if !pak_file.custom_tags.contains(tag) {
+                        pak_file.custom_tags.push(tag.clone());
+                    }
+                }
+                pak_file.custom_tags.sort();
+                pak_file.type_override = old.type_override.clone();
+                pak_file.favorite = old.favorite;
+                pak_file.locked = old.locked;
+                pak_file.dependencies = old.dependencies.clone();
+                if pak_file.readmes.is_empty() {
+                    pak_file.readmes = old.readmes.clone();
+                }
+                pak_file.versions = old.versions.clone();
+            }
+            pak_file.versions.push(installed.version);
+            changed = true;
+        }
+        if !changed {
+            return;
+        }
+        self.sync_metadata();
+        if map.is_empty() {
+            let _ = fs::remove_file(&pending);
+        } else if let Ok(json) = serde_json::to_string_pretty(&map) {
+            let _ = fs::write(&pending, json);
+        }
+    }
+
+    fn apply_pending_pack_sizes(&mut self) {
+        let mut pending = Self::config_path();
+        pending.set_file_name("pending_pack_sizes.json");
+        let Ok(s) = fs::read_to_string(&pending) else { return; };
+        let mut map: std::collections::BTreeMap<String, PackSizes> = match serde_json::from_str(&s) {
+            Ok(m) => m,
+            Err(_) => return,
+        };
+
+        let mut changed = false;
+        for pak_file in &mut self.pak_files {
+            if let Some(sizes) = take_pending(&mut map, &pak_file.path) {
+                pak_file.pack_sizes = Some(sizes);
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+        self.sync_metadata();
+        if map.is_empty() {
+            let _ = fs::remove_file(&pending);
+        } else if let Ok(json) = serde_json::to_string_pretty(&map) {
+            let _ = fs::write(&pending, json);
+        }
+    }
+
+    fn apply_pending_readmes(&mut self) {
+        let mut pending = Self::config_path();
+        pending.set_file_name("pending_readmes.json");
+        let Ok(s) = fs::read_to_string(&pending) else { return; };
+        let mut map: std::collections::BTreeMap<String, Vec<PathBuf>> = match serde_json::from_str(&s) {
+            Ok(m) => m,
+            Err(_) => return,
+        };
+
+        let mut changed = false;
+        for pak_file in &mut self.pak_files {
+            let stem = pak_file.path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            if let Some(readmes) = map.remove(&stem) {
+                pak_file.readmes = readmes;
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+        self.sync_metadata();
+        if map.is_empty() {
+            let _ = fs::remove_file(&pending);
+        } else if let Ok(json) = serde_json::to_string_pretty(&map) {
+            let _ = fs::write(&pending, json);
+        }
+    }
+
     fn rename_custom_tag(&mut self, from: &str, to: &str) {
         if from == to || to.trim().is_empty() { return; }
+        if let Some(color) = self.tag_colors.remove(from) {
+            self.tag_colors.entry(to.to_string()).or_insert(color);
+        }
         for pak in &mut self.pak_files {
             let mut changed = false;
             for t in &mut pak.custom_tags {
@@ -801,10 +3142,33 @@ impl RepakModManager {
                 pak.custom_tags.retain(|t| t != tag);
             }
         }
+        self.tag_colors.remove(tag);
         self.update_search_filter();
         let _ = self.save_state();
     }
 
+    /// Adds the mods passing the current search/tag filters that carry `tag` to the selection
+    fn select_visible_with_tag(&mut self, tag: &str) {
+        let matching: Vec<usize> = self
+            .filtered_mods
+            .iter()
+            .copied()
+            .filter(|&i| self.pak_files.get(i).is_some_and(|p| p.custom_tags.iter().any(|t| t == tag)))
+            .collect();
+        self.selected_mods.extend(matching);
+    }
+
+    /// Adds the visible mods of a folder to the selection, `None` selects the unassigned mods
+    fn select_visible_in_folder(&mut self, folder_id: Option<&str>) {
+        let matching: Vec<usize> = self
+            .filtered_mods
+            .iter()
+            .copied()
+            .filter(|&i| self.pak_files.get(i).is_some_and(|p| p.folder_id.as_deref() == folder_id))
+            .collect();
+        self.selected_mods.extend(matching);
+    }
+
     fn bulk_add_tag_to_selected(&mut self, tag: &str) {
         if tag.trim().is_empty() { return; }
         for &i in &self.selected_mods {
@@ -845,7 +3209,7 @@ impl RepakModManager {
         let has_custom_tag_filter = self.custom_tag_filter_enabled
             && !self.selected_custom_tag_filters.is_empty();
         
-        if !has_search && !has_tag_filter && !has_custom_tag_filter {
+        if !has_search && !has_tag_filter && !has_custom_tag_filter && !self.favorites_only {
             return true;
         }
         self.filtered_mods.contains(&mod_index)
@@ -857,7 +3221,7 @@ impl RepakModManager {
         let has_custom_tag_filter = self.custom_tag_filter_enabled
             && !self.selected_custom_tag_filters.is_empty();
         
-        if !has_search && !has_tag_filter && !has_custom_tag_filter {
+        if !has_search && !has_tag_filter && !has_custom_tag_filter && !self.favorites_only {
             return false;
         }
         self.expanded_folders_for_search.contains(folder_id)
@@ -884,14 +3248,18 @@ impl RepakModManager {
                 Color32::WHITE,
             );
         }
+        let mut widths_changed = false;
         ScrollArea::horizontal()
             .auto_shrink([false, false])
             .show(ui, |ui| {
                 let table = &mut self.table;
                 if let Some(ref mut table) = table {
-                    table.table_ui(ui);
+                    widths_changed = table.table_ui(ui, &mut self.file_table_widths);
                 }
             });
+        if widths_changed {
+            self.save_state().ok();
+        }
         Ok(())
     }
 
@@ -900,12 +3268,50 @@ impl RepakModManager {
             return;
         }
         use egui::{Label, RichText};
+        // cached, detecting reads the mod
+        let DetectedType { mod_type: detected, mixed } = self.detection_of(self.current_pak_file_idx.unwrap());
         let pak = &self.pak_files[self.current_pak_file_idx.unwrap()].reader;
         let pak_path = self.pak_files[self.current_pak_file_idx.unwrap()]
             .path
             .clone();
 
-        let full_paths = pak.files().into_iter().collect::<Vec<_>>();
+        let ctx = ui.ctx().clone();
+        match self.previews.get(&ctx, &pak_path, pak) {
+            Some(texture) => {
+                ui.add(
+                    egui::Image::from_texture(texture)
+                        .max_size(egui::vec2(256.0, 256.0))
+                        .corner_radius(8),
+                );
+            }
+            None => {
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.set_min_size(egui::vec2(256.0, 64.0));
+                    ui.centered_and_justified(|ui| {
+                        ui.weak("No preview image")
+                            .on_hover_text("Put a .png or .jpg with the same name as the mod next to its .pak");
+                    });
+                });
+            }
+        }
+
+        if let Some(manifest) = &self.pak_files[self.current_pak_file_idx.unwrap()].manifest {
+            if let Some(author) = &manifest.author {
+                ui.horizontal(|ui| {
+                    ui.add(Label::new(RichText::new("Author: ").strong()));
+                    ui.label(author);
+                });
+            }
+            if let Some(version) = &manifest.version {
+                ui.horizontal(|ui| {
+                    ui.add(Label::new(RichText::new("Mod version: ").strong()));
+                    ui.label(version);
+                });
+            }
+            if let Some(description) = &manifest.description {
+                ui.add(Label::new(description).wrap());
+            }
+        }
 
         ui.collapsing("Encryption details", |ui| {
             ui.horizontal(|ui| {
@@ -934,6 +3340,18 @@ impl RepakModManager {
                 ui.add(Label::new(RichText::new("Version: ").strong()));
                 ui.add(Label::new(format!("{:?}", pak.version())));
             });
+
+            ui.horizontal(|ui| {
+                ui.add(Label::new(RichText::new("Compression: ").strong()));
+                ui.add(Label::new(pak_compression_text(pak)).wrap());
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(Label::new(RichText::new("Features: ").strong()));
+                let flags = pak_feature_flags(pak);
+                let text = if flags.is_empty() { "None".to_string() } else { flags.join(", ") };
+                ui.add(Label::new(text).wrap());
+            });
         });
         ui.horizontal(|ui| {
             ui.add(Label::new(
@@ -941,34 +3359,121 @@ impl RepakModManager {
                     .strong()
                     .size(self.default_font_size + 1.),
             ));
-            let mut utoc_path = pak_path.to_path_buf();
-            utoc_path.set_extension("utoc");
-
-            let paths = {
-                if utoc_path.exists() {
-                    let file = read_utoc(&utoc_path, pak, &pak_path)
-                        .iter()
-                        .map(|entry| entry.file_path.clone())
-                        .collect::<Vec<_>>();
-                    file
-                } else {
-                    full_paths.clone()
+            match &self.pak_files[self.current_pak_file_idx.unwrap()].type_override {
+                Some(mod_type) => {
+                    ui.add(Label::new(format!("{} (set manually)", mod_type)))
+                        .on_hover_text(format!("Detected as {}", detected));
                 }
-            };
-
-            ui.add(Label::new(get_current_pak_characteristics(paths)));
+                None => {
+                    ui.add(Label::new(detected));
+                }
+            }
         });
-        if self.table.is_none() {
-            self.table = Some(FileTable::new(pak, &pak_path));
+        if !mixed.is_empty() {
+            ui.horizontal(|ui| {
+                ui.add(Label::new(RichText::new("Mixed content: ").strong()));
+                ui.label(mixed.join(", "))
+                    .on_hover_text("The mod has assets of several kinds, its type comes from the first kind found");
+            });
         }
-    }
-    fn show_pak_files_in_dir(&mut self, ui: &mut egui::Ui) {
+        let dependencies = &self.pak_files[self.current_pak_file_idx.unwrap()].dependencies;
+        if !dependencies.is_empty() {
+            ui.add(Label::new(RichText::new("Requires: ").strong()))
+                .on_hover_text("Set from the right-click menu of the mod");
+            for dependency in dependencies {
+                ui.horizontal(|ui| {
+                    ui.add_space(8.0);
+                    match self.dependency_index(dependency) {
+                        Some(i) if self.pak_files[i].enabled => {
+                            ui.label(format!("✔ {}", self.get_mod_display_name(&self.pak_files[i])));
+                        }
+                        Some(i) => {
+                            let name = self.get_mod_display_name(&self.pak_files[i]);
+                            ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {} (disabled)", name));
+                        }
+                        None => {
+                            let text = format!("⚠ {} (not installed)", dependency.name);
+                            ui.colored_label(ui.visuals().error_fg_color, text);
+                        }
+                    }
+                });
+            }
+        }
+        let versions = &self.pak_files[self.current_pak_file_idx.unwrap()].versions;
+        if let Some(last) = versions.last() {
+            let history = versions
+                .iter()
+                .rev()
+                .map(|v| format!("{} ({})", format_age(v.installed_at), &v.source_hash[..v.source_hash.len().min(16)]))
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui.horizontal(|ui| {
+                ui.add(Label::new(RichText::new("Installed: ").strong()));
+                let updates = match versions.len() {
+                    1 => String::new(),
+                    n => format!(", {} versions", n),
+                };
+                ui.label(format!("{}{}", format_age(last.installed_at), updates))
+                    .on_hover_text(format!("Installs through Repak, newest first:\n{}", history));
+            });
+        }
+        if let Ok(staged) = fs::read_link(&pak_path) {
+            ui.horizontal(|ui| {
+                ui.add(Label::new(RichText::new("Linked from: ").strong()));
+                ui.add(Label::new(RichText::new(staged.to_string_lossy()).monospace()).wrap())
+                    .on_hover_text("Installed to the staging folder, disabling or deleting the mod only touches the link");
+            });
+        }
+        if let Some(sizes) = self.pak_files[self.current_pak_file_idx.unwrap()].pack_sizes {
+            ui.horizontal(|ui| {
+                ui.add(Label::new(RichText::new("Packed size: ").strong()));
+                ui.label(sizes.describe())
+                    .on_hover_text("Size of the mod's files before packing and of the pak/utoc/ucas it was packed into");
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.add(Label::new(RichText::new("SHA-256: ").strong()));
+            match self.hashes.get(&pak_path) {
+                Some(Ok(hash)) => {
+                    ui.add(Label::new(RichText::new(&hash[..16]).monospace()))
+                        .on_hover_text(format!("{}\nHash of the pak plus utoc/ucas, equal for identical copies of a mod", hash));
+                    if ui.add(egui::Button::new("Copy").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        ui.ctx().copy_text(hash.clone());
+                    }
+                }
+                Some(Err(e)) => {
+                    ui.weak("unavailable").on_hover_text(e.as_str());
+                }
+                None => {
+                    ui.spinner();
+                    ui.weak("Hashing...");
+                    ui.ctx().request_repaint_after(Duration::from_millis(200));
+                }
+            }
+        });
+        if self.table.is_none() {
+            self.table = Some(FileTable::new(pak, &pak_path));
+        }
+    }
+    fn show_pak_files_in_dir(&mut self, ui: &mut egui::Ui) {
         // Enhanced scrolling with better performance for large mod lists
         ScrollArea::vertical()
             .auto_shrink([false, false])
             .max_height(ui.available_height())
             .stick_to_bottom(false)
+            // dragging selects mods in selection mode
+            .drag_to_scroll(!self.selection_mode)
             .show(ui, |ui| {
+                let compact = self.list_density == ListDensity::Compact;
+                if compact {
+                    ui.spacing_mut().item_spacing.y = 2.0;
+                    ui.spacing_mut().button_padding = egui::vec2(6.0, 2.0);
+                }
+                // compact rows drop the per-mod box
+                let row_frame = |ui: &egui::Ui| match compact {
+                    true => egui::Frame::NONE.inner_margin(egui::Margin::symmetric(4, 1)),
+                    false => egui::Frame::group(ui.style()),
+                };
                 ui.vertical(|ui| {
                     // Create bubbly search and filter section
                     ui.group(|ui| {
@@ -996,6 +3501,20 @@ impl RepakModManager {
                                 
                                 ui.separator();
 
+                                let star = if self.favorites_only { "★ Favorites" } else { "☆ Favorites" };
+                                if ui
+                                    .add(
+                                        egui::Button::new(RichText::new(star).strong().color(self.accent()))
+                                            .selected(self.favorites_only)
+                                            .corner_radius(egui::CornerRadius::same(12)),
+                                    )
+                                    .on_hover_text("Only show starred mods")
+                                    .clicked()
+                                {
+                                    self.favorites_only = !self.favorites_only;
+                                    self.update_search_filter();
+                                }
+
                                 // Bubbly tag filter button
                                 let filter_button_text = if self.tag_filter_enabled {
                                     format!("Filter ({} selected)", self.selected_tag_filters.len())
@@ -1023,6 +3542,7 @@ impl RepakModManager {
                                     self.selected_tag_filters.clear();
                                     self.custom_tag_filter_enabled = false;
                                     self.selected_custom_tag_filters.clear();
+                                    self.favorites_only = false;
                                     self.update_search_filter();
                                 }
 
@@ -1104,7 +3624,18 @@ impl RepakModManager {
                                 let tags: Vec<String> = self.get_all_custom_tags().into_iter().collect();
                                 for t in tags {
                                     ui.horizontal(|ui| {
-                                        ui.label(t.clone());
+                                        let current = self.tag_colors.get(&t).copied();
+                                        let mut color = current.unwrap_or([0x80, 0x80, 0x80]);
+                                        if ui.color_edit_button_srgb(&mut color).on_hover_text("Chip color").changed() {
+                                            self.tag_colors.insert(t.clone(), color);
+                                            let _ = self.save_state();
+                                        }
+                                        let default_fill = ui.style().visuals.extreme_bg_color;
+                                        ui.add(self.tag_chip(&t, default_fill));
+                                        if current.is_some() && ui.small_button("Clear color").clicked() {
+                                            self.tag_colors.remove(&t);
+                                            let _ = self.save_state();
+                                        }
                                         if ui.button("Rename").clicked() {
                                             self.rename_tag_from = Some(t.clone());
                                             self.rename_tag_to = t.clone();
@@ -1195,173 +3726,323 @@ impl RepakModManager {
                     
                     ui.separator();
                     
-                    // Bubbly folder creation UI
-                    ui.group(|ui| {
-                        ui.set_width(ui.available_width());
-                        ui.horizontal(|ui| {
-                            ui.add_space(8.0);
-                            if ui.add(egui::Button::new("New Folder").corner_radius(egui::CornerRadius::same(12))).clicked() {
-                                self.creating_folder = true;
-                            }
-                            
-                            if ui.add(egui::Button::new("Expand All").corner_radius(egui::CornerRadius::same(12))).clicked() {
-                                for folder in &mut self.folders {
-                                    folder.expanded = true;
-                                }
-                                self.save_state().ok();
-                            }
-                            
-                            if ui.add(egui::Button::new("Collapse All").corner_radius(egui::CornerRadius::same(12))).clicked() {
-                                for folder in &mut self.folders {
-                                    folder.expanded = false;
-                                }
-                                self.save_state().ok();
-                            }
-                            
-                            if self.creating_folder {
-                                ui.separator();
-                                ui.label(egui::RichText::new("Name:").strong());
-                                let response = ui.add(TextEdit::singleline(&mut self.new_folder_name).desired_width(120.0));
-                                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                                    if !self.new_folder_name.trim().is_empty() {
-                                        self.create_folder(self.new_folder_name.clone());
-                                        self.new_folder_name.clear();
-                                        self.creating_folder = false;
-                                    }
-                                }
-                                if ui.add(egui::Button::new("Create").corner_radius(egui::CornerRadius::same(8))).clicked() {
-                                    if !self.new_folder_name.trim().is_empty() {
-                                        self.create_folder(self.new_folder_name.clone());
-                                        self.new_folder_name.clear();
-                                        self.creating_folder = false;
+                    if self.list_grouping == ListGrouping::Type {
+                        self.folder_header_rects.clear();
+                        self.show_type_groups(ui, &row_frame);
+                    } else {
+                        self.show_folder_groups(ui, &row_frame);
+                    }
+
+                    let system_mods = (0..self.pak_files.len())
+                        .filter(|&i| self.is_system_mod(i) && self.is_mod_visible(i))
+                        .collect::<Vec<_>>();
+                    if !system_mods.is_empty() {
+                        ui.separator();
+                        let title = format!("System / game files ({})", system_mods.len());
+                        egui::CollapsingHeader::new(RichText::new(title).strong().size(16.0).color(self.accent()))
+                            .id_salt("system_mods")
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                ui.checkbox(&mut self.system_mods_unlocked, "Allow changes")
+                                    .on_hover_text("These paks look like they belong to the game, disabling or deleting them can break it");
+                                for i in system_mods {
+                                    if self.system_mods_unlocked {
+                                        let row = row_frame(ui).show(ui, |ui| {
+                                            ui.set_width(ui.available_width());
+                                            ui.horizontal(|ui| self.show_mod_entry_by_index(ui, i));
+                                        });
+                                        self.scroll_to_row(ui, i, row.response.rect);
+                                    } else {
+                                        let entry = &self.pak_files[i];
+                                        ui.horizontal(|ui| {
+                                            ui.label(self.get_mod_display_name(entry));
+                                            ui.weak(entry.path.file_name().unwrap_or_default().to_string_lossy());
+                                        });
                                     }
                                 }
-                                if ui.add(egui::Button::new("Cancel").corner_radius(egui::CornerRadius::same(8))).clicked() {
-                                    self.creating_folder = false;
-                                    self.new_folder_name.clear();
-                                }
-                            }
-                        });
-                    });
+                            });
+                    }
+                });
+            });
+    }
 
+    /// Whether the mod at `index` is listed with the normal mods, game files get their own section
+    fn in_mod_list(&self, index: usize) -> bool {
+        self.is_mod_visible(index) && !self.is_system_mod(index)
+    }
+
+    /// Folders and their mods followed by the ungrouped mods, the default mod list layout
+    fn show_folder_groups(&mut self, ui: &mut egui::Ui, row_frame: &dyn Fn(&egui::Ui) -> egui::Frame) {
+        // Bubbly folder creation UI
+        ui.group(|ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                ui.add_space(8.0);
+                if ui.add(egui::Button::new("New Folder").corner_radius(egui::CornerRadius::same(12))).clicked() {
+                    self.creating_folder = true;
+                }
+            
+                if ui.add(egui::Button::new("Expand All").corner_radius(egui::CornerRadius::same(12))).clicked() {
+                    for folder in &mut self.folders {
+                        folder.expanded = true;
+                    }
+                    self.save_state().ok();
+                }
+            
+                if ui.add(egui::Button::new("Collapse All").corner_radius(egui::CornerRadius::same(12))).clicked() {
+                    for folder in &mut self.folders {
+                        folder.expanded = false;
+                    }
+                    self.save_state().ok();
+                }
+            
+                if self.creating_folder {
                     ui.separator();
+                    ui.label(egui::RichText::new("Name:").strong());
+                    let response = ui.add(TextEdit::singleline(&mut self.new_folder_name).desired_width(120.0));
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        if !self.new_folder_name.trim().is_empty() {
+                            self.create_folder(self.new_folder_name.clone());
+                            self.new_folder_name.clear();
+                            self.creating_folder = false;
+                        }
+                    }
+                    if ui.add(egui::Button::new("Create").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        if !self.new_folder_name.trim().is_empty() {
+                            self.create_folder(self.new_folder_name.clone());
+                            self.new_folder_name.clear();
+                            self.creating_folder = false;
+                        }
+                    }
+                    if ui.add(egui::Button::new("Cancel").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        self.creating_folder = false;
+                        self.new_folder_name.clear();
+                    }
+                }
+            });
+        });
 
-                    // Display folders with bubbly styling
-                    let folders_clone = self.folders.clone();
-                    for folder in &folders_clone {
-                        // Bubbly folder container
-                        ui.group(|ui| {
-                            ui.set_width(ui.available_width());
-                            ui.horizontal(|ui| {
-                                ui.with_layout(egui::Layout::left_to_right(Align::LEFT), |ui| {
-                                    ui.set_max_width(ui.available_width() * 0.85);
-                                    ui.add_space(8.0);
-                                    
-                                    let folder_icon = if folder.expanded { "▼" } else { "▶" };
-                                    let folder_label = format!("{} {}", folder_icon, folder.name);
-                                    
-                                    let folder_response = ui.add(
-                                        Label::new(RichText::new(folder_label).strong().size(16.0).color(self.accent()))
-                                        .selectable(false)
-                                        .sense(egui::Sense::click())
-                                    );
-                                    
-                                    if folder_response.clicked() {
-                                        if let Some(f) = self.folders.iter_mut().find(|f| f.id == folder.id) {
-                                            f.expanded = !f.expanded;
-                                            self.save_state().ok();
-                                        }
-                                    }
+        ui.separator();
 
-                                    folder_response.context_menu(|ui| {
-                                        if ui.button("Delete folder").clicked() {
-                                            // Remove folder assignment from mods
-                                            for mod_entry in &mut self.pak_files {
-                                                if mod_entry.folder_id.as_ref() == Some(&folder.id) {
-                                                    mod_entry.folder_id = None;
-                                                }
-                                            }
-                                            // Remove folder
-                                            self.folders.retain(|f| f.id != folder.id);
-                                            self.save_state().ok();
-                                            ui.close_menu();
-                                        }
-                                    });
-                                });
+        // Display folders with bubbly styling
+        let folders_clone = self.folders.clone();
+        // folder dropped on another folder header, applied after the loop
+        let mut folder_drop: Option<(String, usize)> = None;
+        let file_drop_folder = self.file_drop_folder();
+        self.folder_header_rects.clear();
+        for (position, folder) in folders_clone.iter().enumerate() {
+            // Bubbly folder container
+            let header = ui.group(|ui| {
+                ui.set_width(ui.available_width());
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::left_to_right(Align::LEFT), |ui| {
+                        ui.set_max_width(ui.available_width() * 0.85);
+                        ui.add_space(8.0);
+                        ui.dnd_drag_source(
+                            egui::Id::new(("folder_drag", &folder.id)),
+                            FolderDrag(folder.id.clone()),
+                            |ui| ui.label(RichText::new("⬍").weak()),
+                        )
+                        .response
+                        .on_hover_text("Drag onto another folder to move it there");
+                    
+                        let folder_icon = if folder.expanded { "▼" } else { "▶" };
+                        let folder_label = format!("{} {}", folder_icon, folder.name);
+                    
+                        let folder_response = ui.add(
+                            Label::new(RichText::new(folder_label).strong().size(16.0).color(self.accent()))
+                            .selectable(false)
+                            .sense(egui::Sense::click())
+                        );
+                    
+                        if folder_response.clicked() {
+                            if let Some(f) = self.folders.iter_mut().find(|f| f.id == folder.id) {
+                                f.expanded = !f.expanded;
+                                self.save_state().ok();
+                            }
+                        }
 
-                                ui.with_layout(egui::Layout::right_to_left(Align::RIGHT), |ui| {
-                                    let mut folder_enabled = folder.enabled;
-                                    let toggler = ui.add(ios_widget::toggle(&mut folder_enabled));
-                                    if toggler.clicked() {
-                                        self.toggle_folder(&folder.id);
+                        folder_response.context_menu(|ui| {
+                            if ui.button("Delete folder").clicked() {
+                                // Remove folder assignment from mods
+                                for mod_entry in &mut self.pak_files {
+                                    if mod_entry.folder_id.as_ref() == Some(&folder.id) {
+                                        mod_entry.folder_id = None;
                                     }
-                                });
-                            });
-                        });
-
-                        // Display mods in this folder with bubbly styling
-                        let should_expand = folder.expanded || self.should_expand_folder_for_search(&folder.id);
-                        if should_expand {
-                            let folder_id = folder.id.clone();
-                            let pak_files_len = self.pak_files.len();
-                            for i in 0..pak_files_len {
-                                if self.pak_files[i].folder_id.as_ref() == Some(&folder_id) && self.is_mod_visible(i) {
-                                    // Bubbly mod entry container
-                                    ui.group(|ui| {
-                                        ui.set_width(ui.available_width() - 16.0);
-                                        ui.horizontal(|ui| {
-                                            ui.add_space(24.0); // Indent for folder contents
-                                            
-                                            // Highlight matched mods with bubbly colors
-                                            let is_search_match = !self.search_query.trim().is_empty() && 
-                                                self.filtered_mods.contains(&i);
-                                            
-                                            if is_search_match {
-                                                ui.visuals_mut().override_text_color = Some(self.accent());
-                                            }
-                                            
-                                            self.show_mod_entry_by_index(ui, i);
-                                            
-                                            if is_search_match {
-                                                ui.visuals_mut().override_text_color = None;
-                                            }
-                                        });
-                                    });
                                 }
+                                // Remove folder
+                                self.folders.retain(|f| f.id != folder.id);
+                                self.save_state().ok();
+                                ui.close_menu();
                             }
+                        });
+                    });
+
+                    ui.with_layout(egui::Layout::right_to_left(Align::RIGHT), |ui| {
+                        let mut folder_enabled = folder.enabled;
+                        let toggler = ui
+                            .add(ios_widget::toggle(&mut folder_enabled))
+                            .on_hover_text("Enables or disables every mod in this folder, renaming their pak files");
+                        if toggler.clicked() {
+                            self.toggle_folder(&folder.id);
                         }
+                    });
+                });
+            });
+            // a header scrolled out of view can't be dropped on
+            let visible = header.response.rect.intersect(ui.clip_rect());
+            self.folder_header_rects.push((folder.id.clone(), visible));
+            if file_drop_folder.as_ref() == Some(&folder.id) {
+                let rect = header.response.rect;
+                ui.painter().rect_stroke(rect, 4.0, Stroke::new(2.0, self.accent()), egui::StrokeKind::Outside);
+            }
+            let hovered_by = header.response.dnd_hover_payload::<FolderDrag>();
+            if hovered_by.is_some_and(|dragged| dragged.0 != folder.id) {
+                let rect = header.response.rect;
+                ui.painter().hline(rect.x_range(), rect.top(), Stroke::new(2.0, self.accent()));
+            }
+            if let Some(dragged) = header.response.dnd_release_payload::<FolderDrag>() {
+                folder_drop = Some((dragged.0.clone(), position));
+            }
+
+            // Display mods in this folder with bubbly styling
+            let should_expand = folder.expanded || self.should_expand_folder_for_search(&folder.id);
+            if should_expand {
+                let folder_id = folder.id.clone();
+                let pak_files_len = self.pak_files.len();
+                for i in 0..pak_files_len {
+                    if self.pak_files[i].folder_id.as_ref() == Some(&folder_id) && self.in_mod_list(i) {
+                        self.show_mod_row(ui, row_frame, i, true);
                     }
+                }
+            }
+        }
+        if let Some((folder_id, position)) = folder_drop {
+            self.move_folder(&folder_id, position);
+        }
 
-                    // Display ungrouped mods with bubbly styling
-                    ui.separator();
-                    ui.label(egui::RichText::new("Ungrouped Mods").strong().size(16.0).color(self.accent()));
-                    let pak_files_len = self.pak_files.len();
-                    for i in 0..pak_files_len {
-                        if self.pak_files[i].folder_id.is_none() && self.is_mod_visible(i) {
-                            // Bubbly ungrouped mod container
-                            ui.group(|ui| {
-                                ui.set_width(ui.available_width());
-                                ui.horizontal(|ui| {
-                                    ui.add_space(8.0);
-                                    // Highlight matched mods with bubbly colors
-                                    let is_search_match = !self.search_query.trim().is_empty() && 
-                                        self.filtered_mods.contains(&i);
-                                    
-                                    if is_search_match {
-                                        ui.visuals_mut().override_text_color = Some(self.accent());
-                                    }
-                                    
-                                    self.show_mod_entry_by_index(ui, i);
-                                    
-                                    if is_search_match {
-                                        ui.visuals_mut().override_text_color = None;
-                                    }
-                                });
-                            });
+        // Display ungrouped mods with bubbly styling
+        ui.separator();
+        ui.label(egui::RichText::new("Ungrouped Mods").strong().size(16.0).color(self.accent()));
+        let pak_files_len = self.pak_files.len();
+        for i in 0..pak_files_len {
+            if self.pak_files[i].folder_id.is_none() && self.in_mod_list(i) {
+                self.show_mod_row(ui, row_frame, i, false);
+            }
+        }
+    }
+
+    /// One row of the mod list, `indented` for mods listed under a folder or type header
+    fn show_mod_row(&mut self, ui: &mut egui::Ui, row_frame: &dyn Fn(&egui::Ui) -> egui::Frame, i: usize, indented: bool) {
+        let row = row_frame(ui).show(ui, |ui| {
+            ui.set_width(ui.available_width() - if indented { 16.0 } else { 0.0 });
+            ui.horizontal(|ui| {
+                ui.add_space(if indented { 24.0 } else { 8.0 });
+                // Highlight matched mods with bubbly colors
+                let is_search_match =
+                    !self.search_query.trim().is_empty() && self.filtered_mods.contains(&i);
+                if is_search_match {
+                    ui.visuals_mut().override_text_color = Some(self.accent());
+                }
+                self.show_mod_entry_by_index(ui, i);
+                if is_search_match {
+                    ui.visuals_mut().override_text_color = None;
+                }
+            });
+        });
+        self.scroll_to_row(ui, i, row.response.rect);
+        self.drag_select_row(ui, i, row.response.rect);
+    }
+
+    /// Mod list sections by detected type, shown instead of the folders when grouping by type
+    fn show_type_groups(&mut self, ui: &mut egui::Ui, row_frame: &dyn Fn(&egui::Ui) -> egui::Frame) {
+        for (group, mods) in self.type_groups() {
+            let expanded = self.type_group_expanded(&group);
+            ui.group(|ui| {
+                ui.set_width(ui.available_width());
+                ui.horizontal(|ui| {
+                    ui.add_space(8.0);
+                    let icon = if expanded { "▼" } else { "▶" };
+                    let title = format!("{} {} ({})", icon, group, mods.len());
+                    let header = ui.add(
+                        Label::new(RichText::new(title).strong().size(16.0).color(self.accent()))
+                            .selectable(false)
+                            .sense(egui::Sense::click()),
+                    );
+                    if header.clicked() {
+                        if !self.collapsed_type_groups.remove(&group) {
+                            self.collapsed_type_groups.insert(group.clone());
                         }
+                        self.save_state().ok();
                     }
                 });
             });
+            if !expanded {
+                continue;
+            }
+            for i in mods {
+                self.show_mod_row(ui, row_frame, i, true);
+            }
+        }
+    }
+
+    /// Paks that look like they came with the game: no `_P` patch suffix, or a name matching
+    /// `system_mod_patterns`. Only when `separate_system_mods` is on.
+    fn is_system_mod(&self, index: usize) -> bool {
+        if !self.separate_system_mods {
+            return false;
+        }
+        let stem = self.pak_files[index].path.file_stem().unwrap_or_default().to_string_lossy();
+        let name = stem.to_lowercase();
+        !name.ends_with("_p")
+            || self
+                .system_mod_patterns
+                .split([',', '\n'])
+                .map(|p| p.trim().to_lowercase())
+                .any(|p| !p.is_empty() && name.contains(&p))
+    }
+
+    fn scroll_to_row(&mut self, ui: &egui::Ui, index: usize, rect: egui::Rect) {
+        if self.scroll_to_current && self.current_pak_file_idx == Some(index) {
+            ui.scroll_to_rect(rect, None);
+            self.scroll_to_current = false;
+        }
+    }
+
+    fn set_selected(&mut self, index: usize, selected: bool) {
+        if selected {
+            self.selected_mods.insert(index);
+        } else {
+            self.selected_mods.remove(&index);
+        }
+    }
+
+    /// Selects or clears every visible mod between `from` and `to`, both included
+    fn select_range(&mut self, from: usize, to: usize, selected: bool) {
+        let order = self.visible_mod_order();
+        let (Some(a), Some(b)) = (
+            order.iter().position(|&i| i == from),
+            order.iter().position(|&i| i == to),
+        ) else {
+            self.set_selected(to, selected);
+            return;
+        };
+        for &i in &order[a.min(b)..=a.max(b)] {
+            self.set_selected(i, selected);
+        }
+    }
+
+    // the row the drag started on is only painted once the pointer left it, a plain click on
+    // its checkbox is handled by the checkbox itself
+    fn drag_select_row(&mut self, ui: &egui::Ui, index: usize, rect: egui::Rect) {
+        let Some((origin, selected)) = self.drag_select else {
+            return;
+        };
+        if index != origin && ui.rect_contains_pointer(rect) {
+            self.set_selected(origin, selected);
+            self.set_selected(index, selected);
+            self.last_selected_idx = Some(index);
+        }
     }
 
     fn show_mod_entry_by_index(&mut self, ui: &mut egui::Ui, index: usize) {
@@ -1380,7 +4061,17 @@ impl RepakModManager {
         let mut stop_editing = false;
         let mut new_custom_name: Option<String> = None;
         let mut reset_name = false;
+        let mut toggle_lock = false;
+        let mut dependency_toggle: Option<(ModDependency, bool)> = None;
         let mut new_folder_id: Option<Option<String>> = None;
+        let mut new_type_override: Option<Option<String>> = None;
+        let mut rescan_type = false;
+        let mut open_load_order = false;
+        let mut unpack_project_to: Option<PathBuf> = None;
+        let unpacking_project = self.unpack_project_results.is_some();
+        let mut copy_mod_to: Option<PathBuf> = None;
+        let copying_mod = self.copy_mod_results.is_some();
+        let type_override = self.pak_files[index].type_override.clone();
         let folders_clone = self.folders.clone();
         // Custom tags temp state for this context menu
         let available_custom_tags: Vec<String> = self.get_all_custom_tags().into_iter().collect();
@@ -1394,6 +4085,8 @@ impl RepakModManager {
         let pak_reader = self.pak_files[index].reader.clone();
         let pak_path = self.pak_files[index].path.clone();
         let has_custom_name = self.pak_files[index].custom_name.is_some();
+        let locked = self.pak_files[index].locked;
+        let locked_hint = "Locked, unlock it from the right-click menu first";
         
         ui.horizontal(|ui| {
             ui.with_layout(egui::Layout::left_to_right(Align::LEFT), |ui| {
@@ -1401,15 +4094,42 @@ impl RepakModManager {
                 // Selection checkbox for bulk operations
                 if self.selection_mode {
                     let mut checked = self.selected_mods.contains(&index);
-                    if ui.checkbox(&mut checked, "").changed() {
-                        if checked { self.selected_mods.insert(index); } else { self.selected_mods.remove(&index); }
+                    let response = ui
+                        .checkbox(&mut checked, "")
+                        .on_hover_text("Shift-click selects a range, drag over the list to select several");
+                    if response.changed() {
+                        let shift = ui.input(|i| i.modifiers.shift);
+                        match self.last_selected_idx.filter(|_| shift) {
+                            Some(anchor) => self.select_range(anchor, index, checked),
+                            None => self.set_selected(index, checked),
+                        }
+                        self.last_selected_idx = Some(index);
+                    }
+                    if response.is_pointer_button_down_on() && self.drag_select.is_none() {
+                        self.drag_select = Some((index, !self.selected_mods.contains(&index)));
                     }
                     ui.add_space(4.0);
                 }
+                if self.show_list_thumbnails {
+                    let ctx = ui.ctx().clone();
+                    let thumb_size = match self.list_density {
+                        ListDensity::Comfortable => egui::vec2(32.0, 32.0),
+                        ListDensity::Compact => egui::vec2(20.0, 20.0),
+                    };
+                    match self.previews.get(&ctx, &pak_path, &pak_reader) {
+                        Some(texture) => {
+                            ui.add(egui::Image::from_texture(texture).max_size(thumb_size).corner_radius(4));
+                        }
+                        // keep names aligned with the rows that do have a thumbnail
+                        None => {
+                            ui.allocate_space(thumb_size);
+                        }
+                    }
+                }
 
                 if is_editing {
                     let mut temp_name = current_name.unwrap_or_else(|| {
-                        pak_path.file_stem().unwrap().to_string_lossy().to_string()
+                        pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string()
                     });
                     
                     let response = ui.add(TextEdit::singleline(&mut temp_name).desired_width(200.0));
@@ -1450,13 +4170,60 @@ impl RepakModManager {
                     }
                 } else {
                     // Bubbly mod entry styling
-                    let pakfile = ui.add(
-                        Label::new(RichText::new(display_name).strong().size(14.0).color(
-                            if self.current_pak_file_idx == Some(index) { self.accent() } else { ui.style().visuals.text_color() }
-                        ))
+                    let name_size = match self.list_density {
+                        ListDensity::Comfortable => 14.0,
+                        ListDensity::Compact => 12.0,
+                    };
+                    let mut name = RichText::new(display_name).strong().size(name_size);
+                    let state_colors = match self.skip_state_colors {
+                        true => None,
+                        false => self.state_colors(self.mod_state(index)),
+                    };
+                    if let Some(fill) = state_colors.and_then(|(_, fill)| fill) {
+                        name = name.background_color(fill);
+                    }
+                    let name_color = match state_colors {
+                        _ if self.current_pak_file_idx == Some(index) => self.accent(),
+                        Some((color, _)) => color,
+                        None => ui.style().visuals.text_color(),
+                    };
+                    let mut pakfile = ui.add(
+                        Label::new(name.color(name_color))
                         .truncate()
                         .selectable(true),
                     );
+                    if pakfile.hovered() {
+                        pakfile = pakfile.on_hover_text(self.mod_details(index));
+                    }
+                    let (kind, hover) = match self.pak_files[index].iostore {
+                        true => (
+                            "iostore",
+                            "IoStore mod: a small .pak plus a .utoc and .ucas holding the assets, the format the game itself uses. All three files belong to the mod.",
+                        ),
+                        false => (
+                            "pak",
+                            "Classic pak: all assets in a single .pak file. Can be turned into an iostore mod with File > Convert mods.",
+                        ),
+                    };
+                    ui.label(RichText::new(kind).small().weak()).on_hover_text(hover);
+                    if let Some(chunk) = pakchunk_index(&pak_path.file_stem().unwrap_or_default().to_string_lossy()) {
+                        ui.weak(format!("chunk {}", chunk))
+                            .on_hover_text("pakchunk mods are listed after the others, sorted by chunk number");
+                    }
+                    if let Some(missing) = self.outdated_assets.as_ref().and_then(|o| o.get(&mod_key(&pak_path))) {
+                        let mut hover = format!(
+                            "{} assets of this mod aren't in the game file list, it may be broken since a game update:",
+                            missing.len()
+                        );
+                        for asset in missing.iter().take(10) {
+                            hover.push_str(&format!("\n{}", asset));
+                        }
+                        if missing.len() > 10 {
+                            hover.push_str("\n...");
+                        }
+                        ui.colored_label(ui.visuals().warn_fg_color, "⚠ may be outdated")
+                            .on_hover_text(hover);
+                    }
 
                     if pakfile.clicked() {
                         should_set_current = true;
@@ -1464,18 +4231,75 @@ impl RepakModManager {
                     }
 
                     pakfile.context_menu(|ui| {
-                        if ui.button("Rename mod").clicked() {
+                        if ui
+                            .add_enabled(!locked, egui::Button::new("Rename mod"))
+                            .on_disabled_hover_text(locked_hint)
+                            .clicked()
+                        {
                             start_editing = true;
                             ui.close_menu();
                             ui.ctx().request_repaint();
                         }
                         
-                        if has_custom_name && ui.button("Reset to original name").clicked() {
+                        if has_custom_name
+                            && ui
+                                .add_enabled(!locked, egui::Button::new("Reset to original name"))
+                                .on_disabled_hover_text(locked_hint)
+                                .clicked()
+                        {
                             reset_name = true;
                             should_save = true;
                             ui.close_menu();
                         }
 
+                        if ui
+                            .button(if locked { "Unlock" } else { "Lock" })
+                            .on_hover_text("Locked mods can't be disabled, deleted or renamed, not even by bulk actions")
+                            .clicked()
+                        {
+                            toggle_lock = true;
+                            should_save = true;
+                            ui.close_menu();
+                        }
+
+                        ui.menu_button("Requires", |ui| {
+                            ui.weak("Mods this one needs, enabling it warns when one is off or missing");
+                            ui.separator();
+                            let required = self.pak_files[index]
+                                .dependencies
+                                .iter()
+                                .map(|dependency| (dependency.clone(), self.dependency_index(dependency)))
+                                .collect::<Vec<_>>();
+                            // required mods that are gone stay listed so they can be removed
+                            for (dependency, _) in required.iter().filter(|(_, found)| found.is_none()) {
+                                let mut checked = true;
+                                let label = format!("{} (not installed)", dependency.name);
+                                if ui.checkbox(&mut checked, label).changed() {
+                                    dependency_toggle = Some((dependency.clone(), false));
+                                    should_save = true;
+                                }
+                            }
+                            let mut others = (0..self.pak_files.len())
+                                .filter(|&i| i != index && !self.is_system_mod(i))
+                                .map(|i| (i, self.get_mod_display_name(&self.pak_files[i])))
+                                .collect::<Vec<_>>();
+                            others.sort_by_cached_key(|(_, name)| name.to_lowercase());
+                            ScrollArea::vertical().max_height(320.).show(ui, |ui| {
+                                for (i, name) in others {
+                                    let existing = required.iter().find(|(_, found)| *found == Some(i));
+                                    let mut checked = existing.is_some();
+                                    if ui.checkbox(&mut checked, name).changed() {
+                                        let dependency = match existing {
+                                            Some((dependency, _)) => dependency.clone(),
+                                            None => self.dependency_on(i),
+                                        };
+                                        dependency_toggle = Some((dependency, checked));
+                                        should_save = true;
+                                    }
+                                }
+                            });
+                        });
+
                         ui.separator();
                         
                         ui.menu_button("Assign to folder", |ui| {
@@ -1494,6 +4318,34 @@ impl RepakModManager {
                             }
                         });
 
+                        ui.menu_button("Set type", |ui| {
+                            if ui.selectable_label(type_override.is_none(), "Detect automatically").clicked() {
+                                new_type_override = Some(None);
+                                should_save = true;
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            for category in MOD_CATEGORIES {
+                                let selected = type_override.as_deref() == Some(category);
+                                if ui.selectable_label(selected, category).clicked() {
+                                    new_type_override = Some(Some(category.to_string()));
+                                    should_save = true;
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Override the detected type. Audio and Movies mods are packed without iostore conversion");
+                        if ui
+                            .add_enabled(self.type_scan_results.is_none(), Button::new("Re-scan type"))
+                            .on_hover_text("Read the mod again and detect its type, after changing its files outside the app")
+                            .on_disabled_hover_text("A scan is already running")
+                            .clicked()
+                        {
+                            rescan_type = true;
+                            ui.close_menu();
+                        }
+
                         ui.separator();
 
                         // Custom Tags submenu: toggle existing tags, add new ones
@@ -1530,31 +4382,132 @@ impl RepakModManager {
 
                         ui.separator();
 
-                        if ui.button("Extract pak to directory").clicked() {
-                            should_set_current = true;
-                            let dir = rfd::FileDialog::new().pick_folder();
-                            if let Some(dir) = dir {
-                                let mod_name = pak_path.file_stem().unwrap().to_string_lossy().to_string();
-                                let to_create = dir.join(&mod_name);
-                                fs::create_dir_all(&to_create).unwrap();
-
-                                let installable_mod = InstallableMod {
-                                    mod_name: mod_name.clone(),
-                                    mod_type: "".to_string(),
-                                    reader: Option::from(pak_reader.clone()),
-                                    mod_path: pak_path.clone(),
-                                    ..Default::default()
-                                };
-                                if let Err(e) = extract_pak_to_dir(&installable_mod, to_create) {
-                                    error!("Failed to extract pak directory: {}", e);
+                        match self.pak_files[index].readmes.as_slice() {
+                            [] => {}
+                            [readme] => {
+                                if ui.button("Open readme").clicked() {
+                                    open_readme(readme);
+                                    ui.close_menu();
                                 }
                             }
-                            ui.close_menu();
+                            readmes => {
+                                ui.menu_button("Open readme", |ui| {
+                                    for readme in readmes {
+                                        let name = readme.file_name().unwrap_or_default().to_string_lossy().to_string();
+                                        if ui.button(name).clicked() {
+                                            open_readme(readme);
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
+                            }
                         }
-                        
+
+                        if ui
+                            .add_enabled(self.mod_state(index) == ModState::Conflicting, Button::new("Set load order..."))
+                            .on_hover_text("Choose which of the mods sharing files with this one wins, by numbering their priorities")
+                            .on_disabled_hover_text("Only for enabled mods sharing files with another enabled mod")
+                            .clicked()
+                        {
+                            open_load_order = true;
+                            ui.close_menu();
+                        }
+
+                        let log_key = pak_path.file_stem().unwrap_or_default().to_string_lossy().to_lowercase();
+                        if ui
+                            .add_enabled(self.install_logs.contains_key(&log_key), Button::new("View install log"))
+                            .on_hover_text("What was logged while this mod was installed or converted")
+                            .on_disabled_hover_text("Only mods installed or converted since Repak was started have one")
+                            .clicked()
+                        {
+                            self.install_log_view = Some(log_key);
+                            ui.close_menu();
+                        }
+
+                        if ui
+                            .add_enabled(!copying_mod, Button::new("Copy mod files to..."))
+                            .on_hover_text("Copies the pak, and the utoc and ucas of iostore mods, to another folder. Mods can't be dragged out of the window.")
+                            .on_disabled_hover_text("Another mod is being copied")
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            if let Some(dest) = rfd::FileDialog::new().set_title("Copy mod files to").pick_folder() {
+                                copy_mod_to = Some(dest);
+                            }
+                        }
+
+                        let tool_name = self
+                            .external_tool
+                            .as_ref()
+                            .map(|tool| tool.file_stem().unwrap_or_default().to_string_lossy().to_string());
+                        let open_with = ui
+                            .add_enabled(
+                                tool_name.is_some(),
+                                egui::Button::new(format!("Open with {}", tool_name.as_deref().unwrap_or("external tool"))),
+                            )
+                            .on_disabled_hover_text("Pick a tool under Settings > Paths > External tool");
+                        if open_with.clicked() {
+                            ui.close_menu();
+                            if let Some(tool) = &self.external_tool {
+                                open_in_external_tool(tool, &pak_path);
+                            }
+                        }
+
+                        if ui.button("Extract pak to directory").clicked() {
+                            should_set_current = true;
+                            let dir = rfd::FileDialog::new().pick_folder();
+                            if let Some(dir) = dir {
+                                let mod_name = pak_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                                let to_create = dir.join(&mod_name);
+                                let installable_mod = InstallableMod {
+                                    mod_name: mod_name.clone(),
+                                    mod_type: "".to_string(),
+                                    reader: Option::from(pak_reader.clone()),
+                                    mod_path: pak_path.clone(),
+                                    ..Default::default()
+                                };
+                                if let Err(e) = fs::create_dir_all(&to_create) {
+                                    error!("Failed to create {}: {}", to_create.display(), e);
+                                } else if let Err(e) = extract_pak_to_dir(&installable_mod, to_create) {
+                                    error!("Failed to extract pak directory: {}", e);
+                                }
+                            }
+                            ui.close_menu();
+                        }
+
+                        if ui
+                            .add_enabled(!unpacking_project, Button::new("Unpack as project..."))
+                            .on_hover_text("Extract the mod for editing, \"Repack project\" packs it again with the same name and settings")
+                            .on_disabled_hover_text("Another mod is being unpacked")
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            if let Some(dest) = rfd::FileDialog::new().set_title("Unpack project to").pick_folder() {
+                                unpack_project_to = Some(dest);
+                            }
+                        }
+
+                        let compactable = is_compactable(&self.mod_type_of(index));
+                        let optimize_btn = ui
+                            .add_enabled(
+                                compactable && self.compact_running.is_none(),
+                                egui::Button::new("Optimize compression..."),
+                            )
+                            .on_disabled_hover_text(if compactable {
+                                "Another mod is being optimized"
+                            } else {
+                                "Audio and movie mods won't get any smaller"
+                            });
+                        if optimize_btn.clicked() {
+                            self.compact_target = Some(pak_path.clone());
+                            ui.close_menu();
+                        }
+
                         let is_deleting_this = self.deleting_mods.contains(&pak_path);
-                        let del_btn = ui.add_enabled(!is_deleting_this, egui::Button::new("Delete mod"));
-                        if del_btn.clicked() {
+                        let del_btn = ui
+                            .add_enabled(!is_deleting_this && !locked, egui::Button::new("Delete mod"))
+                            .on_disabled_hover_text(if locked { locked_hint } else { "Being deleted" });
+                        if del_btn.clicked() && self.confirm_game_closed() {
                             // Queue deletion on background thread (non-blocking)
                             self.ensure_delete_worker();
                             let utoc_path = pak_path.with_extension("utoc");
@@ -1566,7 +4519,8 @@ impl RepakModManager {
                                 self.deleting_mods.insert(pak_path.clone());
                                 // Defer actual UI list mutation until after iteration
                                 self.pending_remove_paths.push(pak_path.clone());
-                                if let Err(e) = tx.send(files_to_delete) {
+                                let job = DeleteJob { mods: vec![pak_path.clone()], files: files_to_delete };
+                                if let Err(e) = tx.send(job) {
                                     error!("Failed to queue delete: {}", e);
                                     // If we failed to enqueue, clear deleting state
                                     self.deleting_mods.remove(&pak_path);
@@ -1583,23 +4537,37 @@ impl RepakModManager {
                             ui.ctx().request_repaint();
                         }
                     });
-                    if ui.add(egui::Button::new("✏").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                    if ui
+                        .add_enabled(!locked, egui::Button::new("✏").corner_radius(egui::CornerRadius::same(8)))
+                        .on_disabled_hover_text(locked_hint)
+                        .clicked()
+                    {
                         start_editing = true;
                     }
+                    let favorite = self.pak_files[index].favorite;
+                    let star = ui
+                        .add(
+                            egui::Button::new(RichText::new(if favorite { "★" } else { "☆" }).color(self.accent()))
+                                .frame(false),
+                        )
+                        .on_hover_text(if favorite { "Remove from favorites" } else { "Add to favorites" });
+                    if star.clicked() {
+                        self.pak_files[index].favorite = !favorite;
+                        should_save = true;
+                        self.update_search_filter();
+                    }
+                    if locked {
+                        ui.label(RichText::new("🔒").color(self.accent()))
+                            .on_hover_text("Locked: can't be disabled, deleted or renamed until it is unlocked from the right-click menu");
+                    }
 
                     // Show custom tag chips for this mod
                     if !self.pak_files[index].custom_tags.is_empty() {
                         ui.add_space(8.0);
                         ui.horizontal_wrapped(|ui| {
+                            let default_fill = ui.style().visuals.extreme_bg_color;
                             for tag in &self.pak_files[index].custom_tags {
-                                let chip = egui::Button::new(
-                                    egui::RichText::new(tag).size(10.0)
-                                )
-                                .fill(ui.style().visuals.extreme_bg_color)
-                                .frame(true)
-                                .corner_radius(egui::CornerRadius::same(10))
-                                .small();
-                                let _ = ui.add(chip);
+                                let _ = ui.add(self.tag_chip(tag, default_fill));
                             }
                         });
                     }
@@ -1638,7 +4606,9 @@ impl RepakModManager {
             
             ui.with_layout(egui::Layout::right_to_left(Align::RIGHT), |ui| {
                 let mut enabled = pak_enabled;
-                let toggler = ui.add(ios_widget::toggle(&mut enabled));
+                let toggler = ui
+                    .add_enabled(!(locked && pak_enabled), ios_widget::toggle(&mut enabled))
+                    .on_disabled_hover_text(locked_hint);
                 if toggler.clicked() {
                     should_toggle = true;
                     should_save = true;
@@ -1662,41 +4632,43 @@ impl RepakModManager {
         if reset_name {
             self.pak_files[index].custom_name = None;
         }
+
+        if toggle_lock {
+            self.pak_files[index].locked = !locked;
+        }
+
+        if let Some((dependency, required)) = dependency_toggle {
+            let dependencies = &mut self.pak_files[index].dependencies;
+            dependencies.retain(|d| *d != dependency);
+            if required {
+                dependencies.push(dependency);
+            }
+        }
         
         if let Some(folder_id) = new_folder_id {
             self.pak_files[index].folder_id = folder_id;
         }
+
+        if let Some(type_override) = new_type_override {
+            self.pak_files[index].type_override = type_override;
+            self.update_search_filter();
+        }
+        if rescan_type {
+            self.rescan_type(index);
+        }
+        if open_load_order {
+            self.open_load_order(index);
+        }
+        if let Some(dest) = unpack_project_to {
+            let entry = &self.pak_files[index];
+            self.start_unpack_project(entry.path.clone(), entry.reader.clone(), dest);
+        }
+        if let Some(dest) = copy_mod_to {
+            self.start_copy_mod(self.pak_files[index].path.clone(), dest);
+        }
         
         if should_toggle {
-            // Handle toggle manually to avoid borrowing conflicts
-            let pak_file = &mut self.pak_files[index];
-            pak_file.enabled = !pak_file.enabled;
-            
-            if pak_file.enabled {
-                let new_pak = pak_file.path.with_extension("pak");
-                match std::fs::rename(&pak_file.path, &new_pak) {
-                    Ok(_) => {
-                        pak_file.path = new_pak;
-                        info!("Enabled mod: {:?}", pak_file.path);
-                    }
-                    Err(e) => {
-                        warn!("Failed to enable mod: {:?}", e);
-                        pak_file.enabled = false;
-                    }
-                }
-            } else {
-                let new_pak = pak_file.path.with_extension("bak_repak");
-                match std::fs::rename(&pak_file.path, &new_pak) {
-                    Ok(_) => {
-                        pak_file.path = new_pak;
-                        info!("Disabled mod: {:?}", pak_file.path);
-                    }
-                    Err(e) => {
-                        warn!("Failed to disable mod: {:?}", e);
-                        pak_file.enabled = true;
-                    }
-                }
-            }
+            self.toggle_mod_at(index);
         }
         
         // Apply tag toggles
@@ -1744,17 +4716,40 @@ impl RepakModManager {
     }
 
     fn load(ctx: &eframe::CreationContext) -> std::io::Result<Self> {
-        let (tx, rx) = channel();
         let path = Self::config_path();
+        reload_aes_keys();
         let mut shit = if path.exists() {
             info!("Loading config: {}", path.to_string_lossy());
-            let data = fs::read_to_string(path)?;
-            let mut config: Self = serde_json::from_str(&data)?;
+            let data = fs::read_to_string(&path)?;
+            let mut config: Self = match serde_json::from_str::<Self>(&data) {
+                Ok(config) => {
+                    if !config.skip_config_backups {
+                        config_backup::rotate(&path);
+                    }
+                    config
+                }
+                Err(e) => {
+                    // a damaged config is restored from its backups first, old releases
+                    // never wrote backups so their configs fall through to the import
+                    warn!("Unable to parse config ({}), looking for a backup", e);
+                    match Self::restore_config_backup(&path, &e) {
+                        Some(config) => config,
+                        None => {
+                            info!("No backup restored, trying the old repak-gui format");
+                            Self::import_legacy_config(&path, &data).ok_or(e)?
+                        }
+                    }
+                }
+            };
             // Ensure the editable text field reflects the saved path after restart
             config.game_path_input = config.game_path.to_string_lossy().to_string();
+            MEMORY_MAP_UCAS.store(config.memory_map_ucas, Ordering::Relaxed);
 
             debug!("Setting custom style");
             setup_custom_style(&ctx.egui_ctx);
+            if config.cjk_font {
+                fonts::set_fonts(&ctx.egui_ctx, true);
+            }
             debug!("Setting font size: {}", config.default_font_size);
             set_custom_font_size(&ctx.egui_ctx, config.default_font_size);
             if config.use_custom_palette {
@@ -1766,6 +4761,7 @@ impl RepakModManager {
 
             info!("Loading mods: {}", config.game_path.to_string_lossy());
             config.collect_pak_files();
+            config.reload_game_paths();
             config.update_search_filter();
 
             let mut show_welcome = false;
@@ -1777,9 +4773,13 @@ impl RepakModManager {
                 show_welcome = true;
             }
             config.version = Option::from(VERSION.to_string());
-            config.hide_welcome = !show_welcome;
+            config.hide_welcome = !show_welcome || config.skip_welcome_on_update;
             config.welcome_screen = Some(ShowWelcome{});
-            config.receiver = Some(rx);
+            if config.usage_stats_enabled {
+                // saved right away, so a crash before the next save is still noticed
+                config.usage_stats.start_session(true);
+                let _ = config.save_state();
+            }
 
             Ok(config)
         } else {
@@ -1790,162 +4790,1710 @@ impl RepakModManager {
             let mut x = Self::new(ctx);
             x.welcome_screen = Some(ShowWelcome{});
             x.hide_welcome=false;
-            x.receiver = Some(rx);
             Ok(x)
         };
 
         if let Ok(ref mut shit) = shit {
-            let path = shit.game_path.clone();
-            thread::spawn(move || {
-                let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res| {
-                    if let Ok(event) = res {
-                        tx.send(event).unwrap();
-                    }
-                })
-                .unwrap();
-
-                if path.exists() {
-                    watcher.watch(&path, RecursiveMode::Recursive).unwrap();
-                }
-
-                // Keep the thread alive
-                loop {
-                    thread::sleep(Duration::from_secs(1));
-                }
-            });
+            if shit.skip_folder_watch {
+                info!("Not watching the mod folder, changes show up after a refresh");
+            } else {
+                shit.start_folder_watcher();
+            }
             shit.collect_pak_files();
+            shit.restore_last_selection();
+            shit.restart_download_watcher(&ctx.egui_ctx);
         }
 
         shit
     }
-    fn save_state(&mut self) -> std::io::Result<()> {
-        // Sync pak_files metadata back to mod_metadata for persistence
-        self.sync_metadata();
-        
-        let path = Self::config_path();
-        let json = serde_json::to_string_pretty(self)?;
-        info!("Saving config: {}", path.to_string_lossy());
-        fs::write(path, json)?;
-        Ok(())
-    }
-    
-    fn sync_metadata(&mut self) {
-        // Clear existing metadata and rebuild from current pak_files
-        self.mod_metadata.clear();
-        
-        for pak_file in &self.pak_files {
-            let metadata = ModMetadata {
-                path: pak_file.path.clone(),
-                custom_name: pak_file.custom_name.clone(),
-                folder_id: pak_file.folder_id.clone(),
-                custom_tags: pak_file.custom_tags.clone(),
-            };
-            self.mod_metadata.push(metadata);
+
+    /// Starts or stops watching the mod folder after `skip_folder_watch` changed
+    fn apply_folder_watch(&mut self) {
+        self.watch_rescan_at = None;
+        match self.skip_folder_watch {
+            true => self.receiver = None,
+            false if self.receiver.is_none() => self.start_folder_watcher(),
+            false => {}
         }
     }
 
-    /// Preview hovering files:
-    fn preview_files_being_dropped(&self, ctx: &egui::Context, rect: egui::Rect) {
-        use egui::{Align2, Color32, Id, LayerId, Order, TextStyle};
+    /// Watches the mod folder, and the key files next to the exe, for changes that reload
+    /// the mod list. The watcher stops once `receiver` is dropped.
+    fn start_folder_watcher(&mut self) {
+        let (tx, rx) = channel();
+        let path = self.install_dir();
+        thread::spawn(move || {
+            let events = tx.clone();
+            let watcher = notify::recommended_watcher(move |res| {
+                if let Ok(event) = res {
+                    let _ = events.send(event);
+                }
+            });
+            let mut watcher: RecommendedWatcher = match watcher {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    error!("Unable to watch the mod folder, use Refresh to pick up changes: {}", e);
+                    return;
+                }
+            };
 
-        if !ctx.input(|i| i.raw.hovered_files.is_empty()) {
-            let painter =
-                ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("file_drop_target")));
+            if path.exists() {
+                if let Err(e) = watcher.watch(&path, RecursiveMode::Recursive) {
+                    error!("Unable to watch {}, use Refresh to pick up changes: {}", path.display(), e);
+                }
+            }
+            // keys.txt / aes.json live next to the exe
+            if let Some(dir) = keys_dir() {
+                if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                    warn!("Unable to watch {} for key file changes: {}", dir.to_string_lossy(), e);
+                }
+            }
 
-            let msg = match self.game_path.is_dir() {
-                true => "Drop mod files here",
-                false => "Choose a game directory first!!!",
-            };
-            painter.rect_filled(rect, 0.0, Color32::from_rgba_unmultiplied(241, 24, 14, 40));
-            painter.text(
-                rect.center(),
-                Align2::CENTER_CENTER,
-                msg,
-                TextStyle::Heading.resolve(&ctx.style()),
-                Color32::WHITE,
-            );
+            // Keep the thread alive while the app listens, update ignores the heartbeat
+            while tx.send(Event::new(EventKind::Other)).is_ok() {
+                thread::sleep(Duration::from_secs(1));
+            }
+            debug!("Stopped watching the mod folder");
+        });
+        self.receiver = Some(rx);
+    }
+
+    fn watched_downloads_dir(&self) -> Option<PathBuf> {
+        self.downloads_dir.clone().or_else(dirs::download_dir)
+    }
+
+    /// (Re)starts watching the downloads folder, or stops if the option is off
+    fn restart_download_watcher(&mut self, ctx: &egui::Context) {
+        self.download_watcher = None;
+        if !self.watch_downloads {
+            return;
+        }
+        let Some(dir) = self.watched_downloads_dir() else {
+            warn!("No downloads folder to watch");
+            return;
+        };
+        match DownloadWatcher::new(&dir, ctx.clone()) {
+            Ok(watcher) => self.download_watcher = Some(watcher),
+            Err(e) => error!("Unable to watch {}: {}", dir.display(), e),
         }
     }
 
-    fn check_drop(&mut self, ctx: &egui::Context) {
-        if !self.game_path.is_dir() {
+    /// Asks whether to install mods that finished downloading
+    fn show_download_prompt(&mut self, ctx: &egui::Context) {
+        if let Some(watcher) = &mut self.download_watcher {
+            for path in watcher.poll() {
+                if !self.downloaded_mods.contains(&path) {
+                    self.downloaded_mods.push(path);
+                }
+            }
+            if watcher.is_waiting() {
+                ctx.request_repaint_after(Duration::from_secs(1));
+            }
+        }
+        if self.downloaded_mods.is_empty() || self.install_mod_dialog.is_some() {
             return;
         }
-        ctx.input(|i| {
-            if !i.raw.dropped_files.is_empty() {
-                let dropped_files = i.raw.dropped_files.clone();
-                // Check if all files are either directories or have the .pak extension
-                let all_valid = dropped_files.iter().all(|file| {
-                    let path = file.path.clone().unwrap();
-                    path.is_dir()
-                        || path
-                            .extension()
-                            .map(|ext| ext == "pak" || ext == "zip" || ext == "rar")
-                            .unwrap_or(false)
+
+        let mut install = false;
+        let mut ignore = false;
+        egui::Window::new("New mods downloaded")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::RIGHT_BOTTOM, [-12., -12.])
+            .show(ctx, |ui| {
+                for path in &self.downloaded_mods {
+                    ui.label(path.file_name().unwrap_or_default().to_string_lossy());
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    install = ui
+                        .add_enabled(self.game_path.is_dir(), Button::new("Install").corner_radius(egui::CornerRadius::same(8)))
+                        .on_disabled_hover_text("Choose a mod folder first")
+                        .clicked();
+                    ignore = ui.add(Button::new("Ignore").corner_radius(egui::CornerRadius::same(8))).clicked();
                 });
+            });
 
-                if all_valid {
-                    let mods = map_dropped_file_to_mods(&dropped_files);
-                    if mods.is_empty() {
-                        error!("No mods found in dropped files.");
-                        return;
-                    }
-                    self.file_drop_viewport_open = true;
-                    debug!("Mods: {:?}", mods);
-                    self.install_mod_dialog =
-                        Some(ModInstallRequest::new(mods, self.game_path.clone()));
+        if install {
+            let files = std::mem::take(&mut self.downloaded_mods);
+            self.start_install(files);
+        } else if ignore {
+            self.downloaded_mods.clear();
+        }
+    }
+    /// Takes in the mods indexed since the last frame, returns true when any arrived.
+    /// Keeps repainting while mods are still being read so the index fills in.
+    fn poll_file_index(&mut self, ctx: &egui::Context) -> bool {
+        let changed = self.file_index.poll();
+        if changed {
+            // checked again once every mod is in
+            self.outdated_assets = None;
+            self.mod_conflicts = None;
+        }
+        if self.file_index.pending() > 0 {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+        changed
+    }
 
-                    if let Some(dialog) = &self.install_mod_dialog {
-                        trace!("Installing mod: {:#?}", dialog.mods);
-                    }
-                } else {
-                    // Handle the case where not all dropped files are valid
-                    // You can show an error or prompt the user here
-                    warn!(
-                        "Not all files are valid. Only directories or .pak files are allowed."
-                    );
-                }
-            }
+    /// Mods shipping `path`, as indices into `pak_files`. Mods the file index hasn't read yet
+    /// are missed.
+    fn find_mods_containing(&self, path: &str) -> Vec<usize> {
+        let holders = self.file_index.mods_containing(path);
+        self.pak_files
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| holders.contains(&mod_key(&m.path).as_path()))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Re-reads the known game path list, or drops it when the outdated check is off
+    fn reload_game_paths(&mut self) {
+        self.game_paths = None;
+        self.outdated_assets = None;
+        if !self.check_outdated {
+            return;
+        }
+        let path = Self::config_path().with_file_name(GAME_PATHS_FILE);
+        if !path.exists() {
+            return;
+        }
+        match GamePaths::load(&path) {
+            Ok(paths) => self.game_paths = Some(paths),
+            Err(e) => warn!("Unable to read {}: {}", path.display(), e),
+        }
+    }
+
+    /// Asks for a game path list and copies it to the config folder
+    fn import_game_paths(&mut self) {
+        let Some(source) = FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+        let result = GamePaths::load(&source).and_then(|_| {
+            fs::copy(&source, Self::config_path().with_file_name(GAME_PATHS_FILE))
+                .map(|_| ())
+                .map_err(|e| e.to_string())
         });
+        if let Err(e) = result {
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Unable to load game file list")
+                .set_description(e)
+                .set_buttons(MessageButtons::Ok)
+                .show();
+        }
+        self.reload_game_paths();
     }
 
-    fn show_menu_bar(&mut self, ui: &mut egui::Ui) -> Result<(), repak::Error> {
-        egui::menu::bar(ui, |ui| {
-            ui.menu_button("File", |ui| {
-                let msg = match self.game_path.is_dir() {
-                    true => "Drop mod files here",
-                    false => "Choose a game directory first!!!",
-                };
+    /// Fills `outdated_assets` once every mod is in the file index, while the check is on
+    fn update_outdated_check(&mut self) {
+        if self.outdated_assets.is_some() || self.file_index.pending() > 0 {
+            return;
+        }
+        let Some(game_paths) = &self.game_paths else {
+            return;
+        };
+        let outdated = self
+            .pak_files
+            .iter()
+            .filter_map(|m| {
+                let missing = game_paths.missing(&self.file_index.files_of(&m.path)?);
+                (!missing.is_empty()).then(|| (mod_key(&m.path), missing))
+            })
+            .collect::<std::collections::HashMap<_, _>>();
+        info!("{} mods may be outdated", outdated.len());
+        self.outdated_assets = Some(outdated);
+    }
 
-                if ui
-                    .add_enabled(self.game_path.is_dir(), Button::new("Install mods").corner_radius(egui::CornerRadius::same(8)))
-                    .on_hover_text(msg)
-                    .clicked()
-                {
-                    ui.close_menu(); // Closes the menu
-                    let mod_files = rfd::FileDialog::new()
-                        .set_title("Pick mods")
-                        .pick_files()
-                        .unwrap_or_default();
+    /// Fills `mod_conflicts` once every mod is in the file index, and again whenever a mod
+    /// is enabled or disabled
+    fn update_mod_conflicts(&mut self) {
+        if self.file_index.pending() > 0 {
+            return;
+        }
+        let enabled = self.pak_files.iter().filter(|m| m.enabled).map(|m| m.path.clone()).collect::<Vec<_>>();
+        if self.mod_conflicts.as_ref().is_some_and(|(among, _)| *among == enabled) {
+            return;
+        }
+        let conflicting = self.file_index.overlapping(&enabled);
+        debug!("{} enabled mods share files", conflicting.len());
+        self.mod_conflicts = Some((enabled, conflicting));
+    }
 
-                    if mod_files.is_empty() {
-                        error!("No mods found in dropped files.");
-                        return;
-                    }
+    fn mod_state(&self, index: usize) -> ModState {
+        let entry = &self.pak_files[index];
+        if entry.incomplete {
+            ModState::Broken
+        } else if !entry.enabled {
+            ModState::Disabled
+        } else if self.mod_conflicts.as_ref().is_some_and(|(_, keys)| keys.contains(&mod_key(&entry.path))) {
+            ModState::Conflicting
+        } else {
+            ModState::Normal
+        }
+    }
 
-                    let mods = map_paths_to_mods(&mod_files);
-                    if mods.is_empty() {
-                        error!("No mods found in dropped files.");
-                        return;
+    /// Name color of a mod in `state` and the fill behind it, `None` for the usual look
+    fn state_colors(&self, state: ModState) -> Option<(Color32, Option<Color32>)> {
+        let p = &self.custom_palette;
+        match state {
+            ModState::Normal => None,
+            ModState::Disabled => Some((CustomPalette::rgba(p.state_disabled), None)),
+            ModState::Conflicting => {
+                let color = CustomPalette::rgba(p.state_conflicting);
+                Some((color, Some(color.gamma_multiply(0.15))))
+            }
+            ModState::Broken => {
+                let color = CustomPalette::rgba(p.state_broken);
+                Some((color, Some(color.gamma_multiply(0.15))))
+            }
+        }
+    }
+
+    /// `index_changed` is set on frames that brought newly indexed mods
+    fn show_file_search(&mut self, ctx: &egui::Context, index_changed: bool) {
+        if !self.show_file_search {
+            return;
+        }
+        let mut refresh = index_changed;
+
+        let names = self
+            .pak_files
+            .iter()
+            .map(|m| (mod_key(&m.path), self.get_mod_display_name(m)))
+            .collect::<std::collections::HashMap<_, _>>();
+        let mut open = true;
+        let mut select: Option<PathBuf> = None;
+        egui::Window::new("Find file in mods")
+            .open(&mut open)
+            .default_size([560., 420.])
+            .show(ctx, |ui| {
+                refresh |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.file_search_query)
+                            .hint_text("Asset path, e.g. Characters/1011/Meshes")
+                            .desired_width(f32::INFINITY),
+                    )
+                    .changed();
+                let index = &self.file_index;
+                if refresh {
+                    self.file_search_results = index.search(&self.file_search_query, FILE_SEARCH_LIMIT);
+                }
+                ui.horizontal(|ui| {
+                    if index.pending() > 0 {
+                        ui.spinner();
                     }
+                    ui.weak(format!(
+                        "{} files in {} of {} mods indexed",
+                        index.file_count(),
+                        index.mod_count(),
+                        self.pak_files.len()
+                    ));
+                });
+                ui.separator();
 
-                    self.file_drop_viewport_open = true;
-                    self.install_mod_dialog =
-                        Some(ModInstallRequest::new(mods, self.game_path.clone()));
+                if self.file_search_results.is_empty() && !self.file_search_query.trim().is_empty() {
+                    ui.label("No mod contains a matching file.");
                 }
+                ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for (key, files) in &self.file_search_results {
+                        let name = names
+                            .get(key)
+                            .cloned()
+                            .unwrap_or_else(|| key.display().to_string());
+                        if ui
+                            .link(RichText::new(format!("{} ({})", name, files.len())).strong())
+                            .on_hover_text("Show this mod")
+                            .clicked()
+                        {
+                            select = Some(key.clone());
+                        }
+                        for file in files {
+                            ui.label(RichText::new(file).monospace());
+                        }
+                        ui.add_space(6.0);
+                    }
+                    let shown = self.file_search_results.iter().map(|(_, f)| f.len()).sum::<usize>();
+                    if shown >= FILE_SEARCH_LIMIT {
+                        ui.weak(format!("Only the first {} matches are shown, refine the search", FILE_SEARCH_LIMIT));
+                    }
+                });
+            });
+        self.show_file_search = open;
 
-                if ui
-                    .add_enabled(self.game_path.is_dir(), Button::new("Pack folder").corner_radius(egui::CornerRadius::same(8)))
+        if let Some(key) = select {
+            if let Some(index) = self.pak_files.iter().position(|m| mod_key(&m.path) == key) {
+                self.select_mod(index);
+                self.scroll_to_current = true;
+            }
+        }
+    }
+
+    /// Lists the paks no known key opens, each with a button to type in its key
+    fn show_encrypted_paks(&mut self, ui: &mut egui::Ui) {
+        if self.encrypted_paks.is_empty() {
+            return;
+        }
+        let mut prompt_for = None;
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("🔒 {} mod(s) are encrypted with an unknown key", self.encrypted_paks.len()),
+            );
+            ui.menu_button("Encrypted mods", |ui| {
+                for path in &self.encrypted_paks {
+                    ui.horizontal(|ui| {
+                        ui.label(path.file_stem().unwrap_or_default().to_string_lossy())
+                            .on_hover_text(path.display().to_string());
+                        if ui.button("Enter key...").clicked() {
+                            prompt_for = Some(path.clone());
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+        });
+        if let Some(path) = prompt_for {
+            self.key_prompt = Some(KeyPrompt { path, key: String::new(), error: None });
+        }
+    }
+
+    fn show_key_prompt(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = self.key_prompt.as_mut() else {
+            return;
+        };
+        let mut open = true;
+        let mut try_key = false;
+        let mut cancel = false;
+        egui::Window::new("Enter AES key")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} is encrypted. Enter the AES key its author published to read it:",
+                    prompt.path.file_name().unwrap_or_default().to_string_lossy()
+                ));
+                let edit = ui.add(
+                    TextEdit::singleline(&mut prompt.key)
+                        .hint_text("0x... (hex or base64)")
+                        .desired_width(420.),
+                );
+                try_key = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if let Some(error) = &prompt.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                ui.horizontal(|ui| {
+                    try_key |= ui
+                        .add_enabled(!prompt.key.trim().is_empty(), Button::new("Try key").corner_radius(egui::CornerRadius::same(8)))
+                        .clicked();
+                    cancel = ui.add(Button::new("Cancel").corner_radius(egui::CornerRadius::same(8))).clicked();
+                });
+            });
+        if !open || cancel {
+            self.key_prompt = None;
+            return;
+        }
+        if !try_key || prompt.key.trim().is_empty() {
+            return;
+        }
+        let pak = match open_pak_with_key(&prompt.path, &prompt.key) {
+            Ok(pak) => pak,
+            Err(e) => {
+                prompt.error = Some(e);
+                return;
+            }
+        };
+        let guid = guid_text(pak.encryption_guid());
+        match add_keys(&[(guid.clone(), prompt.key.trim().to_string())]) {
+            Ok(0) => {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Warning)
+                    .set_title("Key not saved")
+                    .set_description(if guid.is_empty() {
+                        // default GUID keys are only skipped when the very same key is known
+                        format!("The key opens {}, and it is already in the keyring.", prompt.path.display())
+                    } else {
+                        format!(
+                            "The key opens {}, but {} already has a key for {}. Replace that key by hand to use this one.",
+                            prompt.path.display(),
+                            aes_keys::KEY_FILE_NAMES[0],
+                            guid
+                        )
+                    })
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+            }
+            Ok(_) => {
+                info!("Added the key of {} to the keyring", prompt.path.display());
+                self.refresh_requested = true;
+            }
+            Err(e) => {
+                prompt.error = Some(format!("The key works but could not be saved: {}", e));
+                return;
+            }
+        }
+        self.key_prompt = None;
+    }
+
+    /// Selected mods the batch rename applies to, game files only while they are unlocked
+    fn batch_rename_targets(&self) -> Vec<usize> {
+        self.selected_mods
+            .iter()
+            .copied()
+            .filter(|&i| i < self.pak_files.len())
+            .filter(|&i| self.system_mods_unlocked || !self.is_system_mod(i))
+            .collect()
+    }
+
+    /// Names the batch rename gives the selected mods, in list order. Locked mods and
+    /// file name collisions come back as errors.
+    fn batch_rename_preview(&mut self, rename: &BatchRename) -> Vec<RenamePreview> {
+        let selected = self.batch_rename_targets();
+        let mut previews = vec![];
+        for (n, index) in selected.into_iter().enumerate() {
+            let mod_type = self.mod_type_of(index);
+            let entry = &self.pak_files[index];
+            let old_name = self.get_mod_display_name(entry);
+            let stem = entry.path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let (file, _) = split_priority(&stem);
+            let folder = entry
+                .folder_id
+                .as_ref()
+                .and_then(|id| self.folders.iter().find(|f| &f.id == id))
+                .map_or("", |f| f.name.as_str());
+            let fields = PatternFields {
+                name: &old_name,
+                file,
+                mod_type: &mod_type,
+                author: entry.manifest.as_ref().and_then(|m| m.author.as_deref()).unwrap_or(""),
+                folder,
+            };
+            let mut new_name = expand_pattern(&rename.pattern, &fields, rename.start + n);
+            if entry.locked {
+                new_name = Err("Locked, left alone".to_string());
+            }
+            // the suffix decides the load order, only the part before it changes
+            let suffix = &stem[file.len()..];
+            let new_stem = match (&new_name, rename.rename_files) {
+                (Ok(name), true) => Some(format!("{}{}", sanitize_file_name(name), suffix)),
+                _ => None,
+            };
+            previews.push(RenamePreview { index, old_name, new_name, new_stem });
+        }
+        if rename.rename_files {
+            let renames = previews
+                .iter()
+                .filter_map(|p| Some((self.pak_files[p.index].path.clone(), p.new_stem.clone()?)))
+                .collect::<Vec<_>>();
+            let collisions = rename_collisions(&renames);
+            for preview in &mut previews {
+                if let Some(problem) = collisions.get(&self.pak_files[preview.index].path) {
+                    preview.new_name = Err(problem.clone());
+                    preview.new_stem = None;
+                }
+            }
+        }
+        previews
+    }
+
+    fn show_batch_rename(&mut self, ctx: &egui::Context) {
+        let Some(mut rename) = self.batch_rename.take() else {
+            return;
+        };
+        // the preview looks for existing files, so it is only made again when something changed
+        let targets = self.batch_rename_targets().into_iter().map(|i| self.pak_files[i].path.clone()).collect();
+        let key = (rename.pattern.clone(), rename.start, rename.rename_files, targets);
+        if rename.previewed_for.as_ref() != Some(&key) {
+            rename.preview = self.batch_rename_preview(&rename);
+            rename.previewed_for = Some(key);
+        }
+        let previews = std::mem::take(&mut rename.preview);
+        let mut rename = Some(rename);
+        let mut apply = false;
+        let renaming = self.normalize_results.is_some();
+        let mut open = true;
+        egui::Window::new("Rename selected mods")
+            .open(&mut open)
+            .default_size([560., 420.])
+            .show(ctx, |ui| {
+                let settings = rename.as_mut().unwrap();
+                ui.horizontal(|ui| {
+                    ui.label("Pattern:");
+                    ui.add(TextEdit::singleline(&mut settings.pattern).desired_width(260.));
+                    ui.label("Start at:");
+                    ui.add(egui::DragValue::new(&mut settings.start).range(0..=99999));
+                });
+                ui.horizontal_wrapped(|ui| {
+                    for (placeholder, help) in PLACEHOLDERS {
+                        if ui.small_button(placeholder).on_hover_text(help).clicked() {
+                            settings.pattern.push_str(placeholder);
+                        }
+                    }
+                });
+                ui.checkbox(&mut settings.rename_files, "Rename the files too")
+                    .on_hover_text("Renames the .pak with its .utoc, .ucas and preview. The _P suffix is kept so the load order doesn't change.");
+                ui.separator();
+
+                let blocked = previews.iter().filter(|p| p.new_name.is_err()).count();
+                ScrollArea::vertical().max_height(280.).auto_shrink([false, true]).show(ui, |ui| {
+                    egui::Grid::new("batch_rename_preview").num_columns(3).striped(true).show(ui, |ui| {
+                        for preview in &previews {
+                            ui.label(&preview.old_name);
+                            ui.label("→");
+                            match (&preview.new_name, &preview.new_stem) {
+                                (Ok(name), Some(stem)) => ui.label(name).on_hover_text(format!("File: {}", stem)),
+                                (Ok(name), None) => ui.label(name),
+                                (Err(e), _) => ui.colored_label(ui.visuals().error_fg_color, e),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let ready = previews.len() > blocked && !(settings.rename_files && renaming);
+                    apply = ui
+                        .add_enabled(ready, Button::new("Rename").corner_radius(egui::CornerRadius::same(8)))
+                        .on_disabled_hover_text("Nothing to rename, or a rename is still running")
+                        .clicked();
+                    if blocked > 0 {
+                        ui.weak(format!("{} mod(s) will be left alone", blocked));
+                    }
+                    if ui.add(Button::new("Cancel").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        rename = None;
+                    }
+                });
+            });
+        if !open {
+            return;
+        }
+        let Some(mut rename) = rename else {
+            return;
+        };
+        if !apply {
+            rename.preview = previews;
+            self.batch_rename = Some(rename);
+            return;
+        }
+
+        let mut file_renames = vec![];
+        for preview in previews {
+            let Ok(name) = preview.new_name else {
+                continue;
+            };
+            let entry = &mut self.pak_files[preview.index];
+            entry.custom_name = Some(name);
+            if let Some(stem) = preview.new_stem {
+                file_renames.push((entry.path.clone(), stem));
+            }
+        }
+        info!("Batch renamed {} mod(s), {} file rename(s)", self.selected_mods.len(), file_renames.len());
+        self.update_search_filter();
+        let _ = self.save_state();
+        if !file_renames.is_empty() {
+            let (res_tx, res_rx) = channel();
+            std::thread::spawn(move || {
+                let _ = res_tx.send(rename_mods(&file_renames));
+            });
+            self.normalize_results = Some(res_rx);
+        }
+    }
+
+    /// Opens the load order helper for the mod at `index` and the enabled mods sharing
+    /// files with it. Their files come from the file index, which has every mod once the
+    /// mod shows as conflicting.
+    fn open_load_order(&mut self, index: usize) {
+        let Some(files) = self.file_index.files_of(&self.pak_files[index].path) else {
+            warn!("{:?} isn't indexed yet", self.pak_files[index].path);
+            return;
+        };
+        let mut mods = vec![(self.pak_files[index].path.clone(), files.clone())];
+        for other in (0..self.pak_files.len()).filter(|&i| i != index && self.pak_files[i].enabled) {
+            let Some(other_files) = self.file_index.files_of(&self.pak_files[other].path) else {
+                continue;
+            };
+            if !shared_files(&files, &other_files).is_empty() {
+                mods.push((self.pak_files[other].path.clone(), other_files));
+            }
+        }
+        if mods.len() < 2 {
+            rfd::MessageDialog::new()
+                .set_title("Set load order")
+                .set_description("No enabled mod shares files with this one anymore.")
+                .set_buttons(MessageButtons::Ok)
+                .show();
+            return;
+        }
+        let fixed = mods
+            .iter()
+            .filter_map(|(path, _)| {
+                let i = self.pak_files.iter().position(|m| m.path == *path)?;
+                if self.pak_files[i].locked {
+                    Some((path.clone(), "locked"))
+                } else if !self.system_mods_unlocked && self.is_system_mod(i) {
+                    Some((path.clone(), "game file"))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.load_order = Some(LoadOrder::new(mods, fixed));
+    }
+
+    fn show_load_order(&mut self, ctx: &egui::Context) {
+        let Some(mut order) = self.load_order.take() else {
+            return;
+        };
+        // mods deleted or renamed since the helper was opened drop out
+        let before = order.mods.len();
+        order.mods.retain(|(path, _)| self.pak_files.iter().any(|m| m.path == *path));
+        if order.mods.len() < 2 {
+            return;
+        }
+        if order.mods.len() != before {
+            order.update_shared();
+        }
+        let names = order
+            .mods
+            .iter()
+            .map(|(path, _)| {
+                self.pak_files
+                    .iter()
+                    .find(|m| m.path == *path)
+                    .map(|m| self.get_mod_display_name(m))
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let new_stems = order.new_stems(self.default_priority);
+        let renames = order
+            .mods
+            .iter()
+            .zip(&new_stems)
+            .filter(|((path, _), _)| !order.fixed.contains_key(path))
+            .map(|((path, _), stem)| (path.clone(), stem.clone()))
+            .collect::<Vec<_>>();
+        let problems = rename_collisions(&renames);
+        let renaming = self.normalize_results.is_some();
+
+        let mut open = true;
+        let mut apply = false;
+        let mut cancel = false;
+        let mut swap = None;
+        egui::Window::new("Set load order")
+            .open(&mut open)
+            .default_size([620., 460.])
+            .show(ctx, |ui| {
+                ui.label("The top mod loads first, the bottom one loads last and wins every file it shares.");
+                ui.weak("Applying renames the mods with ascending priorities, the names in the list stay.");
+                ui.separator();
+                egui::Grid::new("load_order").num_columns(4).striped(true).show(ui, |ui| {
+                    let last = order.mods.len() - 1;
+                    for (position, ((path, _), name)) in order.mods.iter().zip(&names).enumerate() {
+                        let movable = !order.is_fixed(position);
+                        ui.horizontal(|ui| {
+                            let up = movable && position > 0 && !order.is_fixed(position - 1);
+                            if ui.add_enabled(up, Button::new("⬆").small()).clicked() {
+                                swap = Some((position, position - 1));
+                            }
+                            let down = movable && position < last && !order.is_fixed(position + 1);
+                            if ui.add_enabled(down, Button::new("⬇").small()).clicked() {
+                                swap = Some((position, position + 1));
+                            }
+                        });
+                        ui.label(name);
+                        let old_stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                        match (order.fixed.get(path), problems.get(path)) {
+                            (Some(reason), _) => ui.weak(format!("{} ({}, keeps its name)", old_stem, reason)),
+                            (None, Some(problem)) => ui.colored_label(ui.visuals().error_fg_color, problem),
+                            (None, None) if old_stem == new_stems[position] => ui.weak(old_stem),
+                            (None, None) => ui.label(format!("{} → {}", old_stem, new_stems[position])),
+                        };
+                        let files = &order.mods[position].1;
+                        let shared = order.shared.iter().filter(|(file, _)| files.contains(file)).count();
+                        let won = order.shared.iter().filter(|(_, winner)| *winner == position).count();
+                        ui.label(format!("wins {} of {} shared files", won, shared));
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                egui::CollapsingHeader::new(format!("Shared files ({})", order.shared.len()))
+                    .id_salt("load_order_shared")
+                    .show(ui, |ui| {
+                        let row_height = ui.text_style_height(&TextStyle::Body);
+                        ScrollArea::vertical().max_height(220.).auto_shrink([false, true]).show_rows(
+                            ui,
+                            row_height,
+                            order.shared.len(),
+                            |ui, rows| {
+                                for (file, winner) in &order.shared[rows] {
+                                    ui.horizontal(|ui| {
+                                        ui.monospace(file);
+                                        ui.label("→");
+                                        ui.strong(&names[*winner]);
+                                    });
+                                }
+                            },
+                        );
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let changes = renames
+                        .iter()
+                        .any(|(path, stem)| path.file_stem().is_some_and(|old| old.to_string_lossy() != *stem));
+                    let ready = changes && problems.is_empty() && !renaming;
+                    apply = ui
+                        .add_enabled(ready, Button::new("Apply order").corner_radius(egui::CornerRadius::same(8)))
+                        .on_disabled_hover_text("Nothing to rename, a name is taken, or a rename is still running")
+                        .clicked();
+                    cancel = ui.add(Button::new("Cancel").corner_radius(egui::CornerRadius::same(8))).clicked();
+                });
+            });
+        if let Some((a, b)) = swap {
+            order.swap(a, b);
+        }
+        if !open || cancel {
+            return;
+        }
+        if !apply {
+            self.load_order = Some(order);
+            return;
+        }
+        if !self.confirm_game_closed() {
+            self.load_order = Some(order);
+            return;
+        }
+        info!("Setting the load order of {} mods: {}", renames.len(), new_stems.join(", "));
+        let (res_tx, res_rx) = channel();
+        std::thread::spawn(move || {
+            let _ = res_tx.send(rename_mods(&renames));
+        });
+        self.normalize_results = Some(res_rx);
+    }
+
+    /// Adds `count` to a usage counter, when counting is turned on
+    fn count_usage(&mut self, event: UsageEvent, count: usize) {
+        if self.usage_stats_enabled {
+            self.usage_stats.record(event, count as u64);
+        }
+    }
+
+    fn show_usage_stats(&mut self, ctx: &egui::Context) {
+        if !self.show_usage_stats {
+            return;
+        }
+        let mut open = true;
+        let mut reset = false;
+        egui::Window::new("Usage stats")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if !self.usage_stats_enabled {
+                    ui.weak("Counting is off, turn on \"Count feature usage\" under Settings > Advanced");
+                }
+                ui.label(match self.usage_stats.since() {
+                    Some(since) => format!("Counting since {}", format_age(since)),
+                    None => "Nothing counted yet".to_string(),
+                });
+                egui::Grid::new("usage_stats").num_columns(2).striped(true).show(ui, |ui| {
+                    for event in UsageEvent::ALL {
+                        ui.label(event.label());
+                        ui.label(self.usage_stats.count(event).to_string());
+                        ui.end_row();
+                    }
+                });
+                ui.weak("Only stored in the config on this PC, nothing is sent anywhere");
+                ui.horizontal(|ui| {
+                    if ui
+                        .add(Button::new("Copy").corner_radius(egui::CornerRadius::same(8)))
+                        .on_hover_text("Copy the counts to paste them into a bug report")
+                        .clicked()
+                    {
+                        ui.ctx().copy_text(self.usage_stats.summary());
+                    }
+                    reset = ui.add(Button::new("Reset").corner_radius(egui::CornerRadius::same(8))).clicked();
+                });
+            });
+        if reset {
+            self.usage_stats.reset();
+            let _ = self.save_state();
+        }
+        if !open {
+            self.show_usage_stats = false;
+        }
+    }
+
+    fn show_install_log(&mut self, ctx: &egui::Context) {
+        let Some(key) = self.install_log_view.clone() else {
+            return;
+        };
+        let Some(lines) = self.install_logs.get(&key) else {
+            self.install_log_view = None;
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("Install log: {}", key))
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([640.0, 360.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} line(s)", lines.len()));
+                    if ui
+                        .add(Button::new("Copy").corner_radius(egui::CornerRadius::same(8)))
+                        .on_hover_text("Copy the log to paste it into a bug report")
+                        .clicked()
+                    {
+                        ui.ctx().copy_text(lines.join("\n"));
+                    }
+                });
+                ui.separator();
+                ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                    for line in lines {
+                        let text = RichText::new(line).monospace();
+                        let text = match line.split_once(']').map(|(level, _)| level) {
+                            Some("[ERROR") => text.color(ui.visuals().error_fg_color),
+                            Some("[WARN") => text.color(ui.visuals().warn_fg_color),
+                            _ => text,
+                        };
+                        ui.label(text);
+                    }
+                });
+            });
+        if !open {
+            self.install_log_view = None;
+        }
+    }
+
+    fn show_share_list(&mut self, ctx: &egui::Context) {
+        if !self.show_share_list {
+            return;
+        }
+        // the same hashes as the details panel, `None` for mods that couldn't be read
+        let mut hashing = 0;
+        let hashes = self
+            .pak_files
+            .iter()
+            .map(|m| m.path.clone())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|path| {
+                let hash = match self.hashes.get(&path) {
+                    Some(Ok(hash)) => Some(short_hash(hash)),
+                    Some(Err(_)) => None,
+                    None => {
+                        hashing += 1;
+                        None
+                    }
+                };
+                (path, hash)
+            })
+            .collect::<Vec<_>>();
+        if hashing > 0 {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+
+        let mods = self
+            .pak_files
+            .iter()
+            .map(|m| (m.path.clone(), self.get_mod_display_name(m), m.enabled))
+            .collect::<Vec<_>>();
+        let mut open = true;
+        egui::Window::new("Share mod list")
+            .open(&mut open)
+            .default_size([520., 460.])
+            .show(ctx, |ui| {
+                if hashing > 0 {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Hashing {} of {} mods...", hashing, hashes.len()));
+                    });
+                    return;
+                }
+                let hash_of = hashes
+                    .iter()
+                    .filter_map(|(path, hash)| Some((path, hash.as_ref()?)))
+                    .collect::<std::collections::HashMap<_, _>>();
+
+                ui.label(RichText::new("Your enabled mods").strong());
+                let shared = mods
+                    .iter()
+                    .filter(|(_, _, enabled)| *enabled)
+                    .filter_map(|(path, name, _)| {
+                        let hash = hash_of.get(path)?;
+                        Some(SharedMod { name: name.clone(), hash: hash.to_string() })
+                    })
+                    .collect::<Vec<_>>();
+                let code = encode(&shared);
+                ui.add(
+                    TextEdit::multiline(&mut code.as_str())
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY),
+                );
+                ui.horizontal(|ui| {
+                    if ui.add(Button::new("Copy").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        ui.ctx().copy_text(code.clone());
+                    }
+                    ui.weak(format!("{} mods, only their names and hashes are in the code", shared.len()));
+                });
+                ui.separator();
+
+                ui.label(RichText::new("Compare with someone else's code").strong());
+                ui.add(
+                    TextEdit::multiline(&mut self.share_code_input)
+                        .hint_text("Paste a code here")
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY),
+                );
+                if self.share_code_input.trim().is_empty() {
+                    return;
+                }
+                let list = match decode(&self.share_code_input) {
+                    Ok(list) => list,
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                        return;
+                    }
+                };
+                // mods are matched by content, whatever the other person named them
+                let local = hash_of
+                    .iter()
+                    .map(|(path, hash)| (hash.as_str(), *path))
+                    .collect::<std::collections::HashMap<_, _>>();
+                let found = list.iter().filter(|m| local.contains_key(m.hash.as_str())).count();
+                ui.label(format!("{} of {} mods are in your library", found, list.len()));
+                ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for shared in &list {
+                        match local.get(shared.hash.as_str()) {
+                            Some(path) => {
+                                let enabled = mods.iter().any(|(p, _, enabled)| p == *path && *enabled);
+                                let text = if enabled {
+                                    format!("✔ {}", shared.name)
+                                } else {
+                                    format!("✔ {} (disabled)", shared.name)
+                                };
+                                ui.label(text).on_hover_text(path.display().to_string());
+                            }
+                            None => {
+                                ui.colored_label(ui.visuals().error_fg_color, format!("🗙 {} (missing)", shared.name));
+                            }
+                        }
+                    }
+                });
+            });
+        self.show_share_list = open;
+    }
+
+    /// Offers to load the newest readable launch backup after the config failed to parse.
+    /// The broken config is kept next to it as `.broken`.
+    fn restore_config_backup(path: &Path, error: &serde_json::Error) -> Option<Self> {
+        let (backup, config) = config_backup::backups(path).into_iter().find_map(|backup| {
+            let data = fs::read_to_string(&backup).ok()?;
+            match serde_json::from_str::<Self>(&data) {
+                Ok(config) => Some((backup, config)),
+                Err(e) => {
+                    warn!("Skipping unreadable config backup {}: {}", backup.display(), e);
+                    None
+                }
+            }
+        })?;
+
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Error)
+            .set_title("Unable to load config")
+            .set_description(format!(
+                "Your config could not be read:\n{}\n\nRestore the backup from the last successful launch?\n{}",
+                error,
+                backup.to_string_lossy()
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        if !matches!(result, rfd::MessageDialogResult::Yes) {
+            return None;
+        }
+
+        let broken = path.with_extension("json.broken");
+        if let Err(e) = fs::copy(path, &broken) {
+            warn!("Unable to keep the broken config: {}", e);
+        }
+        if let Err(e) = fs::copy(&backup, path) {
+            error!("Failed to restore config backup: {}", e);
+        }
+        info!("Restored config from {}", backup.display());
+        Some(config)
+    }
+
+    /// Builds a config from one written by an older repak-gui and writes it back in
+    /// the current format. The old file is kept next to it as a backup.
+    fn import_legacy_config(path: &Path, data: &str) -> Option<Self> {
+        let legacy = parse_legacy_config(data)?;
+        let backup = match backup_config(path) {
+            Ok(backup) => backup,
+            Err(e) => {
+                error!("Failed to back up old config, not importing it: {}", e);
+                return None;
+            }
+        };
+
+        let mut config = Self {
+            game_path: legacy.game_path,
+            default_font_size: legacy.default_font_size.unwrap_or(18.0),
+            ..Default::default()
+        };
+        // old folder ids may be missing or clash with ours, so every folder gets a new id
+        let mut folder_ids = Vec::new();
+        for folder in legacy.folders {
+            let id = Uuid::new_v4().to_string();
+            folder_ids.push((folder.id, folder.name.clone(), id.clone()));
+            config.folders.push(ModFolder {
+                id,
+                name: folder.name,
+                enabled: true,
+                expanded: true,
+                color: None,
+            });
+        }
+        for legacy_mod in legacy.mods {
+            let folder_id = legacy_mod.folder.and_then(|old| {
+                folder_ids
+                    .iter()
+                    .find(|(old_id, name, _)| old_id.as_ref() == Some(&old) || *name == old)
+                    .map(|(_, _, id)| id.clone())
+            });
+            config.mod_metadata.push(ModMetadata {
+                path: legacy_mod.path,
+                custom_name: legacy_mod.custom_name,
+                folder_id,
+                custom_tags: vec![],
+                pack_sizes: None,
+                type_override: None,
+                favorite: false,
+                readmes: vec![],
+                enabled_before_folder_off: None,
+                versions: vec![],
+                locked: false,
+                dependencies: vec![],
+            });
+        }
+
+        // written directly, save_state would rebuild mod_metadata from the not yet loaded mods
+        match serde_json::to_string_pretty(&config) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    error!("Failed to write imported config: {}", e);
+                }
+            }
+            Err(e) => error!("Failed to serialize imported config: {}", e),
+        }
+        info!(
+            "Imported old config: {} folders, {} mods",
+            config.folders.len(),
+            config.mod_metadata.len()
+        );
+        rfd::MessageDialog::new()
+            .set_title("Config imported")
+            .set_description(format!(
+                "Your settings from an older Repak GUI version were imported.\n\nThe old config was kept at:\n{}",
+                backup.to_string_lossy()
+            ))
+            .set_buttons(MessageButtons::Ok)
+            .show();
+        Some(config)
+    }
+    fn save_state(&mut self) -> std::io::Result<()> {
+        // Sync pak_files metadata back to mod_metadata for persistence
+        self.sync_metadata();
+        self.last_selected_mod = self
+            .current_pak_file_idx
+            .and_then(|i| self.pak_files.get(i))
+            .map(|entry| entry.path.clone());
+        
+        let path = Self::config_path();
+        let json = serde_json::to_string_pretty(self)?;
+        info!("Saving config: {}", path.to_string_lossy());
+        fs::write(path, json)?;
+        Ok(())
+    }
+    
+    fn sync_metadata(&mut self) {
+        // Clear existing metadata and rebuild from current pak_files
+        self.mod_metadata.clear();
+        
+        for pak_file in &self.pak_files {
+            let metadata = ModMetadata {
+                path: pak_file.path.clone(),
+                custom_name: pak_file.custom_name.clone(),
+                folder_id: pak_file.folder_id.clone(),
+                custom_tags: pak_file.custom_tags.clone(),
+                pack_sizes: pak_file.pack_sizes,
+                type_override: pak_file.type_override.clone(),
+                favorite: pak_file.favorite,
+                readmes: pak_file.readmes.clone(),
+                enabled_before_folder_off: pak_file.enabled_before_folder_off,
+                versions: pak_file.versions.clone(),
+                locked: pak_file.locked,
+                dependencies: pak_file.dependencies.clone(),
+            };
+            self.mod_metadata.push(metadata);
+        }
+    }
+
+    /// Preview hovering files:
+    fn preview_files_being_dropped(&self, ctx: &egui::Context, rect: egui::Rect) {
+        use egui::{Align2, Color32, Id, LayerId, Order, TextStyle};
+
+        if !ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            let painter =
+                ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("file_drop_target")));
+
+            let folder = self
+                .file_drop_folder()
+                .and_then(|id| self.folders.iter().find(|f| f.id == id))
+                .map(|f| f.name.clone());
+            let msg = match (self.game_path.is_dir(), folder) {
+                (true, Some(folder)) => format!("Drop to install into {}", folder),
+                (true, None) => "Drop mod files here".to_string(),
+                (false, _) => "Choose a game directory first!!!".to_string(),
+            };
+            painter.rect_filled(rect, 0.0, Color32::from_rgba_unmultiplied(241, 24, 14, 40));
+            painter.text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                msg,
+                TextStyle::Heading.resolve(&ctx.style()),
+                Color32::WHITE,
+            );
+        }
+    }
+
+    /// Ctrl+V with the path of a pak or archive on the clipboard installs it, for download
+    /// paths copied from the browser. Only while no text field has focus.
+    fn check_paste(&mut self, ctx: &egui::Context) {
+        if !self.game_path.is_dir() || ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        let pasted = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            })
+        });
+        let Some(text) = pasted else {
+            return;
+        };
+
+        let (files, rejected): (Vec<_>, Vec<_>) = pasted_paths(&text).into_iter().partition(|path| {
+            let ext = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+            path.is_file() && (ext == "pak" || is_archive_path(path))
+        });
+        for path in &rejected {
+            warn!("Not installing pasted {}: not an existing .pak file or archive", path.display());
+        }
+        if files.is_empty() {
+            return;
+        }
+        info!("Installing {} pasted file(s)", files.len());
+        self.start_install(files);
+    }
+
+    /// Records where the pointer is while files are dragged over the window. Windows reports
+    /// no pointer moves during such a drag, the pointer position from before it began is
+    /// stale then and is not used.
+    fn track_file_drag(&mut self, ctx: &egui::Context) {
+        let (dragging, moved_to) = ctx.input(|i| {
+            let dragging = !i.raw.hovered_files.is_empty() || !i.raw.dropped_files.is_empty();
+            let moved_to = i.events.iter().rev().find_map(|event| match event {
+                egui::Event::PointerMoved(pos) => Some(*pos),
+                _ => None,
+            });
+            (dragging, moved_to)
+        });
+        if !dragging {
+            self.file_drag_pos = None;
+        } else if moved_to.is_some() {
+            self.file_drag_pos = moved_to;
+        }
+    }
+
+    /// Folder whose header is under the dragged files, see `track_file_drag`. Files go to the
+    /// root of the mod folder when their position is unknown.
+    fn file_drop_folder(&self) -> Option<String> {
+        let pos = self.file_drag_pos?;
+        self.folder_header_rects
+            .iter()
+            .find(|(_, rect)| rect.contains(pos))
+            .map(|(id, _)| id.clone())
+    }
+
+    fn check_drop(&mut self, ctx: &egui::Context) {
+        if !self.game_path.is_dir() {
+            return;
+        }
+        // only a drop onto the window itself, files dropped onto the install window go to the root
+        let folder_id = match ctx.input(|i| i.raw.dropped_files.is_empty()) {
+            true => None,
+            false => self.file_drop_folder(),
+        };
+        let mut dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        // files dropped onto the install window are queued like the others
+        if let Some(dialog) = &mut self.install_mod_dialog {
+            dropped_files.append(&mut dialog.dropped_files);
+        }
+        if dropped_files.is_empty() {
+            return;
+        }
+
+        // Install whatever is a directory, pak or archive and report the rest
+        let mut skipped = vec![];
+        let valid_files = dropped_files
+            .into_iter()
+            .filter(|file| {
+                let Some(path) = &file.path else {
+                    skipped.push(format!("{}: not a file on disk", file.name));
+                    return false;
+                };
+                let ext = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+                if path.is_dir() || ext == "pak" || is_archive_path(path) {
+                    return true;
+                }
+                skipped.push(format!(
+                    "{}: only folders, .pak, .zip and .rar files (or parts of them) can be installed",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ));
+                false
+            })
+            .collect::<Vec<_>>();
+
+        if !skipped.is_empty() {
+            warn!("Skipped {} dropped file(s):\n{}", skipped.len(), skipped.join("\n"));
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Some files were skipped")
+                .set_description(format!(
+                    "{} dropped file(s) can't be installed:\n\n{}{}",
+                    skipped.len(),
+                    skipped.join("\n"),
+                    if valid_files.is_empty() { "\n\nNothing left to install." } else { "" }
+                ))
+                .set_buttons(MessageButtons::Ok)
+                .show();
+        }
+        if valid_files.is_empty() {
+            error!("No mods found in dropped files.");
+            return;
+        }
+
+        let paths = valid_files.into_iter().filter_map(|file| file.path).collect();
+        if let Some(folder) = folder_id.as_ref().and_then(|id| self.folders.iter().find(|f| f.id == *id)) {
+            info!("Installing the dropped files into folder {}", folder.name);
+        }
+        self.install_into_folder(paths, folder_id);
+
+        if let Some(dialog) = &self.install_mod_dialog {
+            trace!("Installing mod: {:#?}", dialog.mods);
+        }
+    }
+
+    fn pick_mods_to_install(&mut self) {
+        let mod_files = rfd::FileDialog::new()
+            .set_title("Pick mods")
+            .pick_files()
+            .unwrap_or_default();
+
+        if mod_files.is_empty() {
+            error!("No mods found in dropped files.");
+            return;
+        }
+
+        self.start_install(mod_files);
+    }
+
+    /// The layout of quick install mode: a drop zone above a list of mods with their on /
+    /// off switch. Folders, tags and details stay hidden until the full layout is back.
+    fn show_quick_install(&mut self, ctx: &egui::Context) {
+        self.folder_header_rects.clear();
+        egui::TopBottomPanel::top("quick_top_panel").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Quick install");
+                ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                    if ui
+                        .add(Button::new("Full layout").corner_radius(egui::CornerRadius::same(8)))
+                        .on_hover_text("Show folders, tags, mod contents and details again")
+                        .clicked()
+                    {
+                        self.quick_install = false;
+                        self.save_state().ok();
+                    }
+                    if ui.add(Button::new("Settings").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        self.show_settings_window = true;
+                    }
+                });
+            });
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let hovering = ctx.input(|i| !i.raw.hovered_files.is_empty());
+            let stroke = match hovering {
+                true => Stroke::new(2.0, self.accent()),
+                false => ui.visuals().widgets.noninteractive.bg_stroke,
+            };
+            egui::Frame::group(ui.style())
+                .stroke(stroke)
+                .corner_radius(egui::CornerRadius::same(12))
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.set_min_height(160.0);
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(40.0);
+                        if !self.game_path.is_dir() {
+                            ui.label(RichText::new("Choose a mod folder in Settings first").size(18.0));
+                            return;
+                        }
+                        ui.label(RichText::new("Drop mods here to install them").size(20.0).strong());
+                        ui.label(RichText::new("Folders, .pak, .zip and .rar files").weak());
+                        ui.add_space(8.0);
+                        if ui.add(Button::new("Browse...").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                            self.pick_mods_to_install();
+                        }
+                    });
+                });
+
+            ui.add_space(8.0);
+            // system mods stay out of reach here unless they were unlocked in the full layout
+            let shown = (0..self.pak_files.len())
+                .filter(|&i| self.system_mods_unlocked || !self.is_system_mod(i))
+                .collect::<Vec<_>>();
+            ui.label(format!("Installed mods ({})", shown.len()));
+            let mut toggle = None;
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                for index in shown {
+                    let pak_file = &self.pak_files[index];
+                    let mut enabled = pak_file.enabled;
+                    let name = self.get_mod_display_name(pak_file);
+                    let checkbox = ui
+                        .add_enabled(
+                            !pak_file.locked && !self.deleting_mods.contains(&pak_file.path),
+                            egui::Checkbox::new(&mut enabled, name),
+                        )
+                        .on_disabled_hover_text(match pak_file.locked {
+                            true => "Locked, unlock it from the right-click menu in the full layout",
+                            false => "Being deleted",
+                        });
+                    if checkbox.clicked() {
+                        toggle = Some(index);
+                    }
+                }
+                if self.pak_files.is_empty() {
+                    ui.label(RichText::new("No mods installed yet").weak());
+                }
+            });
+            if let Some(index) = toggle {
+                self.toggle_mod_at(index);
+                self.save_state().ok();
+            }
+        });
+    }
+
+    /// The regular layout: menus and toolbars, the mod list on the left, contents and
+    /// details of the current mod on the right
+    fn show_full_layout(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            if let Err(e) = self.show_menu_bar(ui) {
+                error!("Error: {}", e);
+            }
+
+            ui.separator();
+            self.show_file_dialog(ui);
+            self.show_encrypted_paks(ui);
+
+            // Bulk actions toolbar
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.selection_mode, "Selection mode");
+                if self.selection_mode {
+                    let count = self.selected_mods.len();
+                    ui.label(format!("Selected: {}", count));
+
+                    let mut select_tag: Option<String> = None;
+                    let mut select_folder: Option<Option<String>> = None;
+                    ui.menu_button("Select by tag", |ui| {
+                        let tags = self.get_all_custom_tags();
+                        if tags.is_empty() {
+                            ui.label("No custom tags yet");
+                        }
+                        for tag in tags {
+                            if ui.button(&tag).clicked() {
+                                select_tag = Some(tag);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Select by folder", |ui| {
+                        for folder in &self.folders {
+                            if ui.button(&folder.name).clicked() {
+                                select_folder = Some(Some(folder.id.clone()));
+                                ui.close_menu();
+                            }
+                        }
+                        if ui.button("Not in a folder").clicked() {
+                            select_folder = Some(None);
+                            ui.close_menu();
+                        }
+                    })
+                    .response
+                    .on_hover_text("Only mods matching the current search and filters are selected");
+                    let mut move_to: Option<Option<String>> = None;
+                    let mut create_and_move = false;
+                    ui.add_enabled_ui(count > 0, |ui| {
+                        ui.menu_button("Move to folder", |ui| {
+                            for folder in &self.folders {
+                                if ui.button(&folder.name).clicked() {
+                                    move_to = Some(Some(folder.id.clone()));
+                                    ui.close_menu();
+                                }
+                            }
+                            if ui.button("No folder").clicked() {
+                                move_to = Some(None);
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            ui.horizontal(|ui| {
+                                let edit = ui.add(
+                                    egui::TextEdit::singleline(&mut self.bulk_folder_name)
+                                        .hint_text("New folder...")
+                                        .desired_width(140.0),
+                                );
+                                let entered = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                let has_name = !self.bulk_folder_name.trim().is_empty();
+                                if (ui.add_enabled(has_name, Button::new("Create")).clicked() || entered) && has_name {
+                                    create_and_move = true;
+                                    ui.close_menu();
+                                }
+                            });
+                        });
+                    });
+                    if create_and_move {
+                        let name = std::mem::take(&mut self.bulk_folder_name).trim().to_string();
+                        move_to = Some(Some(self.create_folder(name)));
+                    }
+                    if let Some(folder_id) = move_to {
+                        self.move_selected_to_folder(folder_id);
+                    }
+                    if ui
+                        .add_enabled(count > 0, Button::new("Rename selected...").corner_radius(egui::CornerRadius::same(8)))
+                        .on_hover_text("Rename the selected mods with a pattern like {type}_{name}")
+                        .clicked()
+                    {
+                        self.batch_rename = Some(BatchRename {
+                            pattern: "{name}".to_string(),
+                            start: 1,
+                            rename_files: false,
+                            previewed_for: None,
+                            preview: vec![],
+                        });
+                    }
+                    if let Some(tag) = select_tag {
+                        self.select_visible_with_tag(&tag);
+                    }
+                    if let Some(folder_id) = select_folder {
+                        self.select_visible_in_folder(folder_id.as_deref());
+                    }
+                    if ui.add_enabled(count > 0, Button::new("Clear selection").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        self.selected_mods.clear();
+                    }
+
+                    let can_delete = count > 0;
+                    if ui.add_enabled(can_delete, Button::new("Delete selected mods").corner_radius(egui::CornerRadius::same(8))).clicked()
+                        && self.confirm_game_closed()
+                    {
+                        // Build list of base pak paths from selected indices
+                        let mut base_paths: Vec<std::path::PathBuf> = Vec::new();
+                        let mut locked_names = Vec::new();
+                        for &i in &self.selected_mods {
+                            if let Some(m) = self.pak_files.get(i) {
+                                if m.locked {
+                                    locked_names.push(m.path.file_stem().unwrap_or_default().to_string_lossy().to_string());
+                                    continue;
+                                }
+                                base_paths.push(m.path.clone());
+                            }
+                        }
+                        if !locked_names.is_empty() {
+                            warn!("Not deleting {} locked mod(s): {}", locked_names.len(), locked_names.join(", "));
+                            rfd::MessageDialog::new()
+                                .set_level(rfd::MessageLevel::Info)
+                                .set_title("Locked mods kept")
+                                .set_description(format!(
+                                    "{} selected mod(s) are locked and were not deleted:\n\n{}",
+                                    locked_names.len(),
+                                    locked_names.join("\n")
+                                ))
+                                .set_buttons(MessageButtons::Ok)
+                                .show();
+                        }
+
+                        self.queue_delete(base_paths);
+                        ui.ctx().request_repaint();
+                    }
+                }
+            });
+
+            if let Some(ref running) = self.compact_running {
+                let name = running.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let done = self.compact_progress.load(Ordering::SeqCst);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Optimizing {}...", name));
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / COMPACT_STEPS as f32)
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                });
+                ui.ctx().request_repaint_after(Duration::from_millis(200));
+            }
+            if let Some((done, total)) = self.delete_progress {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Deleting mods ({}/{} files)...", done, total));
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                });
+                ui.ctx().request_repaint_after(Duration::from_millis(200));
+            }
+            if self.unpack_project_results.is_some() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Unpacking {} as a project...", self.unpack_project_name));
+                });
+                ui.ctx().request_repaint_after(Duration::from_millis(200));
+            }
+            if self.copy_mod_results.is_some() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Copying {}...", self.copy_mod_name));
+                });
+                ui.ctx().request_repaint_after(Duration::from_millis(200));
+            }
+            if self.extract_results.is_some() {
+                let done = self.extract_progress.load(Ordering::SeqCst);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Extracting mods ({}/{})...", done, self.extract_total));
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / self.extract_total.max(1) as f32)
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                });
+                ui.ctx().request_repaint_after(Duration::from_millis(200));
+            }
+            if self.type_scan_results.is_some() {
+                let done = self.type_scan_progress.load(Ordering::SeqCst);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Re-scanning mod types ({}/{})...", done, self.type_scan_total));
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / self.type_scan_total.max(1) as f32)
+                            .desired_width(200.0)
+                            .show_percentage(),
+                    );
+                });
+                ui.ctx().request_repaint_after(Duration::from_millis(200));
+            }
+        });
+
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let enabled = self.pak_files.iter().filter(|m| m.enabled).count();
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} mods ({} enabled, {} disabled)",
+                    self.pak_files.len(),
+                    enabled,
+                    self.pak_files.len() - enabled
+                ));
+                ui.separator();
+                ui.label(format!("{} on disk", format_bytes(self.library_size)))
+                    .on_hover_text(format!("Size of everything in {}", self.install_dir().display()));
+                if self.skip_folder_watch {
+                    ui.separator();
+                    ui.weak("Not watching the mod folder")
+                        .on_hover_text("Turned off under Settings > Paths, press Refresh (F5) after changing files outside Repak");
+                }
+            });
+        });
+
+        egui::SidePanel::left("left_panel")
+            .min_width(300.)
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    ui.set_height(ui.available_height());
+                    ui.horizontal(|ui| {
+                        ui.label("Mod files");
+                        ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+                            if ui
+                                .add(Button::new("⟳ Refresh").corner_radius(egui::CornerRadius::same(8)))
+                                .on_hover_text("Rescan the mod folder (F5), for changes the folder watcher missed")
+                                .clicked()
+                            {
+                                self.refresh_requested = true;
+                            }
+                        });
+                    });
+                    ui.group(|ui| {
+                        ui.set_width(ui.available_width());
+                        ui.set_height(ui.available_height());
+                        self.show_pak_files_in_dir(ui);
+                    });
+                });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical(|ui| {
+                // Files section (top)
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.set_height(ui.available_height() * 0.6);
+                    self.list_pak_contents(ui).expect("TODO: panic message");
+                });
+
+                ui.separator();
+
+                // Details section (below Files)
+                ui.label("Details");
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.set_height(ui.available_height());
+                    self.show_pak_details(ui);
+                });
+            });
+        });
+    }
+
+    fn show_menu_bar(&mut self, ui: &mut egui::Ui) -> Result<(), repak::Error> {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                let msg = match self.game_path.is_dir() {
+                    true => "Drop mod files here",
+                    false => "Choose a game directory first!!!",
+                };
+
+                if ui
+                    .add_enabled(self.game_path.is_dir(), Button::new("Install mods").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text(msg)
+                    .clicked()
+                {
+                    ui.close_menu(); // Closes the menu
+                    self.pick_mods_to_install();
+                }
+
+                if ui
+                    .add_enabled(self.game_path.is_dir(), Button::new("Pack folder").corner_radius(egui::CornerRadius::same(8)))
                     .on_hover_text(msg)
                     .clicked()
                 {
@@ -1967,43 +6515,908 @@ impl RepakModManager {
                     }
                     self.file_drop_viewport_open = true;
                     self.install_mod_dialog =
-                        Some(ModInstallRequest::new(mods, self.game_path.clone()));
+                        Some(self.install_request(mods));
+                }
+                if ui
+                    .add_enabled(self.game_path.is_dir(), Button::new("Repack project...").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text("Pack a folder made with \"Unpack as project\" using the name and settings of the original mod")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    let Some(project_dir) = rfd::FileDialog::new().set_title("Pick a project folder").pick_folder() else {
+                        return;
+                    };
+                    if RepakProject::load(&project_dir).is_none() {
+                        rfd::MessageDialog::new()
+                            .set_level(rfd::MessageLevel::Error)
+                            .set_title("Not a project")
+                            .set_description(format!(
+                                "{} has no readable {} file. Use \"Unpack as project\" on a mod first.",
+                                project_dir.display(),
+                                PROJECT_FILE
+                            ))
+                            .set_buttons(MessageButtons::Ok)
+                            .show();
+                        return;
+                    }
+                    let mods = map_paths_to_mods(&[project_dir]);
+                    if mods.is_empty() {
+                        error!("No files found in the project folder.");
+                        return;
+                    }
+                    self.file_drop_viewport_open = true;
+                    self.install_mod_dialog = Some(self.install_request(mods));
+                }
+                let can_normalize = self.normalize_results.is_none()
+                    && self.mod_naming != ModNaming::KeepOriginal
+                    && !self.pak_files.is_empty();
+                if ui
+                    .add_enabled(can_normalize, Button::new("Normalize mod names").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text("Rename every installed mod to the file naming chosen in Settings")
+                    .on_disabled_hover_text(if self.mod_naming == ModNaming::KeepOriginal {
+                        "Pick a suffix or priority under Settings > Install defaults first"
+                    } else {
+                        "No mods to rename, or a rename is already running"
+                    })
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.start_normalize_names();
+                }
+                if ui
+                    .add_enabled(
+                        self.pak_files.iter().any(|m| !m.enabled),
+                        Button::new("Purge disabled mods...").corner_radius(egui::CornerRadius::same(8)),
+                    )
+                    .on_hover_text("Delete every disabled mod with its utoc and ucas, locked mods are kept")
+                    .on_disabled_hover_text("No disabled mods")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.purge_disabled_mods();
+                }
+                if ui
+                    .add_enabled(
+                        self.type_scan_results.is_none() && !self.pak_files.is_empty(),
+                        Button::new("Re-scan all types").corner_radius(egui::CornerRadius::same(8)),
+                    )
+                    .on_hover_text("Read every mod again and detect its type, without reloading the whole list")
+                    .on_disabled_hover_text("No mods to scan, or a scan is already running")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.start_type_scan(self.pak_files.iter().map(|m| m.path.clone()).collect());
+                }
+                if ui
+                    .add(Button::new("Find file in mods...").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text("Search the files of every installed mod to see which ones replace an asset")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.show_file_search = true;
+                }
+                if ui
+                    .add_enabled(!self.pak_files.is_empty(), Button::new("Share mod list...").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text("Get a code listing your enabled mods, or check which mods of someone else's code you are missing")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.show_share_list = true;
+                }
+                let extract_selected = self.selection_mode && !self.selected_mods.is_empty();
+                let extract_label = if extract_selected {
+                    "Extract selected mods..."
+                } else {
+                    "Extract all mods..."
+                };
+                if ui
+                    .add_enabled(
+                        self.extract_results.is_none() && !self.pak_files.is_empty(),
+                        Button::new(extract_label).corner_radius(egui::CornerRadius::same(8)),
+                    )
+                    .on_hover_text("Extract every mod into its own folder, for archiving or editing")
+                    .on_disabled_hover_text("No mods to extract, or an extraction is already running")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.start_extract_all();
+                }
+                if ui
+                    .add_enabled(!self.pak_files.is_empty(), Button::new("Convert mods...").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text("Turn classic paks into iostore mods or the other way around")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.show_convert_mods = true;
+                }
+                if ui
+                    .add_enabled(!self.pak_files.is_empty(), Button::new("Export mod list...").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text("Save the list of installed mods as a CSV file or a markdown table")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.export_mod_list();
+                }
+                if ui
+                    .add(Button::new("Create bug report").corner_radius(egui::CornerRadius::same(8)))
+                    .on_hover_text("Zip the log, the settings without AES keys and some system info into one file to attach to a bug report")
+                    .clicked()
+                {
+                    ui.close_menu();
+                    self.create_bug_report();
                 }
                 if ui.add(Button::new("Quit").corner_radius(egui::CornerRadius::same(8))).clicked() {
                     ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
                 }
             });
 
-            ui.menu_button("Settings", |ui| {
+            if ui.add(Button::new("Settings").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                self.show_settings_window = true;
+            }
+
+            if ui
+                .add(Button::new("Quick install").corner_radius(egui::CornerRadius::same(8)))
+                .on_hover_text("Hide everything but the mod list and a drop zone, dropped mods install right away")
+                .clicked()
+            {
+                self.quick_install = true;
+                self.save_state().ok();
+            }
+
+            if ui.add(Button::new("💖 Donate").corner_radius(egui::CornerRadius::same(12))).clicked() {
+                self.hide_welcome = false;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Creates the `~mods` folder found on first launch. Games under Program Files often
+    /// don't allow that, so instead of failing this offers to pick another folder or to run
+    /// as administrator. Returns an empty path when the user skips, the mod folder can be
+    /// chosen in the main window then.
+    fn create_mods_dir(mods_dir: PathBuf) -> PathBuf {
+        let mut dir = mods_dir;
+        loop {
+            let Err(e) = fs::create_dir_all(&dir).and_then(|_| check_writable(&dir)) else {
+                return dir;
+            };
+            warn!("Unable to use {} as the mod folder: {}", dir.display(), e);
+            let permission = e.kind() == std::io::ErrorKind::PermissionDenied;
+            let reason = if permission {
+                "Windows doesn't let Repak write there, which is common for games installed under Program Files."
+            } else {
+                "The folder can't be created or written to."
+            };
+            let description = format!(
+                "Repak can't create the mod folder {}:\n{}\n\n{}\n\nPick a different folder, or run Repak as administrator to use this one.",
+                dir.display(),
+                e,
+                reason
+            );
+            const PICK: &str = "Pick another folder";
+            const ELEVATE: &str = "Run as administrator";
+            const SKIP: &str = "Skip";
+            let buttons = if cfg!(target_os = "windows") {
+                MessageButtons::YesNoCancelCustom(PICK.to_string(), ELEVATE.to_string(), SKIP.to_string())
+            } else {
+                MessageButtons::OkCancelCustom(PICK.to_string(), SKIP.to_string())
+            };
+            let result = rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Can't create the mod folder")
+                .set_description(description)
+                .set_buttons(buttons)
+                .show();
+            match result {
+                rfd::MessageDialogResult::Custom(choice) if choice == PICK => {
+                    let Some(picked) = FileDialog::new().set_title("Pick the mod folder").pick_folder() else {
+                        continue;
+                    };
+                    dir = Self::confirm_mods_dir(picked);
+                }
+                #[cfg(target_os = "windows")]
+                rfd::MessageDialogResult::Custom(choice) if choice == ELEVATE => {
+                    match crate::utils::relaunch_elevated() {
+                        Ok(()) => std::process::exit(0),
+                        Err(e) => error!("Unable to restart as administrator: {}", e),
+                    }
+                }
+                _ => {
+                    warn!("No mod folder set, it has to be picked in the main window");
+                    return PathBuf::new();
+                }
+            }
+        }
+    }
+
+    /// Offers to switch to the `~mods` folder when `path` looks like the game or Paks folder,
+    /// creating it if needed. Returns the folder to use.
+    fn confirm_mods_dir(path: PathBuf) -> PathBuf {
+        let Some((mods_dir, picked)) = suggest_mods_dir(&path) else {
+            return path;
+        };
+        let result = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Not a ~mods folder")
+            .set_description(format!(
+                "{} looks like {}. Mods have to go in a ~mods folder to be loaded by the game.\n\nUse {} instead?{}",
+                path.display(),
+                picked,
+                mods_dir.display(),
+                if mods_dir.is_dir() { "" } else { " It will be created." }
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        if !matches!(result, rfd::MessageDialogResult::Yes) {
+            warn!("Using {} as the mod folder", path.display());
+            return path;
+        }
+        if let Err(e) = fs::create_dir_all(&mods_dir) {
+            rfd::MessageDialog::new()
+                .set_title("Error")
+                .set_description(format!("Failed to create {}: {}", mods_dir.display(), e))
+                .set_buttons(MessageButtons::Ok)
+                .show();
+            return path;
+        }
+        mods_dir
+    }
+
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        if !self.show_settings_window {
+            return;
+        }
+        let mut open = true;
+        let mut changed = false;
+        let (mut export, mut import) = (false, false);
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([560., 400.])
+            .show(ctx, |ui| {
                 ui.add(
-                    egui::Slider::new(&mut self.default_font_size, 12.0..=32.0).text("Font size"),
+                    TextEdit::singleline(&mut self.settings_search)
+                        .hint_text("Search settings...")
+                        .desired_width(f32::INFINITY),
                 );
-                set_custom_font_size(ui.ctx(), self.default_font_size);
                 ui.horizontal(|ui| {
-                    let mode = match ui.ctx().style().visuals.dark_mode {
-                        true => "Switch to light mode",
-                        false => "Switch to dark mode",
-                    };
-                    ui.add(egui::Label::new(mode).halign(Align::Center));
-                    egui::widgets::global_theme_preference_switch(ui);
+                    export = ui
+                        .add(Button::new("Export settings...").corner_radius(egui::CornerRadius::same(8)))
+                        .on_hover_text("Save these settings, the theme and the AES keys to a file, to set up Repak the same way on another PC. Mods and folders aren't included.")
+                        .clicked();
+                    import = ui
+                        .add(Button::new("Import settings...").corner_radius(egui::CornerRadius::same(8)))
+                        .on_hover_text("Load settings from an exported file, settings it doesn't have are kept")
+                        .clicked();
                 });
-
                 ui.separator();
-                ui.checkbox(&mut self.use_custom_palette, "Enable custom color palette");
-                if self.use_custom_palette {
-                    if ui.button("Open Palette Editor...").clicked() {
+
+                let query = self.settings_search.trim().to_lowercase();
+                let sections = if query.is_empty() {
+                    vec![self.settings_section]
+                } else {
+                    SettingsSection::ALL
+                        .into_iter()
+                        .filter(|section| {
+                            section
+                                .entries()
+                                .iter()
+                                .any(|entry| entry.to_lowercase().contains(&query))
+                        })
+                        .collect()
+                };
+
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| {
+                        ui.set_width(130.);
+                        for section in SettingsSection::ALL {
+                            let selected = query.is_empty() && self.settings_section == section;
+                            if ui.selectable_label(selected, section.label()).clicked() {
+                                self.settings_section = section;
+                                self.settings_search.clear();
+                                self.settings_scroll = 0.0;
+                                self.settings_scroll_restored = false;
+                                changed = true;
+                            }
+                        }
+                    });
+                    ui.separator();
+
+                    let mut scroll = ScrollArea::vertical()
+                        .id_salt(("settings_page", self.settings_section.label()));
+                    if !self.settings_scroll_restored {
+                        scroll = scroll.vertical_scroll_offset(self.settings_scroll);
+                        self.settings_scroll_restored = true;
+                    }
+                    let output = scroll.show(ui, |ui| {
+                        if sections.is_empty() {
+                            ui.label("No settings match your search.");
+                        }
+                        for section in &sections {
+                            if !query.is_empty() {
+                                ui.heading(section.label());
+                            }
+                            changed |= self.settings_section_ui(ui, *section, &query);
+                            ui.add_space(8.);
+                        }
+                    });
+                    if query.is_empty() {
+                        self.settings_scroll = output.state.offset.y;
+                    }
+                });
+            });
+        if !open {
+            self.show_settings_window = false;
+            self.settings_scroll_restored = false;
+            changed = true;
+        }
+        if export {
+            self.export_settings(ctx);
+        }
+        if import {
+            self.import_settings(ctx);
+        }
+        if changed {
+            self.save_state().ok();
+        }
+    }
+
+    /// Draws the settings of one page, filtered by `query`. Returns true when a setting changed.
+    fn settings_section_ui(&mut self, ui: &mut egui::Ui, section: SettingsSection, query: &str) -> bool {
+        let shown = |entry: &str| query.is_empty() || entry.to_lowercase().contains(query);
+        let mut changed = false;
+        match section {
+            SettingsSection::Appearance => {
+                if shown("Font size") {
+                    let slider = ui.add(
+                        egui::Slider::new(&mut self.default_font_size, 12.0..=32.0).text("Font size"),
+                    );
+                    if slider.changed() {
+                        set_custom_font_size(ui.ctx(), self.default_font_size);
+                    }
+                    changed |= slider.drag_stopped() || (slider.changed() && !slider.dragged());
+                }
+                if shown("Fonts for Chinese / Japanese / Korean") {
+                    if ui
+                        .checkbox(&mut self.cjk_font, "Show Chinese / Japanese / Korean mod names")
+                        .on_hover_text("Loads a CJK font installed with the system as fallback. Takes some extra memory, names in those scripts show as boxes without it.")
+                        .changed()
+                    {
+                        fonts::set_fonts(ui.ctx(), self.cjk_font);
+                        changed = true;
+                    }
+                }
+                if shown("Dark / light mode") {
+                    ui.horizontal(|ui| {
+                        let mode = match ui.ctx().style().visuals.dark_mode {
+                            true => "Switch to light mode",
+                            false => "Switch to dark mode",
+                        };
+                        ui.add(egui::Label::new(mode).halign(Align::Center));
+                        egui::widgets::global_theme_preference_switch(ui);
+                    });
+                }
+                if shown("Custom color palette") {
+                    if ui
+                        .checkbox(&mut self.use_custom_palette, "Enable custom color palette")
+                        .changed()
+                    {
+                        changed = true;
+                        if !self.use_custom_palette {
+                            restore_builtin_theme(ui.ctx());
+                        }
+                    }
+                    if self.use_custom_palette && ui.button("Open Palette Editor...").clicked() {
                         self.show_palette_window = true;
-                        ui.close_menu();
                     }
                 }
-            });
-
-            if ui.add(Button::new("💖 Donate").corner_radius(egui::CornerRadius::same(12))).clicked() {
-                self.hide_welcome = false;
+                if shown("Preview thumbnails in mod list") {
+                    changed |= ui
+                        .checkbox(&mut self.show_list_thumbnails, "Show preview thumbnails in mod list")
+                        .changed();
+                }
+                if shown("Color mods by state") {
+                    let mut color_states = !self.skip_state_colors;
+                    if ui
+                        .checkbox(&mut color_states, "Color mods by state")
+                        .on_hover_text(
+                            "Dim disabled mods and tint mods sharing files with another enabled mod or missing \
+                             part of their files. The colors can be changed in the Palette Editor.",
+                        )
+                        .changed()
+                    {
+                        self.skip_state_colors = !color_states;
+                        changed = true;
+                    }
+                }
+                if shown("Group mod list") {
+                    ui.horizontal(|ui| {
+                        ui.label("Mod list sections:");
+                        for grouping in [ListGrouping::Folder, ListGrouping::Type] {
+                            changed |= ui
+                                .radio_value(&mut self.list_grouping, grouping, grouping.label())
+                                .changed();
+                        }
+                    })
+                    .response
+                    .on_hover_text("Grouping by type lists mods under their detected type, your folders are kept");
+                }
+                if shown("Mod list density") {
+                    ui.horizontal(|ui| {
+                        ui.label("Mod list density:");
+                        for density in [ListDensity::Comfortable, ListDensity::Compact] {
+                            changed |= ui
+                                .radio_value(&mut self.list_density, density, density.label())
+                                .changed();
+                        }
+                    })
+                    .response
+                    .on_hover_text("Compact fits more mods on screen");
+                }
+                if shown("Quick install mode") {
+                    changed |= ui
+                        .checkbox(&mut self.quick_install, "Quick install mode")
+                        .on_hover_text(
+                            "Show only the mod list and a drop zone. Dropped mods install right away \
+                             with the defaults from Install defaults.",
+                        )
+                        .changed();
+                }
+            }
+            SettingsSection::Paths => {
+                if shown("Mod folder") {
+                    ui.label("Mod folder:");
+                    ui.label(RichText::new(self.game_path.to_string_lossy()).monospace());
+                    if ui.add(Button::new("Change...").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                        if let Some(path) = FileDialog::new().pick_folder() {
+                            self.game_path = Self::confirm_mods_dir(path);
+                            self.game_path_input = self.game_path.to_string_lossy().to_string();
+                            self.revalidate_staging_dir();
+                            self.pending_restart = true;
+                            changed = true;
+                        }
+                    }
+                }
+                if shown("Watch downloads folder") {
+                    ui.add_space(8.);
+                    let mut restart_watcher = ui
+                        .checkbox(&mut self.watch_downloads, "Watch downloads folder")
+                        .on_hover_text("Offer to install .pak, .zip and .rar files as soon as they finish downloading")
+                        .changed();
+                    let dir = self
+                        .watched_downloads_dir()
+                        .map(|dir| dir.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "No folder selected".to_string());
+                    ui.add_enabled_ui(self.watch_downloads, |ui| {
+                        ui.label(RichText::new(dir).monospace());
+                        ui.horizontal(|ui| {
+                            if ui.add(Button::new("Change...").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                                if let Some(path) = FileDialog::new().pick_folder() {
+                                    self.downloads_dir = Some(path);
+                                    restart_watcher = true;
+                                }
+                            }
+                            if self.downloads_dir.is_some()
+                                && ui.add(Button::new("Use default").corner_radius(egui::CornerRadius::same(8))).clicked()
+                            {
+                                self.downloads_dir = None;
+                                restart_watcher = true;
+                            }
+                        });
+                    });
+                    if restart_watcher {
+                        self.restart_download_watcher(&ui.ctx().clone());
+                        changed = true;
+                    }
+                }
+                if shown("Watch mod folder") {
+                    let mut watch = !self.skip_folder_watch;
+                    if ui
+                        .checkbox(&mut watch, "Watch mod folder for changes")
+                        .on_hover_text(
+                            "Reload the list when files in the mod folder or the key files change. Turn \
+                             it off on network drives or when an antivirus makes the list rescan all \
+                             the time, then use Refresh (F5) after changing files outside Repak.",
+                        )
+                        .changed()
+                    {
+                        self.skip_folder_watch = !watch;
+                        self.apply_folder_watch();
+                        changed = true;
+                    }
+                }
+                if shown("Rescan delay") {
+                    changed |= ui
+                        .add_enabled(
+                            !self.skip_folder_watch,
+                            egui::Slider::new(&mut self.watch_debounce_ms, 0..=5000)
+                                .text("ms rescan delay")
+                                .step_by(50.0),
+                        )
+                        .on_hover_text("How long the mod folder has to be quiet before changes in it reload the list. Raise it if the list stutters while copying many files")
+                        .changed();
+                }
+                if shown("External tool") {
+                    ui.add_space(8.);
+                    ui.label("External tool:")
+                        .on_hover_text("Program the \"Open with\" entry of the mod context menu starts with the mod's pak, e.g. FModel");
+                    let tool = self
+                        .external_tool
+                        .as_ref()
+                        .map(|tool| tool.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "None".to_string());
+                    ui.label(RichText::new(tool).monospace());
+                    ui.horizontal(|ui| {
+                        if ui.add(Button::new("Change...").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                            if let Some(path) = FileDialog::new().set_title("Pick external tool").pick_file() {
+                                self.external_tool = Some(path);
+                                changed = true;
+                            }
+                        }
+                        if self.external_tool.is_some()
+                            && ui.add(Button::new("Clear").corner_radius(egui::CornerRadius::same(8))).clicked()
+                        {
+                            self.external_tool = None;
+                            changed = true;
+                        }
+                    });
+                }
+            }
+            SettingsSection::InstallDefaults => {
+                if shown("Install target") {
+                    ui.label("Install mods to:");
+                    let mut target = self.install_target;
+                    for option in [InstallTarget::Mods, InstallTarget::Paks] {
+                        ui.radio_value(&mut target, option, option.label());
+                    }
+                    if target != self.install_target {
+                        let previous = std::mem::replace(&mut self.install_target, target);
+                        if let Err(e) = check_writable(&self.install_dir()) {
+                            rfd::MessageDialog::new()
+                                .set_level(rfd::MessageLevel::Error)
+                                .set_title("Can't install there")
+                                .set_description(format!("{} can't be used: {}", self.install_dir().display(), e))
+                                .set_buttons(MessageButtons::Ok)
+                                .show();
+                            self.install_target = previous;
+                        } else {
+                            changed = true;
+                            self.refresh_requested = true;
+                            // the file watcher only picks up the new folder after a restart
+                            self.pending_restart = true;
+                            self.revalidate_staging_dir();
+                        }
+                    }
+                    ui.label(RichText::new(self.install_dir().to_string_lossy()).monospace().weak());
+                    if self.install_target == InstallTarget::Paks && self.mod_naming == ModNaming::KeepOriginal {
+                        let note = format!("Mods installed to Paks always get the _{}_P suffix", self.default_priority);
+                        ui.label(RichText::new(note).weak());
+                    }
+                }
+                if shown("Staging folder") {
+                    let mut staged = self.staging_dir.is_some();
+                    let toggled = ui
+                        .checkbox(&mut staged, "Install to a staging folder and link mods into the game")
+                        .on_hover_text(
+                            "Keeps the installed files in a library folder, the game folder gets links to them. \
+                             Where links aren't permitted, like Windows without developer mode, they are copied. \
+                             Disabling or deleting a mod only touches its link, \"Clean up...\" removes staged files left without one.",
+                        )
+                        .changed();
+                    let pick = match (toggled, staged) {
+                        (true, true) => true,
+                        (true, false) => {
+                            self.staging_dir = None;
+                            changed = true;
+                            false
+                        }
+                        _ => false,
+                    };
+                    if let Some(dir) = &self.staging_dir {
+                        ui.label(RichText::new(dir.to_string_lossy()).monospace().weak());
+                    }
+                    let mut change = false;
+                    if self.staging_dir.is_some() {
+                        ui.horizontal(|ui| {
+                            change = ui.add(Button::new("Change...").corner_radius(egui::CornerRadius::same(8))).clicked();
+                            if ui
+                                .add(Button::new("Clean up...").corner_radius(egui::CornerRadius::same(8)))
+                                .on_hover_text("Delete staged files that aren't linked into the game folder anymore")
+                                .clicked()
+                            {
+                                self.clean_up_staging();
+                            }
+                        });
+                    }
+                    if pick || change {
+                        match FileDialog::new().set_title("Pick a staging folder").pick_folder() {
+                            Some(dir) if self.staging_conflict(&dir).is_some() => {
+                                rfd::MessageDialog::new()
+                                    .set_level(rfd::MessageLevel::Error)
+                                    .set_title("Can't stage there")
+                                    .set_description(self.staging_conflict(&dir).unwrap_or_default())
+                                    .set_buttons(MessageButtons::Ok)
+                                    .show();
+                            }
+                            Some(dir) => match check_writable(&dir) {
+                                Ok(()) => {
+                                    info!("Staging installs in {}", dir.display());
+                                    self.staging_dir = Some(dir);
+                                    changed = true;
+                                }
+                                Err(e) => {
+                                    rfd::MessageDialog::new()
+                                        .set_level(rfd::MessageLevel::Error)
+                                        .set_title("Can't stage there")
+                                        .set_description(format!("{} can't be used: {}", dir.display(), e))
+                                        .set_buttons(MessageButtons::Ok)
+                                        .show();
+                                }
+                            },
+                            None => {}
+                        }
+                    }
+                }
+                if shown("Installed mod file names") {
+                    ui.label("Installed mod file names:");
+                    changed |= naming_ui(ui, &mut self.mod_naming, self.default_priority);
+                }
+                if shown("Default priority") {
+                    ui.horizontal(|ui| {
+                        ui.label("Default priority:");
+                        let drag = ui.add(
+                            egui::DragValue::new(&mut self.default_priority)
+                                .range(0..=DEFAULT_PRIORITY)
+                                .prefix("_")
+                                .suffix("_P"),
+                        );
+                        changed |= drag.drag_stopped() || (drag.changed() && !drag.dragged());
+                        if ui
+                            .add_enabled(self.default_priority != DEFAULT_PRIORITY, Button::new("Reset"))
+                            .clicked()
+                        {
+                            self.default_priority = DEFAULT_PRIORITY;
+                            changed = true;
+                        }
+                    })
+                    .response
+                    .on_hover_text("Number \"Append _<number>_P\" uses for new installs. Installed mods keep their names until renamed with Normalize mod names.");
+                }
+                if shown("Install threads") {
+                    let cores = thread::available_parallelism().map_or(1, |n| n.get());
+                    let mut limit = self.install_threads != 0;
+                    if ui
+                        .checkbox(&mut limit, "Limit threads used for installing")
+                        .on_hover_text("Packing and texture fixes use every core by default, which can make a running game stutter")
+                        .changed()
+                    {
+                        self.install_threads = if limit { (cores / 2).max(1) } else { 0 };
+                        changed = true;
+                    }
+                    if limit {
+                        changed |= ui
+                            .add(egui::Slider::new(&mut self.install_threads, 1..=cores).text("threads"))
+                            .changed();
+                    }
+                }
+                if shown("Suggest mesh / texture fixes") {
+                    let mut suggest = !self.skip_suggested_fixes;
+                    if ui
+                        .checkbox(&mut suggest, "Pre-check mesh / texture fixes by mod type")
+                        .on_hover_text("Fix mesh starts checked for mods with skeletal meshes and Fix textures for skins with textures. Either can still be changed per mod.")
+                        .changed()
+                    {
+                        self.skip_suggested_fixes = !suggest;
+                        changed = true;
+                    }
+                }
+                if shown("Ignored files when packing") {
+                    ui.horizontal(|ui| {
+                        ui.label("Ignored files when packing folders:");
+                        if ui
+                            .add_enabled(self.pack_ignore != DEFAULT_PACK_IGNORE, Button::new("Reset"))
+                            .clicked()
+                        {
+                            self.pack_ignore = default_pack_ignore();
+                            changed = true;
+                        }
+                    });
+                    changed |= ui
+                        .add(
+                            TextEdit::multiline(&mut self.pack_ignore)
+                                .hint_text("Extensions like .bak or parts of paths, * and ? as wildcards")
+                                .desired_rows(2),
+                        )
+                        .on_hover_text("Files matching these are left out of loose folder mods, the log lists every file skipped")
+                        .changed();
+                }
+                if shown("Tag new mods by type") {
+                    if ui
+                        .checkbox(&mut self.auto_type_tags, "Tag new mods by type")
+                        .on_hover_text("Adds a custom tag for the detected mod type when installing")
+                        .changed()
+                    {
+                        if self.auto_type_tags && self.type_tags.is_empty() {
+                            for category in DETECTED_CATEGORIES {
+                                self.type_tags.insert(category.to_string(), category.to_string());
+                            }
+                        }
+                        changed = true;
+                    }
+                    if self.auto_type_tags {
+                        egui::Grid::new("type_tags").num_columns(2).show(ui, |ui| {
+                            for category in DETECTED_CATEGORIES {
+                                ui.label(category);
+                                let tag = self.type_tags.entry(category.to_string()).or_default();
+                                changed |= ui
+                                    .add(TextEdit::singleline(tag).hint_text("no tag").desired_width(140.0))
+                                    .changed();
+                                ui.end_row();
+                            }
+                        });
+                    }
+                }
+                if shown("Install folders by type") {
+                    ui.add_space(8.);
+                    ui.label("Install folders by type:")
+                        .on_hover_text("Sub folder of ~mods new mods of each type are installed to, e.g. \"audio\". Empty installs to ~mods itself. Not used when installing to the game's Paks folder.");
+                    egui::Grid::new("type_dirs").num_columns(2).show(ui, |ui| {
+                        for category in DETECTED_CATEGORIES {
+                            ui.label(category);
+                            let dir = self.type_dirs.entry(category.to_string()).or_default();
+                            changed |= ui
+                                .add(TextEdit::singleline(dir).hint_text("~mods").desired_width(140.0))
+                                .changed();
+                            ui.end_row();
+                        }
+                    });
+                }
+                if shown("Verify installed mods") {
+                    let mut verify = !self.skip_install_verify;
+                    if ui
+                        .checkbox(&mut verify, "Verify installed mods")
+                        .on_hover_text(
+                            "Read every installed pak back right after writing it. A full disk, an \
+                             interrupted write or a wrong AES key then fails the install instead of the game.",
+                        )
+                        .changed()
+                    {
+                        self.skip_install_verify = !verify;
+                        changed = true;
+                    }
+                }
+                if shown("Name already installed") {
+                    ui.horizontal(|ui| {
+                        ui.label("Name already installed:");
+                        for policy in NameCollision::ALL {
+                            changed |= ui
+                                .radio_value(&mut self.name_collision, policy, policy.label())
+                                .changed();
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "What happens when a new mod gets the file name of a mod already installed. \
+                         Keep both installs it as name_1, name_2, ... Updates marked to replace \
+                         the installed mod always overwrite it.",
+                    );
+                }
+            }
+            SettingsSection::Advanced => {
+                if shown("Check for file conflicts when enabling a mod") {
+                    let mut check_conflicts = !self.skip_conflict_check;
+                    if ui
+                        .checkbox(&mut check_conflicts, "Check for file conflicts when enabling a mod")
+                        .changed()
+                    {
+                        self.skip_conflict_check = !check_conflicts;
+                        changed = true;
+                    }
+                }
+                if shown("Show welcome screen after updates") {
+                    let mut show_welcome = !self.skip_welcome_on_update;
+                    if ui
+                        .checkbox(&mut show_welcome, "Show welcome screen after updates")
+                        .on_hover_text("It can always be opened again with the Donate button")
+                        .changed()
+                    {
+                        self.skip_welcome_on_update = !show_welcome;
+                        changed = true;
+                    }
+                }
+                if shown("Back up config on launch") {
+                    let mut backups = !self.skip_config_backups;
+                    if ui
+                        .checkbox(&mut backups, "Back up config on launch")
+                        .on_hover_text(format!(
+                            "Keeps the last {} configs that loaded fine, offered for restore if the config ever breaks",
+                            config_backup::BACKUP_COUNT
+                        ))
+                        .changed()
+                    {
+                        self.skip_config_backups = !backups;
+                        changed = true;
+                    }
+                }
+                if shown("Memory-map .ucas files") {
+                    if ui
+                        .checkbox(&mut self.memory_map_ucas, "Memory-map .ucas files")
+                        .on_hover_text("Faster browsing and extraction of big iostore mods. Files that can't be mapped, e.g. on some network drives, are read normally")
+                        .changed()
+                    {
+                        MEMORY_MAP_UCAS.store(self.memory_map_ucas, Ordering::Relaxed);
+                        changed = true;
+                    }
+                }
+                if shown("Flag mods that may be outdated") {
+                    if ui
+                        .checkbox(&mut self.check_outdated, "Flag mods that may be outdated")
+                        .on_hover_text("Mods replacing assets that aren't in the game file list get a \"may be outdated\" badge")
+                        .changed()
+                    {
+                        self.reload_game_paths();
+                        changed = true;
+                    }
+                    if self.check_outdated {
+                        ui.horizontal(|ui| {
+                            match &self.game_paths {
+                                Some(paths) => ui.label(format!("{} known game files", paths.count())),
+                                None => ui.weak("No game file list loaded"),
+                            };
+                            if ui
+                                .button("Load list...")
+                                .on_hover_text("A JSON list of asset paths like \"Marvel/Content/...\", copied to the config folder")
+                                .clicked()
+                            {
+                                self.import_game_paths();
+                            }
+                            if ui.button("Reload").clicked() {
+                                self.reload_game_paths();
+                            }
+                        });
+                    }
+                }
+                if shown("Folders remember mod states") {
+                    changed |= ui
+                        .checkbox(&mut self.folder_keeps_mod_states, "Folders remember mod states")
+                        .on_hover_text("Switching a folder back on only turns on the mods that were on before it was switched off")
+                        .changed();
+                }
+                if shown("List game files separately") {
+                    changed |= ui
+                        .checkbox(&mut self.separate_system_mods, "List game files separately")
+                        .on_hover_text("Paks without the _P suffix are shown read-only under \"System / game files\"")
+                        .changed();
+                    if self.separate_system_mods {
+                        ui.label("Also treat these as game files:");
+                        changed |= ui
+                            .add(
+                                TextEdit::multiline(&mut self.system_mod_patterns)
+                                    .hint_text("Parts of file names, e.g. pakchunk0, global")
+                                    .desired_rows(2),
+                            )
+                            .changed();
+                    }
+                }
+                if shown("Count feature usage") {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .checkbox(&mut self.usage_stats_enabled, "Count feature usage")
+                            .on_hover_text("Counts installs, deletes, conversions, extractions and crashes in the config, to look up when reporting a bug. Nothing is ever sent anywhere.")
+                            .changed()
+                        {
+                            self.usage_stats.set_enabled(self.usage_stats_enabled);
+                            changed = true;
+                        }
+                        if ui.button("Show stats").clicked() {
+                            self.show_usage_stats = true;
+                        }
+                    });
+                }
             }
-        });
-
-        Ok(())
+        }
+        changed
     }
 
     fn show_file_dialog(&mut self, ui: &mut egui::Ui) {
@@ -2022,8 +7435,9 @@ impl RepakModManager {
                 if resp.lost_focus() {
                     let candidate = PathBuf::from(self.game_path_input.clone());
                     if candidate != self.game_path && candidate.is_dir() {
-                        self.game_path = candidate;
+                        self.game_path = Self::confirm_mods_dir(candidate);
                         self.game_path_input = self.game_path.to_string_lossy().to_string();
+                        self.revalidate_staging_dir();
                         // Persist the new path so it is restored on next launch
                         let _ = self.save_state();
                         // Ask to restart so mods load correctly after changing the directory
@@ -2033,8 +7447,9 @@ impl RepakModManager {
                 let browse_button = flex_ui.add(item(), Button::new("Browse").corner_radius(egui::CornerRadius::same(8)));
                 if browse_button.clicked() {
                     if let Some(path) = FileDialog::new().pick_folder() {
-                        self.game_path = path;
+                        self.game_path = Self::confirm_mods_dir(path);
                         self.game_path_input = self.game_path.to_string_lossy().to_string();
+                        self.revalidate_staging_dir();
                         // Persist the new path so it is restored on next launch
                         let _ = self.save_state();
                         // Ask to restart so mods load correctly after changing the directory
@@ -2074,6 +7489,7 @@ impl RepakModManager {
 }
 impl eframe::App for RepakModManager {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.track_file_drag(ctx);
         // Re-apply custom palette on every frame if enabled, so other flows don't reset it
         if self.use_custom_palette {
             let mut style = (*ctx.style()).clone();
@@ -2105,24 +7521,280 @@ impl eframe::App for RepakModManager {
         let mut collect_pak = false;
 
         if !self.file_drop_viewport_open && self.install_mod_dialog.is_some() {
+            let installed = self.install_mod_dialog.as_ref().map_or(0, |dialog| dialog.installed.len());
+            self.count_usage(UsageEvent::Install, installed);
+            if let Some(dialog) = self.install_mod_dialog.as_mut() {
+                self.install_logs.extend(std::mem::take(&mut dialog.logs).into_iter().map(|(name, lines)| (name.to_lowercase(), lines)));
+                if let Some(folder_id) = dialog.folder_id.take() {
+                    for name in std::mem::take(&mut dialog.installed) {
+                        self.pending_folders.insert(name.to_lowercase(), folder_id.clone());
+                    }
+                }
+            }
+            // the folder watcher may have listed some of them already
+            self.apply_pending_folders();
             self.install_mod_dialog = None;
+            self.install_batch.clear();
+        }
+        // queued drops open one batch at a time once the install window is closed
+        if self.install_mod_dialog.is_none() && !self.install_queue.is_empty() {
+            let (paths, folder_id) = self.install_queue.remove(0);
+            self.install_into_folder(paths, folder_id);
+        }
+        // the archives of an install are unpacked, list the mods found in them
+        if let Some((mods, problems)) = self.install_mod_dialog.as_mut().and_then(|dialog| dialog.extracted_mods()) {
+            if !problems.is_empty() {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Warning)
+                    .set_title("Some archives weren't extracted")
+                    .set_description(format!(
+                        "{} archive(s) could not be extracted:\n\n{}",
+                        problems.len(),
+                        problems.join("\n")
+                    ))
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+            }
+            if mods.is_empty() && !problems.is_empty() {
+                self.file_drop_viewport_open = false;
+            } else if mods.is_empty() {
+                error!("No mods found in the extracted archives.");
+                self.file_drop_viewport_open = false;
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Warning)
+                    .set_title("Nothing to install")
+                    .set_description("No mods were found in the archive.")
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+            } else {
+                // the folder the archives were dropped on carries over to the mods in them
+                let folder_id = self.install_mod_dialog.as_mut().and_then(|dialog| dialog.folder_id.take());
+                let mut request = self.install_request(mods);
+                request.folder_id = folder_id;
+                self.install_mod_dialog = Some(request);
+            }
         }
 
         // Poll background delete results (non-blocking) and schedule refresh
+        let mut finished_deletes = Vec::new();
         if let Some(ref rx) = self.delete_results {
-            while let Ok(res) = rx.try_recv() {
-                match res {
-                    Ok(paths) => {
-                        // Best-effort: first element is the pak path we queued
-                        if let Some(pak_p) = paths.get(0) { self.deleting_mods.remove(pak_p); }
+            while let Ok(update) = rx.try_recv() {
+                match update {
+                    DeleteUpdate::Progress { done, total } => self.delete_progress = Some((done, total)),
+                    DeleteUpdate::Finished { mods, deleted, freed, failed } => {
+                        for pak_p in &mods { self.deleting_mods.remove(pak_p); }
+                        self.delete_progress = None;
                         self.refresh_after_delete = true;
+                        finished_deletes.push((mods.len(), deleted, freed, failed));
                     }
+                }
+            }
+        }
+        for (mod_count, deleted, freed, failed) in finished_deletes {
+            self.count_usage(UsageEvent::Delete, mod_count);
+            for err in &failed {
+                error!("Delete failed: {}", err);
+            }
+            info!("Deleted {} file(s) of {} mod(s), freed {}", deleted, mod_count, format_bytes(freed));
+            // a single mod going away is feedback enough, only bulk deletes get a summary
+            if mod_count > 1 || !failed.is_empty() {
+                let mut description = format!(
+                    "Deleted {} file(s) of {} mod(s), freeing {}.",
+                    deleted,
+                    mod_count,
+                    format_bytes(freed)
+                );
+                if !failed.is_empty() {
+                    description.push_str(&format!("\n\n{} file(s) could not be deleted:\n{}", failed.len(), failed.join("\n")));
+                }
+                rfd::MessageDialog::new()
+                    .set_title("Delete mods")
+                    .set_description(description)
+                    .set_level(if failed.is_empty() { rfd::MessageLevel::Info } else { rfd::MessageLevel::Warning })
+                    .set_buttons(rfd::MessageButtons::Ok)
+                    .show();
+            }
+        }
+
+        // Poll background recompression
+        if let Some(ref rx) = self.compact_results {
+            if let Ok(res) = rx.try_recv() {
+                match res {
+                    Ok(result) => self.compact_pending_confirm = Some(result),
                     Err(err) => {
-                        error!("Delete failed: {}", err);
-                        self.deleting_mods.clear();
-                        self.refresh_after_delete = true;
+                        error!("Optimize failed: {}", err);
+                        rfd::MessageDialog::new()
+                            .set_title("Optimize failed")
+                            .set_description(format!("Unable to recompress the mod:\n{}", err))
+                            .set_buttons(MessageButtons::Ok)
+                            .show();
+                    }
+                }
+                self.compact_running = None;
+                self.compact_results = None;
+                self.refresh_after_delete = true;
+            }
+        }
+
+        // Poll background extraction
+        let extracted = self.extract_results.as_ref().map(|rx| rx.try_recv());
+        if let Some(Err(TryRecvError::Disconnected)) = extracted {
+            self.extract_results = None;
+            error!("The extraction stopped without a result");
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Extract mods")
+                .set_description("The extraction stopped unexpectedly, see the log for details.")
+                .set_buttons(MessageButtons::Ok)
+                .show();
+        }
+        if let Some(Ok(result)) = extracted {
+            self.count_usage(UsageEvent::Extraction, result.extracted);
+            let mut description = format!("Extracted {} mod(s).", result.extracted);
+            if !result.failed.is_empty() {
+                description.push_str(&format!(
+                    "\n\n{} failed:\n{}",
+                    result.failed.len(),
+                    result.failed.join("\n")
+                ));
+            }
+            rfd::MessageDialog::new()
+                .set_level(if result.failed.is_empty() {
+                    rfd::MessageLevel::Info
+                } else {
+                    rfd::MessageLevel::Warning
+                })
+                .set_title("Extract mods")
+                .set_description(description)
+                .set_buttons(MessageButtons::Ok)
+                .show();
+            self.extract_results = None;
+        }
+
+        // Poll background project unpacking
+        let unpacked = match self.unpack_project_results.as_ref().map(|rx| rx.try_recv()) {
+            Some(Ok(result)) => Some(result),
+            Some(Err(TryRecvError::Disconnected)) => Some(Err("the unpack stopped unexpectedly".to_string())),
+            _ => None,
+        };
+        if let Some(result) = unpacked {
+            self.unpack_project_results = None;
+            let (level, description) = match result {
+                Ok(project_dir) => (
+                    rfd::MessageLevel::Info,
+                    format!("Unpacked to {}.\n\nEdit the files in its \"files\" folder, then use File > Repack project.", project_dir.display()),
+                ),
+                Err(e) => {
+                    error!("Failed to unpack project: {}", e);
+                    (rfd::MessageLevel::Error, format!("Unable to unpack the mod: {}", e))
+                }
+            };
+            rfd::MessageDialog::new()
+                .set_level(level)
+                .set_title("Unpack as project")
+                .set_description(description)
+                .set_buttons(MessageButtons::Ok)
+                .show();
+        }
+
+        // Poll background mod copy
+        match self.copy_mod_results.as_ref().map(|rx| rx.try_recv()) {
+            Some(Ok((pak_path, dest, result))) => {
+                self.copy_mod_results = None;
+                show_copy_result(&pak_path, &dest, result);
+            }
+            Some(Err(TryRecvError::Disconnected)) => {
+                self.copy_mod_results = None;
+                error!("Copying {} stopped without a result", self.copy_mod_name);
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Copy mod files")
+                    .set_description("The copy stopped unexpectedly, see the log for details.")
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+            }
+            _ => {}
+        }
+
+        // Poll background type re-scan
+        let type_scan = self.type_scan_results.as_ref().map(|rx| rx.try_recv());
+        if let Some(Err(TryRecvError::Disconnected)) = type_scan {
+            self.type_scan_results = None;
+            error!("The type re-scan stopped without a result");
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Re-scan types")
+                .set_description("The re-scan stopped unexpectedly, see the log for details.")
+                .set_buttons(MessageButtons::Ok)
+                .show();
+        }
+        if let Some(Ok(results)) = type_scan {
+            self.type_scan_results = None;
+            let mut changed = vec![];
+            let mut failed = vec![];
+            let count = results.len();
+            for (path, result) in results {
+                match result {
+                    Ok((reader, detected)) => {
+                        let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                        let mod_type = detected.mod_type.clone();
+                        match self.apply_rescan(path, reader, detected) {
+                            Some(old) if old != mod_type => changed.push(format!("{}: {} -> {}", name, old, mod_type)),
+                            _ => {}
+                        }
+                    }
+                    Err(e) => failed.push(format!("{}: {}", path.display(), e)),
+                }
+            }
+            self.update_search_filter();
+            info!("Re-scanned the types of {} mod(s), {} changed", count, changed.len());
+            let mut description = format!("Re-scanned {} mod(s).", count);
+            if !changed.is_empty() {
+                description.push_str(&format!("\n\n{} changed type:\n{}", changed.len(), changed.join("\n")));
+            }
+            if !failed.is_empty() {
+                description.push_str(&format!("\n\n{} could not be read:\n{}", failed.len(), failed.join("\n")));
+            }
+            // re-scanning a single mod only reports back when something happened
+            let quiet = count == 1 && changed.is_empty() && failed.is_empty();
+            if !quiet {
+                rfd::MessageDialog::new()
+                    .set_level(if failed.is_empty() { rfd::MessageLevel::Info } else { rfd::MessageLevel::Warning })
+                    .set_title(if count == 1 { "Re-scan type" } else { "Re-scan all types" })
+                    .set_description(description)
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+            }
+        }
+
+        // Poll background renaming
+        if let Some(ref rx) = self.normalize_results {
+            if let Ok(result) = rx.try_recv() {
+                // move names, folders and tags over to the new paths before reloading
+                for (from, to) in &result.renamed {
+                    if let Some(entry) = self.pak_files.iter_mut().find(|m| &m.path == from) {
+                        entry.path = to.clone();
                     }
                 }
+                self.save_state().ok();
+
+                let mut description = format!("Renamed {} mod(s).", result.renamed.len());
+                if !result.skipped.is_empty() {
+                    description.push_str(&format!(
+                        "\n\nSkipped {}:\n{}",
+                        result.skipped.len(),
+                        result.skipped.join("\n")
+                    ));
+                }
+                rfd::MessageDialog::new()
+                    .set_title("Rename mods")
+                    .set_description(description)
+                    .set_buttons(MessageButtons::Ok)
+                    .show();
+                self.normalize_results = None;
+                self.current_pak_file_idx = None;
+                self.table = None;
+                self.refresh_after_delete = true;
             }
         }
 
@@ -2152,6 +7824,7 @@ impl eframe::App for RepakModManager {
         }
 
         if self.install_mod_dialog.is_none() {
+            let install_dir = self.install_dir();
             if let Some(ref receiver) = &self.receiver {
                 while let Ok(event) = receiver.try_recv() {
                     match event.kind {
@@ -2160,19 +7833,42 @@ impl eframe::App for RepakModManager {
                         }
                         EventKind::Other => {}
                         _ => {
-                            // If a background delete is in-flight, defer heavy refresh
-                            if self.deleting_mods.is_empty() {
-                                collect_pak = true;
-                            } else {
-                                self.refresh_after_delete = true;
+                            let keys_changed = event.paths.iter().any(|p| is_keys_file(p));
+                            if keys_changed {
+                                info!("Key file changed, reloading AES keys");
+                                reload_aes_keys();
+                            } else if !event.paths.iter().any(|p| p.starts_with(&install_dir)) {
+                                // something else next to the exe, e.g. the log file
+                                continue;
                             }
+                            // every event pushes the rescan back until the folder is quiet
+                            self.watch_rescan_at =
+                                Some(Instant::now() + Duration::from_millis(self.watch_debounce_ms));
                         }
                     }
                 }
             }
         }
+        if let Some(due) = self.watch_rescan_at {
+            let now = Instant::now();
+            if now < due {
+                ctx.request_repaint_after(due - now);
+            } else {
+                self.watch_rescan_at = None;
+                // If a background delete is in-flight, defer heavy refresh
+                if self.deleting_mods.is_empty() {
+                    collect_pak = true;
+                } else {
+                    self.refresh_after_delete = true;
+                }
+            }
+        }
         // if install_mod_dialog is open we dont want to listen to events
 
+        if std::mem::take(&mut self.refresh_requested) {
+            info!("Refreshing mod list");
+            collect_pak = true;
+        }
         if collect_pak && self.deleting_mods.is_empty() {
             trace!("Collecting pak files");
             self.collect_pak_files();
@@ -2181,115 +7877,40 @@ impl eframe::App for RepakModManager {
             self.refresh_after_delete = true;
         }
 
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            if let Err(e) = self.show_menu_bar(ui) {
-                error!("Error: {}", e);
-            }
-
-            ui.separator();
-            self.show_file_dialog(ui);
-
-            // Bulk actions toolbar
-            ui.separator();
-            ui.horizontal(|ui| {
-                ui.checkbox(&mut self.selection_mode, "Selection mode");
-                if self.selection_mode {
-                    let count = self.selected_mods.len();
-                    ui.label(format!("Selected: {}", count));
-                    let can_delete = count > 0;
-                    if ui.add_enabled(can_delete, Button::new("Delete selected mods").corner_radius(egui::CornerRadius::same(8))).clicked() {
-                        // Ensure worker exists
-                        self.ensure_delete_worker();
-
-                        // Build list of base pak paths from selected indices
-                        let mut base_paths: Vec<std::path::PathBuf> = Vec::new();
-                        for &i in &self.selected_mods {
-                            if let Some(m) = self.pak_files.get(i) {
-                                base_paths.push(m.path.clone());
-                            }
-                        }
-
-                        // Prepare files to delete: try fast rename to .pending_delete first
-                        let mut files_to_delete: Vec<std::path::PathBuf> = Vec::new();
-                        for pak_path in &base_paths {
-                            let utoc_path = pak_path.with_extension("utoc");
-                            let ucas_path = pak_path.with_extension("ucas");
-                            for p in [pak_path, &utoc_path, &ucas_path] {
-                                if !p.exists() { continue; }
-                                let mut tmp = p.clone();
-                                let mut ext = tmp.extension().and_then(|s| s.to_str()).unwrap_or("").to_string();
-                                if ext.is_empty() { ext = "pending_delete".to_string(); } else { ext.push_str(".pending_delete"); }
-                                tmp.set_extension(ext);
-                                match std::fs::rename(p, &tmp) {
-                                    Ok(_) => files_to_delete.push(tmp),
-                                    Err(_e) => files_to_delete.push(p.clone()),
-                                }
-                            }
-                        }
-
-                        // Queue one batch job; if channel fails, log and skip
-                        let mut queued = false;
-                        if let Some(tx) = &self.delete_sender {
-                            if tx.send(files_to_delete).is_ok() { queued = true; }
-                        }
-
-                        // Update UI state regardless; background worker will finish deletion
-                        // Mark each as deleting and remove from UI list next frame
-                        for p in base_paths {
-                            self.deleting_mods.insert(p.clone());
-                            self.pending_remove_paths.push(p);
-                        }
-                        // Clear selection and current table
-                        self.selected_mods.clear();
-                        self.current_pak_file_idx = None;
-                        self.table = None;
-                        // Schedule refresh
-                        self.refresh_after_delete = true;
-                        ui.ctx().request_repaint();
-                        if !queued { error!("Failed to queue bulk delete"); }
-                    }
-                }
-            });
-        });
-
-        egui::SidePanel::left("left_panel")
-            .min_width(300.)
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    ui.set_height(ui.available_height());
-                    ui.label("Mod files");
-                    ui.group(|ui| {
-                        ui.set_width(ui.available_width());
-                        ui.set_height(ui.available_height());
-                        self.show_pak_files_in_dir(ui);
-                    });
-                });
-            });
-
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical(|ui| {
-                // Files section (top)
-                ui.group(|ui| {
-                    ui.set_width(ui.available_width());
-                    ui.set_height(ui.available_height() * 0.6);
-                    self.list_pak_contents(ui).expect("TODO: panic message");
-                });
+        self.handle_list_keys(ctx);
+        if !ctx.input(|i| i.pointer.primary_down()) {
+            self.drag_select = None;
+        }
 
-                ui.separator();
+        if self.quick_install {
+            self.show_quick_install(ctx);
+        } else {
+            self.show_full_layout(ctx);
+        }
 
-                // Details section (below Files)
-                ui.label("Details");
-                ui.group(|ui| {
-                    ui.set_width(ui.available_width());
-                    ui.set_height(ui.available_height());
-                    self.show_pak_details(ui);
-                });
-            });
-        });
+        self.show_settings_window(ctx);
+        self.show_usage_stats(ctx);
+        self.show_install_log(ctx);
+        self.show_key_prompt(ctx);
+        self.show_batch_rename(ctx);
+        self.show_load_order(ctx);
+        self.show_download_prompt(ctx);
+        let index_changed = self.poll_file_index(ctx);
+        self.show_file_search(ctx, index_changed);
+        self.show_share_list(ctx);
+        self.show_convert_mods(ctx);
+        if self.check_outdated {
+            self.update_outdated_check();
+        }
+        // "Set load order..." goes by the conflicts too, so they are kept up to date even
+        // when the list isn't colored by them
+        self.update_mod_conflicts();
 
         // Palette editor window (persistent, won't close on slider drag)
         if self.use_custom_palette && self.show_palette_window {
             let mut open = self.show_palette_window;
+            let mut reset_palette = false;
+            let mut disable_palette = false;
             egui::Window::new("Palette Editor")
                 .open(&mut open)
                 .collapsible(false)
@@ -2314,6 +7935,9 @@ impl eframe::App for RepakModManager {
                         .text
                         .map(CustomPalette::rgba)
                         .unwrap_or(ui.style().visuals.text_color());
+                    let mut state_disabled = CustomPalette::rgba(self.custom_palette.state_disabled);
+                    let mut state_conflicting = CustomPalette::rgba(self.custom_palette.state_conflicting);
+                    let mut state_broken = CustomPalette::rgba(self.custom_palette.state_broken);
 
                     ui.horizontal(|ui| {
                         ui.color_edit_button_srgba(&mut accent);
@@ -2347,6 +7971,19 @@ impl eframe::App for RepakModManager {
                         ui.color_edit_button_srgba(&mut text);
                         ui.label("Text color");
                     });
+                    ui.label("Mod states:");
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_srgba(&mut state_disabled);
+                        ui.label("Disabled");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_srgba(&mut state_conflicting);
+                        ui.label("Sharing files");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.color_edit_button_srgba(&mut state_broken);
+                        ui.label("Missing utoc / ucas");
+                    });
 
                     // Persist back to palette
                     self.custom_palette.accent = [accent.r(), accent.g(), accent.b(), accent.a()];
@@ -2377,6 +8014,9 @@ impl eframe::App for RepakModManager {
                         w_open.a(),
                     ];
                     self.custom_palette.text = Some([text.r(), text.g(), text.b(), text.a()]);
+                    self.custom_palette.state_disabled = [state_disabled.r(), state_disabled.g(), state_disabled.b(), state_disabled.a()];
+                    self.custom_palette.state_conflicting = [state_conflicting.r(), state_conflicting.g(), state_conflicting.b(), state_conflicting.a()];
+                    self.custom_palette.state_broken = [state_broken.r(), state_broken.g(), state_broken.b(), state_broken.a()];
 
                     // Apply immediately
                     let mut style = (*ui.ctx().style()).clone();
@@ -2404,13 +8044,34 @@ impl eframe::App for RepakModManager {
                         ui.horizontal(|ui| {
                             ui.label(&p);
                             if ui.button("Load").clicked() {
-                                if let Err(e) = self.load_palette_preset(&p) {
-                                    error!("Failed to load preset: {}", e);
-                                } else {
-                                    let mut style = (*ui.ctx().style()).clone();
-                                    self.apply_custom_palette_to_style(&mut style);
-                                    ui.ctx().set_style(style);
-                                    self.save_state().ok();
+                                match self.load_palette_preset(&p) {
+                                    Err(e) => {
+                                        error!("Failed to load preset: {}", e);
+                                        rfd::MessageDialog::new()
+                                            .set_level(rfd::MessageLevel::Error)
+                                            .set_title("Unable to load preset")
+                                            .set_description(format!("{} could not be read: {}", p, e))
+                                            .set_buttons(MessageButtons::Ok)
+                                            .show();
+                                    }
+                                    Ok(adjusted) => {
+                                        if !adjusted.is_empty() {
+                                            rfd::MessageDialog::new()
+                                                .set_level(rfd::MessageLevel::Warning)
+                                                .set_title("Preset adjusted")
+                                                .set_description(format!(
+                                                    "{} was changed so the interface stays readable:\n\n{}",
+                                                    p,
+                                                    adjusted.join("\n")
+                                                ))
+                                                .set_buttons(MessageButtons::Ok)
+                                                .show();
+                                        }
+                                        let mut style = (*ui.ctx().style()).clone();
+                                        self.apply_custom_palette_to_style(&mut style);
+                                        ui.ctx().set_style(style);
+                                        self.save_state().ok();
+                                    }
                                 }
                             }
                             if ui.button("Delete").clicked() {
@@ -2421,6 +8082,24 @@ impl eframe::App for RepakModManager {
                         });
                     }
 
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Reset to defaults")
+                            .on_hover_text("Put every color back to the stock values")
+                            .clicked()
+                        {
+                            reset_palette = true;
+                        }
+                        if ui
+                            .button("Disable custom palette")
+                            .on_hover_text("Go back to the built-in theme, your colors are kept for later")
+                            .clicked()
+                        {
+                            disable_palette = true;
+                        }
+                    });
+
                     ui.separator();
                     if ui.button("Close").clicked() {
                         self.show_palette_window = false;
@@ -2430,13 +8109,115 @@ impl eframe::App for RepakModManager {
                     self.save_state().ok();
                 });
             self.show_palette_window = open;
+
+            if reset_palette {
+                self.custom_palette = CustomPalette::default();
+                let mut style = (*ctx.style()).clone();
+                self.apply_custom_palette_to_style(&mut style);
+                ctx.set_style(style);
+                self.save_state().ok();
+            }
+            if disable_palette {
+                self.use_custom_palette = false;
+                self.show_palette_window = false;
+                restore_builtin_theme(ctx);
+                self.save_state().ok();
+            }
+        }
+
+        // Optimize compression: pick a level for the chosen mod
+        if let Some(target) = self.compact_target.clone() {
+            let mut open = true;
+            let mut start = false;
+            egui::Window::new("Optimize compression")
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    let name = target.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                    ui.label(format!("Recompress '{}' with a slower, stronger Oodle level.", name));
+                    ui.label("The original files are kept as a backup until you confirm the result.");
+                    ui.separator();
+                    egui::ComboBox::from_label("Oodle level")
+                        .selected_text(OPTIMIZE_LEVELS[self.compact_level_idx].0)
+                        .show_ui(ui, |ui| {
+                            for (idx, (label, _)) in OPTIMIZE_LEVELS.iter().enumerate() {
+                                ui.selectable_value(&mut self.compact_level_idx, idx, *label);
+                            }
+                        });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.add(Button::new("Start").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                            start = true;
+                        }
+                        if ui.add(Button::new("Cancel").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                            self.compact_target = None;
+                        }
+                    });
+                });
+            if start {
+                self.compact_target = None;
+                self.start_compact(target);
+            } else if !open {
+                self.compact_target = None;
+            }
+        }
+
+        // Optimize compression: keep the result or roll back to the backup
+        if let Some(result) = self.compact_pending_confirm.clone() {
+            let mut decided = false;
+            egui::Window::new("Optimize finished")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    let name = result.mod_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                    ui.label(RichText::new(name).strong());
+                    ui.label(format!(
+                        "{} -> {}",
+                        format_bytes(result.original_size),
+                        format_bytes(result.compacted_size)
+                    ));
+                    let saved = result.saved_bytes();
+                    if saved > 0 {
+                        ui.label(RichText::new(format!("Saved {}", format_bytes(saved as u64))).color(self.accent()));
+                    } else {
+                        ui.label("The mod did not get any smaller, restoring the original is recommended.");
+                    }
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.add(Button::new("Keep optimized").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                            if let Err(e) = result.confirm() {
+                                error!("Failed to remove optimize backup: {}", e);
+                            }
+                            decided = true;
+                        }
+                        if ui.add(Button::new("Restore original").corner_radius(egui::CornerRadius::same(8))).clicked() {
+                            if let Err(e) = result.restore() {
+                                error!("Failed to restore original mod: {}", e);
+                            }
+                            decided = true;
+                        }
+                    });
+                });
+            if decided {
+                self.compact_pending_confirm = None;
+                self.refresh_after_delete = true;
+            }
         }
 
         if ctx.input(|i| i.viewport().close_requested()) {
+            self.usage_stats.end_session();
             self.save_state().unwrap();
         }
         self.check_drop(ctx);
+        self.check_paste(ctx);
         if let Some(ref mut install_mod) = self.install_mod_dialog {
+            install_mod.queued = self
+                .install_queue
+                .iter()
+                .flat_map(|(batch, _)| batch)
+                .map(|p| p.file_name().unwrap_or_default().to_string_lossy().to_string())
+                .collect();
             if self.file_drop_viewport_open {
                 install_mod.new_mod_dialog(ctx, &mut self.file_drop_viewport_open);
             }
@@ -2444,6 +8225,185 @@ impl eframe::App for RepakModManager {
     }
 }
 
+/// Compression methods a pak declares, methods repak doesn't know are listed as unsupported
+fn pak_compression_text(pak: &PakReader) -> String {
+    let mut methods = pak.compression().iter().flatten().map(|m| m.to_string()).collect::<Vec<_>>();
+    methods.dedup();
+    methods.extend(pak.unknown_compression().iter().map(|name| format!("{} (unsupported)", name)));
+    if methods.is_empty() {
+        return "None".to_string();
+    }
+    let mut text = methods.join(", ");
+    if pak.version() < repak::Version::V8A {
+        // older paks don't name their methods, the version decides them
+        text.push_str(" (implied by version)");
+    }
+    text
+}
+
+/// Format features of a pak's version, and the frozen index flag of v9 paks
+fn pak_feature_flags(pak: &PakReader) -> Vec<&'static str> {
+    use repak::VersionMajor as V;
+    let major = pak.version().version_major();
+    let mut flags = [
+        (V::NoTimestamps, "no timestamps"),
+        (V::CompressionEncryption, "compression and encryption"),
+        (V::IndexEncryption, "index encryption"),
+        (V::RelativeChunkOffsets, "relative chunk offsets"),
+        (V::DeleteRecords, "delete records"),
+        (V::EncryptionKeyGuid, "encryption key GUID"),
+        (V::FNameBasedCompression, "named compression"),
+        (V::PathHashIndex, "path hash index"),
+        (V::Fnv64BugFix, "FNV-64 fix"),
+    ]
+    .into_iter()
+    .filter(|(since, _)| major >= *since)
+    .map(|(_, name)| name)
+    .collect::<Vec<_>>();
+    if pak.frozen_index() {
+        flags.push("frozen index");
+    }
+    flags
+}
+
+fn detect_mod_type(pak_reader: &PakReader, pak_path: &Path) -> DetectedType {
+    try_detect_mod_type(pak_reader, pak_path).unwrap_or_else(|e| {
+        warn!("{}", e);
+        DetectedType {
+            mod_type: "Unknown".to_string(),
+            mixed: vec![],
+        }
+    })
+}
+
+/// Type of the mod at `pak_path`, failing when its utoc can't be read
+fn try_detect_mod_type(pak_reader: &PakReader, pak_path: &Path) -> Result<DetectedType, String> {
+    let mut utoc_path = pak_path.to_path_buf();
+    utoc_path.set_extension("utoc");
+
+    let entries = match utoc_path.exists() {
+        true => Some(read_utoc(&utoc_path, pak_reader, pak_path)?),
+        false => None,
+    };
+    let paths = match &entries {
+        Some(entries) => entries.iter().map(|entry| entry.file_path.clone()).collect::<Vec<_>>(),
+        None => pak_reader.files().into_iter().collect::<Vec<_>>(),
+    };
+
+    let mod_type = get_current_pak_characteristics(paths.clone());
+    let mut mixed = path_categories(&paths);
+    match mixed.len() {
+        0 | 1 => mixed.clear(),
+        _ => info!("{} mixes {} assets, typed as {}", pak_path.display(), mixed.join(", "), mod_type),
+    }
+    if mod_type != "Unknown" && mixed.is_empty() {
+        return Ok(DetectedType { mod_type, mixed });
+    }
+    // the paths don't say or disagree, e.g. for renamed assets, so look inside a few packages
+    let packages = paths
+        .into_iter()
+        .filter(|p| {
+            let p = p.to_lowercase();
+            p.ends_with(".uasset") || p.ends_with(".umap")
+        })
+        .collect::<Vec<_>>();
+    let sniffed = match &entries {
+        Some(entries) => sniff_mod_type(&packages, |path| {
+            let entry = entries.iter().find(|e| e.file_path == path).ok_or("not in the utoc")?;
+            hex_view::read_package(entry)
+        }),
+        None => sniff_mod_type(&packages, |path| {
+            let file = File::open(pak_path).map_err(|e| e.to_string())?;
+            pak_reader.get(path, &mut BufReader::new(file)).map_err(|e| e.to_string())
+        }),
+    };
+    let mod_type = match sniffed {
+        Some(kind) => {
+            debug!("Detected {} as {} from its contents", pak_path.display(), kind);
+            kind.to_string()
+        }
+        None => mod_type,
+    };
+    Ok(DetectedType { mod_type, mixed })
+}
+
+/// Removes and returns the entry of `pending` recorded for the installed mod at `pak_path`,
+/// enabled or disabled. Entries are keyed by the name the mod was installed under in any
+/// case, an entry for the same name with another priority suffix matches too, as the load
+/// order may have been renumbered since.
+fn take_pending<T>(pending: &mut BTreeMap<String, T>, pak_path: &Path) -> Option<T> {
+    // uppercase so split_priority finds the `_P` of lowercase names
+    let stem = pak_path.file_stem().unwrap_or_default().to_string_lossy().to_uppercase();
+    let base = |name: &str| split_priority(name).0.to_string();
+    let key = pending
+        .keys()
+        .find(|key| key.to_uppercase() == stem)
+        .or_else(|| pending.keys().find(|key| base(&key.to_uppercase()) == base(&stem)))?
+        .clone();
+    pending.remove(&key)
+}
+
+// reports how "Copy mod files to..." went
+fn show_copy_result(pak_path: &Path, dest: &Path, result: std::io::Result<Vec<PathBuf>>) {
+    let (level, description) = match result {
+        Ok(copied) => {
+            info!("Copied {} file(s) of {} to {}", copied.len(), pak_path.display(), dest.display());
+            (rfd::MessageLevel::Info, format!("Copied {} file(s) to {}", copied.len(), dest.display()))
+        }
+        Err(e) => {
+            error!("Failed to copy {}: {}", pak_path.display(), e);
+            (rfd::MessageLevel::Error, format!("Unable to copy the mod: {}", e))
+        }
+    };
+    rfd::MessageDialog::new()
+        .set_level(level)
+        .set_title("Copy mod files")
+        .set_description(description)
+        .set_buttons(MessageButtons::Ok)
+        .show();
+}
+
+/// Pak, utoc and ucas of the mod at `pak_path` together
+fn mod_disk_size(pak_path: &Path) -> u64 {
+    mod_pak_files(pak_path)
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+// the reader of a mod changed outside the app is stale, so the pak is opened again
+fn rescan_mod(pak_path: &Path) -> Result<(PakReader, DetectedType), String> {
+    let reader = open_pak(pak_path).map_err(|e| e.to_string())?;
+    let detected = try_detect_mod_type(&reader, pak_path)?;
+    Ok((reader, detected))
+}
+
+fn open_in_external_tool(tool: &Path, pak_path: &Path) {
+    info!("Opening {} with {}", pak_path.display(), tool.display());
+    if let Err(e) = open_with_tool(tool, pak_path) {
+        error!("Failed to start {}: {}", tool.display(), e);
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Error)
+            .set_title("Open with external tool")
+            .set_description(format!("Unable to start {}: {}", tool.display(), e))
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+}
+
+fn open_readme(path: &Path) {
+    if let Err(e) = open_with_default_app(path) {
+        error!("Failed to open {}: {}", path.display(), e);
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Error)
+            .set_title("Open readme")
+            .set_description(format!("Unable to open the readme: {}", e))
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+}
+
 const ICON: LazyCell<Arc<IconData>> = LazyCell::new(|| {
     let d = eframe::icon_data::from_png_bytes(include_bytes!(
         "../../repak-gui/icons/RepakLogoNonCurveFadedRed-modified.png"
@@ -2482,7 +8442,7 @@ fn custom_panic(_info: &PanicHookInfo) -> ! {
     let msg = format!(
         "Repak has crashed. Please report this issue to the developer with the following information:\
 \n\n{}\
-\nAdditonally include the log file in the bug report"
+\nAdditionally attach the zip made by File > Create bug report after restarting Repak"
         ,_info);
 
     let _x = rfd::MessageDialog::new()
@@ -2501,6 +8461,12 @@ fn main() {
     #[cfg(target_os = "windows")]
     #[cfg(not(debug_assertions))]
     std::panic::set_hook(Box::new(move |info| {
+        // workers unwind into their catch_unwind, or drop the channel the UI polls and
+        // report the failure there
+        if thread::current().name() != Some("main") {
+            error!("A worker thread panicked: {}", info);
+            return;
+        }
         custom_panic(info.into());
     }));
 
@@ -2525,9 +8491,12 @@ fn main() {
             ColorChoice::Auto,
         ),
         WriteLogger::new(LevelFilter::Info, Config::default(), log_file),
+        InstallLogger::new(LevelFilter::Debug),
     ])
     .expect("Failed to initialize logger");
 
+    watch_game();
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1366.0, 768.0])
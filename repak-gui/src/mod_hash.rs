@@ -0,0 +1,103 @@
+use crate::utils::mod_pak_files;
+use log::{debug, warn};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::SystemTime;
+
+type HashResult = Result<String, String>;
+
+struct HashEntry {
+    modified: SystemTime,
+    /// `None` while the worker is still hashing
+    hash: Option<HashResult>,
+}
+
+/// SHA-256 of installed mods, keyed by pak path. Hashing runs on a worker thread and is
+/// redone when one of the mod's files changes on disk.
+pub struct HashCache {
+    hashes: HashMap<PathBuf, HashEntry>,
+    tx: Sender<(PathBuf, SystemTime, HashResult)>,
+    rx: Receiver<(PathBuf, SystemTime, HashResult)>,
+}
+
+impl Default for HashCache {
+    fn default() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            hashes: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+}
+
+impl HashCache {
+    /// Hash of the mod at `pak_path`, `None` while it is being computed
+    pub fn get(&mut self, pak_path: &Path) -> Option<&HashResult> {
+        while let Ok((path, modified, hash)) = self.rx.try_recv() {
+            // a result for an older version of the files is stale
+            if let Some(entry) = self.hashes.get_mut(&path).filter(|e| e.modified == modified) {
+                entry.hash = Some(hash);
+            }
+        }
+
+        let modified = last_modified(pak_path);
+        let stale = self.hashes.get(pak_path).is_none_or(|e| e.modified != modified);
+        if stale {
+            self.hashes.insert(pak_path.to_path_buf(), HashEntry { modified, hash: None });
+            let (tx, path) = (self.tx.clone(), pak_path.to_path_buf());
+            std::thread::spawn(move || {
+                let hash = hash_mod(&path).map_err(|e| {
+                    warn!("Unable to hash {}: {}", path.display(), e);
+                    e.to_string()
+                });
+                let _ = tx.send((path, modified, hash));
+            });
+        }
+        self.hashes[pak_path].hash.as_ref()
+    }
+}
+
+pub fn last_modified(pak_path: &Path) -> SystemTime {
+    mod_pak_files(pak_path)
+        .iter()
+        .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Hash of what an install reads from `path`: `hash_mod` for paks, and for folders every
+/// file together with its path inside the folder. Tells a new version of a mod from a
+/// reinstall of the same files.
+pub fn hash_source(path: &Path) -> io::Result<String> {
+    if !path.is_dir() {
+        return hash_mod(path);
+    }
+    let mut files = vec![];
+    crate::utils::collect_files(&mut files, path)?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in files {
+        let rel = file.strip_prefix(path).unwrap_or(&file).to_string_lossy().replace('\\', "/");
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        io::copy(&mut BufReader::new(File::open(&file)?), &mut hasher)?;
+    }
+    debug!("Hashed {}", path.display());
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// SHA-256 over the pak followed by the utoc and ucas of iostore mods, as lowercase hex.
+/// Two copies of a mod have the same hash no matter what they are named.
+pub fn hash_mod(pak_path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    for path in mod_pak_files(pak_path) {
+        io::copy(&mut BufReader::new(File::open(&path)?), &mut hasher)?;
+    }
+    debug!("Hashed {}", pak_path.display());
+    Ok(hex::encode(hasher.finalize()))
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+/// One installed mod as written by `render`
+pub struct ModListRow {
+    pub name: String,
+    pub file_name: String,
+    pub mod_type: String,
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Format matching the extension of `path`, CSV unless it is `.md`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref() {
+            Some("md") | Some("markdown") => ExportFormat::Markdown,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+const HEADER: [&str; 6] = ["Name", "File", "Type", "Tags", "Folder", "Enabled"];
+
+fn columns(row: &ModListRow) -> [String; 6] {
+    [
+        row.name.clone(),
+        row.file_name.clone(),
+        row.mod_type.clone(),
+        row.tags.join(", "),
+        row.folder.clone().unwrap_or_default(),
+        if row.enabled { "Yes" } else { "No" }.to_string(),
+    ]
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+/// The mod list as a CSV file or a markdown table, one line per mod
+pub fn render(rows: &[ModListRow], format: ExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Csv => {
+            out.push_str(&HEADER.join(","));
+            out.push('\n');
+            for row in rows {
+                let line = columns(row).iter().map(|c| csv_field(c)).collect::<Vec<_>>();
+                out.push_str(&line.join(","));
+                out.push('\n');
+            }
+        }
+        ExportFormat::Markdown => {
+            out.push_str(&format!("| {} |\n", HEADER.join(" | ")));
+            out.push_str(&format!("|{}\n", "---|".repeat(HEADER.len())));
+            for row in rows {
+                let cells = columns(row).iter().map(|c| markdown_cell(c)).collect::<Vec<_>>();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+    }
+    out
+}
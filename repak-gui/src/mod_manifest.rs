@@ -0,0 +1,66 @@
+use log::{debug, warn};
+use repak::PakReader;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// File names recognised as a manifest when packed inside the mod
+const MANIFEST_NAMES: [&str; 2] = ["mod.json", "manifest.json"];
+
+/// Information some mods ship about themselves. Every field is optional, unknown ones
+/// are ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ModManifest {
+    #[serde(alias = "title")]
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl ModManifest {
+    /// Reads the manifest packed in the mod, the one closest to the mount point wins.
+    /// `None` if there is none or it isn't valid JSON.
+    pub fn read(pak_path: &Path, reader: &PakReader) -> Option<Self> {
+        let entry = reader
+            .files()
+            .into_iter()
+            .filter(|file| {
+                let name = file.rsplit('/').next().unwrap_or(file).to_lowercase();
+                MANIFEST_NAMES.contains(&name.as_str())
+            })
+            .min_by_key(|file| file.matches('/').count())?;
+        let mut file = BufReader::new(File::open(pak_path).ok()?);
+        let data = reader.get(&entry, &mut file).ok()?;
+        match serde_json::from_slice::<Self>(&data) {
+            Ok(manifest) => {
+                debug!("Read manifest {} of {}", entry, pak_path.display());
+                Some(manifest.trimmed())
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable manifest {} in {}: {}", entry, pak_path.display(), e);
+                None
+            }
+        }
+    }
+
+    // blank values are treated as missing
+    fn trimmed(self) -> Self {
+        let clean = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Self {
+            name: clean(self.name),
+            author: clean(self.author),
+            description: clean(self.description),
+            version: clean(self.version),
+            tags: self
+                .tags
+                .into_iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+        }
+    }
+}
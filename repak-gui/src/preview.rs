@@ -0,0 +1,78 @@
+use eframe::egui::{self, ColorImage, TextureHandle, TextureOptions};
+use log::{debug, warn};
+use repak::PakReader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Largest edge of a decoded preview, bigger images are downscaled before upload
+const PREVIEW_SIZE: u32 = 256;
+
+/// Image extensions looked up next to the pak
+pub const SIDECAR_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// File names recognised as a preview when packed inside the mod
+const EMBEDDED_NAMES: [&str; 4] = ["preview.png", "preview.jpg", "thumbnail.png", "thumbnail.jpg"];
+
+/// Decoded mod previews keyed by pak path. `None` records that a mod has no preview so
+/// the disk isn't checked again every frame.
+#[derive(Default)]
+pub struct PreviewCache {
+    textures: HashMap<PathBuf, Option<TextureHandle>>,
+}
+
+impl PreviewCache {
+    pub fn get(
+        &mut self,
+        ctx: &egui::Context,
+        pak_path: &Path,
+        reader: &PakReader,
+    ) -> Option<&TextureHandle> {
+        self.textures
+            .entry(pak_path.to_path_buf())
+            .or_insert_with(|| load_preview(ctx, pak_path, reader))
+            .as_ref()
+    }
+
+    /// Drops every cached texture, previews are looked up again on next use
+    pub fn clear(&mut self) {
+        self.textures.clear();
+    }
+}
+
+fn sidecar_bytes(pak_path: &Path) -> Option<Vec<u8>> {
+    SIDECAR_EXTENSIONS
+        .iter()
+        .map(|ext| pak_path.with_extension(ext))
+        .find(|path| path.is_file())
+        .and_then(|path| std::fs::read(path).ok())
+}
+
+fn embedded_bytes(pak_path: &Path, reader: &PakReader) -> Option<Vec<u8>> {
+    let entry = reader.files().into_iter().find(|file| {
+        let name = file.rsplit('/').next().unwrap_or(file).to_lowercase();
+        EMBEDDED_NAMES.contains(&name.as_str())
+    })?;
+    let mut file = BufReader::new(File::open(pak_path).ok()?);
+    reader.get(&entry, &mut file).ok()
+}
+
+fn load_preview(ctx: &egui::Context, pak_path: &Path, reader: &PakReader) -> Option<TextureHandle> {
+    let bytes = sidecar_bytes(pak_path).or_else(|| embedded_bytes(pak_path, reader))?;
+    let image = match image::load_from_memory(&bytes) {
+        Ok(image) => image.thumbnail(PREVIEW_SIZE, PREVIEW_SIZE).to_rgba8(),
+        Err(e) => {
+            warn!("Unable to decode preview for {}: {}", pak_path.display(), e);
+            return None;
+        }
+    };
+    debug!("Loaded preview for {}", pak_path.display());
+    let size = [image.width() as usize, image.height() as usize];
+    let color_image = ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+    Some(ctx.load_texture(
+        format!("preview:{}", pak_path.display()),
+        color_image,
+        TextureOptions::LINEAR,
+    ))
+}
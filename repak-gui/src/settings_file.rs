@@ -0,0 +1,191 @@
+use eframe::egui::ThemePreference;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+/// Marks a settings file, the number is bumped if the format ever changes
+const FORMAT: &str = "repak-settings:1";
+
+// lists the shared settings once, for the keys of the file and the fields an import copies
+macro_rules! settings {
+    ($($field:ident),* $(,)?) => {
+        /// Config fields written to a settings file. Mods, folders, tags and window state
+        /// stay behind so the file is small and can be shared.
+        pub const SETTING_KEYS: &[&str] = &[$(stringify!($field)),*];
+
+        impl crate::RepakModManager {
+            /// Moves the `SETTING_KEYS` fields of `from` over, leaving everything else alone
+            pub(crate) fn copy_settings(&mut self, from: Self) {
+                $(self.$field = from.$field;)*
+            }
+        }
+    };
+}
+
+settings! {
+    game_path,
+    default_font_size,
+    cjk_font,
+    use_custom_palette,
+    custom_palette,
+    show_list_thumbnails,
+    skip_state_colors,
+    list_density,
+    quick_install,
+    list_grouping,
+    watch_debounce_ms,
+    skip_folder_watch,
+    watch_downloads,
+    downloads_dir,
+    external_tool,
+    install_target,
+    staging_dir,
+    mod_naming,
+    default_priority,
+    install_threads,
+    skip_suggested_fixes,
+    skip_install_verify,
+    name_collision,
+    pack_ignore,
+    auto_type_tags,
+    type_tags,
+    type_dirs,
+    skip_conflict_check,
+    skip_welcome_on_update,
+    skip_config_backups,
+    memory_map_ucas,
+    check_outdated,
+    folder_keeps_mod_states,
+    separate_system_mods,
+    system_mod_patterns,
+    usage_stats_enabled,
+}
+
+/// Settings holding a path on the machine they were exported on
+const PATH_KEYS: [&str; 4] = ["game_path", "downloads_dir", "external_tool", "staging_dir"];
+
+/// Contents of a settings file
+pub struct SettingsFile {
+    pub settings: Map<String, Value>,
+    pub theme: Option<ThemePreference>,
+    /// AES keys as (GUID, key), an empty GUID for the default key. Only there when the
+    /// user chose to export them.
+    pub keys: Vec<(String, String)>,
+}
+
+/// What `merge` did with each setting of a file
+#[derive(Default)]
+pub struct MergeReport {
+    pub applied: Vec<String>,
+    /// Setting and why it was left alone
+    pub skipped: Vec<(String, String)>,
+}
+
+fn theme_name(theme: ThemePreference) -> &'static str {
+    match theme {
+        ThemePreference::Dark => "dark",
+        ThemePreference::Light => "light",
+        ThemePreference::System => "system",
+    }
+}
+
+/// Settings file with the `SETTING_KEYS` of `config`, the serialized app config. `keys` is
+/// left out when empty.
+pub fn export(config: &Value, theme: ThemePreference, keys: &[(String, String)]) -> String {
+    let settings = SETTING_KEYS
+        .iter()
+        .filter_map(|key| Some((key.to_string(), config.get(*key)?.clone())))
+        .collect::<Map<_, _>>();
+    let mut file = json!({
+        "format": FORMAT,
+        "settings": settings,
+        "theme": theme_name(theme),
+    });
+    if !keys.is_empty() {
+        file["keys"] = keys
+            .iter()
+            .map(|(guid, key)| json!({ "guid": guid, "key": key }))
+            .collect();
+    }
+    serde_json::to_string_pretty(&file).unwrap()
+}
+
+pub fn parse(data: &str) -> Result<SettingsFile, String> {
+    let root = serde_json::from_str::<Value>(data).map_err(|e| format!("Not valid JSON: {}", e))?;
+    if root.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err("Not a Repak settings file".to_string());
+    }
+    let settings = root
+        .get("settings")
+        .and_then(Value::as_object)
+        .cloned()
+        .ok_or_else(|| "The file has no settings".to_string())?;
+    let theme = match root.get("theme").and_then(Value::as_str) {
+        None => None,
+        Some("dark") => Some(ThemePreference::Dark),
+        Some("light") => Some(ThemePreference::Light),
+        Some("system") => Some(ThemePreference::System),
+        Some(other) => return Err(format!("Unknown theme \"{}\"", other)),
+    };
+    let keys = root
+        .get("keys")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|entry| {
+            let guid = entry.get("guid").and_then(Value::as_str);
+            let key = entry.get("key").and_then(Value::as_str);
+            match (guid, key) {
+                (Some(guid), Some(key)) => Ok((guid.to_string(), key.to_string())),
+                _ => Err(format!("Broken key entry {}", entry)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(SettingsFile { settings, theme, keys })
+}
+
+// maps gain the imported entries and lists the imported items they lack, anything else is
+// replaced
+fn merge_value(current: &mut Value, imported: &Value) {
+    match (current, imported) {
+        (Value::Object(current), Value::Object(imported)) => {
+            for (key, value) in imported {
+                current.insert(key.clone(), value.clone());
+            }
+        }
+        (Value::Array(current), Value::Array(imported)) => {
+            for value in imported {
+                if !current.contains(value) {
+                    current.push(value.clone());
+                }
+            }
+        }
+        (current, imported) => *current = imported.clone(),
+    }
+}
+
+/// Merges the settings of a file into `config`, the serialized app config. Settings the
+/// file doesn't have keep their value, unknown ones are skipped, and so are paths that
+/// don't exist on this machine.
+pub fn merge(config: &mut Value, settings: &Map<String, Value>) -> MergeReport {
+    let mut report = MergeReport::default();
+    for (key, value) in settings {
+        if !SETTING_KEYS.contains(&key.as_str()) {
+            report.skipped.push((key.clone(), "not a known setting".to_string()));
+            continue;
+        }
+        if PATH_KEYS.contains(&key.as_str()) {
+            if let Some(path) = value.as_str().filter(|path| !Path::new(path).exists()) {
+                report.skipped.push((key.clone(), format!("{} doesn't exist here", path)));
+                continue;
+            }
+        }
+        match config.get_mut(key) {
+            Some(current) => merge_value(current, value),
+            None => {
+                config[key.as_str()] = value.clone();
+            }
+        }
+        report.applied.push(key.clone());
+    }
+    report
+}
@@ -0,0 +1,58 @@
+/// Start of every share code, the number is bumped if the format ever changes
+const PREFIX: &str = "repak-modlist:1:";
+
+/// Hex digits of the SHA-256 kept in a share code, plenty to tell mods apart
+pub const SHORT_HASH_LEN: usize = 16;
+
+/// A mod listed in a share code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedMod {
+    pub name: String,
+    /// First `SHORT_HASH_LEN` hex digits of `mod_hash::hash_mod`
+    pub hash: String,
+}
+
+pub fn short_hash(hash: &str) -> String {
+    hash.chars().take(SHORT_HASH_LEN).collect()
+}
+
+// `;` separates mods and `~` the hash from the name, both are escaped inside names
+fn escape(name: &str) -> String {
+    name.replace('%', "%25").replace(';', "%3B").replace('~', "%7E")
+}
+
+fn unescape(name: &str) -> String {
+    name.replace("%7E", "~").replace("%3B", ";").replace("%25", "%")
+}
+
+/// Single line listing `mods`, for pasting into chats. Nothing but names and hashes is
+/// shared and no mod is ever downloaded from it.
+pub fn encode(mods: &[SharedMod]) -> String {
+    let entries = mods
+        .iter()
+        .map(|m| format!("{}~{}", short_hash(&m.hash), escape(&m.name)))
+        .collect::<Vec<_>>();
+    format!("{}{}", PREFIX, entries.join(";"))
+}
+
+pub fn decode(code: &str) -> Result<Vec<SharedMod>, String> {
+    let body = code
+        .trim()
+        .strip_prefix(PREFIX)
+        .ok_or_else(|| "Not a Repak mod list code".to_string())?;
+    body.split(';')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (hash, name) = entry
+                .split_once('~')
+                .ok_or_else(|| format!("Broken entry \"{}\"", entry))?;
+            if hash.len() != SHORT_HASH_LEN || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Broken hash in entry \"{}\"", entry));
+            }
+            Ok(SharedMod {
+                name: unescape(name),
+                hash: hash.to_lowercase(),
+            })
+        })
+        .collect()
+}
@@ -3,6 +3,8 @@ use std::process::Command;
 use std::fs;
 use log::{debug, warn, error};
 use uasset_mesh_patch_rivals::{process_mesh_file, is_mesh_uasset};
+use crate::install_mod::install_mod_logic::patch_filter::PatchFilter;
+use crate::install_mod::install_mod_logic::patch_meshes::is_allowed;
 
 /// Detects if a UAsset file is a mesh using the integrated mesh patch library
 pub fn detect_mesh_with_toolkit(path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
@@ -91,26 +93,83 @@ fn analyze_uasset_for_texture_class(path: &Path) -> Result<bool, Box<dyn std::er
     Ok(has_texture_class)
 }
 
-/// Detects mesh files in a list of mod contents
-pub fn detect_mesh_files(mod_contents: &[String]) -> bool {
+/// Detects skeletal meshes, the only assets the mesh fixer changes. Files on disk are checked
+/// with the mesh patch library, paths inside a pak by the `SK_` naming convention.
+pub fn detect_skeletal_meshes(mod_contents: &[String]) -> bool {
     mod_contents.iter().any(|file| {
-        let lower_file = file.to_lowercase();
-        
-        if lower_file.ends_with(".uasset") {
-            let path = PathBuf::from(file);
-            
-            // Use UAsset Toolkit for accurate detection
-            if let Ok(is_mesh) = detect_mesh_with_toolkit(&path) {
+        if !file.to_lowercase().ends_with(".uasset") {
+            return false;
+        }
+        let path = Path::new(file);
+        if path.is_file() {
+            if let Ok(is_mesh) = is_mesh_uasset(path) {
                 return is_mesh;
             }
-            
-            // Fallback to heuristics
-            return is_mesh_uasset_heuristic(&path);
         }
-        false
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().to_lowercase().starts_with("sk_"))
     })
 }
 
+/// Packages looked inside by `sniff_mod_type`, reading all of a big mod would stall loading
+const SNIFF_SAMPLE: usize = 8;
+
+/// Names found in packages of each kind of asset with the mod type they point to. Class
+/// names are in the name map of pak assets, iostore packages reference classes by hash so
+/// for those textures are told by their pixel format name instead.
+const CONTENT_SIGNATURES: [(&[u8], &str); 14] = [
+    (b"Texture2D", "Texture"),
+    (b"PF_DXT", "Texture"),
+    (b"PF_BC", "Texture"),
+    (b"PF_B8G8R8A8", "Texture"),
+    (b"SkeletalMesh", "Mesh"),
+    (b"StaticMesh", "Mesh"),
+    (b"AkAudioEvent", "Audio"),
+    (b"SoundWave", "Audio"),
+    (b"AnimSequence", "Animation"),
+    (b"AnimMontage", "Animation"),
+    (b"NiagaraSystem", "VFX"),
+    (b"ParticleSystem", "VFX"),
+    (b"WidgetBlueprint", "UI"),
+    (b"PersistentLevel", "Map"),
+];
+
+/// Mod type from the contents of a sample of `packages`, for mods whose paths don't tell
+/// what they are. `read` returns the bytes of a package. `None` when nothing is recognized.
+pub fn sniff_mod_type(packages: &[String], read: impl Fn(&str) -> Result<Vec<u8>, String>) -> Option<&'static str> {
+    let mut votes: Vec<(&'static str, usize)> = vec![];
+    for package in packages.iter().take(SNIFF_SAMPLE) {
+        let kind = if package.to_lowercase().ends_with(".umap") {
+            Some("Map")
+        } else {
+            match read(package) {
+                Ok(data) => CONTENT_SIGNATURES
+                    .iter()
+                    .find(|(name, _)| data.windows(name.len()).any(|w| w == *name))
+                    .map(|(_, kind)| *kind),
+                Err(e) => {
+                    debug!("Unable to read {} for type detection: {}", package, e);
+                    None
+                }
+            }
+        };
+        if let Some(kind) = kind {
+            match votes.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, count)) => *count += 1,
+                None => votes.push((kind, 1)),
+            }
+        }
+    }
+    // the first kind seen wins a tie
+    let mut best: Option<(&'static str, usize)> = None;
+    for (kind, count) in votes {
+        if best.is_none_or(|(_, most)| count > most) {
+            best = Some((kind, count));
+        }
+    }
+    best.map(|(kind, _)| kind)
+}
+
 /// Detects texture files in a list of mod contents
 pub fn detect_texture_files(mod_contents: &[String]) -> bool {
     mod_contents.iter().any(|file| {
@@ -143,12 +202,17 @@ pub fn detect_texture_files(mod_contents: &[String]) -> bool {
 }
 
 /// Patches mesh files using available tools
-pub fn patch_mesh_files(paths: &mut Vec<PathBuf>, mod_dir: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+pub fn patch_mesh_files(
+    paths: &mut Vec<PathBuf>,
+    mod_dir: &PathBuf,
+    filter: &PatchFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
     let uasset_files: Vec<_> = paths
         .iter()
         .filter(|p| {
             p.extension().and_then(|ext| ext.to_str()) == Some("uasset") &&
-            is_mesh_uasset_heuristic(p)
+            is_mesh_uasset_heuristic(p) &&
+            is_allowed(p, mod_dir, filter)
         })
         .collect();
 
@@ -243,3 +307,77 @@ pub fn modify_texture_mipmaps(uasset_path: &Path, uexp_path: &Path) -> Result<bo
     
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{sniff_mod_type, SNIFF_SAMPLE};
+
+    fn packages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// Package bytes with `name` somewhere in the middle, as in a name map
+    fn package_with(name: &str) -> Vec<u8> {
+        [b"\xC1\x83\x2A\x9E\0\0".as_slice(), name.as_bytes(), b"\0\0"].concat()
+    }
+
+    #[test]
+    fn sniff_mod_type_reads_class_names() {
+        let packages = packages(&["Game/A.uasset", "Game/B.uasset"]);
+        let kind = sniff_mod_type(&packages, |_| Ok(package_with("SkeletalMesh")));
+        assert_eq!(kind, Some("Mesh"));
+        let kind = sniff_mod_type(&packages, |_| Ok(package_with("PF_BC7")));
+        assert_eq!(kind, Some("Texture"));
+    }
+
+    #[test]
+    fn sniff_mod_type_types_umaps_without_reading_them() {
+        let packages = packages(&["Game/Maps/Arena.umap"]);
+        let kind = sniff_mod_type(&packages, |_| Err("should not be read".to_string()));
+        assert_eq!(kind, Some("Map"));
+    }
+
+    #[test]
+    fn sniff_mod_type_goes_with_the_majority() {
+        let packages = packages(&["Game/A.uasset", "Game/B.uasset", "Game/C.uasset"]);
+        let kind = sniff_mod_type(&packages, |path| match path {
+            "Game/A.uasset" => Ok(package_with("SoundWave")),
+            _ => Ok(package_with("NiagaraSystem")),
+        });
+        assert_eq!(kind, Some("VFX"));
+    }
+
+    #[test]
+    fn sniff_mod_type_breaks_ties_by_the_first_kind_seen() {
+        let packages = packages(&["Game/A.uasset", "Game/B.uasset"]);
+        let kind = sniff_mod_type(&packages, |path| match path {
+            "Game/A.uasset" => Ok(package_with("AnimSequence")),
+            _ => Ok(package_with("WidgetBlueprint")),
+        });
+        assert_eq!(kind, Some("Animation"));
+    }
+
+    #[test]
+    fn sniff_mod_type_skips_unreadable_and_unknown_packages() {
+        let packages = packages(&["Game/A.uasset", "Game/B.uasset", "Game/C.uasset"]);
+        let kind = sniff_mod_type(&packages, |path| match path {
+            "Game/A.uasset" => Err("corrupt".to_string()),
+            "Game/B.uasset" => Ok(package_with("SomethingElse")),
+            _ => Ok(package_with("AkAudioEvent")),
+        });
+        assert_eq!(kind, Some("Audio"));
+        assert_eq!(sniff_mod_type(&packages, |_| Ok(package_with("Nothing"))), None);
+        assert_eq!(sniff_mod_type(&[], |_| Ok(package_with("StaticMesh"))), None);
+    }
+
+    #[test]
+    fn sniff_mod_type_only_reads_a_sample() {
+        let names = (0..SNIFF_SAMPLE * 2).map(|i| format!("Game/{i}.uasset")).collect::<Vec<_>>();
+        let read = std::cell::Cell::new(0);
+        sniff_mod_type(&names, |_| {
+            read.set(read.get() + 1);
+            Ok(package_with("Texture2D"))
+        });
+        assert_eq!(read.get(), SNIFF_SAMPLE);
+    }
+}
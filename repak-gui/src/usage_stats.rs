@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Something the usage counter keeps count of
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum UsageEvent {
+    Launch,
+    Install,
+    Delete,
+    Conversion,
+    Extraction,
+    /// A session that ended without the window being closed, a crash or a killed process
+    Crash,
+}
+
+impl UsageEvent {
+    pub const ALL: [UsageEvent; 6] = [
+        UsageEvent::Launch,
+        UsageEvent::Install,
+        UsageEvent::Delete,
+        UsageEvent::Conversion,
+        UsageEvent::Extraction,
+        UsageEvent::Crash,
+    ];
+
+    /// Name the count is stored under in the config
+    fn key(self) -> &'static str {
+        match self {
+            UsageEvent::Launch => "launches",
+            UsageEvent::Install => "installs",
+            UsageEvent::Delete => "deletes",
+            UsageEvent::Conversion => "conversions",
+            UsageEvent::Extraction => "extractions",
+            UsageEvent::Crash => "crashes",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UsageEvent::Launch => "Launches",
+            UsageEvent::Install => "Mods installed",
+            UsageEvent::Delete => "Mods deleted",
+            UsageEvent::Conversion => "Mods converted",
+            UsageEvent::Extraction => "Mods extracted",
+            UsageEvent::Crash => "Crashes or forced closes",
+        }
+    }
+}
+
+/// How often features were used, kept in the config and never sent anywhere. Only counted
+/// while `usage_stats_enabled` is set, so users can look it up when reporting a bug.
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageStats {
+    counts: BTreeMap<String, u64>,
+    /// Unix seconds of the first count since the last reset
+    since: Option<u64>,
+    /// Set while the app runs, so it is still set on launch when the last session crashed
+    session_open: bool,
+}
+
+impl UsageStats {
+    pub fn record(&mut self, event: UsageEvent, count: u64) {
+        if count == 0 {
+            return;
+        }
+        self.since.get_or_insert_with(now);
+        *self.counts.entry(event.key().to_string()).or_default() += count;
+    }
+
+    pub fn count(&self, event: UsageEvent) -> u64 {
+        self.counts.get(event.key()).copied().unwrap_or_default()
+    }
+
+    pub fn since(&self) -> Option<u64> {
+        self.since
+    }
+
+    pub fn reset(&mut self) {
+        self.counts.clear();
+        self.since = None;
+    }
+
+    /// Counts a launch, and a crash when the last counted session never ended
+    pub fn start_session(&mut self, enabled: bool) {
+        if enabled {
+            if self.session_open {
+                self.record(UsageEvent::Crash, 1);
+            }
+            self.record(UsageEvent::Launch, 1);
+        }
+        self.session_open = enabled;
+    }
+
+    /// Counting turned on or off while the app runs, the running session is tracked from now
+    /// on without counting it as a launch
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.session_open = enabled;
+    }
+
+    pub fn end_session(&mut self) {
+        self.session_open = false;
+    }
+
+    /// Plain text of every count, to paste into a bug report
+    pub fn summary(&self) -> String {
+        UsageEvent::ALL
+            .iter()
+            .map(|&event| format!("{}: {}", event.label(), self.count(event)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
@@ -1,7 +1,9 @@
 use std::option::Option;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{fs, io};
 
 #[derive(Debug, Deserialize, Serialize, Hash)]
@@ -40,6 +42,34 @@ pub fn collect_files(paths: &mut Vec<PathBuf>, dir: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// How long ago `secs` since the Unix epoch was, e.g. "3 days ago"
+pub fn format_age(secs: u64) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let age = now.saturating_sub(secs);
+    let (value, unit) = match age {
+        0..60 => return "just now".to_string(),
+        60..3600 => (age / 60, "minute"),
+        3600..86400 => (age / 3600, "hour"),
+        _ => (age / 86400, "day"),
+    };
+    format!("{} {}{} ago", value, unit, if value == 1 { "" } else { "s" })
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.2} {}", size, UNITS[unit])
+    }
+}
+
 pub enum ModType {
     Default(String),
     Custom(String),
@@ -67,6 +97,40 @@ pub fn get_character_mod_skin(file: &str) -> Option<ModType> {
         None
     }
 }
+/// Categories `get_current_pak_characteristics` can come up with, see `mod_type_category`
+pub const DETECTED_CATEGORIES: [&str; 5] = ["Character", "UI", "Audio", "Movies", "Unknown"];
+
+/// Broad category of a detected mod type, every character skin becomes "Character"
+pub fn mod_type_category(mod_type: &str) -> &str {
+    if mod_type.starts_with("Character") || mod_type.contains(" - ") {
+        return "Character";
+    }
+    mod_type
+}
+
+/// Categories `get_current_pak_characteristics` recognizes among the paths, in the order
+/// found. With more than one the mod mixes kinds of assets and only the first names its type.
+pub fn path_categories(mod_contents: &[String]) -> Vec<&'static str> {
+    let mut categories = vec![];
+    for file in mod_contents {
+        let path = file
+            .strip_prefix("Marvel/Content/Marvel/")
+            .or_else(|| file.strip_prefix("/Game/Marvel/"))
+            .unwrap_or(file);
+        let category = match path.split('/').next().unwrap_or_default() {
+            "Characters" => "Character",
+            "UI" => "UI",
+            "Movies" => "Movies",
+            _ if path.contains("WwiseAudio") => "Audio",
+            _ => continue,
+        };
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    categories
+}
+
 pub fn get_current_pak_characteristics(mod_contents: Vec<String>) -> String {
     let mut fallback: Option<String> = None;
 
@@ -98,6 +162,9 @@ pub fn get_current_pak_characteristics(mod_contents: Vec<String>) -> String {
 
 
 use log::info;
+
+/// Whether retoc reads .ucas files through a memory map, mirrors the setting of the same name
+pub static MEMORY_MAP_UCAS: AtomicBool = AtomicBool::new(false);
 use serde::{Deserialize, Serialize};
 use regex_lite::Regex;
 
@@ -117,6 +184,202 @@ pub fn find_marvel_rivals() -> Option<PathBuf> {
     None
 }
 
+/// Checks a folder picked as the mod folder. Returns the `~mods` folder it most likely
+/// should have been, with a short description of what was picked instead. Any folder
+/// inside the Paks folder is accepted, the game loads paks from all of them.
+pub fn suggest_mods_dir(path: &Path) -> Option<(PathBuf, &'static str)> {
+    let is_paks = |dir: &Path| dir.file_name().is_some_and(|name| name.eq_ignore_ascii_case("Paks"));
+    if is_paks(path) {
+        return Some((path.join("~mods"), "the Paks folder"));
+    }
+    if path.ancestors().skip(1).any(is_paks) {
+        return None;
+    }
+    for paks in ["MarvelGame/Marvel/Content/Paks", "Marvel/Content/Paks", "Content/Paks"] {
+        let paks = path.join(paks);
+        if paks.is_dir() {
+            return Some((paks.join("~mods"), "the game folder"));
+        }
+    }
+    let mods_dir = find_marvel_rivals().map_or_else(|| path.join("~mods"), |paks| paks.join("~mods"));
+    Some((mods_dir, "a folder outside the game's Paks folder"))
+}
+
+/// Fails unless `dir` is an existing folder new files can be written to
+pub fn check_writable(dir: &Path) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "folder does not exist"));
+    }
+    let probe = dir.join(".repak_write_test");
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+/// Starts Repak again with administrator rights. Returns once the elevated copy has
+/// started, or an error when it didn't, e.g. because the UAC prompt was declined.
+#[cfg(target_os = "windows")]
+pub fn relaunch_elevated() -> io::Result<()> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let exe = std::env::current_exe()?.to_string_lossy().replace('\'', "''");
+    // Start-Process only waits for the launch, and fails if UAC is declined
+    let status = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!("Start-Process -FilePath '{}' -Verb RunAs -ErrorAction Stop", exe),
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "the administrator prompt was declined or failed",
+        )),
+    }
+}
+
+/// Process name of the game, Proton runs it under the same name
+const GAME_EXE: &str = "Marvel-Win64-Shipping.exe";
+
+/// Last result of the game check `watch_game` runs in the background
+static GAME_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// How often `watch_game` checks whether the game is running
+const GAME_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Starts checking in the background whether Marvel Rivals is running, see `game_running`
+pub fn watch_game() {
+    std::thread::spawn(|| loop {
+        GAME_RUNNING.store(check_game_running(), Ordering::Relaxed);
+        std::thread::sleep(GAME_CHECK_INTERVAL);
+    });
+}
+
+/// Whether Marvel Rivals was running at the last background check, without listing the
+/// processes on the calling thread. Windows keeps its paks locked while it is.
+pub fn game_running() -> bool {
+    GAME_RUNNING.load(Ordering::Relaxed)
+}
+
+/// Whether Marvel Rivals is running
+#[cfg(target_os = "windows")]
+fn check_game_running() -> bool {
+    use std::os::windows::process::CommandExt;
+    // keeps tasklist from flashing a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("IMAGENAME eq {}", GAME_EXE), "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains(GAME_EXE))
+}
+
+/// Whether Marvel Rivals is running
+#[cfg(not(target_os = "windows"))]
+fn check_game_running() -> bool {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return false;
+    };
+    processes.filter_map(Result::ok).any(|process| {
+        fs::read(process.path().join("cmdline"))
+            .is_ok_and(|cmdline| String::from_utf8_lossy(&cmdline).contains(GAME_EXE))
+    })
+}
+
+/// Opens `path` with whatever the OS uses for that kind of file
+pub fn open_with_default_app(path: &Path) -> io::Result<()> {
+    if !path.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} no longer exists", path.display()),
+        ));
+    }
+    #[cfg(target_os = "windows")]
+    let opener = "explorer.exe";
+    #[cfg(not(target_os = "windows"))]
+    let opener = "xdg-open";
+    std::process::Command::new(opener).arg(path).spawn().map(|_| ())
+}
+
+/// Opens the folder holding `path` in the file manager, with `path` selected on Windows
+pub fn show_in_folder(path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let spawned = std::process::Command::new("explorer.exe")
+        .arg(format!("/select,{}", path.display()))
+        .spawn();
+    #[cfg(not(target_os = "windows"))]
+    let spawned = std::process::Command::new("xdg-open")
+        .arg(path.parent().unwrap_or(path))
+        .spawn();
+    spawned.map(|_| ())
+}
+
+/// Starts `tool` with `path` as its argument, for external viewers like FModel
+pub fn open_with_tool(tool: &Path, path: &Path) -> io::Result<()> {
+    if !tool.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} no longer exists", tool.display()),
+        ));
+    }
+    std::process::Command::new(tool).arg(path).spawn().map(|_| ())
+}
+
+/// The pak of the mod at `pak_path` and the utoc and ucas of iostore mods, those that exist.
+/// The pak may carry a disabled extension, so it is taken as-is.
+pub fn mod_pak_files(pak_path: &Path) -> Vec<PathBuf> {
+    [
+        pak_path.to_path_buf(),
+        pak_path.with_extension("utoc"),
+        pak_path.with_extension("ucas"),
+    ]
+    .into_iter()
+    .filter(|p| p.is_file())
+    .collect()
+}
+
+/// The pak of a mod and the utoc and ucas of iostore mods, each with where `copy_mod_files`
+/// puts it in `dest_dir`. A disabled mod is copied as a plain `.pak` so the copy is ready to
+/// install.
+pub fn mod_file_copies(pak_path: &Path, dest_dir: &Path) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    // copying a mod onto itself would truncate it
+    if pak_path.parent().is_some_and(|dir| same_dir(dir, dest_dir)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Pick a folder other than the one the mod is in",
+        ));
+    }
+    let stem = pak_path.file_stem().unwrap_or_default().to_string_lossy();
+    let copies = [
+        (pak_path.to_path_buf(), "pak"),
+        (pak_path.with_extension("utoc"), "utoc"),
+        (pak_path.with_extension("ucas"), "ucas"),
+    ]
+    .into_iter()
+    .filter(|(src, _)| src.is_file())
+    .map(|(src, ext)| (src, dest_dir.join(format!("{}.{}", stem, ext))))
+    .collect();
+    Ok(copies)
+}
+
+/// Copies the files of `mod_file_copies` into `dest_dir`, replacing files already there.
+/// Returns the new files.
+pub fn copy_mod_files(pak_path: &Path, dest_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    mod_file_copies(pak_path, dest_dir)?
+        .into_iter()
+        .map(|(src, dest)| fs::copy(&src, &dest).map(|_| dest))
+        .collect()
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
 /// Reads `libraryfolders.vdf` to find additional Steam libraries.
 fn get_steam_library_paths() -> Vec<PathBuf> {
     #[cfg(target_os = "windows")]
@@ -152,3 +415,37 @@ fn get_steam_library_paths() -> Vec<PathBuf> {
 
     paths
 }
+
+/// Paths in pasted text, one per line. Browsers and the file explorer may wrap them in
+/// quotes or copy them as `file:///` URLs.
+pub fn pasted_paths(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(|line| line.trim().trim_matches('"'))
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.strip_prefix("file://") {
+            // `file:///C:/...` on Windows, `file:///home/...` elsewhere
+            Some(url) => PathBuf::from(percent_decode(
+                url.strip_prefix('/').filter(|_| cfg!(windows)).unwrap_or(url),
+            )),
+            None => PathBuf::from(line),
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -1,38 +1,50 @@
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use repak::PakReader;
-use retoc::{action_manifest, ActionManifest, Config, FGuid};
+use retoc::{action_manifest, ActionManifest, Config};
+use crate::aes_keys::with_retoc_keys;
+use crate::utils::MEMORY_MAP_UCAS;
 
-pub fn read_utoc(utoc_path: &Path, pak_reader: &PakReader, pak_path: &Path) -> Vec<crate::file_table::FileEntry> {
-    let action_mn = ActionManifest::new(PathBuf::from(utoc_path));
-    let mut config = Config {
+/// Lists the packages of the utoc at `utoc_path`, failing when it can't be read or decrypted
+pub fn read_utoc(
+    utoc_path: &Path,
+    pak_reader: &PakReader,
+    pak_path: &Path,
+) -> Result<Vec<crate::file_table::FileEntry>, String> {
+    let config = || Config {
         container_header_version_override: None,
+        memory_map: MEMORY_MAP_UCAS.load(Ordering::Relaxed),
         ..Default::default()
     };
-
-    let aes_toc =
-        retoc::AesKey::from_str("0C263D8C22DCB085894899C3A3796383E9BF9DE0CBFB08C9BF2DEF2E84F29D74")
-            .unwrap();
-
-    config.aes_keys.insert(FGuid::default(), aes_toc.clone());
-    let config = Arc::new(config);
-
-    let ops = action_manifest(action_mn,config).expect("Failed to read utoc");
+    let ops = with_retoc_keys(config, |config| {
+        action_manifest(ActionManifest::new(PathBuf::from(utoc_path)), config)
+    })
+    .map_err(|e| format!("Unable to read {}: {:#}", utoc_path.display(), e))?;
     let ret = ops.oplog.entries.iter().map(|entry| {
         let name = entry.packagestoreentry.packagename.clone();
+        let chunks = entry.packagedata.iter().chain(entry.bulkdata.iter());
         crate::file_table::FileEntry {
             file_path: name,
             pak_path: PathBuf::from(pak_path),
             pak_reader: pak_reader.clone(),
             // entry: pak_reader.get_file_entry(entry).unwrap(),
-            compressed: "Unavailable".to_string(),
-            uncompressed: "Unavailable".to_string(),
-            offset: "Unavailable".to_string(),
+            compressed: chunks.clone().map(|c| c.compressed_size).sum(),
+            uncompressed: chunks.clone().map(|c| c.size).sum(),
+            compression: chunks
+                .filter_map(|c| c.compression.clone())
+                .next()
+                .unwrap_or_else(|| "None".to_string()),
+            offset: None,
             bulkdata: Some(entry.bulkdata.len()),
             package_data: Some(entry.packagedata.len()),
+            chunk_paths: entry
+                .packagedata
+                .iter()
+                .chain(entry.bulkdata.iter())
+                .map(|c| c.filename.clone())
+                .collect(),
         }
     }).collect::<Vec<_>>();
 
-    ret
-}
\ No newline at end of file
+    Ok(ret)
+}
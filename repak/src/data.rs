@@ -190,8 +190,24 @@ impl<D: AsRef<[u8]>> PartialEntry<D> {
     }
 }
 
+/// Compressor options beyond the choice of compression method
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct CompressionSettings {
+    /// Oodle level, `Normal` when unset
+    #[cfg(feature = "oodle")]
+    pub(crate) oodle_level: Option<oodle_loader::CompressionLevel>,
+}
+
+/// Compression methods allowed for an entry along with their settings
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Compressor<'a> {
+    /// Allowed methods, the entry is stored uncompressed when empty
+    pub(crate) allowed: &'a [Compression],
+    pub(crate) settings: CompressionSettings,
+}
+
 pub(crate) fn build_partial_entry<D>(
-    allowed_compression: &[Compression],
+    compressor: Compressor<'_>,
     data: D,
     #[allow(unused)] key: &super::Key,
     path: &str,
@@ -210,7 +226,7 @@ where
     }
 
     // TODO possibly select best compression based on some criteria instead of picking first
-    let mut compression = allowed_compression.first().cloned();
+    let mut compression = compressor.allowed.first().cloned();
     let uncompressed_size = data.as_ref().len() as u64;
     let compression_block_size;
 
@@ -227,7 +243,7 @@ where
             let mut compressed_data = vec![];
             let mut blocks = vec![];
             for chunk in data.as_ref().chunks(compression_block_size as usize) {
-                let mut data = compress(compression, chunk)?;
+                let mut data = compress(compression, compressor.settings, chunk)?;
                 if encrypted {
                     pad_zeros_to_alignment(&mut data, 16);
                 }
@@ -292,7 +308,11 @@ where
 }
 
 #[cfg(feature = "compression")]
-fn compress(compression: Compression, data: &[u8]) -> Result<Vec<u8>> {
+fn compress(
+    compression: Compression,
+    #[allow(unused)] settings: CompressionSettings,
+    data: &[u8],
+) -> Result<Vec<u8>> {
     use std::io::Write;
 
     let compressed = match compression {
@@ -318,7 +338,9 @@ fn compress(compression: Compression, data: &[u8]) -> Result<Vec<u8>> {
                 oodle_loader::oodle().unwrap().compress(
                     data.as_ref(),
                     oodle_loader::Compressor::Mermaid,
-                    oodle_loader::CompressionLevel::Normal,
+                    settings
+                        .oodle_level
+                        .unwrap_or(oodle_loader::CompressionLevel::Normal),
                 )?
             }
         }
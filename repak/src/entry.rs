@@ -1,4 +1,7 @@
-use crate::{data::build_partial_entry, Error, Hash};
+use crate::{
+    data::{build_partial_entry, Compressor},
+    Error, Hash,
+};
 
 use super::{ext::BoolExt, ext::ReadExt, Compression, Version, VersionMajor};
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
@@ -102,12 +105,12 @@ impl Entry {
         writer: &mut W,
         version: Version,
         compression_slots: &mut Vec<Option<Compression>>,
-        allowed_compression: &[Compression],
+        compressor: Compressor<'_>,
         data: &[u8],
         #[allow(unused)] key: &super::Key,
         path: &str,
     ) -> Result<Self, Error> {
-        let partial_entry = build_partial_entry(allowed_compression, data, key, path)?;
+        let partial_entry = build_partial_entry(compressor, data, key, path)?;
         let stream_position = writer.stream_position()?;
         let entry = partial_entry.build_entry(version, compression_slots, stream_position)?;
         entry.write(writer, version, crate::entry::EntryLocation::Data)?;
@@ -341,29 +344,52 @@ impl Entry {
     ) -> Result<(), super::Error> {
         reader.seek(io::SeekFrom::Start(self.offset))?;
         Entry::read(reader, version)?;
-        #[cfg(any(feature = "compression", feature = "oodle"))]
-        let data_offset = reader.stream_position()?;
 
-        #[allow(unused_mut)]
-        let mut data = reader.read_len(match self.is_encrypted() {
-            true => align(self.compressed),
-            false => self.compressed,
-        } as usize)?;
+        let compression = self.compression_slot.and_then(|c| compression[c as usize]);
+        // plain entries go through a small buffer, large assets are never held in memory
+        if compression.is_none() && !self.is_encrypted() {
+            io::copy(&mut io::Read::take(&mut *reader, self.compressed), buf)?;
+            buf.flush()?;
+            return Ok(());
+        }
 
-        let limit = crate::data::get_limit(path).min(data.len());
+        let data_offset = reader.stream_position()?;
 
+        // only the first `limit` bytes of an entry are encrypted, they are decrypted up front
+        // and everything after them is read as it is needed
+        #[allow(unused_mut)]
+        let mut prefix = vec![];
         if self.is_encrypted() {
             #[cfg(not(feature = "encryption"))]
             return Err(super::Error::Encryption);
             #[cfg(feature = "encryption")]
             {
-                crate::data::decrypt(key, &mut data[..limit])?;
-                data.truncate(self.compressed as usize);
+                let limit = crate::data::get_limit(path).min(align(self.compressed) as usize);
+                prefix = reader.read_len(limit)?;
+                crate::data::decrypt(key, &mut prefix)?;
             }
         }
 
+        let Some(compression) = compression else {
+            let prefix_len = prefix.len().min(self.compressed as usize);
+            buf.write_all(&prefix[..prefix_len])?;
+            reader.seek(io::SeekFrom::Start(data_offset + prefix_len as u64))?;
+            io::copy(
+                &mut io::Read::take(&mut *reader, self.compressed - prefix_len as u64),
+                buf,
+            )?;
+            buf.flush()?;
+            return Ok(());
+        };
+
+        #[cfg(not(feature = "compression"))]
+        {
+            let _ = compression;
+            Err(super::Error::Compression)
+        }
+
         #[cfg(feature = "compression")]
-        let ranges = {
+        {
             let offset = |index: u64| -> usize {
                 (match version.version_major() >= VersionMajor::RelativeChunkOffsets {
                     true => index - (data_offset - self.offset),
@@ -371,76 +397,95 @@ impl Entry {
                 }) as usize
             };
 
-            match &self.blocks {
+            let ranges = match &self.blocks {
                 Some(blocks) => blocks
                     .iter()
                     .map(|block| offset(block.start)..offset(block.end))
                     .collect::<Vec<_>>(),
                 #[allow(clippy::single_range_in_vec_init)]
-                None => vec![0..data.len()],
-            }
-        };
+                None => vec![0..self.compressed as usize],
+            };
 
-        #[cfg(feature = "compression")]
-        macro_rules! decompress {
-            ($decompressor: ty) => {
-                for range in ranges {
-                    io::copy(&mut <$decompressor>::new(&data[range]), buf)?;
-                }
+            // blocks are read one at a time into this buffer, so only a single compressed and
+            // a single decompressed block are held in memory
+            let mut block = vec![];
+            let mut read_block =
+                |range: std::ops::Range<usize>, block: &mut Vec<u8>| -> io::Result<()> {
+                    block.resize(range.len(), 0);
+                    reader.seek(io::SeekFrom::Start(data_offset + range.start as u64))?;
+                    io::Read::read_exact(&mut *reader, block)?;
+                    if range.start < prefix.len() {
+                        let end = range.end.min(prefix.len());
+                        block[..end - range.start].copy_from_slice(&prefix[range.start..end]);
+                    }
+                    Ok(())
+                };
+
+            let chunk_size = if ranges.len() == 1 {
+                self.uncompressed as usize
+            } else {
+                self.compression_block_size as usize
             };
-        }
 
-        match self.compression_slot.and_then(|c| compression[c as usize]) {
-            None => buf.write_all(&data)?,
-            #[cfg(not(feature = "compression"))]
-            _ => return Err(super::Error::Compression),
-            #[cfg(feature = "compression")]
-            Some(comp) => {
-                let chunk_size = if ranges.len() == 1 {
-                    self.uncompressed as usize
-                } else {
-                    self.compression_block_size as usize
+            macro_rules! decompress {
+                ($decompressor: ty) => {
+                    for range in ranges {
+                        read_block(range, &mut block)?;
+                        io::copy(&mut <$decompressor>::new(&block[..]), buf)?;
+                    }
                 };
+            }
 
-                match comp {
-                    Compression::Zlib => decompress!(flate2::read::ZlibDecoder<&[u8]>),
-                    Compression::Gzip => decompress!(flate2::read::GzDecoder<&[u8]>),
-                    Compression::Zstd => {
-                        for range in ranges {
-                            io::copy(&mut zstd::stream::read::Decoder::new(&data[range])?, buf)?;
-                        }
+            match compression {
+                Compression::Zlib => decompress!(flate2::read::ZlibDecoder<&[u8]>),
+                Compression::Gzip => decompress!(flate2::read::GzDecoder<&[u8]>),
+                Compression::Zstd => {
+                    for range in ranges {
+                        read_block(range, &mut block)?;
+                        io::copy(&mut zstd::stream::read::Decoder::new(&block[..])?, buf)?;
                     }
-                    Compression::LZ4 => {
-                        let mut decompressed = vec![0; self.uncompressed as usize];
-                        for (decomp_chunk, comp_range) in
-                            decompressed.chunks_mut(chunk_size).zip(ranges)
-                        {
-                            lz4_flex::block::decompress_into(&data[comp_range], decomp_chunk)
-                                .map_err(|_| Error::DecompressionFailed(Compression::LZ4))?;
+                }
+                // block by block through one block sized buffer, the last block may be shorter
+                Compression::LZ4 => {
+                    let mut decompressed = vec![0; chunk_size];
+                    let mut remaining = self.uncompressed as usize;
+                    for range in ranges {
+                        if remaining == 0 {
+                            break;
                         }
-                        buf.write_all(&decompressed)?;
+                        let len = chunk_size.min(remaining);
+                        read_block(range, &mut block)?;
+                        lz4_flex::block::decompress_into(&block, &mut decompressed[..len])
+                            .map_err(|_| Error::DecompressionFailed(Compression::LZ4))?;
+                        buf.write_all(&decompressed[..len])?;
+                        remaining -= len;
                     }
-                    #[cfg(feature = "oodle")]
-                    Compression::Oodle => {
-                        let mut decompressed = vec![0; self.uncompressed as usize];
-                        for (decomp_chunk, comp_range) in
-                            decompressed.chunks_mut(chunk_size).zip(ranges)
-                        {
-                            let out =
-                                oodle_loader::oodle()?.decompress(&data[comp_range], decomp_chunk);
-                            if out == 0 {
-                                return Err(Error::DecompressionFailed(Compression::Oodle));
-                            }
+                }
+                #[cfg(feature = "oodle")]
+                Compression::Oodle => {
+                    let mut decompressed = vec![0; chunk_size];
+                    let mut remaining = self.uncompressed as usize;
+                    for range in ranges {
+                        if remaining == 0 {
+                            break;
+                        }
+                        let len = chunk_size.min(remaining);
+                        read_block(range, &mut block)?;
+                        let out =
+                            oodle_loader::oodle()?.decompress(&block, &mut decompressed[..len]);
+                        if out == 0 {
+                            return Err(Error::DecompressionFailed(Compression::Oodle));
                         }
-                        buf.write_all(&decompressed)?;
+                        buf.write_all(&decompressed[..len])?;
+                        remaining -= len;
                     }
-                    #[cfg(not(feature = "oodle"))]
-                    Compression::Oodle => return Err(super::Error::Oodle),
                 }
+                #[cfg(not(feature = "oodle"))]
+                Compression::Oodle => return Err(super::Error::Oodle),
             }
+            buf.flush()?;
+            Ok(())
         }
-        buf.flush()?;
-        Ok(())
     }
 }
 
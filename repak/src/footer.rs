@@ -19,6 +19,8 @@ pub struct Footer {
     pub hash: Hash,
     pub frozen: bool,
     pub compression: Vec<Option<Compression>>,
+    /// Compression names that aren't a known `Compression`, their slots are `None`
+    pub unknown_compression: Vec<String>,
 }
 
 impl Footer {
@@ -34,6 +36,7 @@ impl Footer {
         let index_size = reader.read_u64::<LE>()?;
         let hash = Hash(reader.read_guid()?);
         let frozen = version.version_major() == VersionMajor::FrozenIndex && reader.read_bool()?;
+        let mut unknown_compression = vec![];
         let compression = {
             let mut compression = Vec::with_capacity(match version {
                 ver if ver < Version::V8A => 0,
@@ -41,17 +44,17 @@ impl Footer {
                 _ => 5,
             });
             for _ in 0..compression.capacity() {
-                compression.push(
-                    Compression::from_str(
-                        &reader
-                            .read_len(32)?
-                            .iter()
-                            // filter out whitespace and convert to char
-                            .filter_map(|&ch| (ch != 0).then_some(ch as char))
-                            .collect::<String>(),
-                    )
-                    .ok(),
-                )
+                let name = reader
+                    .read_len(32)?
+                    .iter()
+                    // filter out whitespace and convert to char
+                    .filter_map(|&ch| (ch != 0).then_some(ch as char))
+                    .collect::<String>();
+                let method = Compression::from_str(&name).ok();
+                if method.is_none() && !name.is_empty() {
+                    unknown_compression.push(name);
+                }
+                compression.push(method)
             }
             if version.version_major() < VersionMajor::FNameBasedCompression {
                 compression.push(Some(Compression::Zlib));
@@ -80,6 +83,7 @@ impl Footer {
             hash,
             frozen,
             compression,
+            unknown_compression,
         })
     }
 
@@ -1,4 +1,4 @@
-use crate::data::{build_partial_entry, pad_length};
+use crate::data::{build_partial_entry, pad_length, CompressionSettings, Compressor};
 use crate::entry::Entry;
 use crate::{Compression, Error, PartialEntry};
 
@@ -20,6 +20,7 @@ impl std::fmt::Debug for Hash {
 pub struct PakBuilder {
     key: super::Key,
     allowed_compression: Vec<Compression>,
+    compression_settings: CompressionSettings,
 }
 
 impl Default for PakBuilder {
@@ -33,6 +34,7 @@ impl PakBuilder {
         Self {
             key: Default::default(),
             allowed_compression: Default::default(),
+            compression_settings: Default::default(),
         }
     }
     #[cfg(feature = "encryption")]
@@ -45,6 +47,12 @@ impl PakBuilder {
         self.allowed_compression = compression.into_iter().collect();
         self
     }
+    /// Oodle level entries are compressed at, `Normal` by default
+    #[cfg(feature = "oodle")]
+    pub fn oodle_level(mut self, level: oodle_loader::CompressionLevel) -> Self {
+        self.compression_settings.oodle_level = Some(level);
+        self
+    }
     pub fn reader<R: Read + Seek>(self, reader: &mut R) -> Result<PakReader, super::Error> {
         PakReader::new_any_inner(reader, self.key)
     }
@@ -69,6 +77,7 @@ impl PakBuilder {
             mount_point,
             path_hash_seed,
             self.allowed_compression,
+            self.compression_settings,
         )
     }
 }
@@ -85,6 +94,7 @@ pub struct PakWriter<W: Write + Seek> {
     writer: W,
     key: super::Key,
     allowed_compression: Vec<Compression>,
+    compression_settings: CompressionSettings,
 }
 
 #[derive(Debug, Clone)]
@@ -95,7 +105,9 @@ pub(crate) struct Pak {
     index: Index,
     encrypted_index: bool,
     encryption_guid: Option<u128>,
+    frozen_index: bool,
     compression: Vec<Option<Compression>>,
+    unknown_compression: Vec<String>,
 }
 
 impl Pak {
@@ -107,6 +119,7 @@ impl Pak {
             index: Index::new(path_hash_seed),
             encrypted_index: false,
             encryption_guid: None,
+            frozen_index: false,
             compression: (if version.version_major() < VersionMajor::FNameBasedCompression {
                 vec![
                     Some(Compression::Zlib),
@@ -116,6 +129,7 @@ impl Pak {
             } else {
                 vec![]
             }),
+            unknown_compression: vec![],
         }
     }
 }
@@ -192,6 +206,22 @@ impl PakReader {
         self.pak.index.path_hash_seed
     }
 
+    /// Compression slots of the pak, indexed by `Entry::compression_slot`
+    pub fn compression(&self) -> &[Option<Compression>] {
+        &self.pak.compression
+    }
+
+    /// Compression names declared by the pak that this crate doesn't know. Their slots in
+    /// `compression` are `None`, so entries using them can't be read.
+    pub fn unknown_compression(&self) -> &[String] {
+        &self.pak.unknown_compression
+    }
+
+    /// Whether the index is frozen, only v9 paks carry this flag
+    pub fn frozen_index(&self) -> bool {
+        self.pak.frozen_index
+    }
+
     pub fn get<R: Read + Seek>(&self, path: &str, reader: &mut R) -> Result<Vec<u8>, super::Error> {
         let mut data = Vec::new();
         self.read_file(path, reader, &mut data)?;
@@ -234,6 +264,7 @@ impl PakReader {
         writer.seek(io::SeekFrom::Start(self.pak.index_offset.unwrap()))?;
         Ok(PakWriter {
             allowed_compression: self.pak.compression.iter().filter_map(|c| *c).collect(),
+            compression_settings: Default::default(),
             pak: self.pak,
             key: self.key,
             writer,
@@ -250,12 +281,14 @@ impl<W: Write + Seek> PakWriter<W> {
         mount_point: String,
         path_hash_seed: Option<u64>,
         allowed_compression: Vec<Compression>,
+        compression_settings: CompressionSettings,
     ) -> Self {
         PakWriter {
             pak: Pak::new(version, mount_point, path_hash_seed),
             writer,
             key,
             allowed_compression,
+            compression_settings,
         }
     }
 
@@ -275,10 +308,13 @@ impl<W: Write + Seek> PakWriter<W> {
                 &mut self.writer,
                 self.pak.version,
                 &mut self.pak.compression,
-                if allow_compress {
-                    &self.allowed_compression
-                } else {
-                    &[]
+                Compressor {
+                    allowed: if allow_compress {
+                        &self.allowed_compression
+                    } else {
+                        &[]
+                    },
+                    settings: self.compression_settings,
                 },
                 data.as_ref(),
                 &self.key,
@@ -292,6 +328,7 @@ impl<W: Write + Seek> PakWriter<W> {
     pub fn entry_builder(&self) -> EntryBuilder {
         EntryBuilder {
             allowed_compression: self.allowed_compression.clone(),
+            compression_settings: self.compression_settings,
             key: self.key.clone(),
             mount_point: self.pak.mount_point.clone(),
         }
@@ -352,6 +389,7 @@ impl AsRef<[u8]> for Data<'_> {
 #[derive(Clone)]
 pub struct EntryBuilder {
     allowed_compression: Vec<Compression>,
+    compression_settings: CompressionSettings,
     #[allow(unused)]
     key: super::Key,
     mount_point: String,
@@ -364,11 +402,14 @@ impl EntryBuilder {
         data: D,
         path: &str,
     ) -> Result<PartialEntry<D>, Error> {
-        let compression = compress
-            .then_some(self.allowed_compression.as_slice())
-            .unwrap_or_default();
+        let compressor = Compressor {
+            allowed: compress
+                .then_some(self.allowed_compression.as_slice())
+                .unwrap_or_default(),
+            settings: self.compression_settings,
+        };
         build_partial_entry(
-            compression,
+            compressor,
             data,
             &self.key,
             &root_path(&self.mount_point, path),
@@ -525,7 +566,9 @@ impl Pak {
             index,
             encrypted_index: footer.encrypted,
             encryption_guid: footer.encryption_uuid,
+            frozen_index: footer.frozen,
             compression: footer.compression,
+            unknown_compression: footer.unknown_compression,
         })
     }
 
@@ -675,6 +718,7 @@ impl Pak {
             hash: Default::default(),
             frozen: false,
             compression: self.compression.clone(),
+            unknown_compression: vec![],
         };
 
         #[cfg(feature = "encryption")]
@@ -111,6 +111,7 @@ fn test_read(version: repak::Version, _file_name: &str, bytes: &[u8]) {
 
     assert_eq!(pak.mount_point(), "../mount/point/root/");
     assert_eq!(pak.version(), version);
+    assert!(pak.unknown_compression().is_empty());
     use std::collections::HashSet;
     let files: HashSet<String> = HashSet::from_iter(pak.files());
     assert_eq!(
@@ -299,3 +300,201 @@ matrix_test!(
     ("", /*"_encryptindex"*/),
     test_rewrite_index
 );
+
+/// Writes a single compressed, encrypted entry the way repak-gui builds the companion
+/// chunknames pak of iostore mods, then reads it back
+fn test_compressed_entry_roundtrip(compression: repak::Compression) {
+    use aes::cipher::KeyInit;
+    use base64::{engine::general_purpose, Engine as _};
+    let key = general_purpose::STANDARD.decode(AES_KEY).unwrap();
+    let key = aes::Aes256::new_from_slice(&key).unwrap();
+
+    let data = (0..500)
+        .map(|i| format!("Marvel/Content/Marvel/Characters/1011/Meshes/SK_{i}.uasset"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into_bytes();
+
+    let mut pak_writer = repak::PakBuilder::new()
+        .compression(vec![compression])
+        .key(key.clone())
+        .writer(
+            Cursor::new(vec![]),
+            repak::Version::V11,
+            "../../../".to_owned(),
+            Some(0),
+        );
+    let entry = pak_writer
+        .entry_builder()
+        .build_entry(true, data.clone(), "chunknames")
+        .unwrap();
+    pak_writer
+        .write_entry("chunknames".to_string(), entry)
+        .unwrap();
+    let mut written = Cursor::new(pak_writer.write_index().unwrap().into_inner());
+
+    let pak_reader = repak::PakBuilder::new()
+        .key(key)
+        .reader(&mut written)
+        .unwrap();
+    let entry = pak_reader.get_file_entry("chunknames").unwrap();
+    assert!(entry.compressed < entry.uncompressed);
+    assert_eq!(pak_reader.get("chunknames", &mut written).unwrap(), data);
+}
+
+#[test]
+fn test_compressed_entry_roundtrip_zlib() {
+    test_compressed_entry_roundtrip(repak::Compression::Zlib);
+}
+
+#[cfg(feature = "oodle")]
+#[test]
+fn test_compressed_entry_roundtrip_oodle() {
+    test_compressed_entry_roundtrip(repak::Compression::Oodle);
+}
+
+/// Counts what is written without keeping it, and the largest single write
+#[derive(Default)]
+struct WriteProbe {
+    total: usize,
+    largest_write: usize,
+    checksum: u64,
+}
+
+impl io::Write for WriteProbe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.total += buf.len();
+        self.largest_write = self.largest_write.max(buf.len());
+        self.checksum = buf.iter().fold(self.checksum, |sum, &b| {
+            sum.wrapping_mul(31).wrapping_add(b as u64)
+        });
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A large uncompressed entry is copied to the writer in small pieces rather than read
+/// into one buffer first
+#[test]
+fn test_read_file_streams_large_entry() {
+    const LEN: usize = 64 * 1024 * 1024;
+    let data = (0..LEN).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let expected = data
+        .iter()
+        .fold(0u64, |sum, &b| sum.wrapping_mul(31).wrapping_add(b as u64));
+
+    let mut pak_writer = repak::PakBuilder::new().writer(
+        Cursor::new(vec![]),
+        repak::Version::V11,
+        "../../../".to_owned(),
+        Some(0),
+    );
+    pak_writer
+        .write_file("Marvel/Content/Movies/Large.mp4", false, &data)
+        .unwrap();
+    drop(data);
+    let mut written = Cursor::new(pak_writer.write_index().unwrap().into_inner());
+
+    let pak_reader = repak::PakBuilder::new().reader(&mut written).unwrap();
+    let mut probe = WriteProbe::default();
+    pak_reader
+        .read_file("Marvel/Content/Movies/Large.mp4", &mut written, &mut probe)
+        .unwrap();
+    assert_eq!(probe.total, LEN);
+    assert_eq!(probe.checksum, expected);
+    assert!(
+        probe.largest_write <= 1024 * 1024,
+        "entry was written in one {} byte piece",
+        probe.largest_write
+    );
+}
+
+/// Passes reads through and remembers the largest one
+struct ReadProbe<T> {
+    inner: T,
+    largest_read: usize,
+}
+
+impl<T: Read> Read for ReadProbe<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.largest_read = self.largest_read.max(read);
+        Ok(read)
+    }
+}
+
+impl<T: Seek> Seek for ReadProbe<T> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(style)
+    }
+}
+
+/// A large compressed and encrypted entry is read and decompressed block by block rather
+/// than read into one buffer first
+#[test]
+fn test_read_file_streams_compressed_entry() {
+    use aes::cipher::KeyInit;
+    use base64::{engine::general_purpose, Engine as _};
+    let key = general_purpose::STANDARD.decode(AES_KEY).unwrap();
+    let key = aes::Aes256::new_from_slice(&key).unwrap();
+
+    // noise barely compresses, so the compressed entry stays about as large as the data
+    const LEN: usize = 4 * 1024 * 1024;
+    let mut state = 0x2545_f491_u32;
+    let data = (0..LEN)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 24) as u8
+        })
+        .collect::<Vec<_>>();
+    let expected = data
+        .iter()
+        .fold(0u64, |sum, &b| sum.wrapping_mul(31).wrapping_add(b as u64));
+
+    let mut pak_writer = repak::PakBuilder::new()
+        .compression(vec![repak::Compression::Zlib])
+        .key(key.clone())
+        .writer(
+            Cursor::new(vec![]),
+            repak::Version::V11,
+            "../../../".to_owned(),
+            Some(0),
+        );
+    pak_writer
+        .write_file("Marvel/Content/Marvel/Noise.uasset", true, &data)
+        .unwrap();
+    drop(data);
+    let written = pak_writer.write_index().unwrap().into_inner();
+
+    let pak_reader = repak::PakBuilder::new()
+        .key(key)
+        .reader(&mut Cursor::new(&written))
+        .unwrap();
+    let entry = pak_reader
+        .get_file_entry("Marvel/Content/Marvel/Noise.uasset")
+        .unwrap();
+    assert!(entry.blocks.as_ref().is_some_and(|blocks| blocks.len() > 1));
+
+    let mut reader = ReadProbe {
+        inner: Cursor::new(&written),
+        largest_read: 0,
+    };
+    let mut probe = WriteProbe::default();
+    pak_reader
+        .read_file(
+            "Marvel/Content/Marvel/Noise.uasset",
+            &mut reader,
+            &mut probe,
+        )
+        .unwrap();
+    assert_eq!(probe.total, LEN);
+    assert_eq!(probe.checksum, expected);
+    assert!(
+        reader.largest_read <= 256 * 1024,
+        "entry was read in one {} byte piece",
+        reader.largest_read
+    );
+}
@@ -38,17 +38,22 @@ pub fn compress<S: Write>(
             output.write_all(&buf)?;
         }
         CompressionMethod::Oodle => {
-            let buffer = oodle_loader::oodle()?.compress(
-                input,
-                oodle_loader::Compressor::Mermaid,
-                oodle_loader::CompressionLevel::Normal,
-            )?;
-            output.write_all(&buffer)?;
+            compress_oodle(input, output, oodle_loader::CompressionLevel::Normal)?;
         }
     }
     Ok(())
 }
 
+pub fn compress_oodle<S: Write>(
+    input: &[u8],
+    mut output: S,
+    level: oodle_loader::CompressionLevel,
+) -> Result<()> {
+    let buffer = oodle_loader::oodle()?.compress(input, oodle_loader::Compressor::Mermaid, level)?;
+    output.write_all(&buffer)?;
+    Ok(())
+}
+
 pub fn decompress(compression: CompressionMethod, input: &[u8], output: &mut [u8]) -> Result<()> {
     match compression {
         CompressionMethod::Zlib => {
@@ -1,8 +1,24 @@
 use fs_err as fs;
+use memmap2::Mmap;
 use std::collections::VecDeque;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Condvar, Mutex};
 
+/// Maps `path` into memory for reading. `None` when that isn't possible (e.g. on some
+/// network shares), callers fall back to reading through file handles then.
+pub fn map_file(path: &Path) -> Option<Mmap> {
+    let file = std::fs::File::open(path).ok()?;
+    // SAFETY: the map is only read. Containers are not expected to be modified while they
+    // are open, the same assumption the buffered readers make.
+    match unsafe { Mmap::map(&file) } {
+        Ok(map) => Some(map),
+        Err(err) => {
+            eprintln!("Failed to memory-map {}, using buffered reads: {err}", path.display());
+            None
+        }
+    }
+}
+
 pub struct PooledFileHandle {
     file: Option<fs::File>,
     pool: Arc<FilePoolInner>,
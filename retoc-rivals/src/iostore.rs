@@ -8,11 +8,13 @@ use std::{
 
 use anyhow::{bail, Context, Result};
 use fs_err as fs;
+use memmap2::Mmap;
 
 use crate::{
     chunk_id::FIoChunkIdRaw,
+    compression::CompressionMethod,
     container_header::{EIoContainerHeaderVersion, FIoContainerHeader, StoreEntry},
-    file_pool::FilePool,
+    file_pool::{map_file, FilePool},
     script_objects::ZenScriptObjects,
     ser::*,
     Config, EIoChunkType, EIoStoreTocVersion, FIoChunkHash, FIoChunkId, FPackageId, Toc,
@@ -329,6 +331,8 @@ pub struct IoStoreContainer {
     path: PathBuf,
     toc: Toc,
     cas: FilePool,
+    /// The .ucas mapped into memory when `Config::memory_map` is set, read instead of `cas`
+    mapped_cas: Option<Mmap>,
 
     container_header: Option<FIoContainerHeader>,
 }
@@ -337,6 +341,11 @@ impl IoStoreContainer {
         let path = toc_path.as_ref().to_path_buf();
         let toc: Toc = BufReader::new(fs::File::open(&path)?).de_ctx(config.clone())?;
         let cas = FilePool::new(path.with_extension("ucas"), rayon::max_num_threads())?;
+        let mapped_cas = if config.memory_map {
+            map_file(&path.with_extension("ucas"))
+        } else {
+            None
+        };
 
         let mut container = Self {
             name: path
@@ -347,6 +356,7 @@ impl IoStoreContainer {
             path,
             toc,
             cas,
+            mapped_cas,
 
             container_header: None,
         };
@@ -377,6 +387,12 @@ impl IoStoreContainer {
     pub fn container_path(&self) -> &Path {
         self.path.as_ref()
     }
+    /// uncompressed size, size on disk and compression method of a chunk
+    pub(crate) fn chunk_sizes(&self, chunk_id: FIoChunkId) -> Option<(u64, u64, Option<CompressionMethod>)> {
+        let chunk_id = chunk_id.with_version(self.toc.version);
+        let index = *self.toc.chunk_id_map.get(&chunk_id)?;
+        Some(self.toc.chunk_sizes(index))
+    }
 }
 impl IoStoreTrait for IoStoreContainer {
     fn container_name(&self) -> &str {
@@ -417,6 +433,12 @@ impl IoStoreTrait for IoStoreContainer {
         let index = *self.toc.chunk_id_map.get(&chunk_id).with_context(|| {
             format!("container {:?} does not contain {:?}", self.name, chunk_id)
         })?;
+        if let Some(map) = &self.mapped_cas {
+            return self
+                .toc
+                .read(&mut Cursor::new(&map[..]), index)
+                .with_context(|| format!("Failed to read chunk {chunk_id:?}"));
+        }
         let mut file_lock = self.cas.acquire()?;
         self.toc
             .read(&mut file_lock.file(), index)
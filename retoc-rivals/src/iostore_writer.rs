@@ -25,7 +25,13 @@ pub(crate) struct IoStoreWriter {
     container_header: Option<FIoContainerHeader>,
     // If true, enable Oodle compression and register it in TOC. Defaults to false
     compress_enabled: bool,
+    // Oodle level used for every compressed block. Defaults to Normal
+    oodle_level: oodle_loader::CompressionLevel,
+    // If true, blocks Oodle fails on are stored uncompressed, otherwise writing fails. Defaults to true,
+    // `configure_writer` sets it from `Config::uncompressed_fallback` which is off by default
+    uncompressed_fallback: bool,
     // Logging counters
+    fallback_blocks: u64,
     compressed_blocks_total: u64,
     compressed_blocks_bulk: u64,
     compressed_blocks_shaders: u64,
@@ -69,12 +75,29 @@ impl IoStoreWriter {
             toc,
             container_header,
             compress_enabled,
+            oodle_level: oodle_loader::CompressionLevel::Normal,
+            uncompressed_fallback: true,
+            fallback_blocks: 0,
             compressed_blocks_total: 0,
             compressed_blocks_bulk: 0,
             compressed_blocks_shaders: 0,
             compressed_blocks_export: 0,
         })
     }
+    pub(crate) fn set_oodle_level(&mut self, level: oodle_loader::CompressionLevel) {
+        self.oodle_level = level;
+    }
+    pub(crate) fn set_uncompressed_fallback(&mut self, fallback: bool) {
+        self.uncompressed_fallback = fallback;
+    }
+    /// Turning compression off also drops Oodle from the TOC, the container then reads
+    /// without it
+    pub(crate) fn set_compression_enabled(&mut self, enabled: bool) {
+        self.compress_enabled = enabled;
+        if !enabled {
+            self.toc.compression_methods.retain(|m| *m != CompressionMethod::Oodle);
+        }
+    }
     pub(crate) fn write_chunk_raw(
         &mut self,
         chunk_id_raw: FIoChunkIdRaw,
@@ -132,7 +155,15 @@ impl IoStoreWriter {
             let try_compress = self.compress_enabled && !force_uncompressed && block.len() >= MIN_COMPRESS_SIZE;
             let mut compressed = Vec::new();
             let compressed_ok = if try_compress {
-                compression::compress(CompressionMethod::Oodle, block, &mut compressed).is_ok()
+                match compression::compress_oodle(block, &mut compressed, self.oodle_level) {
+                    Ok(()) => true,
+                    // written as is below, the container only gets larger
+                    Err(_) if self.uncompressed_fallback => {
+                        self.fallback_blocks += 1;
+                        false
+                    }
+                    Err(err) => return Err(err.context("Oodle failed to compress a block")),
+                }
             } else {
                 false
             };
@@ -261,6 +292,12 @@ impl IoStoreWriter {
         } else {
             println!("IoStore compression is disabled (no blocks compressed)");
         }
+        if self.fallback_blocks > 0 {
+            println!(
+                "IoStore compression fallback: {} block(s) stored uncompressed, Oodle failed on them",
+                self.fallback_blocks
+            );
+        }
         Ok(())
     }
 }
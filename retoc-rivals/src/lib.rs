@@ -22,7 +22,7 @@ use bitflags::bitflags;
 use clap::Parser;
 use compression::{decompress, CompressionMethod};
 use container_header::StoreEntry;
-use file_pool::FilePool;
+use file_pool::{map_file, FilePool};
 use fs_err as fs;
 use iostore::{IoStoreTrait, PackageInfo};
 use iostore_writer::IoStoreWriter;
@@ -127,7 +127,7 @@ struct ActionPackRaw {
 }
 
 #[derive(Parser, Debug)]
-struct ActionToLegacy {
+pub struct ActionToLegacy {
     /// Input .utoc or directory with multiple .utoc (e.g. Content/Paks/)
     #[arg(index = 1)]
     input: PathBuf,
@@ -167,6 +167,24 @@ struct ActionToLegacy {
     no_parallel: bool,
 }
 
+impl ActionToLegacy{
+    pub fn new(input: PathBuf, output: PathBuf) -> Self {
+        Self {
+            input,
+            output,
+            filter: vec![],
+            no_assets: false,
+            no_shaders: false,
+            no_compres_shaders: false,
+            dry_run: false,
+            version: None,
+            verbose: false,
+            debug: false,
+            no_parallel: false,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 pub struct ActionToZen {
     /// Input directory or .pak
@@ -331,26 +349,35 @@ pub fn action_manifest(args: ActionManifest, config: Arc<Config>) -> Result<(Pac
             let package_name = get_package_name(&data, container_header_version)
                 .with_context(|| package_path.to_string())?;
 
+            let chunk_data = |id: FIoChunkId, filename: String| {
+                let (size, compressed_size, compression) =
+                    package_info.container().chunk_sizes(id).unwrap_or_default();
+                manifest::ChunkData {
+                    id: id.get_raw(),
+                    filename,
+                    size,
+                    compressed_size,
+                    compression: compression.map(|method| method.as_ref().to_string()),
+                }
+            };
+
             let mut entry = manifest::Op {
                 packagestoreentry: manifest::PackageStoreEntry {
                     packagename: package_name,
                 },
-                packagedata: vec![manifest::ChunkData {
-                    id: chunk_id.get_raw(),
-                    filename: package_path.to_string(),
-                }],
+                packagedata: vec![chunk_data(chunk_id, package_path.to_string())],
                 bulkdata: vec![],
             };
 
             let bulk_id = FIoChunkId::from_package_id(package_info.id(), 0, EIoChunkType::BulkData)
                 .with_version(toc_version);
             if iostore.has_chunk_id(bulk_id) {
-                entry.bulkdata.push(manifest::ChunkData {
-                    id: bulk_id.get_raw(),
-                    filename: UEPath::new(&package_path)
+                entry.bulkdata.push(chunk_data(
+                    bulk_id,
+                    UEPath::new(&package_path)
                         .with_extension("ubulk")
                         .to_string(),
-                });
+                ));
             }
 
             entries.lock().unwrap().push(entry);
@@ -373,6 +400,16 @@ pub fn action_manifest(args: ActionManifest, config: Arc<Config>) -> Result<(Pac
     return Ok((manifest));
 }
 
+/// Reads and decompresses the chunk stored under `path` (as listed by `action_manifest`)
+pub fn read_chunk_by_path(utoc: PathBuf, path: &str, config: Arc<Config>) -> Result<Vec<u8>> {
+    let iostore = iostore::open(utoc, config)?;
+    let chunk = iostore
+        .chunks()
+        .find(|chunk| chunk.path().as_deref() == Some(path))
+        .with_context(|| format!("{path} not found in container"))?;
+    chunk.read()
+}
+
 fn action_info(args: ActionInfo, config: Arc<Config>) -> Result<()> {
     let iostore = iostore::open(args.path, config)?;
     iostore.print_info(0);
@@ -512,38 +549,61 @@ fn action_verify(args: ActionVerify, config: Arc<Config>) -> Result<()> {
 }
 
 fn action_unpack(args: ActionUnpack, config: Arc<Config>) -> Result<()> {
-    let mut stream = BufReader::new(fs::File::open(&args.utoc)?);
-    let ucas = &args.utoc.with_extension("ucas");
+    let count = unpack_to_dir(&args.utoc, &args.output, args.verbose, config)?;
+
+    println!(
+        "unpacked {} files to {}",
+        count,
+        args.output.to_string_lossy()
+    );
+
+    Ok(())
+}
+
+/// Writes every file of the container's directory index below `output`, relative to the
+/// mount point. Returns the number of files written.
+pub fn unpack_to_dir(utoc: &Path, output: &Path, verbose: bool, config: Arc<Config>) -> Result<usize> {
+    let mut stream = BufReader::new(fs::File::open(utoc)?);
+    let ucas = &utoc.with_extension("ucas");
 
+    let mapped = if config.memory_map { map_file(ucas) } else { None };
     let toc: Toc = stream.de_ctx(config)?;
 
-    let output = args.output;
+    let write = |file_name: &String, data: Vec<u8>| -> Result<()> {
+        if verbose {
+            println!("{file_name}");
+        }
+        let path = output.join(file_name);
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir)?;
+        fs::write(path, &data)?;
+        Ok(())
+    };
 
     // TODO extract entries not found in directory index
     // TODO output chunk id manifest
-    toc.file_map.keys().par_bridge().try_for_each_init(
-        || BufReader::new(fs::File::open(ucas).unwrap()),
-        |ucas, file_name| -> Result<()> {
-            if args.verbose {
-                println!("{file_name}");
-            }
-            let data = toc.read(ucas, toc.file_map[file_name])?;
-
-            let path = output.join(file_name);
-            let dir = path.parent().unwrap();
-            fs::create_dir_all(dir)?;
-            fs::write(path, &data)?;
-            Ok(())
-        },
-    )?;
-
-    println!(
-        "unpacked {} files to {}",
-        toc.file_map.len(),
-        output.to_string_lossy()
-    );
+    match &mapped {
+        Some(map) => toc.file_map.keys().par_bridge().try_for_each(|file_name| -> Result<()> {
+            let data = toc.read(&mut Cursor::new(&map[..]), toc.file_map[file_name])?;
+            write(file_name, data)
+        })?,
+        None => {
+            // a missing or locked ucas fails the unpack up front instead of in every worker
+            fs::File::open(ucas).with_context(|| format!("Unable to open {}", ucas.display()))?;
+            toc.file_map.keys().par_bridge().try_for_each_init(
+                || fs::File::open(ucas).map(BufReader::new),
+                |reader, file_name| -> Result<()> {
+                    let reader = reader
+                        .as_mut()
+                        .map_err(|e| anyhow::anyhow!("Unable to open {}: {}", ucas.display(), e))?;
+                    let data = toc.read(reader, toc.file_map[file_name])?;
+                    write(file_name, data)
+                },
+            )?
+        }
+    }
 
-    Ok(())
+    Ok(toc.file_map.len())
 }
 
 mod raw {
@@ -648,6 +708,36 @@ fn action_pack_raw(args: ActionPackRaw, _config: Arc<Config>) -> Result<()> {
     Ok(())
 }
 
+/// Applies the compression settings of `config` to a container writer
+fn configure_writer(writer: &mut IoStoreWriter, config: &Config) {
+    if let Some(level) = config.oodle_compression_level {
+        writer.set_oodle_level(level);
+    }
+    writer.set_uncompressed_fallback(config.uncompressed_fallback);
+    writer.set_compression_enabled(!config.disable_compression);
+}
+
+/// Rewrite every chunk of an existing container into a new .utoc/.ucas pair without
+/// converting assets. Chunk ids, paths and the container header are carried over as-is,
+/// only the compression of the blocks changes (see `Config::oodle_compression_level`).
+/// The output should keep the input file name so the container id stays the same.
+pub fn action_recompress(input: PathBuf, output: PathBuf, config: Arc<Config>) -> Result<()> {
+    let iostore = iostore::open(&input, config.clone())?;
+    let version = iostore
+        .container_file_version()
+        .context("container has no toc version")?;
+
+    let mut writer = IoStoreWriter::new(output, version, None, UEPath::new("../../../").into())?;
+    configure_writer(&mut writer, &config);
+    for chunk in iostore.chunks() {
+        let data = chunk.read()?;
+        let path = chunk.path();
+        writer.write_chunk(chunk.id(), path.as_deref().map(UEPath::new), &data)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
 trait FileWriterTrait: Send + Sync {
     fn write_file(&self, path: String, allow_compress: bool, data: Vec<u8>) -> Result<()>;
 }
@@ -766,7 +856,7 @@ impl FileReaderTrait for PakFileReader {
     }
 }
 
-fn action_to_legacy(args: ActionToLegacy, config: Arc<Config>) -> Result<()> {
+pub fn action_to_legacy(args: ActionToLegacy, config: Arc<Config>) -> Result<()> {
     let log = Log::new(args.verbose, args.debug);
     if args.dry_run {
         action_to_legacy_inner(args, config, &NullFileWriter, &log)?;
@@ -986,6 +1076,7 @@ pub fn action_to_zen(args: ActionToZen, config: Arc<Config>) -> Result<()> {
         Some(container_header_version),
         mount_point.into(),
     )?;
+    configure_writer(&mut writer, &config);
 
     let log = Log::new(args.verbose, args.debug);
     let mut asset_paths = vec![];
@@ -1257,6 +1348,16 @@ fn read_file_opt<P: AsRef<Path>>(path: P) -> Result<Option<Vec<u8>>> {
 pub struct Config {
     pub aes_keys: HashMap<FGuid, AesKey>,
     pub container_header_version_override: Option<EIoContainerHeaderVersion>,
+    /// Oodle level used when writing containers. `None` keeps the writer default (Normal)
+    pub oodle_compression_level: Option<oodle_loader::CompressionLevel>,
+    /// Read .ucas files through a memory map instead of buffered file handles. Falls back to
+    /// the handles for files that can't be mapped.
+    pub memory_map: bool,
+    /// Store blocks Oodle fails to compress uncompressed when writing containers, instead of
+    /// failing the write. Off by default.
+    pub uncompressed_fallback: bool,
+    /// Write containers without compressing any block, e.g. when Oodle can't be loaded
+    pub disable_compression: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -1764,6 +1865,31 @@ impl Toc {
             is_compressed: meta.flags.contains(FIoStoreTocEntryMetaFlags::Compressed),
         }
     }
+    /// uncompressed size, size on disk and compression method of a chunk
+    fn chunk_sizes(&self, toc_entry_index: u32) -> (u64, u64, Option<CompressionMethod>) {
+        let offset_and_length = &self.chunk_offset_lengths[toc_entry_index as usize];
+        let offset = offset_and_length.get_offset();
+        let size = offset_and_length.get_length();
+        if size == 0 {
+            return (0, 0, None);
+        }
+
+        let compression_block_size = self.compression_block_size as u64;
+        let first_block_index = (offset / compression_block_size) as usize;
+        let last_block_index =
+            ((align_u64(offset + size, compression_block_size) - 1) / compression_block_size) as usize;
+
+        let mut compressed_size = 0;
+        let mut compression_method = None;
+        for block in &self.compression_blocks[first_block_index..=last_block_index] {
+            compressed_size += block.get_compressed_size() as u64;
+            let compression_method_index = block.get_compression_method_index() as usize;
+            if compression_method_index != 0 {
+                compression_method = Some(self.compression_methods[compression_method_index - 1]);
+            }
+        }
+        (size, compressed_size, compression_method)
+    }
     fn get_chunk_id_entry_index(&self, chunk_id: FIoChunkId) -> Result<u32> {
         self.chunk_id_map
             .get(&chunk_id)
@@ -1909,6 +2035,102 @@ mod test {
         let chunk_id = FIoChunkId::from_package_id(package_id, 0, EIoChunkType::ExportBundleData);
         dbg!(chunk_id);
     }
+
+    #[test]
+    fn test_guid_from_str() {
+        let guid: FGuid = "0000000A-0000000B-0000000C-0000000D".parse().unwrap();
+        assert_eq!(guid, FGuid { a: 10, b: 11, c: 12, d: 13 });
+        assert_eq!("0x0000000A0000000B0000000C0000000D".parse::<FGuid>().unwrap(), guid);
+        assert!("0000000A-0000000B".parse::<FGuid>().is_err());
+        assert!("zzzzzzzz-0000000B-0000000C-0000000D".parse::<FGuid>().is_err());
+    }
+
+    #[test]
+    fn test_memory_mapped_reads_match() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("retoc_mmap_test_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let utoc = dir.join("test.utoc");
+
+        // chunks below 1 KiB are stored uncompressed, so no Oodle is needed
+        let chunks = (0..4u8)
+            .map(|i| {
+                let package_id = FPackageId::from_name(&format!("/Game/Test{i}"));
+                let chunk_id = FIoChunkId::from_package_id(package_id, 0, EIoChunkType::ExportBundleData);
+                (chunk_id, vec![i; 100 + i as usize * 200])
+            })
+            .collect::<Vec<_>>();
+        let mut writer = IoStoreWriter::new(
+            &utoc,
+            EngineVersion::UE5_3.toc_version(),
+            None,
+            UEPath::new("../../../").into(),
+        )?;
+        for (chunk_id, data) in &chunks {
+            writer.write_chunk(*chunk_id, None, data)?;
+        }
+        writer.finalize()?;
+
+        let read_all = |memory_map| -> Result<Vec<Vec<u8>>> {
+            let config = Arc::new(Config {
+                memory_map,
+                ..Default::default()
+            });
+            let container = iostore::IoStoreContainer::open(&utoc, config)?;
+            chunks.iter().map(|(chunk_id, _)| container.read(*chunk_id)).collect()
+        };
+        let buffered = read_all(false);
+        let mapped = read_all(true);
+        fs::remove_dir_all(&dir)?;
+
+        let expected = chunks.into_iter().map(|(_, data)| data).collect::<Vec<_>>();
+        assert_eq!(buffered?, expected);
+        assert_eq!(mapped?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disabled_compression_stores_blocks_as_is() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("retoc_uncompressed_test_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let utoc = dir.join("test.utoc");
+
+        // big enough to be compressed, so Oodle would be needed if it weren't disabled
+        let chunks = (0..2u8)
+            .map(|i| {
+                let package_id = FPackageId::from_name(&format!("/Game/Big{i}"));
+                let chunk_id = FIoChunkId::from_package_id(package_id, 0, EIoChunkType::ExportBundleData);
+                (chunk_id, vec![i; 8 * 1024])
+            })
+            .collect::<Vec<_>>();
+        let mut writer = IoStoreWriter::new(
+            &utoc,
+            EngineVersion::UE5_3.toc_version(),
+            None,
+            UEPath::new("../../../").into(),
+        )?;
+        let config = Config {
+            disable_compression: true,
+            ..Default::default()
+        };
+        configure_writer(&mut writer, &config);
+        for (chunk_id, data) in &chunks {
+            writer.write_chunk(*chunk_id, None, data)?;
+        }
+        writer.finalize()?;
+
+        let container = iostore::IoStoreContainer::open(&utoc, Arc::new(Config::default()))?;
+        let read = chunks
+            .iter()
+            .map(|(chunk_id, _)| container.read(*chunk_id))
+            .collect::<Result<Vec<_>>>();
+        let ucas_size = fs::metadata(utoc.with_extension("ucas"))?.len();
+        fs::remove_dir_all(&dir)?;
+
+        let expected = chunks.into_iter().map(|(_, data)| data).collect::<Vec<_>>();
+        assert!(ucas_size >= expected.iter().map(|data| data.len() as u64).sum::<u64>());
+        assert_eq!(read?, expected);
+        Ok(())
+    }
 }
 
 use chunk_id::{FIoChunkId, FIoChunkIdRaw};
@@ -2282,6 +2504,28 @@ impl Writeable for FGuid {
         Ok(())
     }
 }
+impl std::str::FromStr for FGuid {
+    type Err = anyhow::Error;
+    /// parses the `AAAAAAAA-BBBBBBBB-CCCCCCCC-DDDDDDDD` form, dashes and a 0x prefix are optional
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex: String = s
+            .strip_prefix("0x")
+            .unwrap_or(s)
+            .chars()
+            .filter(|c| *c != '-')
+            .collect();
+        if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("invalid GUID {s:?}");
+        }
+        let part = |i: usize| u32::from_str_radix(&hex[i * 8..(i + 1) * 8], 16).unwrap();
+        Ok(Self {
+            a: part(0),
+            b: part(1),
+            c: part(2),
+            d: part(3),
+        })
+    }
+}
 #[serde_as]
 #[derive(Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 struct FSHAHash(#[serde_as(as = "serde_with::hex::Hex")] [u8; 20]);
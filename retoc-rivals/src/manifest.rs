@@ -31,4 +31,12 @@ pub struct ChunkData {
     #[serde_as(as = "serde_with::hex::Hex")]
     pub id: FIoChunkIdRaw,
     pub  filename: String,
+    /// Uncompressed size of the chunk
+    #[serde(default)]
+    pub size: u64,
+    /// Size of the chunk inside the ucas
+    #[serde(default)]
+    pub compressed_size: u64,
+    #[serde(default)]
+    pub compression: Option<String>,
 }